@@ -0,0 +1,77 @@
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::config::MediaFieldsConfig;
+use crate::text::{strip_html, truncate};
+
+/// The answer to `wcm ask`, with the rows the LLM was shown.
+#[derive(Debug, Serialize)]
+pub struct Answer {
+    pub answer: String,
+    pub rows: Vec<u64>,
+}
+
+/// One line per book for the prompt: row ID, title, author and every field a
+/// question is likely to filter on, leaving out the ones the row has no value for.
+pub fn describe_row(row: &MediaRow, fields: &MediaFieldsConfig) -> String {
+    let mut parts = vec![
+        format!("#{}", row.id),
+        row.get_text("Title").unwrap_or_default(),
+        format!("by {}", row.get_text("Author").unwrap_or_default()),
+    ];
+    let labelled = [
+        ("categories", row.get_text("Category")),
+        ("type", row.get_text("Media Type")),
+        ("status", row.get_text("Status")),
+        ("location", row.get_text("Location")),
+        ("series", row.get_text(&fields.series).map(|series| match row.get_text(&fields.volume) {
+            Some(volume) => format!("{} {}", series, volume),
+            None => series,
+        })),
+        ("pages", row.get_text(&fields.pages)),
+        ("publisher", row.get_text(&fields.publisher)),
+        ("language", row.get_text(&fields.language)),
+        ("rating", row.get_text("Rating").filter(|rating| rating != "0").map(|rating| format!("{}/10", rating))),
+        ("read on", row.get_text(&fields.read_at)),
+        ("bought", row.get_text(&fields.purchased_at)),
+    ];
+    for (label, value) in labelled {
+        if let Some(value) = value {
+            parts.push(format!("{}: {}", label, value));
+        }
+    }
+    let read = row.fields.get("Read").and_then(|read| read.as_bool()).unwrap_or(false);
+    parts.push(if read { "read" } else { "unread" }.to_string());
+    if let Some(synopsis) = row.get_text("Synopsis") {
+        parts.push(format!("synopsis: {}", first_sentence(&strip_html(&synopsis))));
+    }
+    parts.join(" | ")
+}
+
+/// The synopsis up to its first full stop, at most 200 characters, to keep the
+/// prompt short.
+fn first_sentence(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sentence = match text.find(". ") {
+        Some(end) => &text[..=end],
+        None => text.as_str(),
+    };
+    truncate(sentence, 200)
+}
+
+/// The rows to show the LLM. A library of at most `max_rows` books is shown
+/// whole, so questions that filter on pages, status or category see every
+/// book; a larger one is cut to the `max_rows` books `ranked` puts first, the
+/// row IDs closest to the question, or to the first `max_rows` without a
+/// ranking.
+pub fn select_rows<'a>(rows: &'a [MediaRow], ranked: Option<&[u64]>, max_rows: usize) -> Vec<&'a MediaRow> {
+    if rows.len() <= max_rows {
+        return rows.iter().collect();
+    }
+    match ranked {
+        Some(ranked) => ranked.iter()
+            .filter_map(|id| rows.iter().find(|row| row.id == *id))
+            .take(max_rows)
+            .collect(),
+        None => rows.iter().take(max_rows).collect(),
+    }
+}
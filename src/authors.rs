@@ -0,0 +1,152 @@
+use unicode_normalization::UnicodeNormalization;
+use crate::config::{AuthorFormatConfig, NameOrder};
+use crate::duplicates::fold;
+
+/// Name endings that follow a comma without being given names.
+const SUFFIXES: &[&str] = &["jr", "sr", "ii", "iii", "iv", "phd"];
+
+/// Words that belong to the surname that follows them ("Ursula K. Le Guin",
+/// "Ludwig van Beethoven").
+const PARTICLES: &[&str] = &["da", "de", "del", "della", "der", "di", "du", "la", "le", "st", "ten", "ter", "van", "von"];
+
+/// Splits an author field into people: on `;`, `&`, " and " and commas, except
+/// that a part after a comma made only of initials or a suffix stays with the
+/// name before it ("Tolkien, J. R. R.", "King, Jr.").
+pub fn split_authors(text: &str) -> Vec<String> {
+    let mut authors: Vec<String> = Vec::new();
+    for group in text.split([';', '&']).flat_map(|group| group.split(" and ")) {
+        let mut parts: Vec<String> = Vec::new();
+        for part in group.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match parts.last_mut() {
+                Some(previous) if is_initials(part) || is_suffix(part) => *previous = format!("{}, {}", previous, part),
+                _ => parts.push(part.to_string()),
+            }
+        }
+        authors.extend(parts);
+    }
+    authors
+}
+
+/// Splits an Author field wcm wrote with `format` into people. Names written
+/// "Surname, Given" are separated by semicolons only, so the commas inside
+/// them are not taken for separators.
+pub fn split_author_field(text: &str, format: &AuthorFormatConfig) -> Vec<String> {
+    match format.order {
+        NameOrder::LastFirst => text.split(';').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect(),
+        _ => split_authors(text),
+    }
+}
+
+/// Writes a name the same way whatever form it came in: "Given Surname" rather
+/// than "Surname, Given", and initials spaced as "J. R. R.". A suffix stays at
+/// the end ("King, Martin Luther, Jr." is "Martin Luther King, Jr.").
+pub fn canonical_name(name: &str) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (rest, suffix) = split_suffix(&name);
+    let name = match rest.split_once(',') {
+        Some((surname, given)) if !given.trim().is_empty() && !is_suffix(given) && !given.contains(',') => {
+            let name = format!("{} {}", given.trim(), surname.trim());
+            match suffix {
+                Some(suffix) => format!("{}, {}", name, suffix),
+                None => name,
+            }
+        }
+        _ => name.clone(),
+    };
+    name.split(' ')
+        .map(|word| {
+            let letters: Vec<&str> = word.split('.').filter(|letter| !letter.is_empty()).collect();
+            if word.contains('.') && letters.len() > 1 && letters.iter().all(|letter| letter.chars().count() == 1) {
+                letters.iter().map(|letter| format!("{}.", letter)).collect::<Vec<_>>().join(" ")
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes a book's authors for the Author field as `format` asks: each name in
+/// the chosen order, without accents, and each person once. With the default
+/// format the names are joined as the catalogue gave them.
+pub fn format_authors(authors: &[String], format: &AuthorFormatConfig) -> String {
+    let mut names: Vec<String> = Vec::new();
+    for name in authors.iter().map(|name| name.split_whitespace().collect::<Vec<_>>().join(" ")).filter(|name| !name.is_empty()) {
+        if format.dedupe && names.iter().any(|kept| name_key(kept) == name_key(&name)) {
+            continue;
+        }
+        let name = if format.strip_diacritics { strip_latin_diacritics(&name) } else { name };
+        names.push(name);
+    }
+    match format.order {
+        NameOrder::AsGiven => names.join(", "),
+        NameOrder::FirstLast => names.iter().map(|name| canonical_name(name)).collect::<Vec<_>>().join(", "),
+        // Commas are part of each name, so names are separated with semicolons
+        NameOrder::LastFirst => names.iter().map(|name| inverted_name(name)).collect::<Vec<_>>().join("; "),
+    }
+}
+
+/// "Surname, Given" for a name in any order, keeping a suffix at the end
+/// ("King, Martin Luther, Jr."). A single word stays as it is.
+fn inverted_name(name: &str) -> String {
+    let (name, suffix) = split_suffix(name);
+    let name = canonical_name(name);
+    let words: Vec<&str> = name.split(' ').collect();
+    let mut start = words.len() - 1;
+    while start > 1 && PARTICLES.contains(&words[start - 1].to_lowercase().as_str()) {
+        start -= 1;
+    }
+    let inverted = match start {
+        0 => name.clone(),
+        _ => format!("{}, {}", words[start..].join(" "), words[..start].join(" ")),
+    };
+    match suffix {
+        Some(suffix) => format!("{}, {}", inverted, suffix),
+        None => inverted,
+    }
+}
+
+/// The surname in a name written in either order, lowercased and without
+/// accents, for comparing authors.
+pub fn surname_key(name: &str) -> Option<String> {
+    fold(&canonical_name(split_suffix(name).0)).split_whitespace().last().map(str::to_string)
+}
+
+/// A name without the suffix after its last comma, and the suffix.
+fn split_suffix(name: &str) -> (&str, Option<&str>) {
+    match name.rsplit_once(',') {
+        Some((rest, suffix)) if is_suffix(suffix) => (rest.trim(), Some(suffix.trim())),
+        _ => (name, None),
+    }
+}
+
+/// Drops the accents of Latin letters, leaving the marks other scripts, such as
+/// Thai, need to be read.
+pub(crate) fn strip_latin_diacritics(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut base_is_latin = false;
+    for c in text.nfd() {
+        if unicode_normalization::char::is_combining_mark(c) {
+            if !base_is_latin {
+                stripped.push(c);
+            }
+        } else {
+            base_is_latin = c.is_ascii_alphabetic();
+            stripped.push(c);
+        }
+    }
+    stripped.nfc().collect()
+}
+
+/// What two spellings of the same name have in common, for matching rows.
+pub fn name_key(name: &str) -> String {
+    fold(&canonical_name(name))
+}
+
+fn is_initials(part: &str) -> bool {
+    part.split([' ', '.']).filter(|letter| !letter.is_empty()).all(|letter| letter.chars().count() == 1)
+}
+
+fn is_suffix(part: &str) -> bool {
+    SUFFIXES.contains(&fold(part).replace(' ', "").as_str())
+}
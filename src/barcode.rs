@@ -0,0 +1,297 @@
+use image::{DynamicImage, GrayImage, ImageBuffer, Rgb, RgbImage};
+
+// EAN-13 digit patterns as module widths, starting with a space for the left half
+// and with a bar for the right half. G codes are the L codes reversed.
+const L_PATTERNS: [[u32; 4]; 10] = [
+    [3, 2, 1, 1], [2, 2, 2, 1], [2, 1, 2, 2], [1, 4, 1, 1], [1, 1, 3, 2],
+    [1, 2, 3, 1], [1, 1, 1, 4], [1, 3, 1, 2], [1, 2, 1, 3], [3, 1, 1, 2],
+];
+
+// Parity of the six left-hand digits (false = L, true = G) encodes the first digit
+const FIRST_DIGIT_PARITY: [[bool; 6]; 10] = [
+    [false, false, false, false, false, false],
+    [false, false, true, false, true, true],
+    [false, false, true, true, false, true],
+    [false, false, true, true, true, false],
+    [false, true, false, false, true, true],
+    [false, true, true, false, false, true],
+    [false, true, true, true, false, false],
+    [false, true, false, true, false, true],
+    [false, true, false, true, true, false],
+    [false, true, true, false, true, false],
+];
+
+// Code 128 symbol patterns as bar/space widths, indexed by symbol value.
+// 103-105 are the Start A/B/C symbols; the stop symbol is separate.
+const CODE128_PATTERNS: [[u32; 6]; 106] = [
+    [2, 1, 2, 2, 2, 2], [2, 2, 2, 1, 2, 2], [2, 2, 2, 2, 2, 1], [1, 2, 1, 2, 2, 3], [1, 2, 1, 3, 2, 2],
+    [1, 3, 1, 2, 2, 2], [1, 2, 2, 2, 1, 3], [1, 2, 2, 3, 1, 2], [1, 3, 2, 2, 1, 2], [2, 2, 1, 2, 1, 3],
+    [2, 2, 1, 3, 1, 2], [2, 3, 1, 2, 1, 2], [1, 1, 2, 2, 3, 2], [1, 2, 2, 1, 3, 2], [1, 2, 2, 2, 3, 1],
+    [1, 1, 3, 2, 2, 2], [1, 2, 3, 1, 2, 2], [1, 2, 3, 2, 2, 1], [2, 2, 3, 2, 1, 1], [2, 2, 1, 1, 3, 2],
+    [2, 2, 1, 2, 3, 1], [2, 1, 3, 2, 1, 2], [2, 2, 3, 1, 1, 2], [3, 1, 2, 1, 3, 1], [3, 1, 1, 2, 2, 2],
+    [3, 2, 1, 1, 2, 2], [3, 2, 1, 2, 2, 1], [3, 1, 2, 2, 1, 2], [3, 2, 2, 1, 1, 2], [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3], [2, 1, 2, 3, 2, 1], [2, 3, 2, 1, 2, 1], [1, 1, 1, 3, 2, 3], [1, 3, 1, 1, 2, 3],
+    [1, 3, 1, 3, 2, 1], [1, 1, 2, 3, 1, 3], [1, 3, 2, 1, 1, 3], [1, 3, 2, 3, 1, 1], [2, 1, 1, 3, 1, 3],
+    [2, 3, 1, 1, 1, 3], [2, 3, 1, 3, 1, 1], [1, 1, 2, 1, 3, 3], [1, 1, 2, 3, 3, 1], [1, 3, 2, 1, 3, 1],
+    [1, 1, 3, 1, 2, 3], [1, 1, 3, 3, 2, 1], [1, 3, 3, 1, 2, 1], [3, 1, 3, 1, 2, 1], [2, 1, 1, 3, 3, 1],
+    [2, 3, 1, 1, 3, 1], [2, 1, 3, 1, 1, 3], [2, 1, 3, 3, 1, 1], [2, 1, 3, 1, 3, 1], [3, 1, 1, 1, 2, 3],
+    [3, 1, 1, 3, 2, 1], [3, 3, 1, 1, 2, 1], [3, 1, 2, 1, 1, 3], [3, 1, 2, 3, 1, 1], [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1], [2, 2, 1, 4, 1, 1], [4, 3, 1, 1, 1, 1], [1, 1, 1, 2, 2, 4], [1, 1, 1, 4, 2, 2],
+    [1, 2, 1, 1, 2, 4], [1, 2, 1, 4, 2, 1], [1, 4, 1, 1, 2, 2], [1, 4, 1, 2, 2, 1], [1, 1, 2, 2, 1, 4],
+    [1, 1, 2, 4, 1, 2], [1, 2, 2, 1, 1, 4], [1, 2, 2, 4, 1, 1], [1, 4, 2, 1, 1, 2], [1, 4, 2, 2, 1, 1],
+    [2, 4, 1, 2, 1, 1], [2, 2, 1, 1, 1, 4], [4, 1, 3, 1, 1, 1], [2, 4, 1, 1, 1, 2], [1, 3, 4, 1, 1, 1],
+    [1, 1, 1, 2, 4, 2], [1, 2, 1, 1, 4, 2], [1, 2, 1, 2, 4, 1], [1, 1, 4, 2, 1, 2], [1, 2, 4, 1, 1, 2],
+    [1, 2, 4, 2, 1, 1], [4, 1, 1, 2, 1, 2], [4, 2, 1, 1, 1, 2], [4, 2, 1, 2, 1, 1], [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1], [4, 1, 2, 1, 2, 1], [1, 1, 1, 1, 4, 3], [1, 1, 1, 3, 4, 1], [1, 3, 1, 1, 4, 1],
+    [1, 1, 4, 1, 1, 3], [1, 1, 4, 3, 1, 1], [4, 1, 1, 1, 1, 3], [4, 1, 1, 3, 1, 1], [1, 1, 3, 1, 4, 1],
+    [1, 1, 4, 1, 3, 1], [3, 1, 1, 1, 4, 1], [4, 1, 1, 1, 3, 1], [2, 1, 1, 4, 1, 2], [2, 1, 1, 2, 1, 4],
+    [2, 1, 1, 2, 3, 2],
+];
+const CODE128_START_B: usize = 104;
+const CODE128_STOP: [u32; 7] = [2, 3, 3, 1, 1, 1, 2];
+
+// Scanners need a blank margin of at least ten modules on each side
+const QUIET_ZONE_MODULES: usize = 10;
+
+// Start guard + 6 digits + middle guard + 6 digits + end guard
+const EAN13_RUNS: usize = 3 + 6 * 4 + 5 + 6 * 4 + 3;
+const SCANLINES: u32 = 24;
+
+/// Decodes an EAN-13 barcode (the kind printed on book covers) from a photo.
+/// Scans horizontal lines across the image, then tries again rotated by 90 degrees.
+pub fn decode_ean13(image: &DynamicImage) -> Option<String> {
+    let gray = image.to_luma8();
+    decode_gray(&gray).or_else(|| decode_gray(&image::imageops::rotate90(&gray)))
+}
+
+fn decode_gray(gray: &GrayImage) -> Option<String> {
+    let (width, height) = gray.dimensions();
+    if width < EAN13_RUNS as u32 || height == 0 {
+        return None;
+    }
+
+    // Start from the middle, where the barcode usually is, and work outwards
+    let step = (height / (SCANLINES + 1)).max(1);
+    let middle = height / 2;
+    let mut rows = Vec::new();
+    for i in 0..=SCANLINES / 2 {
+        rows.push(middle.saturating_sub(i * step));
+        rows.push((middle + i * step).min(height - 1));
+    }
+
+    rows.into_iter().find_map(|y| {
+        let row: Vec<u8> = (0..width).map(|x| gray.get_pixel(x, y)[0]).collect();
+        let runs = to_runs(&row);
+        decode_runs(&runs).or_else(|| {
+            let reversed: Vec<(bool, u32)> = runs.iter().rev().copied().collect();
+            decode_runs(&reversed)
+        })
+    })
+}
+
+/// Converts a row of luminance values into alternating (is_dark, width) runs.
+fn to_runs(row: &[u8]) -> Vec<(bool, u32)> {
+    let min = row.iter().copied().min().unwrap_or(0) as u32;
+    let max = row.iter().copied().max().unwrap_or(255) as u32;
+    if max - min < 40 {
+        return Vec::new();
+    }
+    let threshold = (min + max) / 2;
+
+    let mut runs: Vec<(bool, u32)> = Vec::new();
+    for &value in row {
+        let dark = (value as u32) < threshold;
+        match runs.last_mut() {
+            Some((last_dark, width)) if *last_dark == dark => *width += 1,
+            _ => runs.push((dark, 1)),
+        }
+    }
+    runs
+}
+
+fn decode_runs(runs: &[(bool, u32)]) -> Option<String> {
+    if runs.len() < EAN13_RUNS {
+        return None;
+    }
+
+    (0..=runs.len() - EAN13_RUNS)
+        .filter(|&start| runs[start].0)
+        .find_map(|start| decode_at(&runs[start..start + EAN13_RUNS]))
+}
+
+fn decode_at(runs: &[(bool, u32)]) -> Option<String> {
+    let widths: Vec<u32> = runs.iter().map(|(_, w)| *w).collect();
+    let total: u32 = widths.iter().sum();
+    let module = total as f32 / 95.0;
+
+    // Guards are all single-module bars and spaces
+    let guard_ok = |range: std::ops::Range<usize>| {
+        widths[range].iter().all(|&w| (w as f32 / module - 1.0).abs() < 0.7)
+    };
+    if !guard_ok(0..3) || !guard_ok(27..32) || !guard_ok(56..59) {
+        return None;
+    }
+
+    let mut digits = Vec::with_capacity(13);
+    let mut parity = [false; 6];
+
+    for (i, is_g_parity) in parity.iter_mut().enumerate() {
+        let start = 3 + i * 4;
+        let (digit, is_g) = match_digit(&widths[start..start + 4], true)?;
+        digits.push(digit);
+        *is_g_parity = is_g;
+    }
+
+    for i in 0..6 {
+        let start = 32 + i * 4;
+        let (digit, _) = match_digit(&widths[start..start + 4], false)?;
+        digits.push(digit);
+    }
+
+    let first = FIRST_DIGIT_PARITY.iter().position(|p| *p == parity)? as u32;
+    digits.insert(0, first);
+
+    if ean13_check_digit(&digits[..12]) != digits[12] {
+        return None;
+    }
+
+    Some(digits.iter().map(|d| char::from_digit(*d, 10).unwrap_or('0')).collect())
+}
+
+/// Returns the best matching digit and whether it used G (even) parity.
+fn match_digit(widths: &[u32], left_half: bool) -> Option<(u32, bool)> {
+    let total: u32 = widths.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let normalized: Vec<f32> = widths.iter().map(|&w| w as f32 * 7.0 / total as f32).collect();
+
+    let distance = |pattern: &[u32; 4]| -> f32 {
+        normalized.iter().zip(pattern.iter())
+            .map(|(n, p)| (n - *p as f32).abs())
+            .sum()
+    };
+
+    let mut best: Option<(u32, bool, f32)> = None;
+    for (digit, pattern) in L_PATTERNS.iter().enumerate() {
+        let mut candidates = vec![(false, *pattern)];
+        if left_half {
+            let mut reversed = *pattern;
+            reversed.reverse();
+            candidates.push((true, reversed));
+        }
+
+        for (is_g, candidate) in candidates {
+            let d = distance(&candidate);
+            if best.map(|(_, _, best_d)| d < best_d).unwrap_or(true) {
+                best = Some((digit as u32, is_g, d));
+            }
+        }
+    }
+
+    best.filter(|(_, _, d)| *d < 1.5).map(|(digit, is_g, _)| (digit, is_g))
+}
+
+/// Encodes printable ASCII text as Code 128 (code set B). Returns the module
+/// pattern, `true` for bars, or `None` if the text has unsupported characters.
+pub fn encode_code128(text: &str) -> Option<Vec<bool>> {
+    if text.is_empty() || !text.chars().all(|c| (' '..='~').contains(&c)) {
+        return None;
+    }
+
+    let values: Vec<usize> = text.bytes().map(|b| (b - b' ') as usize).collect();
+    let checksum = values.iter().enumerate()
+        .fold(CODE128_START_B, |sum, (i, value)| sum + (i + 1) * value) % 103;
+
+    let mut modules = Vec::new();
+    let symbols = std::iter::once(CODE128_START_B).chain(values).chain(std::iter::once(checksum));
+    for symbol in symbols {
+        push_widths(&mut modules, &CODE128_PATTERNS[symbol], true);
+    }
+    push_widths(&mut modules, &CODE128_STOP, true);
+    Some(modules)
+}
+
+/// Encodes an EAN-13 (ISBN-13) barcode from 12 or 13 digits. With 13 digits the
+/// check digit is verified; with 12 it is computed.
+pub fn encode_ean13(digits: &str) -> Option<Vec<bool>> {
+    let mut values: Vec<u32> = digits.chars().map(|c| c.to_digit(10)).collect::<Option<_>>()?;
+    if values.len() != 12 && values.len() != 13 {
+        return None;
+    }
+
+    let check = ean13_check_digit(&values[..12]);
+    match values.get(12) {
+        Some(&given) if given != check => return None,
+        Some(_) => {}
+        None => values.push(check),
+    }
+
+    let parity = FIRST_DIGIT_PARITY[values[0] as usize];
+    let mut modules = Vec::new();
+    push_widths(&mut modules, &[1, 1, 1], true);
+    for (i, &digit) in values[1..7].iter().enumerate() {
+        let mut pattern = L_PATTERNS[digit as usize];
+        if parity[i] {
+            pattern.reverse();
+        }
+        push_widths(&mut modules, &pattern, false);
+    }
+    push_widths(&mut modules, &[1, 1, 1, 1, 1], false);
+    for &digit in &values[7..] {
+        push_widths(&mut modules, &L_PATTERNS[digit as usize], true);
+    }
+    push_widths(&mut modules, &[1, 1, 1], true);
+    Some(modules)
+}
+
+/// Converts an ISBN-10 or ISBN-13 (with or without hyphens) to the 13 digits
+/// printed in its EAN-13 barcode.
+pub fn isbn_to_ean13(isbn: &str) -> Option<String> {
+    let cleaned: String = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    match cleaned.len() {
+        13 if cleaned.chars().all(|c| c.is_ascii_digit()) => Some(cleaned),
+        // The ISBN-10 check digit (possibly X) is dropped and recomputed
+        10 if cleaned[..9].chars().all(|c| c.is_ascii_digit()) => {
+            let mut ean = format!("978{}", &cleaned[..9]);
+            let digits: Vec<u32> = ean.chars().filter_map(|c| c.to_digit(10)).collect();
+            ean.push(char::from_digit(ean13_check_digit(&digits), 10)?);
+            Some(ean)
+        }
+        _ => None,
+    }
+}
+
+/// Renders a module pattern as a black-on-white image no wider than `max_width`,
+/// using the widest whole-pixel module size that fits, including quiet zones.
+pub fn render_barcode(modules: &[bool], max_width: u32, height: u32) -> RgbImage {
+    let total_modules = (modules.len() + 2 * QUIET_ZONE_MODULES) as u32;
+    let module_width = (max_width / total_modules).max(1);
+    let mut img = ImageBuffer::from_pixel(total_modules * module_width, height, Rgb([255, 255, 255]));
+
+    for (i, _) in modules.iter().enumerate().filter(|(_, &bar)| bar) {
+        let start_x = (QUIET_ZONE_MODULES + i) as u32 * module_width;
+        for x in start_x..start_x + module_width {
+            for y in 0..height {
+                img.put_pixel(x, y, Rgb([0, 0, 0]));
+            }
+        }
+    }
+
+    img
+}
+
+fn ean13_check_digit(digits: &[u32]) -> u32 {
+    let sum: u32 = digits.iter().enumerate()
+        .map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+fn push_widths(modules: &mut Vec<bool>, widths: &[u32], start_with_bar: bool) {
+    let mut bar = start_with_bar;
+    for &width in widths {
+        modules.extend(std::iter::repeat_n(bar, width as usize));
+        bar = !bar;
+    }
+}
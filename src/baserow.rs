@@ -1,7 +1,11 @@
-use reqwest;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::config::BaserowConfig;
+use crate::ui::{self, Role};
+use crate::rate_limit::SendWithRetry;
+
+// Largest page Baserow's list rows endpoint will return
+const MAX_PAGE_SIZE: u32 = 200;
 
 #[derive(Debug, Clone)]
 pub struct BaserowClient {
@@ -31,7 +35,14 @@ pub struct Storage {
     pub fields: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MediaRow {
+    pub id: u64,
+    #[serde(flatten)]
+    pub fields: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MediaEntry {
     #[serde(rename = "Title")]
     pub title: String,
@@ -49,23 +60,62 @@ pub struct MediaEntry {
     pub rating: u32,
     #[serde(rename = "Media Type")]
     pub media_type: Option<u64>,
-    #[serde(rename = "Location", skip_serializing_if = "Vec::is_empty")]
+    #[serde(rename = "Location", default, skip_serializing_if = "Vec::is_empty")]
     pub location: Vec<u64>, // Array of location IDs - left empty for manual entry
-    #[serde(rename = "Cover", skip_serializing_if = "Vec::is_empty")]
+    #[serde(rename = "Cover", default, skip_serializing_if = "Vec::is_empty")]
     pub cover: Vec<CoverImage>, // Array of cover images
     #[serde(rename = "Status")]
     pub status: u64, // Status field (3028=In Place, 3029=Active, 3030=On Loan)
+    /// Fields whose names come from the config, such as the call number field
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Names of the media table fields `MediaEntry` writes to.
+pub const MEDIA_FIELDS: &[&str] = &[
+    "Title", "Author", "ISBN", "Synopsis", "Category", "Read", "Rating", "Media Type", "Location", "Cover", "Status",
+];
+
+/// A row of a table wcm links to by name, such as the authors table.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NamedRow {
+    pub id: u64,
+    #[serde(flatten)]
+    pub fields: HashMap<String, serde_json::Value>,
+}
+
+impl NamedRow {
+    pub fn get_name(&self) -> Option<&str> {
+        self.fields.get("Name").and_then(|v| v.as_str())
+    }
+}
+
+/// A field of a Baserow table, as listed by the fields endpoint.
+#[derive(Debug, Deserialize)]
+pub struct TableField {
+    pub id: u64,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CoverImage {
     pub name: String,
+    /// Where Baserow serves the uploaded file; not sent back with the row
+    #[serde(skip)]
+    pub url: Option<String>,
+}
+
+impl From<FileUploadResponse> for CoverImage {
+    fn from(uploaded: FileUploadResponse) -> Self {
+        Self { name: uploaded.name, url: Some(uploaded.url) }
+    }
 }
 
 
 #[derive(Debug, Deserialize)]
 pub struct FileUploadResponse {
-    #[allow(dead_code)]
     pub url: String,
     pub name: String,
     #[allow(dead_code)]
@@ -84,11 +134,10 @@ pub struct FileUploadResponse {
     pub uploaded_at: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct CreatedEntry {
     pub id: u64,
     #[serde(flatten)]
-    #[allow(dead_code)]
     pub fields: HashMap<String, serde_json::Value>,
 }
 
@@ -117,8 +166,46 @@ impl Storage {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string())
     }
+
+    /// Reads a "Last modified" style field, which Baserow returns as an ISO 8601 UTC timestamp.
+    pub fn get_timestamp(&self, field: &str) -> Option<&str> {
+        self.fields.get(field).and_then(|v| v.as_str())
+    }
 }
 
+impl MediaRow {
+    /// Reads a field as display text. Link row and select fields are reduced to their values.
+    pub fn get_text(&self, field: &str) -> Option<String> {
+        let text = match self.fields.get(field)? {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Object(o) => o.get("value")?.as_str()?.to_string(),
+            serde_json::Value::Array(items) => items.iter()
+                .filter_map(|item| item.get("value").and_then(|v| v.as_str()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => return None,
+        };
+        Some(text).filter(|t| !t.trim().is_empty())
+    }
+}
+
+/// The web app address of a row, which opens it in `view_id`, or in the table's
+/// first view without one.
+pub fn row_url(base_url: &str, database_id: u64, table_id: u64, view_id: Option<u64>, row_id: u64) -> String {
+    match view_id {
+        Some(view_id) => format!("{}/database/{}/table/{}/{}/row/{}", base_url.trim_end_matches('/'), database_id, table_id, view_id, row_id),
+        None => format!("{}/database/{}/table/{}/row/{}", base_url.trim_end_matches('/'), database_id, table_id, row_id),
+    }
+}
+
+/// Options of the media table's `Status` single select field.
+pub const STATUS_OPTIONS: &[(u64, &str)] = &[
+    (3028, "In Place"),
+    (3029, "Active"),
+    (3030, "On Loan"),
+];
+
 #[derive(Debug)]
 pub enum BaserowError {
     RequestFailed(reqwest::Error),
@@ -152,6 +239,17 @@ impl BaserowClient {
         Self { client, config }
     }
 
+    /// Names of the optional media table fields.
+    pub fn fields(&self) -> &crate::config::MediaFieldsConfig {
+        &self.config.fields
+    }
+
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
     async fn make_request<T>(&self, endpoint: &str) -> Result<T, BaserowError>
     where
         T: for<'de> Deserialize<'de>,
@@ -161,13 +259,20 @@ impl BaserowClient {
             endpoint
         );
 
-        println!("Making request to: {}", url);
+        self.get_json(&url).await
+    }
+
+    async fn get_json<T>(&self, url: &str) -> Result<T, BaserowError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        ui::detail(format!("Making request to: {}", url));
 
         let response = self.client
-            .get(&url)
+            .get(url)
             .header("Authorization", format!("Token {}", self.config.api_token))
             .header("Content-Type", "application/json")
-            .send()
+            .send_with_retry()
             .await?;
 
         match response.status() {
@@ -183,26 +288,99 @@ impl BaserowClient {
         }
     }
 
+    /// Lists the fields of a table, to check the names wcm reads and writes exist.
+    pub async fn fetch_fields(&self, table_id: u64) -> Result<Vec<TableField>, BaserowError> {
+        let url = format!("{}/api/database/fields/table/{}/",
+            self.config.base_url.trim_end_matches('/'),
+            table_id
+        );
+
+        self.get_json(&url).await
+    }
+
+    /// Fetches every row of a table, following Baserow's pagination.
+    pub async fn fetch_all_rows<T>(&self, table_id: u64) -> Result<Vec<T>, BaserowError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut rows = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = format!("{}/api/database/rows/table/{}/?user_field_names=true&page={}&size={}",
+                self.config.base_url.trim_end_matches('/'),
+                table_id,
+                page,
+                MAX_PAGE_SIZE
+            );
+            let response: BaserowResponse<T> = self.get_json(&url).await?;
+            let last_page = response.next.is_none() || response.results.is_empty();
+            rows.extend(response.results);
+
+            if last_page {
+                return Ok(rows);
+            }
+            page += 1;
+        }
+    }
+
     pub async fn fetch_categories(&self) -> Result<Vec<Category>, BaserowError> {
-        println!("Fetching categories from Baserow...");
+        ui::detail("Fetching categories from Baserow...");
         
         let response: BaserowResponse<Category> = self
             .make_request(&self.config.categories_table_id.to_string())
             .await?;
 
-        println!("Found {} categories", response.results.len());
+        ui::detail(format!("Found {} categories", response.results.len()));
         Ok(response.results)
     }
 
     pub async fn fetch_storage_entries(&self) -> Result<Vec<Storage>, BaserowError> {
-        println!("Fetching storage entries from Baserow...");
+        ui::detail("Fetching storage entries from Baserow...");
         
-        let response: BaserowResponse<Storage> = self
-            .make_request(&self.config.storage_table_id.to_string())
+        let storage_entries: Vec<Storage> = self
+            .fetch_all_rows(self.config.storage_table_id)
             .await?;
 
-        println!("Found {} storage entries", response.results.len());
-        Ok(response.results)
+        ui::detail(format!("Found {} storage entries", storage_entries.len()));
+        Ok(storage_entries)
+    }
+
+    pub async fn fetch_media_page(&self, page: u32, size: u32) -> Result<BaserowResponse<MediaRow>, BaserowError> {
+        let url = format!("{}/api/database/rows/table/{}/?user_field_names=true&page={}&size={}", 
+            self.config.base_url.trim_end_matches('/'), 
+            self.config.media_table_id,
+            page,
+            size
+        );
+
+        self.get_json(&url).await
+    }
+
+    pub async fn fetch_all_media(&self) -> Result<Vec<MediaRow>, BaserowError> {
+        self.fetch_all_rows(self.config.media_table_id).await
+    }
+
+    /// Lists the media rows whose `Location` links to the given storage box.
+    pub async fn fetch_media_in_storage(&self, storage_id: u64, size: u32) -> Result<BaserowResponse<MediaRow>, BaserowError> {
+        let url = format!("{}/api/database/rows/table/{}/?user_field_names=true&size={}&filter__Location__link_row_has={}",
+            self.config.base_url.trim_end_matches('/'),
+            self.config.media_table_id,
+            size.clamp(1, MAX_PAGE_SIZE),
+            storage_id
+        );
+
+        self.get_json(&url).await
+    }
+
+    pub async fn fetch_media_row(&self, row_id: u64) -> Result<MediaRow, BaserowError> {
+        let url = format!("{}/api/database/rows/table/{}/{}/?user_field_names=true",
+            self.config.base_url.trim_end_matches('/'),
+            self.config.media_table_id,
+            row_id
+        );
+
+        self.get_json(&url).await
     }
 
     pub async fn find_storage_by_id(&self, storage_id: u64) -> Result<Option<Storage>, BaserowError> {
@@ -221,21 +399,21 @@ impl BaserowClient {
 
 
     pub async fn create_media_entry(&self, entry_data: MediaEntry) -> Result<CreatedEntry, BaserowError> {
-        println!("Creating new media entry in Baserow...");
+        ui::info("Creating new media entry in Baserow...");
         
         let url = format!("{}/api/database/rows/table/{}/?user_field_names=true", 
             self.config.base_url.trim_end_matches('/'), 
             self.config.media_table_id
         );
 
-        println!("Making request to: {}", url);
+        ui::detail(format!("Making request to: {}", url));
 
         let response = self.client
             .post(&url)
             .header("Authorization", format!("Token {}", self.config.api_token))
             .header("Content-Type", "application/json")
             .json(&entry_data)
-            .send()
+            .send_with_retry()
             .await?;
 
         let status = response.status();
@@ -251,10 +429,117 @@ impl BaserowClient {
         let created_entry: CreatedEntry = response.json().await
             .map_err(|e| BaserowError::InvalidResponse(e.to_string()))?;
 
-        println!("Successfully created entry with ID: {}", created_entry.id);
+        ui::detail(format!("Successfully created entry with ID: {}", created_entry.id));
         Ok(created_entry)
     }
 
+    /// Creates a row with the given fields in any table, such as a new author.
+    pub async fn create_row(&self, table_id: u64, fields: &serde_json::Value) -> Result<CreatedEntry, BaserowError> {
+        let url = format!("{}/api/database/rows/table/{}/?user_field_names=true",
+            self.config.base_url.trim_end_matches('/'),
+            table_id
+        );
+
+        ui::detail(format!("Making request to: {}", url));
+
+        let response = self.client
+            .post(&url)
+            .header("Authorization", format!("Token {}", self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(fields)
+            .send_with_retry()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => response.json().await
+                .map_err(|e| BaserowError::InvalidResponse(e.to_string())),
+            reqwest::StatusCode::UNAUTHORIZED => Err(BaserowError::AuthenticationFailed),
+            status => {
+                let error_text = response.text().await.unwrap_or_default();
+                Err(BaserowError::InvalidResponse(format!("Failed to create row in table {}: HTTP {} - {}", table_id, status, error_text)))
+            }
+        }
+    }
+
+    /// Changes the given fields of a media row, leaving the others as they are.
+    pub async fn update_media_row(&self, row_id: u64, fields: &serde_json::Value) -> Result<MediaRow, BaserowError> {
+        self.update_row(self.config.media_table_id, row_id, fields).await
+    }
+
+    /// Changes the given fields of a row in any table, leaving the others as they are.
+    pub async fn update_row<T>(&self, table_id: u64, row_id: u64, fields: &serde_json::Value) -> Result<T, BaserowError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = format!("{}/api/database/rows/table/{}/{}/?user_field_names=true",
+            self.config.base_url.trim_end_matches('/'),
+            table_id,
+            row_id
+        );
+
+        ui::detail(format!("Making request to: {}", url));
+
+        let response = self.client
+            .patch(&url)
+            .header("Authorization", format!("Token {}", self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(fields)
+            .send_with_retry()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => response.json().await
+                .map_err(|e| BaserowError::InvalidResponse(e.to_string())),
+            reqwest::StatusCode::UNAUTHORIZED => Err(BaserowError::AuthenticationFailed),
+            reqwest::StatusCode::NOT_FOUND => Err(BaserowError::NotFound),
+            status => {
+                let error_text = response.text().await.unwrap_or_default();
+                Err(BaserowError::InvalidResponse(format!("Failed to update row {}: HTTP {} - {}", row_id, status, error_text)))
+            }
+        }
+    }
+
+    /// Checks that the token may create and delete rows in the media table by
+    /// adding a blank row and deleting it again.
+    pub async fn check_write_access(&self) -> Result<(), BaserowError> {
+        let url = format!("{}/api/database/rows/table/{}/",
+            self.config.base_url.trim_end_matches('/'),
+            self.config.media_table_id
+        );
+
+        ui::detail(format!("Making request to: {}", url));
+
+        let response = self.client
+            .post(&url)
+            .header("Authorization", format!("Token {}", self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({}))
+            .send_with_retry()
+            .await?;
+
+        let created: CreatedEntry = match response.status() {
+            status if status.is_success() => response.json().await
+                .map_err(|e| BaserowError::InvalidResponse(e.to_string()))?,
+            reqwest::StatusCode::UNAUTHORIZED => return Err(BaserowError::AuthenticationFailed),
+            status => return Err(BaserowError::InvalidResponse(format!("Failed to create a row: HTTP {}", status))),
+        };
+
+        let response = self.client
+            .delete(format!("{}{}/", url, created.id))
+            .header("Authorization", format!("Token {}", self.config.api_token))
+            .send_with_retry()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(BaserowError::InvalidResponse(format!(
+                "Created test row {} but could not delete it: HTTP {}",
+                created.id,
+                status
+            ))),
+        }
+    }
+
     pub fn find_category_ids_by_names(&self, category_names: &[String], available_categories: &[Category]) -> Vec<u64> {
         let mut category_ids = Vec::new();
         
@@ -266,7 +551,7 @@ impl BaserowClient {
             }) {
                 category_ids.push(category.id);
             } else {
-                println!("Warning: Category '{}' not found in available categories", name);
+                ui::warn(format!("Warning: Category '{}' not found in available categories", name));
             }
         }
         
@@ -274,40 +559,37 @@ impl BaserowClient {
     }
 
     pub async fn test_connection(&self) -> Result<(), BaserowError> {
-        println!("Testing Baserow connection...");
+        ui::info("Testing Baserow connection...");
         
         let url = format!("{}/api/database/rows/table/{}/?user_field_names=true&size=1", 
             self.config.base_url.trim_end_matches('/'), 
             self.config.categories_table_id
         );
 
-        println!("Testing URL: {}", url);
+        ui::detail(format!("Testing URL: {}", url));
         
         let response = self.client
             .get(&url)
             .header("Authorization", format!("Token {}", self.config.api_token))
             .header("Content-Type", "application/json")
-            .send()
+            .send_with_retry()
             .await?;
 
         match response.status() {
-            reqwest::StatusCode::OK => {
-                println!("Baserow connection successful!");
-                Ok(())
-            }
+            reqwest::StatusCode::OK => Ok(()),
             reqwest::StatusCode::UNAUTHORIZED => {
-                println!("Authentication failed - check your API token");
+                ui::error("Authentication failed - check your API token");
                 Err(BaserowError::AuthenticationFailed)
             }
             reqwest::StatusCode::NOT_FOUND => {
-                println!("Categories table not found - check your table ID");
+                ui::error("Categories table not found - check your table ID");
                 Err(BaserowError::NotFound)
             }
             status => {
-                println!("Connection failed with status: {}", status);
+                ui::error(format!("Connection failed with status: {}", status));
                 let text = response.text().await.unwrap_or_default();
                 if !text.is_empty() {
-                    println!("Response body: {}", text);
+                    ui::error(format!("Response body: {}", text));
                 }
                 Err(BaserowError::InvalidResponse(format!("HTTP {}", status)))
             }
@@ -316,8 +598,15 @@ impl BaserowClient {
 
 
     pub async fn upload_file_direct(&self, image_data: Vec<u8>, filename: &str) -> Result<FileUploadResponse, BaserowError> {
-        println!("Uploading cover image file directly to Baserow...");
-        
+        ui::info("Uploading cover image file directly to Baserow...");
+
+        let upload_response = self.upload_file(image_data, filename).await?;
+        ui::info(format!("Successfully uploaded cover image file: {}", upload_response.name));
+        Ok(upload_response)
+    }
+
+    /// Uploads a file to Baserow's user files without printing progress.
+    pub async fn upload_file(&self, image_data: Vec<u8>, filename: &str) -> Result<FileUploadResponse, BaserowError> {
         let url = format!("{}/api/user-files/upload-file/", 
             self.config.base_url.trim_end_matches('/')
         );
@@ -343,16 +632,13 @@ impl BaserowClient {
             .post(&url)
             .header("Authorization", format!("Token {}", self.config.api_token))
             .multipart(form)
-            .send()
+            .send_with_retry()
             .await?;
 
         match response.status() {
             reqwest::StatusCode::OK => {
-                let upload_response: FileUploadResponse = response.json().await
-                    .map_err(|e| BaserowError::InvalidResponse(format!("Failed to parse upload response: {}", e)))?;
-                
-                println!("Successfully uploaded cover image file: {}", upload_response.name);
-                Ok(upload_response)
+                response.json().await
+                    .map_err(|e| BaserowError::InvalidResponse(format!("Failed to parse upload response: {}", e)))
             }
             reqwest::StatusCode::UNAUTHORIZED => Err(BaserowError::AuthenticationFailed),
             status => {
@@ -369,18 +655,19 @@ impl BaserowClient {
 
 pub fn display_categories(categories: &[Category]) {
     if categories.is_empty() {
-        println!("No categories found");
+        ui::show("No categories found");
         return;
     }
 
-    println!("\nAvailable categories:");
+    ui::show("");
+    ui::show(ui::paint(Role::Heading, "Available categories"));
     for (index, category) in categories.iter().enumerate() {
         let name = category.get_name().unwrap_or_else(|| format!("Category {}", category.id));
         let description = category.get_description()
             .map(|d| format!(" - {}", d))
             .unwrap_or_default();
         
-        println!("  {}. {}{}", index + 1, name, description);
+        ui::show(format!("  {}. {}{}", index + 1, name, description));
     }
-    println!();
+    ui::show("");
 }
\ No newline at end of file
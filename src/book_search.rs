@@ -1,10 +1,512 @@
 use async_trait::async_trait;
-use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use crate::compare::SourcedField;
+use crate::config::{AuthorFormatConfig, Config, EntryDefaults, TransliterationMethod};
+use crate::error::WcmError;
+use crate::filters::SearchFilters;
+use crate::identifiers::CatalogueId;
+use crate::last_search::{LastSearch, SavedResult};
+use crate::offline::OfflineCache;
+use crate::series::SeriesVolume;
+use crate::similar::{self, SimilarBook, SimilarSource};
+use crate::timing::{self, Stage};
+use crate::ui::{self, Role};
+use rust_i18n::t;
+use crate::rate_limit::SendWithRetry;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum BookResult {
     Google(crate::google_books::BookItem),
     OpenLibrary(crate::open_library::OpenLibraryBook),
+    Manual(ManualBook),
+}
+
+/// A book entered by hand with `wcm add --manual` or loaded from a saved draft,
+/// for items no metadata API knows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManualBook {
+    pub title: String,
+    pub author: String,
+    pub isbn: Option<String>,
+    pub cover_url: Option<String>,
+}
+
+/// What a book cost and where it was bought, from `--price`, `--purchased-at` and
+/// `--store`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Purchase {
+    pub price: Option<f64>,
+    /// `YYYY-MM-DD`
+    pub purchased_at: Option<String>,
+    pub store: Option<String>,
+}
+
+impl Purchase {
+    pub fn is_empty(&self) -> bool {
+        self.price.is_none() && self.purchased_at.is_none() && self.store.is_none()
+    }
+}
+
+/// Collector's details of the copy being added, from `--condition`,
+/// `--edition-note`, `--signed` and `--provenance`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CopyDetails {
+    pub condition: Option<Condition>,
+    /// Printing or edition, e.g. "First edition, third printing"
+    pub edition: Option<String>,
+    #[serde(default)]
+    pub signed: bool,
+    /// Where the copy came from, e.g. "Gift from Grandma" or "Secondhand shop"
+    pub provenance: Option<String>,
+}
+
+impl CopyDetails {
+    pub fn is_empty(&self) -> bool {
+        self.condition.is_none() && self.edition.is_none() && !self.signed && self.provenance.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Condition {
+    New,
+    Good,
+    Worn,
+}
+
+impl Condition {
+    pub const ALL: [Condition; 3] = [Condition::New, Condition::Good, Condition::Worn];
+
+    /// As written to `baserow.fields.condition` and given to `--condition`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Condition::New => "new",
+            Condition::Good => "good",
+            Condition::Worn => "worn",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|condition| condition.name().eq_ignore_ascii_case(value.trim()))
+    }
+
+    pub fn label(self) -> Cow<'static, str> {
+        match self {
+            Condition::New => t!("condition.new"),
+            Condition::Good => t!("condition.good"),
+            Condition::Worn => t!("condition.worn"),
+        }
+    }
+}
+
+/// A confirmed entry written by `--save-draft`, to be submitted later, possibly from
+/// another machine, with `--from-draft`. Categories are stored by name and the cover
+/// by URL, so nothing is uploaded until the draft is submitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedDraft {
+    pub title: String,
+    pub author: String,
+    pub isbn: Option<String>,
+    pub categories: Vec<String>,
+    pub synopsis: String,
+    pub is_ebook: bool,
+    pub status: u64,
+    /// Missing in drafts saved before these were configurable; `app.defaults` applies then
+    #[serde(default)]
+    pub read: Option<bool>,
+    #[serde(default)]
+    pub rating: Option<u32>,
+    #[serde(default)]
+    pub location: Vec<u64>,
+    #[serde(default)]
+    pub call_number: Option<String>,
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub volume_number: Option<u32>,
+    #[serde(default, skip_serializing_if = "Purchase::is_empty")]
+    pub purchase: Purchase,
+    #[serde(default)]
+    pub cover_url: Option<String>,
+    #[serde(default)]
+    pub publisher: Option<String>,
+    #[serde(default)]
+    pub pages: Option<u32>,
+    #[serde(default)]
+    pub content_warnings: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "CopyDetails::is_empty")]
+    pub copy: CopyDetails,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddedBook {
+    pub id: u64,
+    pub title: String,
+    pub author: String,
+    pub categories: Vec<String>,
+    pub synopsis: String,
+}
+
+impl AddedBook {
+    fn new(id: u64, prepared: &PreparedBook) -> Self {
+        Self {
+            id,
+            title: prepared.title.clone(),
+            author: prepared.author.clone(),
+            categories: prepared.categories.clone(),
+            synopsis: prepared.synopsis.clone(),
+        }
+    }
+}
+
+/// Matches category names given on the command line to the categories table,
+/// ignoring case, and returns them as the table spells them.
+fn pinned_categories(names: &[String], available: &[crate::baserow::Category]) -> Result<Vec<String>, WcmError> {
+    let mut matched = Vec::new();
+    let mut unknown = Vec::new();
+    for name in names {
+        let found = available.iter()
+            .filter_map(|category| category.get_name())
+            .find(|category| category.to_lowercase() == name.trim().to_lowercase());
+        match found {
+            Some(category) if !matched.contains(&category) => matched.push(category),
+            Some(_) => {}
+            None => unknown.push(name.as_str()),
+        }
+    }
+
+    if unknown.is_empty() {
+        Ok(matched)
+    } else {
+        Err(WcmError::NotFound(t!("error.unknown_categories", names = unknown.join(", ")).into_owned()))
+    }
+}
+
+/// Shows a Baserow status option in the current language.
+fn status_label(id: u64) -> Cow<'static, str> {
+    match id {
+        3028 => t!("status.in_place"),
+        3029 => t!("status.active"),
+        3030 => t!("status.on_loan"),
+        _ => t!("status.unknown"),
+    }
+}
+
+/// A line of the source menu: the field's value in the entry and the catalogue
+/// it came from.
+fn source_item(records: &crate::compare::SourceRecords, prepared: &PreparedBook, field: SourcedField) -> String {
+    let value = prepared.sourced_value(field);
+    let source = match records.source_of(field, value.as_deref()) {
+        Some(source) => Cow::Borrowed(source.name()),
+        None => t!("sources.neither"),
+    };
+    let shown = match (field, value) {
+        (_, None) => t!("compare.missing").into_owned(),
+        (SourcedField::Description, Some(value)) => t!("compare.words", count = value.split_whitespace().count()).into_owned(),
+        (SourcedField::Cover, Some(_)) => t!("sources.cover").into_owned(),
+        (_, Some(value)) if value.chars().count() > 50 => crate::text::truncate(&value, 47),
+        (_, Some(value)) => value,
+    };
+    t!("sources.item", field = field.label(), value = shown, source = source).into_owned()
+}
+
+/// Reports a book the CLI added, as the command result.
+fn report_added(added: &AddedBook) {
+    ui::result(ui::paint(Role::Success, t!("result.added", id = added.id)), added);
+}
+
+/// Compact, serializable view of a search result for API responses.
+#[derive(Debug, Serialize)]
+pub struct BookSummary {
+    pub title: String,
+    pub author: String,
+    pub published: Option<String>,
+    pub isbn: Option<String>,
+}
+
+impl From<&BookResult> for BookSummary {
+    fn from(book: &BookResult) -> Self {
+        Self {
+            title: book.get_full_title(),
+            author: book.get_all_authors(),
+            published: book.get_published_date(),
+            isbn: book.get_isbn(),
+        }
+    }
+}
+
+/// A book with its categories and synopsis decided, ready to be written to Baserow.
+/// Title, author and ISBN start out as the API's values and can be corrected before
+/// submitting.
+#[derive(Debug, Clone)]
+pub struct PreparedBook {
+    pub book: BookResult,
+    pub title: String,
+    pub author: String,
+    pub isbn: Option<String>,
+    /// Linked in `baserow.fields.publisher` when `publishers_table_id` is set
+    pub publisher: Option<String>,
+    /// Written to `baserow.fields.pages`
+    pub pages: Option<u32>,
+    pub categories: Vec<String>,
+    pub synopsis: String,
+    pub is_ebook: bool,
+    /// `Status` option ID, see `baserow::STATUS_OPTIONS`
+    pub status: u64,
+    pub read: bool,
+    /// 0 leaves the book unrated
+    pub rating: u32,
+    /// Storage rows the book is linked to; empty leaves the location to fill in later
+    pub location: Vec<u64>,
+    pub available_categories: Vec<crate::baserow::Category>,
+    pub call_number: Option<String>,
+    pub series: Option<String>,
+    /// The book's place in `series`
+    pub volume_number: Option<u32>,
+    pub purchase: Purchase,
+    pub copy: CopyDetails,
+    /// Set for magazine issues added with `wcm add magazine`
+    pub issue: Option<crate::periodicals::Issue>,
+    /// Row ID of the box set a volume added with `--boxset` belongs to
+    pub box_set: Option<u64>,
+    pub possible_duplicates: Vec<crate::duplicates::PossibleDuplicate>,
+    /// Detected with `language::book_language`, written to `baserow.fields.language`
+    pub language: Option<crate::language::Language>,
+    /// From the LLM when `content_warnings` is enabled; empty when it found none
+    pub content_warnings: Option<Vec<String>>,
+    /// Both catalogues' records of the book, set with `--compare` so the
+    /// confirmation menu can take each field from either
+    pub sources: Option<crate::compare::SourceRecords>,
+}
+
+impl PreparedBook {
+    pub fn new(
+        book: &BookResult,
+        categories: Vec<String>,
+        synopsis: String,
+        is_ebook: bool,
+        available_categories: Vec<crate::baserow::Category>,
+        defaults: &EntryDefaults,
+    ) -> Self {
+        Self {
+            book: book.clone(),
+            title: book.get_full_title(),
+            author: book.get_all_authors(),
+            isbn: book.get_isbn(),
+            publisher: book.get_publisher().filter(|publisher| !publisher.trim().is_empty()),
+            pages: book.get_page_count(),
+            categories,
+            synopsis,
+            is_ebook,
+            status: defaults.status,
+            read: defaults.read,
+            rating: defaults.rating,
+            location: Vec::new(),
+            available_categories,
+            call_number: None,
+            series: None,
+            volume_number: None,
+            purchase: Purchase::default(),
+            copy: CopyDetails::default(),
+            issue: None,
+            box_set: None,
+            language: crate::language::book_language(book),
+            content_warnings: None,
+            possible_duplicates: Vec::new(),
+            sources: None,
+        }
+    }
+
+    /// The entry's value of a field `sources` can set.
+    pub fn sourced_value(&self, field: SourcedField) -> Option<String> {
+        match field {
+            SourcedField::Title => Some(self.title.clone()),
+            SourcedField::Author => Some(self.author.clone()),
+            SourcedField::Isbn => self.isbn.clone(),
+            SourcedField::Publisher => self.publisher.clone(),
+            SourcedField::Pages => self.pages.map(|pages| pages.to_string()),
+            SourcedField::Description => Some(self.synopsis.clone()),
+            SourcedField::Cover => crate::merge::cover_url(&self.book),
+        }
+    }
+
+    /// Sets a field to a value from `SourceRecords::value`.
+    pub fn set_sourced_value(&mut self, field: SourcedField, value: String) {
+        match field {
+            SourcedField::Title => self.title = value,
+            SourcedField::Author => self.author = value,
+            SourcedField::Isbn => self.isbn = Some(value),
+            SourcedField::Publisher => self.publisher = Some(value),
+            SourcedField::Pages => self.pages = value.parse().ok(),
+            SourcedField::Description => self.synopsis = value,
+            SourcedField::Cover => crate::merge::set_cover(&mut self.book, value),
+        }
+    }
+}
+
+/// Serializable confirmation summary of a prepared book awaiting approval.
+#[derive(Debug, Serialize)]
+pub struct DraftSummary {
+    pub draft_id: u64,
+    pub title: String,
+    pub author: String,
+    pub isbn: Option<String>,
+    #[serde(rename = "type")]
+    pub media_type: String,
+    pub categories: Vec<String>,
+    pub synopsis: String,
+    pub call_number: Option<String>,
+    pub series: Option<String>,
+    pub volume_number: Option<u32>,
+    pub purchase: Purchase,
+    pub copy: CopyDetails,
+    pub language: Option<String>,
+    pub content_warnings: Option<Vec<String>>,
+    pub possible_duplicates: Vec<crate::duplicates::PossibleDuplicate>,
+}
+
+impl DraftSummary {
+    pub fn new(draft_id: u64, prepared: &PreparedBook) -> Self {
+        Self {
+            draft_id,
+            title: prepared.title.clone(),
+            author: prepared.author.clone(),
+            isbn: prepared.isbn.clone(),
+            media_type: if prepared.is_ebook { "Ebook" } else { "Physical Book" }.to_string(),
+            categories: prepared.categories.clone(),
+            synopsis: prepared.synopsis.clone(),
+            call_number: prepared.call_number.clone(),
+            series: prepared.series.clone(),
+            volume_number: prepared.volume_number,
+            purchase: prepared.purchase.clone(),
+            copy: prepared.copy.clone(),
+            language: prepared.language.as_ref().map(|language| language.code.clone()),
+            content_warnings: prepared.content_warnings.clone(),
+            possible_duplicates: prepared.possible_duplicates.clone(),
+        }
+    }
+}
+
+/// Prepared books waiting for the user to accept or cancel them, for front-ends
+/// where preparing and confirming happen in separate requests.
+#[derive(Debug, Default)]
+pub struct DraftStore {
+    drafts: Mutex<HashMap<u64, Draft>>,
+    next_id: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Draft {
+    prepared: PreparedBook,
+    /// The chat the draft was prepared for, when only that chat may take it
+    owner: Option<i64>,
+    created: std::time::Instant,
+}
+
+impl DraftStore {
+    /// How long a draft waits to be accepted or cancelled before it is dropped.
+    pub const TTL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+    /// The most drafts kept at once; the oldest is dropped to make room.
+    pub const MAX_DRAFTS: usize = 100;
+
+    pub fn insert(&self, prepared: PreparedBook) -> u64 {
+        self.store(prepared, None)
+    }
+
+    /// Like `insert`, for a draft only `take_for` with the same `owner` can take.
+    pub fn insert_for(&self, owner: i64, prepared: PreparedBook) -> u64 {
+        self.store(prepared, Some(owner))
+    }
+
+    pub fn take(&self, id: u64) -> Option<PreparedBook> {
+        self.remove(id, None)
+    }
+
+    /// Takes a draft stored with `insert_for(owner, ..)`; any other draft is left alone.
+    pub fn take_for(&self, owner: i64, id: u64) -> Option<PreparedBook> {
+        self.remove(id, Some(owner))
+    }
+
+    /// Drafts nobody answered would otherwise pile up for as long as the server runs,
+    /// so the expired ones, and then the oldest over the cap, are dropped first.
+    fn store(&self, prepared: PreparedBook, owner: Option<i64>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut drafts = self.drafts.lock().unwrap();
+        drafts.retain(|_, draft| draft.created.elapsed() < Self::TTL);
+        while drafts.len() >= Self::MAX_DRAFTS {
+            let Some(oldest) = drafts.keys().min().copied() else { break };
+            drafts.remove(&oldest);
+        }
+        drafts.insert(id, Draft { prepared, owner, created: std::time::Instant::now() });
+        id
+    }
+
+    fn remove(&self, id: u64, owner: Option<i64>) -> Option<PreparedBook> {
+        let mut drafts = self.drafts.lock().unwrap();
+        let draft = drafts.get(&id)?;
+        if draft.owner != owner || draft.created.elapsed() >= Self::TTL {
+            return None;
+        }
+        drafts.remove(&id).map(|draft| draft.prepared)
+    }
+}
+
+/// Fields of a prepared book that can be changed from the confirmation menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditableField {
+    Title,
+    Author,
+    Isbn,
+    Categories,
+    Synopsis,
+    Type,
+    Status,
+    Read,
+    Rating,
+    Series,
+    Location,
+    /// Only offered when both catalogues' records are at hand
+    Sources,
+}
+
+impl EditableField {
+    const ALL: [EditableField; 12] = [
+        EditableField::Title,
+        EditableField::Author,
+        EditableField::Isbn,
+        EditableField::Categories,
+        EditableField::Synopsis,
+        EditableField::Type,
+        EditableField::Status,
+        EditableField::Read,
+        EditableField::Rating,
+        EditableField::Series,
+        EditableField::Location,
+        EditableField::Sources,
+    ];
+
+    fn label(&self) -> Cow<'static, str> {
+        match self {
+            EditableField::Title => t!("field.title"),
+            EditableField::Author => t!("field.author"),
+            EditableField::Isbn => t!("field.isbn"),
+            EditableField::Categories => t!("field.categories"),
+            EditableField::Synopsis => t!("field.synopsis"),
+            EditableField::Type => t!("field.type"),
+            EditableField::Status => t!("field.status"),
+            EditableField::Read => t!("field.read"),
+            EditableField::Rating => t!("field.rating"),
+            EditableField::Series => t!("field.series"),
+            EditableField::Location => t!("field.location"),
+            EditableField::Sources => t!("field.sources"),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -18,6 +520,7 @@ impl BookResult {
         match self {
             BookResult::Google(book) => book.get_full_title(),
             BookResult::OpenLibrary(book) => book.get_full_title(),
+            BookResult::Manual(book) => book.title.clone(),
         }
     }
 
@@ -25,6 +528,16 @@ impl BookResult {
         match self {
             BookResult::Google(book) => book.get_all_authors(),
             BookResult::OpenLibrary(book) => book.get_all_authors(),
+            BookResult::Manual(book) => book.author.clone(),
+        }
+    }
+
+    /// Each author's name on its own, as the catalogue gave it.
+    pub fn get_authors(&self) -> Vec<String> {
+        match self {
+            BookResult::Google(book) => book.volume_info.authors.clone().unwrap_or_default(),
+            BookResult::OpenLibrary(book) => book.author_name.clone().unwrap_or_default(),
+            BookResult::Manual(book) => crate::authors::split_authors(&book.author),
         }
     }
 
@@ -34,7 +547,51 @@ impl BookResult {
             BookResult::OpenLibrary(book) => book.get_latest_publish_year()
                 .map(|y| y.to_string())
                 .or_else(|| book.get_latest_publish_date()),
+            BookResult::Manual(_) => None,
+        }
+    }
+
+    pub fn get_publisher(&self) -> Option<String> {
+        match self {
+            BookResult::Google(book) => book.volume_info.publisher.clone(),
+            BookResult::OpenLibrary(book) => book.get_primary_publisher(),
+            BookResult::Manual(_) => None,
+        }
+    }
+
+    pub fn get_isbn(&self) -> Option<String> {
+        match self {
+            BookResult::Google(book) => book.get_isbn_13().or_else(|| book.get_isbn_10()),
+            BookResult::OpenLibrary(book) => book.get_best_isbn(),
+            BookResult::Manual(book) => book.isbn.clone(),
+        }
+    }
+
+    pub fn get_description(&self) -> Option<String> {
+        match self {
+            BookResult::Google(book) => book.volume_info.description.as_deref().map(crate::text::strip_html),
+            BookResult::OpenLibrary(book) => book.description.as_deref().map(crate::text::strip_html),
+            BookResult::Manual(_) => None,
+        }
+        .filter(|description| !description.trim().is_empty())
+    }
+
+    pub fn get_page_count(&self) -> Option<u32> {
+        match self {
+            BookResult::Google(book) => book.volume_info.page_count,
+            BookResult::OpenLibrary(book) => book.number_of_pages_median,
+            BookResult::Manual(_) => None,
+        }
+        .filter(|pages| *pages > 0)
+    }
+
+    pub fn get_subjects(&self) -> Vec<String> {
+        match self {
+            BookResult::Google(book) => book.volume_info.categories.clone(),
+            BookResult::OpenLibrary(book) => book.subject.clone(),
+            BookResult::Manual(_) => None,
         }
+        .unwrap_or_default()
     }
 
     pub fn display_info(&self, config: &Config) -> tokio::task::JoinHandle<()> {
@@ -53,6 +610,19 @@ impl BookResult {
                     crate::open_library::display_open_library_book_info(&book, &config).await;
                 })
             }
+            BookResult::Manual(book) => {
+                let book = book.clone();
+                tokio::spawn(async move {
+                    let rows = vec![
+                        ("Title".to_string(), book.title),
+                        ("Author(s)".to_string(), book.author),
+                    ];
+                    ui::info("");
+                    ui::info(ui::paint(Role::Heading, "Book Information (entered by hand)"));
+                    ui::info(ui::fields(&rows));
+                    ui::info("");
+                })
+            }
         }
     }
 }
@@ -61,18 +631,18 @@ pub fn interactive_select_book(results: &SearchResults) -> Result<Option<&BookRe
     use dialoguer::{Select, theme::ColorfulTheme};
 
     let items: Vec<String> = results.books.iter().map(|book| {
-        format!("{} by {} ({})", 
-            book.get_full_title(), 
-            book.get_all_authors(),
-            book.get_published_date().unwrap_or_else(|| "Unknown year".to_string())
-        )
+        t!("select.item",
+            title = book.get_full_title(),
+            author = book.get_all_authors(),
+            year = book.get_published_date().unwrap_or_else(|| t!("select.unknown_year").into_owned())
+        ).into_owned()
     }).collect();
     
     let mut items_with_cancel = items;
-    items_with_cancel.push("Cancel - don't add any book".to_string());
+    items_with_cancel.push(t!("select.cancel").into_owned());
     
     let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a book to add")
+        .with_prompt(t!("select.prompt"))
         .items(&items_with_cancel)
         .default(0)
         .interact()?;
@@ -85,10 +655,55 @@ pub fn interactive_select_book(results: &SearchResults) -> Result<Option<&BookRe
     }
 }
 
+/// A search by title, from the exact query that was asked for to the looser
+/// ones tried when it finds nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TitleQuery {
+    TitleAuthor(String, String),
+    Title(String),
+    /// Title and author as plain words, without field syntax such as `intitle:`
+    Keywords(String),
+}
+
+impl TitleQuery {
+    /// The queries to try in turn for `title` and `author`, each but the first with
+    /// a note on how it was relaxed: without the subtitle, with the author's name in
+    /// Latin letters, as plain keywords, and finally by title alone. Without an
+    /// author only the subtitle and the field syntax are dropped.
+    pub fn widened(title: &str, author: Option<&str>) -> Vec<(TitleQuery, Option<Cow<'static, str>>)> {
+        let title = title.trim();
+        let main_title = title.split([':', '(']).next().unwrap_or(title).split(" - ").next().unwrap_or(title).trim();
+        let main_title = if main_title.is_empty() { title } else { main_title };
+
+        let mut queries: Vec<(TitleQuery, Option<Cow<'static, str>>)> = match author.map(str::trim).filter(|author| !author.is_empty()) {
+            Some(author) => vec![
+                (TitleQuery::TitleAuthor(title.to_string(), author.to_string()), None),
+                (TitleQuery::TitleAuthor(main_title.to_string(), author.to_string()), Some(t!("search.without_subtitle"))),
+                (TitleQuery::TitleAuthor(main_title.to_string(), deunicode::deunicode(author)), Some(t!("search.transliterated_author"))),
+                (TitleQuery::Keywords(format!("{} {}", main_title, author)), Some(t!("search.keywords"))),
+                (TitleQuery::Title(main_title.to_string()), Some(t!("search.title_only"))),
+            ],
+            None => vec![
+                (TitleQuery::Title(title.to_string()), None),
+                (TitleQuery::Title(main_title.to_string()), Some(t!("search.without_subtitle"))),
+                (TitleQuery::Keywords(main_title.to_string()), Some(t!("search.keywords"))),
+            ],
+        };
+        // A relaxation that changes nothing for this title would repeat a query
+        let mut seen = Vec::new();
+        queries.retain(|(query, _)| {
+            let new = !seen.contains(query);
+            seen.push(query.clone());
+            new
+        });
+        queries
+    }
+}
+
 #[async_trait]
 pub trait BookSearcher {
     async fn search_by_isbn(&self, isbn: &str) -> Result<SearchResults, Box<dyn std::error::Error>>;
-    async fn search_by_title_author(&self, title: &str, author: &str) -> Result<SearchResults, Box<dyn std::error::Error>>;
+    async fn search_by_title(&self, query: &TitleQuery) -> Result<SearchResults, Box<dyn std::error::Error>>;
 }
 
 #[async_trait]
@@ -106,8 +721,12 @@ impl BookSearcher for crate::google_books::GoogleBooksClient {
         })
     }
 
-    async fn search_by_title_author(&self, title: &str, author: &str) -> Result<SearchResults, Box<dyn std::error::Error>> {
-        let response = self.search_by_title_author(title, author).await?;
+    async fn search_by_title(&self, query: &TitleQuery) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let response = match query {
+            TitleQuery::TitleAuthor(title, author) => self.search_by_title_author(title, author).await?,
+            TitleQuery::Title(title) => self.search_by_title(title).await?,
+            TitleQuery::Keywords(keywords) => self.search_by_keywords(keywords).await?,
+        };
         let books = response.items.unwrap_or_default()
             .into_iter()
             .map(BookResult::Google)
@@ -135,8 +754,12 @@ impl BookSearcher for crate::open_library::OpenLibraryClient {
         })
     }
 
-    async fn search_by_title_author(&self, title: &str, author: &str) -> Result<SearchResults, Box<dyn std::error::Error>> {
-        let response = self.search_by_title_author(title, author).await?;
+    async fn search_by_title(&self, query: &TitleQuery) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let response = match query {
+            TitleQuery::TitleAuthor(title, author) => self.search_by_title_author(title, author).await?,
+            TitleQuery::Title(title) => self.search_by_title(title).await?,
+            TitleQuery::Keywords(keywords) => self.search_by_keywords(keywords).await?,
+        };
         let books = response.docs
             .into_iter()
             .map(BookResult::OpenLibrary)
@@ -153,6 +776,33 @@ pub struct CombinedBookSearcher {
     google_client: crate::google_books::GoogleBooksClient,
     open_library_client: crate::open_library::OpenLibraryClient,
     baserow_client: crate::baserow::BaserowClient,
+    loc_client: crate::loc::LocClient,
+    classifier: crate::classification::Classifier,
+    http_client: reqwest::Client,
+    draft_output: Option<PathBuf>,
+    filters: SearchFilters,
+    /// Categories from `--category`, used instead of asking the LLM
+    categories: Vec<String>,
+    /// Synopsis from `--synopsis` or `--synopsis-file`, stored as given
+    synopsis: Option<String>,
+    /// Cleared by `--no-cover` to leave covers for later
+    covers: bool,
+    /// Series and volume from `--series` and `--volume-number`, used over detected ones
+    series: Option<String>,
+    volume_number: Option<u32>,
+    /// ASIN from `--asin`, stored in `baserow.fields.asin`
+    asin: Option<String>,
+    purchase: Purchase,
+    copy: CopyDetails,
+    /// Set by `--compare` to search both catalogues and show them side by side
+    compare: bool,
+    /// Set by `--boxset` to the volume titles from `--volume`, asked for when empty
+    boxset: Option<Vec<String>>,
+    /// Set by `--edit` to review the entry as YAML in `$EDITOR` instead of the menu
+    edit: bool,
+    /// Set by `--offline` to work from the caches and queue the entry for `wcm retry`
+    offline: bool,
+    offline_cache: Option<OfflineCache>,
     config: Config,
 }
 
@@ -167,397 +817,1985 @@ impl CombinedBookSearcher {
             google_client,
             open_library_client,
             baserow_client,
+            loc_client: crate::loc::LocClient::new(config.classification.loc_base_url.clone()),
+            classifier: crate::classification::Classifier::new(config.clone()),
+            http_client: reqwest::Client::new(),
+            draft_output: None,
+            filters: SearchFilters::default(),
+            categories: Vec::new(),
+            synopsis: None,
+            covers: true,
+            series: None,
+            volume_number: None,
+            asin: None,
+            purchase: Purchase::default(),
+            copy: CopyDetails::default(),
+            compare: false,
+            boxset: None,
+            edit: false,
+            offline: false,
+            offline_cache: OfflineCache::from_config(&config.app),
             config,
         }
     }
 
-    pub async fn search_by_isbn(&self, isbn: &str, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
-        if self.config.app.verbose {
-            println!("Fetching book data from Google Books API...");
+    /// Uses `client` for every request the searcher makes: catalogue lookups,
+    /// cover downloads, LLM calls and Baserow, except for Baserow and Ollama
+    /// servers configured with `accept_invalid_certs`.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.google_client = self.google_client.with_http_client(client.clone());
+        self.open_library_client = self.open_library_client.with_http_client(client.clone());
+        // A Baserow server with a self-signed certificate keeps the client that accepts it
+        if !self.config.baserow.accept_invalid_certs {
+            self.baserow_client = self.baserow_client.with_http_client(client.clone());
         }
+        self.classifier = self.classifier.with_http_client(client.clone());
+        self.loc_client = self.loc_client.with_http_client(client.clone());
+        self.http_client = client;
+        self
+    }
+
+    /// The client passed to `with_http_client`, for services outside the searcher
+    /// such as the Telegram Bot API.
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    /// Narrows title and series searches; ISBN lookups are not filtered.
+    pub fn with_filters(mut self, filters: SearchFilters) -> Self {
+        self.google_client = self.google_client.with_filters(filters.clone());
+        self.open_library_client = self.open_library_client.with_filters(filters.clone());
+        self.filters = filters;
+        self
+    }
+
+    /// Files books under these categories instead of asking the LLM. The names are
+    /// matched against the categories table when a book is added.
+    pub fn with_categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Stores this synopsis verbatim, skipping the length check and the LLM. A
+    /// blank one is ignored.
+    pub fn with_synopsis(mut self, synopsis: Option<String>) -> Self {
+        self.synopsis = synopsis.map(|synopsis| synopsis.trim().to_string()).filter(|synopsis| !synopsis.is_empty());
+        self
+    }
+
+    /// Whether covers are looked up and uploaded; without them entries are created
+    /// with an empty cover and drafts are saved without a cover URL.
+    pub fn with_covers(mut self, covers: bool) -> Self {
+        self.covers = covers;
+        self
+    }
+
+    /// Records every book as part of this series, or as this volume, in place of
+    /// what `wcm search --series` detects.
+    pub fn with_series(mut self, series: Option<String>, volume_number: Option<u32>) -> Self {
+        self.series = series.map(|series| series.trim().to_string()).filter(|series| !series.is_empty());
+        self.volume_number = volume_number;
+        self
+    }
+
+    /// Records the ASIN the book was added by.
+    pub fn with_asin(mut self, asin: Option<String>) -> Self {
+        self.asin = asin;
+        self
+    }
+
+    /// Searches Google Books and Open Library both, printing their records of the
+    /// book side by side and offering the results of both to pick from.
+    pub fn with_compare(mut self, compare: bool) -> Self {
+        self.compare = compare;
+        self
+    }
+
+    /// Opens the prepared entry as YAML in `$EDITOR` and submits it as saved,
+    /// instead of the confirmation menu.
+    pub fn with_edit(mut self, edit: bool) -> Self {
+        self.edit = edit;
+        self
+    }
+
+    /// Works from the caches alone: the catalogue clients must have been given
+    /// an offline `HttpCache`, categories, LLM results and covers come from
+    /// `app.cache_dir`, the checks that need the library or the network are
+    /// skipped, and the entry is queued in `app.retry_dir` instead of created.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Adds the book as a box set, followed by an entry for each volume in it
+    /// linked to the box set's row. The volume titles are asked for when none
+    /// are given.
+    pub fn with_boxset(mut self, volumes: Option<Vec<String>>) -> Self {
+        self.boxset = volumes;
+        self
+    }
+
+    /// Records this purchase on every book added.
+    pub fn with_purchase(mut self, purchase: Purchase) -> Self {
+        self.purchase = purchase;
+        self
+    }
+
+    /// Records these details of the copy on every book added.
+    pub fn with_copy_details(mut self, copy: CopyDetails) -> Self {
+        self.copy = copy;
+        self
+    }
+
+    /// Starts new entries with these values instead of `app.defaults`, e.g. for `--read`.
+    pub fn with_entry_defaults(mut self, defaults: EntryDefaults) -> Self {
+        self.config.app.defaults = defaults;
+        self
+    }
+
+    /// Writes confirmed books to this file as a draft instead of adding them to Baserow.
+    pub fn with_draft_output(mut self, path: Option<PathBuf>) -> Self {
+        self.draft_output = path;
+        self
+    }
+
+    pub async fn find_by_isbn(&self, isbn: &str) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        ui::detail("Fetching book data from Google Books API...");
         
         // Try Google Books first
-        match BookSearcher::search_by_isbn(&self.google_client, isbn).await {
-            Ok(results) if !results.books.is_empty() => {
-                return self.handle_search_results(results, isbn, is_ebook).await;
-            }
+        match timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.google_client, isbn)).await {
+            Ok(results) if !results.books.is_empty() => return Ok(results),
             Ok(_) => {
-                if self.config.app.verbose {
-                    println!("No results from Google Books API, trying Open Library...");
-                }
+                ui::detail("No results from Google Books API, trying Open Library...");
             }
             Err(e) => {
-                if self.config.app.verbose {
-                    println!("Google Books API error: {}, trying Open Library...", e);
-                }
+                ui::detail(format!("Google Books API error: {}, trying Open Library...", e));
             }
         }
         
         // Fallback to Open Library
-        if self.config.app.verbose {
-            println!("Fetching book data from Open Library API...");
-        }
-        
-        let results = BookSearcher::search_by_isbn(&self.open_library_client, isbn).await?;
-        
-        if results.books.is_empty() {
-            println!("No books found for ISBN: {} in either Google Books or Open Library", isbn);
-            return Ok(None);
-        }
+        ui::detail("Fetching book data from Open Library API...");
         
-        self.handle_search_results(results, isbn, is_ebook).await
+        timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.open_library_client, isbn)).await
     }
 
-    pub async fn search_by_title_author(&self, title: &str, author: &str, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
-        if self.config.app.verbose {
-            println!("Searching for books on Google Books API...");
+    /// Searches by title and author, relaxing the query step by step while nothing
+    /// is found (see `TitleQuery::widened`). Results of a relaxed query have the
+    /// relaxation noted in their source.
+    pub async fn find_by_title_author(&self, title: &str, author: Option<&str>) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let mut results = SearchResults { books: Vec::new(), source: String::new() };
+        for (query, relaxation) in TitleQuery::widened(title, author) {
+            if let Some(relaxation) = &relaxation {
+                ui::detail(format!("No books found, searching again {}...", relaxation));
+            }
+            results = self.find_by_title_query(&query).await?;
+            if !results.books.is_empty() {
+                if let Some(relaxation) = relaxation {
+                    ui::info(t!("search.widened", how = relaxation));
+                    results.source = format!("{} ({})", results.source, relaxation);
+                }
+                break;
+            }
         }
+        Ok(results)
+    }
+
+    async fn find_by_title_query(&self, query: &TitleQuery) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        ui::detail("Searching for books on Google Books API...");
         
         // Try Google Books first
-        match BookSearcher::search_by_title_author(&self.google_client, title, author).await {
-            Ok(results) if !results.books.is_empty() => {
-                return self.handle_search_results(results, &format!("title: '{}', author: '{}'", title, author), is_ebook).await;
-            }
+        match timing::time(Stage::Metadata, BookSearcher::search_by_title(&self.google_client, query)).await.map(|results| self.apply_filters(results)) {
+            Ok(results) if !results.books.is_empty() => return Ok(results),
             Ok(_) => {
-                if self.config.app.verbose {
-                    println!("No results from Google Books API, trying Open Library...");
-                }
+                ui::detail("No results from Google Books API, trying Open Library...");
             }
             Err(e) => {
-                if self.config.app.verbose {
-                    println!("Google Books API error: {}, trying Open Library...", e);
-                }
+                ui::detail(format!("Google Books API error: {}, trying Open Library...", e));
             }
         }
         
         // Fallback to Open Library
-        if self.config.app.verbose {
-            println!("Searching for books on Open Library API...");
-        }
-        
-        let results = BookSearcher::search_by_title_author(&self.open_library_client, title, author).await?;
+        ui::detail("Searching for books on Open Library API...");
         
-        if results.books.is_empty() {
-            println!("No books found for title: '{}' and author: '{}' in either Google Books or Open Library", title, author);
-            return Ok(None);
-        }
-        
-        self.handle_search_results(results, &format!("title: '{}', author: '{}'", title, author), is_ebook).await
+        timing::time(Stage::Metadata, BookSearcher::search_by_title(&self.open_library_client, query)).await
+            .map(|results| self.apply_filters(results))
     }
 
-    async fn handle_search_results(&self, results: SearchResults, search_query: &str, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
-        let selected_book = if results.books.len() > 1 {
-            // Limit to max_search_results for display
-            let display_books = if results.books.len() > self.config.app.max_search_results {
-                &results.books[..self.config.app.max_search_results]
-            } else {
-                &results.books
-            };
-            
-            let truncated_results = SearchResults {
-                books: display_books.to_vec(),
-                source: results.source.clone(),
-            };
-            
-            println!("Found {} books from {} for {} (showing top {}):", 
-                results.books.len(), results.source, search_query, display_books.len());
-            
-            match interactive_select_book(&truncated_results) {
-                Ok(Some(selected_book)) => Some(selected_book.clone()),
-                Ok(None) => {
-                    println!("No book selected.");
-                    return Ok(None);
-                }
-                Err(e) => {
-                    if self.config.app.verbose {
-                        println!("Error in interactive selection: {}", e);
-                    }
-                    // Fall through to show first result
-                    results.books.first().cloned()
-                }
+    /// `find_by_title_author` for `--compare`: each query goes to both catalogues,
+    /// and the search is only relaxed while neither finds anything.
+    async fn compare_by_title_author(&self, title: &str, author: Option<&str>) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let mut results = SearchResults { books: Vec::new(), source: String::new() };
+        for (query, relaxation) in TitleQuery::widened(title, author) {
+            if let Some(relaxation) = &relaxation {
+                ui::detail(format!("No books found, searching again {}...", relaxation));
             }
-        } else {
-            results.books.first().cloned()
-        };
-        
-        if let Some(book) = selected_book {
-            // Display book information
-            let handle = book.display_info(&self.config);
-            handle.await?;
-            
-            // Fetch categories from Baserow
-            match self.baserow_client.fetch_categories().await {
-                Ok(categories) => {
-                    if !categories.is_empty() {
-                        if self.config.app.verbose {
-                            crate::baserow::display_categories(&categories);
-                        }
-                        
-                        // Perform LLM-powered category selection
-                        match self.select_categories_with_llm(&book, &categories).await {
-                            Ok(selected_categories) => {
-                                println!("Selected categories: {}", selected_categories.join(", "));
-                                
-                                // Check if synopsis needs to be generated
-                                let final_synopsis = match self.generate_synopsis_if_needed(&book).await {
-                                    Ok(Some(synopsis)) => {
-                                        println!("\n=== Generated Synopsis ===");
-                                        println!("{}", synopsis);
-                                        println!("========================\n");
-                                        synopsis
-                                    }
-                                    Ok(None) => {
-                                        if self.config.app.verbose {
-                                            println!("Existing synopsis is sufficient, no LLM generation needed.");
-                                        }
-                                        // Use existing description as synopsis
-                                        match &book {
-                                            BookResult::Google(google_book) => {
-                                                google_book.volume_info.description.as_deref().unwrap_or("No description available").to_string()
-                                            }
-                                            BookResult::OpenLibrary(_) => "No description available".to_string(),
-                                        }
-                                    }
-                                    Err(e) => {
-                                        eprintln!("Failed to generate synopsis: {}", e);
-                                        // Use existing description as fallback
-                                        match &book {
-                                            BookResult::Google(google_book) => {
-                                                google_book.volume_info.description.as_deref().unwrap_or("No description available").to_string()
-                                            }
-                                            BookResult::OpenLibrary(_) => "No description available".to_string(),
-                                        }
-                                    }
-                                };
-                                
-                                // Display pre-flight confirmation
-                                if !self.show_preflight_confirmation(&book, &selected_categories, &final_synopsis, is_ebook)? {
-                                    println!("Operation cancelled by user.");
-                                    return Ok(Some(book));
-                                }
-                                
-                                // Handle cover image upload after confirmation
-                                let cover_images = self.handle_cover_image_upload(&book).await;
-                                
-                                // Create Baserow entry with all the collected data
-                                match self.create_baserow_entry(&book, &selected_categories, &final_synopsis, &categories, is_ebook, cover_images).await {
-                                    Ok(entry_id) => {
-                                        println!("✅ Successfully added book to library! Entry ID: {}", entry_id);
-                                    }
-                                    Err(e) => {
-                                        eprintln!("❌ Failed to create Baserow entry: {}", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to select categories with LLM: {}", e);
-                                println!("Available categories:");
-                                crate::baserow::display_categories(&categories);
-                            }
-                        }
-                    } else {
-                        println!("No categories found in Baserow table.");
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to fetch categories from Baserow: {}", e);
-                    if self.config.app.verbose {
-                        eprintln!("Make sure your Baserow API token and categories table ID are correct.");
-                    }
+            ui::detail("Searching for books on Google Books and Open Library...");
+            let (google, open_library) = tokio::join!(
+                timing::time(Stage::Metadata, BookSearcher::search_by_title(&self.google_client, &query)),
+                timing::time(Stage::Metadata, BookSearcher::search_by_title(&self.open_library_client, &query)),
+            );
+            results = self.compare_results(google.map(|results| self.apply_filters(results)), open_library.map(|results| self.apply_filters(results))).await?;
+            if !results.books.is_empty() {
+                if let Some(relaxation) = relaxation {
+                    ui::info(t!("search.widened", how = relaxation));
+                    results.source = format!("{} ({})", results.source, relaxation);
                 }
+                break;
             }
-            
-            return Ok(Some(book));
         }
-        
-        Ok(None)
+        Ok(results)
     }
 
-    async fn select_categories_with_llm(
+    /// Prints the comparison table for the book both catalogues found, then
+    /// returns the results of both, taking turns so that either catalogue's best
+    /// results are among those offered for selection. One catalogue failing
+    /// is not an error as long as the other answers.
+    async fn compare_results(
         &self,
-        book: &BookResult,
-        categories: &[crate::baserow::Category],
-    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        if self.config.app.verbose {
-            println!("Enhancing book information with web search...");
-        }
-
+        google: Result<SearchResults, Box<dyn std::error::Error>>,
+        open_library: Result<SearchResults, Box<dyn std::error::Error>>,
+    ) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let (google, open_library) = match (google, open_library) {
+            (Ok(google), Ok(open_library)) => (google, open_library),
+            (Ok(google), Err(e)) => {
+                ui::detail(format!("Open Library API error: {}", e));
+                (google, SearchResults { books: Vec::new(), source: "Open Library".to_string() })
+            }
+            (Err(e), Ok(open_library)) => {
+                ui::detail(format!("Google Books API error: {}", e));
+                (SearchResults { books: Vec::new(), source: "Google Books".to_string() }, open_library)
+            }
+            (Err(e), Err(_)) => return Err(e),
+        };
+        if google.books.is_empty() && open_library.books.is_empty() {
+            return Ok(google);
+        }
+
+        match crate::compare::pair(&google.books, &open_library.books) {
+            Some((item, ol_book)) => {
+                let isbn = ol_book.get_best_isbn().or_else(|| item.get_isbn_13()).or_else(|| item.get_isbn_10());
+                let edition = match isbn {
+                    Some(isbn) => timing::time(Stage::Metadata, self.open_library_client.get_edition_by_isbn(&isbn)).await
+                        .inspect_err(|e| ui::detail(format!("Open Library edition lookup failed: {}", e)))
+                        .ok(),
+                    None => None,
+                };
+                let google_cover = crate::merge::google_cover(item);
+                let ol_cover = ol_book.get_cover_url().or_else(|| edition.as_ref()?.get_cover_url());
+                let mut covers = [None, None];
+                for (size, url) in covers.iter_mut().zip([google_cover, ol_cover]) {
+                    if let Some(url) = url.filter(|_| self.covers) {
+                        *size = self.cover_dimensions(&url).await;
+                    }
+                }
+                let fields = crate::compare::compare(item, ol_book, edition.as_ref(), covers);
+                ui::show("");
+                ui::show(ui::paint(Role::Heading, t!("compare.heading")));
+                ui::show(crate::compare::format_table(&fields));
+                ui::show("");
+            }
+            None => ui::info(t!("compare.no_pair")),
+        }
+
+        let source = match (google.books.is_empty(), open_library.books.is_empty()) {
+            (false, false) => format!("{} and {}", google.source, open_library.source),
+            (false, true) => google.source,
+            _ => open_library.source,
+        };
+        let mut books = Vec::with_capacity(google.books.len() + open_library.books.len());
+        let mut google_books = google.books.into_iter();
+        let mut open_library_books = open_library.books.into_iter();
+        loop {
+            let next = [google_books.next(), open_library_books.next()];
+            if next.iter().all(Option::is_none) {
+                break;
+            }
+            books.extend(next.into_iter().flatten());
+        }
+        Ok(SearchResults { books, source })
+    }
+
+    /// Drops the results `--year`, `--publisher` and `--lang` rule out.
+    fn apply_filters(&self, mut results: SearchResults) -> SearchResults {
+        if !self.filters.is_empty() {
+            let found = results.books.len();
+            results.books.retain(|book| self.filters.matches(book));
+            ui::detail(format!("{} of {} results from {} match the filters", results.books.len(), found, results.source));
+        }
+        results
+    }
+
+    pub async fn search_by_isbn(&self, isbn: &str, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
+        let results = if self.compare {
+            ui::detail("Fetching book data from Google Books and Open Library...");
+            let (google, open_library) = tokio::join!(
+                timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.google_client, isbn)),
+                timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.open_library_client, isbn)),
+            );
+            self.compare_results(google, open_library).await?
+        } else {
+            self.find_by_isbn(isbn).await?
+        };
+        
+        if results.books.is_empty() {
+            return Err(WcmError::NotFound(t!("error.no_books_isbn", isbn = isbn).into_owned()).into());
+        }
+        
+        self.handle_search_results(results, isbn, is_ebook).await
+    }
+
+    /// Finds a book by LCCN or OCLC number in Open Library, which indexes both. An
+    /// LCCN Open Library does not know is looked up in the Library of Congress
+    /// catalogue, which gives the title and author but no cover or synopsis.
+    pub async fn find_by_catalogue_id(&self, id: &CatalogueId) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        ui::detail(format!("Searching Open Library for {}...", id));
+        let response = match id {
+            CatalogueId::Lccn(lccn) => timing::time(Stage::Metadata, self.open_library_client.search_by_lccn(lccn)).await,
+            CatalogueId::Oclc(oclc) => timing::time(Stage::Metadata, self.open_library_client.search_by_oclc(oclc)).await,
+        };
+        match response {
+            Ok(response) if !response.docs.is_empty() => {
+                return Ok(SearchResults {
+                    books: response.docs.into_iter().map(BookResult::OpenLibrary).collect(),
+                    source: "Open Library".to_string(),
+                });
+            }
+            Ok(_) => ui::detail(format!("Open Library has no edition with {}", id)),
+            Err(e) => ui::detail(format!("Open Library API error: {}", e)),
+        }
+
+        let CatalogueId::Lccn(lccn) = id else {
+            return Ok(SearchResults { books: Vec::new(), source: "Open Library".to_string() });
+        };
+        if self.offline {
+            return Err(WcmError::NotFound(t!("offline.no_loc").into_owned()).into());
+        }
+        ui::detail("Looking the LCCN up in the Library of Congress catalogue...");
+        let book = timing::time(Stage::Metadata, self.loc_client.find_by_lccn(lccn)).await?;
+        Ok(SearchResults {
+            books: book.into_iter().map(BookResult::Manual).collect(),
+            source: "Library of Congress".to_string(),
+        })
+    }
+
+    /// Finds a book by Amazon ASIN. An ASIN that is an ISBN-10, as for printed
+    /// books, is looked up as an ISBN; a Kindle ASIN is looked up in Open Library,
+    /// which records the ASINs of many editions.
+    pub async fn find_by_asin(&self, asin: &str) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        if let Some(isbn) = crate::identifiers::asin_as_isbn(asin) {
+            ui::detail(format!("ASIN {} is an ISBN-10, searching by ISBN", asin));
+            return self.find_by_isbn(&isbn).await;
+        }
+
+        ui::detail(format!("Searching Open Library for ASIN {}...", asin));
+        let response = timing::time(Stage::Metadata, self.open_library_client.search_by_asin(asin)).await?;
+        Ok(SearchResults {
+            books: response.docs.into_iter().map(BookResult::OpenLibrary).collect(),
+            source: "Open Library".to_string(),
+        })
+    }
+
+    /// Adds a book by ASIN, always as an ebook.
+    pub async fn search_by_asin(&self, asin: &str, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
+        let results = self.find_by_asin(asin).await?;
+
+        if results.books.is_empty() {
+            return Err(WcmError::NotFound(t!("error.no_books_asin", asin = asin).into_owned()).into());
+        }
+
+        self.handle_search_results(results, &format!("ASIN {}", asin), is_ebook).await
+    }
+
+    pub async fn search_by_catalogue_id(&self, id: &CatalogueId, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
+        let results = self.find_by_catalogue_id(id).await?;
+
+        if results.books.is_empty() {
+            return Err(WcmError::NotFound(t!("error.no_books_catalogue_id", id = id.to_string()).into_owned()).into());
+        }
+
+        self.handle_search_results(results, &id.to_string(), is_ebook).await
+    }
+
+    pub async fn search_by_title_author(&self, title: &str, author: Option<&str>, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
+        let results = if self.compare {
+            self.compare_by_title_author(title, author).await?
+        } else {
+            self.find_by_title_author(title, author).await?
+        };
+        
+        if results.books.is_empty() {
+            let message = match author {
+                Some(author) => t!("error.no_books_title", title = title, author = author),
+                None => t!("error.no_books_title_only", title = title),
+            };
+            return Err(WcmError::NotFound(message.into_owned()).into());
+        }
+        
+        let query = match author {
+            Some(author) => format!("title: '{}', author: '{}'", title, author),
+            None => format!("title: '{}'", title),
+        };
+        self.handle_search_results(results, &query, is_ebook).await
+    }
+
+    /// Searches by title and author like `wcm add --title`, but only lists the
+    /// results and keeps them for `wcm add --from-last`.
+    pub async fn search_and_remember(&self, title: &str, author: Option<&str>) -> Result<LastSearch, Box<dyn std::error::Error>> {
+        let results = self.find_by_title_author(title, author).await?;
+        if results.books.is_empty() {
+            let message = match author {
+                Some(author) => t!("error.no_books_title", title = title, author = author),
+                None => t!("error.no_books_title_only", title = title),
+            };
+            return Err(WcmError::NotFound(message.into_owned()).into());
+        }
+        let query = match author {
+            Some(author) => format!("{} / {}", title, author),
+            None => title.to_string(),
+        };
+        let saved = results.books.into_iter().take(self.config.app.max_search_results).map(SavedResult::new).collect();
+        Ok(self.remember_results(&query, saved))
+    }
+
+    /// Adds the `number`th book of the list the last search kept, counting from 1.
+    pub async fn add_from_last(&self, number: usize, is_ebook: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Path::new(&self.config.app.last_search_file);
+        let search = LastSearch::load(path)?;
+        let result = search.pick(number).map_err(WcmError::NotFound)?;
+        ui::detail(format!("Adding result {} of the search for {}", number, search.query));
+        self.add_selected(&result.book, is_ebook, result.series.as_deref().map(|series| (series, result.volume))).await
+    }
+
+    /// Saves a result list for `wcm add --from-last`. Not being able to is only
+    /// worth a detail message, since the search itself worked.
+    fn remember_results(&self, query: &str, results: Vec<SavedResult>) -> LastSearch {
+        let search = LastSearch { query: query.to_string(), results };
+        let path = &self.config.app.last_search_file;
+        if !path.trim().is_empty() {
+            if let Err(e) = search.save(Path::new(path)) {
+                ui::detail(format!("Could not save the results to {}: {}", path, e));
+            }
+        }
+        search
+    }
+
+    /// Finds the volumes of a series in both APIs, in series order. One API failing
+    /// is not an error as long as the other answers.
+    pub async fn find_series(&self, series: &str) -> Result<Vec<SeriesVolume>, Box<dyn std::error::Error>> {
+        ui::detail("Searching for the series on Google Books API...");
+        let google = timing::time(Stage::Metadata, self.google_client.search_by_series(series)).await;
+        ui::detail("Searching for the series on Open Library API...");
+        let open_library = timing::time(Stage::Metadata, self.open_library_client.search_by_series(series)).await;
+
+        let mut books = Vec::new();
+        match google {
+            Ok(response) => books.extend(response.items.unwrap_or_default().into_iter().map(BookResult::Google)),
+            Err(e) if open_library.is_err() => return Err(e),
+            Err(e) => ui::detail(format!("Google Books API error: {}", e)),
+        }
+        match open_library {
+            Ok(response) => books.extend(response.docs.into_iter().map(BookResult::OpenLibrary)),
+            Err(e) => ui::detail(format!("Open Library API error: {}", e)),
+        }
+
+        books.retain(|book| self.filters.matches(book));
+        Ok(crate::series::collect_volumes(series, books))
+    }
+
+    /// Finds books comparable to `row`: Google Books volumes by the same author
+    /// and in the same subjects, Open Library works sharing its subjects, and, with
+    /// `use_llm`, the LLM's own suggestions. The subjects come from the catalogues'
+    /// records for the row's ISBN, or its categories when they have none. A failing
+    /// source is skipped.
+    pub async fn find_similar(&self, row: &crate::baserow::MediaRow, owned: &[crate::baserow::MediaRow], use_llm: bool) -> Result<Vec<SimilarBook>, Box<dyn std::error::Error>> {
+        let title = row.get_text("Title").unwrap_or_default();
+        let author = row.get_text("Author").unwrap_or_default();
+
+        let mut subjects: Vec<String> = Vec::new();
+        if let Some(isbn) = row.get_text("ISBN") {
+            ui::detail("Looking up the book's subjects...");
+            if let Ok(response) = timing::time(Stage::Metadata, self.google_client.search_by_isbn(&isbn)).await {
+                subjects.extend(response.items.unwrap_or_default().into_iter().take(1).flat_map(|book| similar::subjects(&BookResult::Google(book))));
+            }
+            if let Ok(response) = timing::time(Stage::Metadata, self.open_library_client.search_by_isbn(&isbn)).await {
+                subjects.extend(response.docs.into_iter().take(1).flat_map(|book| similar::subjects(&BookResult::OpenLibrary(book))));
+            }
+        }
+        if subjects.is_empty() {
+            subjects = row.get_text("Category")
+                .map(|categories| categories.split(", ").map(str::to_string).collect())
+                .unwrap_or_default();
+        }
+
+        let mut books: Vec<(BookResult, SimilarSource)> = Vec::new();
+        let mut queries = Vec::new();
+        if !author.is_empty() {
+            queries.push(timing::time(Stage::Metadata, self.google_client.search_by_author(&author)).await);
+        }
+        for subject in subjects.iter().take(2) {
+            queries.push(timing::time(Stage::Metadata, self.google_client.search_by_subject(subject)).await);
+        }
+        for response in queries {
+            match response {
+                Ok(response) => books.extend(response.items.unwrap_or_default().into_iter().map(|book| (BookResult::Google(book), SimilarSource::GoogleBooks))),
+                Err(e) => ui::detail(format!("Google Books API error: {}", e)),
+            }
+        }
+        for subject in subjects.iter().take(3) {
+            match timing::time(Stage::Metadata, self.open_library_client.search_by_subject(subject)).await {
+                Ok(response) => books.extend(response.docs.into_iter().map(|book| (BookResult::OpenLibrary(book), SimilarSource::OpenLibrary))),
+                Err(e) => ui::detail(format!("Open Library API error: {}", e)),
+            }
+        }
+        books.retain(|(book, _)| self.filters.matches(book));
+
+        let mut suggestions = Vec::new();
+        if use_llm {
+            let mut description = format!("{} by {}", title, author);
+            if !subjects.is_empty() {
+                description.push_str(&format!(" (subjects: {})", subjects.join(", ")));
+            }
+            let llm = crate::llm::LlmProvider::from_config(&self.config)
+                .map(|llm| llm.with_http_client(self.http_client.clone()));
+            let result = match llm {
+                Ok(llm) => timing::time(Stage::Llm, llm.similar_books(&description, 10)).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(found) => suggestions = found,
+                Err(e) => ui::warn(t!("similar.llm_failed", error = e.to_string())),
+            }
+        }
+
+        Ok(similar::collect_similar(&title, &subjects, books, suggestions, owned))
+    }
+
+    /// Lists the volumes of a series and adds the ones picked, one after the other,
+    /// each through the usual confirmation. Declining a volume moves on to the next.
+    pub async fn add_series(&self, series: &str, is_ebook: bool) -> Result<(), Box<dyn std::error::Error>> {
+        use dialoguer::{theme::ColorfulTheme, MultiSelect};
+
+        let volumes = self.find_series(series).await?;
+        if volumes.is_empty() {
+            return Err(WcmError::NotFound(t!("error.no_books_series", series = series).into_owned()).into());
+        }
+
+        let items: Vec<String> = volumes.iter().map(|volume| {
+            t!("series.item",
+                number = volume.number.map(|number| number.to_string()).unwrap_or_else(|| "-".to_string()),
+                title = volume.book.get_full_title(),
+                author = volume.book.get_all_authors(),
+                year = volume.book.get_published_date().unwrap_or_else(|| t!("select.unknown_year").into_owned())
+            ).into_owned()
+        }).collect();
+
+        ui::show(t!("series.found", count = volumes.len(), series = series));
+        self.remember_results(series, volumes.iter()
+            .map(|volume| SavedResult { book: volume.book.clone(), series: Some(series.to_string()), volume: volume.number })
+            .collect());
+        let picked = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(t!("series.prompt"))
+            .items(&items)
+            .interact()?;
+        if picked.is_empty() {
+            return Err(WcmError::Cancelled.into());
+        }
+
+        let total = picked.len();
+        let mut added = 0;
+        for (done, index) in picked.into_iter().enumerate() {
+            let book = &volumes[index].book;
+            ui::info(ui::paint(Role::Heading, format!("[{}/{}] {}", done + 1, total, book.get_full_title())));
+            match self.add_selected(book, is_ebook, Some((series, volumes[index].number))).await {
+                Ok(()) => added += 1,
+                Err(e) => match e.downcast_ref::<WcmError>() {
+                    Some(WcmError::Cancelled) => ui::show(t!("series.skipped")),
+                    _ => ui::error(t!("series.failed", title = book.get_full_title(), error = e)),
+                },
+            }
+        }
+
+        ui::info(t!("series.done", added = added, total = total));
+        Ok(())
+    }
+
+    /// Adds a book entirely by hand: every field is entered in a form and no metadata
+    /// API is consulted. The LLM can still pick categories and write the synopsis.
+    pub async fn add_manually(&self, title: Option<&str>, author: Option<&str>, is_ebook: bool) -> Result<(), Box<dyn std::error::Error>> {
+        use dialoguer::{theme::ColorfulTheme, Confirm};
+
+        let available_categories = self.available_categories().await?;
+        if available_categories.is_empty() {
+            return Err("No categories found in Baserow table".into());
+        }
+
+        let mut prepared = PreparedBook::new(&BookResult::Manual(ManualBook::default()), Vec::new(), String::new(), is_ebook, available_categories, &self.config.app.defaults);
+        prepared.title = title.unwrap_or_default().to_string();
+        prepared.author = author.unwrap_or_default().to_string();
+
+        let mut storages = None;
+        for field in [EditableField::Title, EditableField::Author, EditableField::Isbn] {
+            self.edit_field(&mut prepared, field, &mut storages).await?;
+        }
+        let book = BookResult::Manual(ManualBook {
+            title: prepared.title.clone(),
+            author: prepared.author.clone(),
+            isbn: prepared.isbn.clone(),
+            cover_url: None,
+        });
+        prepared.book = book.clone();
+
+        if !self.categories.is_empty() {
+            prepared.categories = pinned_categories(&self.categories, &prepared.available_categories)?;
+        }
+        if let Some(synopsis) = &self.synopsis {
+            prepared.synopsis = synopsis.clone();
+        }
+
+        // Nothing is left for the LLM when both were given on the command line
+        let theme = ColorfulTheme::default();
+        let use_llm = (prepared.categories.is_empty() || prepared.synopsis.is_empty())
+            && Confirm::with_theme(&theme)
+                .with_prompt(t!("manual.use_llm"))
+                .default(true)
+                .interact()?;
+
+        if use_llm && prepared.categories.is_empty() {
+            match self.select_categories_with_llm(&book, &prepared.available_categories).await {
+                Ok(categories) => {
+                    ui::info(format!("Selected categories: {}", categories.join(", ")));
+                    prepared.categories = categories;
+                }
+                Err(e) => ui::warn(format!("Failed to select categories with LLM: {}", e)),
+            }
+        }
+        if use_llm && prepared.synopsis.is_empty() {
+            match self.generate_synopsis_if_needed(&book).await {
+                Ok(synopsis) => prepared.synopsis = synopsis.unwrap_or_default(),
+                Err(e) => ui::warn(format!("Failed to generate synopsis: {}", e)),
+            }
+        }
+        // Anything the LLM did not fill in is asked for
+        if prepared.categories.is_empty() {
+            self.edit_field(&mut prepared, EditableField::Categories, &mut storages).await?;
+            if prepared.categories.is_empty() {
+                return Err(t!("manual.category_required").into());
+            }
+        }
+        if prepared.synopsis.is_empty() {
+            self.edit_field(&mut prepared, EditableField::Synopsis, &mut storages).await?;
+        }
+        for field in [EditableField::Type, EditableField::Status, EditableField::Location] {
+            self.edit_field(&mut prepared, field, &mut storages).await?;
+        }
+        self.apply_details(&mut prepared, None);
+
+        prepared.call_number = self.resolve_call_number(&book).await;
+        prepared.content_warnings = self.resolve_content_warnings(&prepared).await;
+        prepared.possible_duplicates = self.find_possible_duplicates(&book).await;
+
+        if !self.show_preflight_confirmation(&mut prepared, &mut storages).await? {
+            return Err(WcmError::Cancelled.into());
+        }
+
+        if let Some(path) = &self.draft_output {
+            return self.save_draft(&prepared, path);
+        }
+        if self.offline {
+            return self.queue_offline(&prepared, self.cached_cover(&prepared.book)).await;
+        }
+
+        // There is no cover to fetch; it can be uploaded in Baserow afterwards
+        let entry_id = self.create_baserow_entry(&prepared, Vec::new()).await?;
+        report_added(&AddedBook::new(entry_id, &prepared));
+        Ok(())
+    }
+
+    /// Adds one issue of a magazine or other periodical. Nothing is looked up: there
+    /// is no ISBN, the synopsis is left empty unless given, and the cover comes from
+    /// `cover_url` or a scan in `cover_file`, if either is given.
+    pub async fn add_magazine(
+        &self,
+        title: &str,
+        issue: crate::periodicals::Issue,
+        publisher: Option<&str>,
+        cover_url: Option<&str>,
+        cover_file: Option<&Path>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let available_categories = self.available_categories().await?;
+        if available_categories.is_empty() {
+            return Err("No categories found in Baserow table".into());
+        }
+
+        let book = BookResult::Manual(ManualBook {
+            title: crate::periodicals::issue_title(title, &issue),
+            author: publisher.unwrap_or_default().trim().to_string(),
+            isbn: None,
+            cover_url: cover_url.map(str::to_string),
+        });
+        let mut prepared = PreparedBook::new(&book, Vec::new(), self.synopsis.clone().unwrap_or_default(), false, available_categories, &self.config.app.defaults);
+        prepared.issue = Some(issue);
+        prepared.purchase = self.purchase.clone();
+
+        let mut storages = None;
+        if !self.categories.is_empty() {
+            prepared.categories = pinned_categories(&self.categories, &prepared.available_categories)?;
+        } else {
+            self.edit_field(&mut prepared, EditableField::Categories, &mut storages).await?;
+            if prepared.categories.is_empty() {
+                return Err(t!("manual.category_required").into());
+            }
+        }
+        for field in [EditableField::Status, EditableField::Location] {
+            self.edit_field(&mut prepared, field, &mut storages).await?;
+        }
+        prepared.possible_duplicates = self.find_possible_duplicates(&book).await;
+
+        if !self.show_preflight_confirmation(&mut prepared, &mut storages).await? {
+            return Err(WcmError::Cancelled.into());
+        }
+
+        if self.offline {
+            let cover = match cover_file {
+                Some(path) => Some(std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?),
+                None if cover_url.is_some() => self.cached_cover(&prepared.book),
+                None => None,
+            };
+            return self.queue_offline(&prepared, cover).await;
+        }
+        let cover_images = match cover_file {
+            Some(path) => {
+                let filename = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "cover.jpg".to_string());
+                let data = std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+                let uploaded = timing::time(Stage::Upload, self.baserow_client.upload_file(data, &filename)).await?;
+                vec![uploaded.into()]
+            }
+            None if cover_url.is_some() => self.handle_cover_image_upload(&prepared.book).await,
+            None => Vec::new(),
+        };
+        let entry_id = self.create_baserow_entry(&prepared, cover_images).await?;
+        report_added(&AddedBook::new(entry_id, &prepared));
+        Ok(())
+    }
+
+    /// Submits a draft written by `--save-draft`, after showing it for review in the
+    /// usual confirmation menu.
+    pub async fn add_from_draft(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let draft: SavedDraft = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{} is not a saved draft: {}", path.display(), e))?;
+
+        let available_categories = self.available_categories().await?;
+        let book = BookResult::Manual(ManualBook {
+            title: draft.title.clone(),
+            author: draft.author.clone(),
+            isbn: draft.isbn.clone(),
+            cover_url: draft.cover_url.clone(),
+        });
+
+        let mut prepared = PreparedBook::new(&book, draft.categories, draft.synopsis, draft.is_ebook, available_categories, &self.config.app.defaults);
+        prepared.status = draft.status;
+        prepared.read = draft.read.unwrap_or(prepared.read);
+        prepared.rating = draft.rating.unwrap_or(prepared.rating);
+        prepared.location = draft.location;
+        prepared.call_number = draft.call_number;
+        prepared.series = draft.series;
+        prepared.volume_number = draft.volume_number;
+        prepared.purchase = draft.purchase;
+        prepared.publisher = draft.publisher;
+        prepared.pages = draft.pages;
+        prepared.content_warnings = draft.content_warnings;
+        prepared.copy = draft.copy;
+        prepared.possible_duplicates = self.find_possible_duplicates(&book).await;
+
+        if !self.show_preflight_confirmation(&mut prepared, &mut None).await? {
+            return Err(WcmError::Cancelled.into());
+        }
+
+        let added = self.submit_prepared(&prepared).await?;
+        report_added(&added);
+        Ok(())
+    }
+
+    fn save_draft(&self, prepared: &PreparedBook, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let draft = SavedDraft {
+            title: prepared.title.clone(),
+            author: prepared.author.clone(),
+            isbn: prepared.isbn.clone(),
+            categories: prepared.categories.clone(),
+            synopsis: prepared.synopsis.clone(),
+            is_ebook: prepared.is_ebook,
+            status: prepared.status,
+            read: Some(prepared.read),
+            rating: Some(prepared.rating),
+            location: prepared.location.clone(),
+            call_number: prepared.call_number.clone(),
+            series: prepared.series.clone(),
+            volume_number: prepared.volume_number,
+            purchase: prepared.purchase.clone(),
+            cover_url: self.get_cover_image_url(&prepared.book),
+            publisher: prepared.publisher.clone(),
+            pages: prepared.pages,
+            content_warnings: prepared.content_warnings.clone(),
+            copy: prepared.copy.clone(),
+        };
+
+        std::fs::write(path, serde_json::to_string_pretty(&draft)?)?;
+        ui::result(
+            t!("result.draft_saved", path = path.display()),
+            &serde_json::json!({ "draft": path }),
+        );
+        Ok(())
+    }
+
+    /// Runs the category and synopsis steps for an already selected book without
+    /// any interactive prompts, so the result can be confirmed elsewhere.
+    pub async fn prepare_book(&self, book: &BookResult, is_ebook: bool) -> Result<PreparedBook, Box<dyn std::error::Error>> {
+        let book = &self.apply_merge_policy(book).await;
+        let available_categories = self.available_categories().await?;
+        if available_categories.is_empty() {
+            return Err("No categories found in Baserow table".into());
+        }
+        
+        // Nobody is there to ask for the categories once the LLM budget is used up
+        let categories = self.choose_categories(book, &available_categories).await.map_err(|e| {
+            if crate::llm::LlmError::is_budget_exceeded(e.as_ref()) {
+                t!("llm_budget.categories_needed").into_owned().into()
+            } else {
+                e
+            }
+        })?;
+        let synopsis = self.resolve_synopsis(book).await;
+        
+        let mut prepared = PreparedBook::new(book, categories, synopsis, is_ebook, available_categories, &self.config.app.defaults);
+        prepared.author = self.entry_author(book);
+        self.apply_details(&mut prepared, None);
+        self.warn_on_other_language(&prepared);
+        prepared.call_number = self.resolve_call_number(book).await;
+        prepared.content_warnings = self.resolve_content_warnings(&prepared).await;
+        prepared.possible_duplicates = self.find_possible_duplicates(book).await;
+        Ok(prepared)
+    }
+
+    /// Uploads the cover and creates the Baserow entry for a prepared book.
+    pub async fn submit_prepared(&self, prepared: &PreparedBook) -> Result<AddedBook, Box<dyn std::error::Error>> {
+        let cover_images = self.handle_cover_image_upload(&prepared.book).await;
+        
+        let entry_id = self.create_baserow_entry(prepared, cover_images).await?;
+        Ok(AddedBook::new(entry_id, prepared))
+    }
+
+    /// Runs the full add pipeline for an already selected book without any
+    /// interactive prompts. Used by the HTTP server where nobody is at the terminal.
+    pub async fn add_book_unattended(&self, book: &BookResult, is_ebook: bool) -> Result<AddedBook, Box<dyn std::error::Error>> {
+        let prepared = self.prepare_book(book, is_ebook).await?;
+        self.submit_prepared(&prepared).await
+    }
+
+    async fn handle_search_results(&self, results: SearchResults, search_query: &str, is_ebook: bool) -> Result<Option<BookResult>, Box<dyn std::error::Error>> {
+        let selected_book = if results.books.len() > 1 {
+            // Limit to max_search_results for display
+            let display_books = if results.books.len() > self.config.app.max_search_results {
+                &results.books[..self.config.app.max_search_results]
+            } else {
+                &results.books
+            };
+            
+            let truncated_results = SearchResults {
+                books: display_books.to_vec(),
+                source: results.source.clone(),
+            };
+            self.remember_results(search_query, display_books.iter().cloned().map(SavedResult::new).collect());
+            
+            ui::show(t!("select.found",
+                count = results.books.len(), source = results.source, query = search_query, shown = display_books.len()));
+            
+            match interactive_select_book(&truncated_results) {
+                Ok(Some(selected_book)) => Some(selected_book.clone()),
+                Ok(None) => return Err(WcmError::Cancelled.into()),
+                Err(e) => {
+                    ui::detail(format!("Error in interactive selection: {}", e));
+                    // Fall through to show first result
+                    results.books.first().cloned()
+                }
+            }
+        } else {
+            results.books.first().cloned()
+        };
+        
+        if let Some(book) = selected_book {
+            self.add_selected(&book, is_ebook, None).await?;
+            return Ok(Some(book));
+        }
+        
+        Ok(None)
+    }
+
+    /// Runs the interactive add for a book the user picked: categories, synopsis,
+    /// the confirmation menu, then the Baserow entry (or the draft file).
+    /// `detected` is the series and volume number found by `add_series`.
+    async fn add_selected(
+        &self,
+        book: &BookResult,
+        is_ebook: bool,
+        detected: Option<(&str, Option<u32>)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let book = &self.apply_merge_policy(book).await;
+
+        // Display book information
+        let handle = book.display_info(&self.config);
+        handle.await?;
+        
+        // Fetch categories from Baserow
+        match self.available_categories().await {
+            Ok(categories) => {
+                if !categories.is_empty() {
+                    if ui::is_verbose() {
+                        crate::baserow::display_categories(&categories);
+                    }
+                    
+                    // Perform LLM-powered category selection, unless given with --category;
+                    // once the LLM budget is used up they are asked for below
+                    let selected_categories = match self.choose_categories(book, &categories).await {
+                        Ok(selected_categories) => selected_categories,
+                        Err(e) if crate::llm::LlmError::is_budget_exceeded(e.as_ref()) => Vec::new(),
+                        Err(e) => {
+                            if self.categories.is_empty() {
+                                ui::warn(t!("error.categories_llm"));
+                            }
+                            crate::baserow::display_categories(&categories);
+                            return Err(e);
+                        }
+                    };
+                    
+                    // Check if synopsis needs to be generated
+                    let final_synopsis = self.resolve_synopsis(book).await;
+                    
+                    let mut prepared = PreparedBook::new(book, selected_categories, final_synopsis, is_ebook, categories, &self.config.app.defaults);
+                    let mut storages = None;
+                    if prepared.categories.is_empty() {
+                        self.edit_field(&mut prepared, EditableField::Categories, &mut storages).await?;
+                        if prepared.categories.is_empty() {
+                            return Err(t!("manual.category_required").into());
+                        }
+                    } else {
+                        ui::info(format!("Selected categories: {}", prepared.categories.join(", ")));
+                    }
+                    prepared.author = self.entry_author(book);
+                    self.apply_details(&mut prepared, detected);
+                    self.warn_on_other_language(&prepared);
+                    prepared.call_number = self.resolve_call_number(book).await;
+                    prepared.content_warnings = self.resolve_content_warnings(&prepared).await;
+                    prepared.possible_duplicates = self.find_possible_duplicates(book).await;
+                    if self.compare {
+                        prepared.sources = self.source_records(book).await;
+                    }
+                    
+                    // Display pre-flight confirmation
+                    if !self.show_preflight_confirmation(&mut prepared, &mut storages).await? {
+                        return Err(WcmError::Cancelled.into());
+                    }
+                    
+                    if let Some(path) = &self.draft_output {
+                        self.save_draft(&prepared, path)?;
+                        return Ok(());
+                    }
+                    if self.offline {
+                        return self.queue_offline(&prepared, self.cached_cover(&prepared.book)).await;
+                    }
+                    
+                    // Handle cover image upload after confirmation; the cover may have been switched to the other catalogue's
+                    let cover_images = self.handle_cover_image_upload(&prepared.book).await;
+                    
+                    // Create Baserow entry with all the collected data
+                    let entry_id = self.create_baserow_entry(&prepared, cover_images).await?;
+                    report_added(&AddedBook::new(entry_id, &prepared));
+                    if let Some(titles) = &self.boxset {
+                        self.add_box_set_volumes(entry_id, &prepared, titles).await?;
+                    }
+                } else {
+                    return Err(WcmError::NotFound(t!("error.no_categories").into_owned()).into());
+                }
+            }
+            Err(e) => {
+                if !self.offline {
+                    ui::detail("Make sure your Baserow API token and categories table ID are correct.");
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The categories from `with_categories` when there are any, otherwise the
+    /// ones the LLM picks.
+    async fn choose_categories(
+        &self,
+        book: &BookResult,
+        categories: &[crate::baserow::Category],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        if self.categories.is_empty() {
+            self.select_categories_with_llm(book, categories).await
+        } else {
+            Ok(pinned_categories(&self.categories, categories)?)
+        }
+    }
+
+    async fn select_categories_with_llm(
+        &self,
+        book: &BookResult,
+        categories: &[crate::baserow::Category],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let key = crate::offline::book_key(book);
+        if self.offline {
+            ui::detail("Offline, using the categories the LLM picked before");
+            let cached: Option<Vec<String>> = self.offline_cache.as_ref().and_then(|cache| cache.load_llm("categories", &key));
+            let cached = cached.ok_or_else(|| WcmError::NotFound(t!("offline.no_llm_categories").into_owned()))?;
+            return Ok(pinned_categories(&cached, categories)?);
+        }
+
+        // Checked first so no web search is made once the LLM budget is used up
+        let llm_provider = crate::llm::LlmProvider::from_config(&self.config)?
+            .with_http_client(self.http_client.clone());
+
+        ui::detail("Enhancing book information with web search...");
+
         // Get basic book information
         let title = book.get_full_title();
         let author = book.get_all_authors();
-        let existing_description = match book {
-            BookResult::Google(google_book) => {
-                google_book.volume_info.description.as_deref().unwrap_or("No description available")
+        let mut existing_description = book.get_description().unwrap_or_else(|| "No description available".to_string());
+        let subjects = book.get_subjects();
+        if !subjects.is_empty() {
+            existing_description.push_str(&format!("\nSubjects: {}", subjects.join(", ")));
+        }
+
+        // Enhance with web search
+        let enhanced_info = crate::web_search::enhance_book_info_with_search(
+            &title,
+            &author,
+            &existing_description,
+            &self.http_client,
+        ).await;
+
+        ui::detail("Enhanced book information prepared, consulting LLM for category selection...");
+
+        // Use LLM to select categories
+        let selected_categories = timing::time(Stage::Llm, llm_provider.select_categories(&enhanced_info, categories)).await?;
+        if let Some(cache) = &self.offline_cache {
+            if let Err(e) = cache.store_llm("categories", &key, &selected_categories) {
+                ui::detail(format!("Could not write to the cache: {}", e));
+            }
+        }
+
+        Ok(selected_categories)
+    }
+
+    /// Returns the synopsis to store: the one given with `with_synopsis`, an
+    /// LLM-generated one when the existing description is too short, otherwise
+    /// the description from the API.
+    async fn resolve_synopsis(&self, book: &BookResult) -> String {
+        if let Some(synopsis) = &self.synopsis {
+            ui::detail("Using the synopsis given on the command line");
+            return synopsis.clone();
+        }
+
+        let existing_description = || book.get_description().unwrap_or_else(|| "No description available".to_string());
+        
+        match self.generate_synopsis_if_needed(book).await {
+            Ok(Some(synopsis)) => {
+                ui::info("");
+                ui::info(ui::paint(Role::Heading, "Generated Synopsis"));
+                ui::info(&synopsis);
+                ui::info("");
+                synopsis
+            }
+            Ok(None) => {
+                ui::detail("Existing synopsis is sufficient, no LLM generation needed.");
+                // Use existing description as synopsis
+                existing_description()
+            }
+            Err(e) if crate::llm::LlmError::is_budget_exceeded(e.as_ref()) => {
+                ui::detail("LLM budget used up, keeping the existing synopsis");
+                existing_description()
+            }
+            Err(e) => {
+                ui::warn(format!("Failed to generate synopsis: {}", e));
+                // Use existing description as fallback
+                existing_description()
+            }
+        }
+    }
+
+    /// Sets the details given on the command line: the purchase, the copy details,
+    /// and the series and volume number from `with_series`, falling back to the
+    /// ones detected for the book.
+    fn apply_details(&self, prepared: &mut PreparedBook, detected: Option<(&str, Option<u32>)>) {
+        let (detected_series, detected_volume) = detected.unwrap_or(("", None));
+        prepared.series = self.series.clone()
+            .or_else(|| (!detected_series.is_empty()).then(|| detected_series.trim().to_string()));
+        prepared.volume_number = self.volume_number.or(detected_volume);
+        prepared.purchase = self.purchase.clone();
+        prepared.copy = self.copy.clone();
+    }
+
+    /// Adds an entry for each volume of the box set just added as row `parent_id`,
+    /// after asking for the titles when `titles` is empty and confirming the
+    /// list. A volume that fails is reported and the rest are still added.
+    async fn add_box_set_volumes(&self, parent_id: u64, parent: &PreparedBook, titles: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+        let theme = ColorfulTheme::default();
+
+        let mut titles = titles.to_vec();
+        if titles.is_empty() {
+            loop {
+                let title: String = Input::with_theme(&theme)
+                    .with_prompt(t!("boxset.volume_prompt", number = titles.len() + 1))
+                    .allow_empty(true)
+                    .interact_text()?;
+                if title.trim().is_empty() {
+                    break;
+                }
+                titles.push(title);
+            }
+        }
+        let volumes = crate::boxset::volume_entries(parent, parent_id, &titles, &self.config.app.defaults);
+        if volumes.is_empty() {
+            ui::warn(t!("boxset.no_volumes"));
+            return Ok(());
+        }
+
+        ui::show("");
+        ui::show(ui::paint(Role::Heading, t!("boxset.heading", title = &parent.title)));
+        for volume in &volumes {
+            ui::show(format!("  {}. {}", volume.volume_number.unwrap_or_default(), volume.title));
+        }
+        let confirmed = Confirm::with_theme(&theme)
+            .with_prompt(t!("boxset.confirm", count = volumes.len()))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            return Err(WcmError::Cancelled.into());
+        }
+
+        let mut added = 0;
+        for volume in &volumes {
+            let number = volume.volume_number.unwrap_or_default();
+            match self.create_baserow_entry(volume, Vec::new()).await {
+                Ok(id) => {
+                    added += 1;
+                    ui::info(t!("boxset.added", number = number, title = &volume.title, id = id));
+                }
+                Err(e) => ui::warn(t!("boxset.failed", number = number, title = &volume.title, error = e.to_string())),
+            }
+        }
+        ui::info(ui::paint(Role::Success, t!("boxset.summary", added = added, total = volumes.len(), id = parent_id)));
+        Ok(())
+    }
+
+    /// The Author field for a book from a catalogue, written as `author_format`
+    /// asks.
+    fn entry_author(&self, book: &BookResult) -> String {
+        let authors = book.get_authors();
+        if self.config.author_format == AuthorFormatConfig::default() || authors.is_empty() {
+            return book.get_all_authors();
+        }
+        crate::authors::format_authors(&authors, &self.config.author_format)
+    }
+
+    /// Warns when the book is not in the language asked for with `--lang`, as
+    /// when a catalogue files a translation under the original's language.
+    fn warn_on_other_language(&self, prepared: &PreparedBook) {
+        if !self.config.language_detection.enabled {
+            return;
+        }
+        if let (Some(preferred), Some(language)) = (&self.filters.lang, &prepared.language) {
+            if !crate::language::is_preferred(language, preferred) {
+                ui::warn(t!("language.other_language", language = &language.name, preferred = preferred));
+            }
+        }
+    }
+
+    /// The romanized title to store for a book with a Thai title, when
+    /// transliteration is enabled. An unavailable LLM falls back to the simple
+    /// method rather than leaving the field empty.
+    async fn romanize_title(&self, title: &str) -> Option<String> {
+        let settings = &self.config.transliteration;
+        if !settings.enabled || !crate::transliteration::is_thai(title) {
+            return None;
+        }
+        // Offline, the simple method stands in for the LLM
+        if settings.method == TransliterationMethod::Llm && !self.offline {
+            let llm = crate::llm::LlmProvider::from_config(&self.config)
+                .map(|llm| llm.with_http_client(self.http_client.clone()));
+            let result = match llm {
+                Ok(llm) => timing::time(Stage::Llm, llm.transliterate_title(title)).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(romanized) => return Some(romanized),
+                Err(e) => ui::warn(t!("transliteration.llm_failed", error = e.to_string())),
+            }
+        }
+        Some(crate::transliteration::simple(title))
+    }
+
+    /// Asks the LLM for content warnings when they are enabled. A failed request
+    /// is reported and leaves the book unchecked rather than blocking the add.
+    async fn resolve_content_warnings(&self, prepared: &PreparedBook) -> Option<Vec<String>> {
+        let settings = &self.config.content_warnings;
+        if !settings.enabled || settings.kinds.is_empty() {
+            return None;
+        }
+        if self.offline {
+            ui::detail("Offline, skipping the content warnings");
+            return None;
+        }
+
+        ui::detail("Asking the LLM for content warnings...");
+        let info = crate::content_warnings::describe(&prepared.title, &prepared.author, &prepared.categories, &prepared.synopsis);
+        let llm = crate::llm::LlmProvider::from_config(&self.config)
+            .map(|llm| llm.with_http_client(self.http_client.clone()));
+        let result = match llm {
+            Ok(llm) => timing::time(Stage::Llm, llm.content_warnings(&info, &settings.kinds)).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(warnings) => Some(warnings),
+            Err(e) => {
+                ui::warn(t!("content_warnings.failed", error = e.to_string()));
+                None
+            }
+        }
+    }
+
+    /// Looks up the call number when classification is enabled.
+    async fn resolve_call_number(&self, book: &BookResult) -> Option<String> {
+        if !self.config.classification.enabled {
+            return None;
+        }
+        if self.offline {
+            ui::detail("Offline, the call number will have to be assigned later");
+            return None;
+        }
+
+        let call_number = self.classifier.call_number(book).await;
+        if call_number.is_none() {
+            ui::info(format!("No {} classification found, the call number will have to be assigned by hand.",
+                self.config.classification.scheme.name()));
+        }
+        call_number
+    }
+
+    /// Compares the book against every row in the library. A failed lookup only
+    /// skips the check, it never blocks adding the book.
+    async fn find_possible_duplicates(&self, book: &BookResult) -> Vec<crate::duplicates::PossibleDuplicate> {
+        if self.offline {
+            ui::warn(t!("offline.no_duplicate_check"));
+            return Vec::new();
+        }
+        ui::detail("Checking the library for duplicates...");
+
+        match timing::time(Stage::Library, self.baserow_client.fetch_all_media()).await {
+            Ok(rows) => {
+                let mut duplicates = crate::duplicates::find_possible_duplicates(book, &rows);
+                for id in self.rows_with_same_cover(book).await {
+                    if let Some(row) = rows.iter().find(|row| row.id == id).filter(|_| duplicates.iter().all(|duplicate| duplicate.id != id)) {
+                        duplicates.push(crate::duplicates::PossibleDuplicate::new(row, &t!("duplicates.same_cover")));
+                    }
+                }
+                duplicates
+            }
+            Err(e) => {
+                ui::warn(format!("Could not check the library for duplicates: {}", e));
+                Vec::new()
             }
-            BookResult::OpenLibrary(_) => "No description available",
-        };
+        }
+    }
 
-        // Enhance with web search
-        let enhanced_info = crate::web_search::enhance_book_info_with_search(
-            &title,
-            &author,
-            existing_description,
-        ).await;
+    fn cover_hashes_path(&self) -> Option<&Path> {
+        Some(self.config.app.cover_hashes_file.as_str()).filter(|file| !file.is_empty()).map(Path::new)
+    }
 
-        if self.config.app.verbose {
-            println!("Enhanced book information prepared, consulting LLM for category selection...");
+    /// The rows whose stored cover hash matches the book's cover, which catches
+    /// another edition or a retitled copy that metadata alone does not.
+    async fn rows_with_same_cover(&self, book: &BookResult) -> Vec<u64> {
+        let Some(path) = self.cover_hashes_path().filter(|_| self.covers) else { return Vec::new() };
+        let hashes = match crate::cover_hashes::load(path) {
+            Ok(hashes) if !hashes.is_empty() => hashes,
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                ui::warn(format!("Could not read the cover hashes: {}", e));
+                return Vec::new();
+            }
+        };
+        let Some(url) = self.get_cover_image_url(book) else { return Vec::new() };
+        match self.cover_hash(&url).await {
+            Some(hash) => crate::cover_hashes::same_cover(&hashes, hash),
+            None => Vec::new(),
         }
+    }
 
-        // Use LLM to select categories
-        let llm_provider = crate::llm::LlmProvider::from_config(&self.config)?;
-        let selected_categories = llm_provider.select_categories(&enhanced_info, categories).await?;
+    /// The perceptual hash of the image at `url`, or `None` when it cannot be
+    /// downloaded or read.
+    async fn cover_hash(&self, url: &str) -> Option<u64> {
+        let hash = async {
+            let bytes = self.download_cover(url).await?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(crate::cover_hashes::hash_bytes(&bytes)?)
+        };
+        match timing::time(Stage::Upload, hash).await {
+            Ok(hash) => Some(hash),
+            Err(e) => {
+                ui::detail(format!("Could not hash the cover {}: {}", url, e));
+                None
+            }
+        }
+    }
 
-        Ok(selected_categories)
+    /// Stores the hash of the cover uploaded to `url` for row `row_id`, so later
+    /// books with the same cover are flagged. Failing only skips the hash;
+    /// returns whether it was stored.
+    pub async fn record_cover_hash(&self, row_id: u64, url: &str) -> bool {
+        let Some(path) = self.cover_hashes_path() else { return false };
+        let Some(hash) = self.cover_hash(url).await else { return false };
+        match crate::cover_hashes::record(path, row_id, hash) {
+            Ok(()) => true,
+            Err(e) => {
+                ui::warn(format!("Could not store the cover hash: {}", e));
+                false
+            }
+        }
     }
 
     async fn generate_synopsis_if_needed(
         &self,
         book: &BookResult,
     ) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        let existing_description = match book {
-            BookResult::Google(google_book) => {
-                google_book.volume_info.description.as_deref().unwrap_or("")
-            }
-            BookResult::OpenLibrary(_) => "",
-        };
+        let existing_description = book.get_description().unwrap_or_default();
 
         // Count words in existing description
         let word_count = existing_description
             .split_whitespace()
             .count();
 
-        if self.config.app.verbose {
-            println!("Existing synopsis has {} words (minimum required: {})", 
-                word_count, self.config.app.min_synopsis_words);
-        }
+        ui::detail(format!("Existing synopsis has {} words (minimum required: {})",
+            word_count, self.config.app.min_synopsis_words));
 
         // Check if synopsis is too short or missing
         if word_count < self.config.app.min_synopsis_words {
-            println!("Synopsis too short ({} words), generating enhanced synopsis with LLM...", word_count);
-
-            // Get enhanced book information for synopsis generation
-            let title = book.get_full_title();
-            let author = book.get_all_authors();
-            
-            let enhanced_info = crate::web_search::enhance_book_info_with_search(
-                &title,
-                &author,
-                existing_description,
-            ).await;
-
-            // Generate synopsis using LLM
-            let llm_provider = crate::llm::LlmProvider::from_config(&self.config)?;
-            let generated_synopsis = llm_provider.generate_synopsis(
-                &enhanced_info, 
-                self.config.app.target_synopsis_words
-            ).await?;
-
+            ui::info(format!("Synopsis too short ({} words), generating enhanced synopsis with LLM...", word_count));
+            let generated_synopsis = self.generate_synopsis(&book.get_full_title(), &book.get_all_authors(), &existing_description).await?;
             Ok(Some(generated_synopsis))
         } else {
             Ok(None)
         }
     }
 
+    /// Writes a synopsis with the LLM from a web search for the book and the
+    /// description it already has, if any.
+    pub async fn generate_synopsis(&self, title: &str, author: &str, existing_description: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let key = crate::offline::title_key(title, author);
+        if self.offline {
+            ui::detail("Offline, using the synopsis the LLM wrote before");
+            let cached = self.offline_cache.as_ref().and_then(|cache| cache.load_llm("synopsis", &key));
+            return Ok(cached.ok_or_else(|| WcmError::NotFound(t!("offline.no_llm_synopsis").into_owned()))?);
+        }
+
+        let llm_provider = crate::llm::LlmProvider::from_config(&self.config)?
+            .with_http_client(self.http_client.clone());
+        let enhanced_info = crate::web_search::enhance_book_info_with_search(
+            title,
+            author,
+            existing_description,
+            &self.http_client,
+        ).await;
+
+        let detection = self.config.language_detection;
+        let language = (detection.enabled && detection.synopsis_in_book_language)
+            .then(|| crate::language::text_language(title, Some(existing_description)))
+            .flatten();
+        let generated_synopsis = timing::time(Stage::Llm, llm_provider.generate_synopsis(
+            &enhanced_info,
+            self.config.app.target_synopsis_words,
+            language.as_ref().map(|language| language.name.as_str()),
+        )).await?;
+        if let Some(cache) = &self.offline_cache {
+            if let Err(e) = cache.store_llm("synopsis", &key, &generated_synopsis) {
+                ui::detail(format!("Could not write to the cache: {}", e));
+            }
+        }
+        Ok(generated_synopsis)
+    }
+
     async fn create_baserow_entry(
         &self,
-        book: &BookResult,
-        selected_categories: &[String],
-        synopsis: &str,
-        available_categories: &[crate::baserow::Category],
-        is_ebook: bool,
+        prepared: &PreparedBook,
         cover_images: Vec<crate::baserow::CoverImage>,
     ) -> Result<u64, Box<dyn std::error::Error>> {
-        if self.config.app.verbose {
-            println!("Preparing Baserow entry with collected data...");
+        let cover_url = cover_images.first().and_then(|cover| cover.url.clone());
+        let entry = self.media_entry(prepared, cover_images).await?;
+
+        crate::hooks::before_add(&self.config.hooks, &entry).await
+            .map_err(|e| t!("hooks.pre_add_refused", error = e.to_string()).into_owned())?;
+
+        // Create the entry in Baserow, keeping the payload if that fails
+        match timing::time(Stage::Create, self.baserow_client.create_media_entry(entry.clone())).await {
+            Ok(created_entry) => {
+                crate::metrics::record_add();
+                crate::hooks::after_add(&self.config.hooks, &created_entry).await;
+                if let Some(url) = cover_url {
+                    self.record_cover_hash(created_entry.id, &url).await;
+                }
+                Ok(created_entry.id)
+            }
+            Err(e) => {
+                match crate::retry::save_failed_entry(Path::new(&self.config.app.retry_dir), &entry) {
+                    Ok(path) => ui::warn(t!("retry.saved", path = path.display())),
+                    Err(save_error) => ui::error(t!("retry.not_saved", error = save_error)),
+                }
+                Err(e.into())
+            }
         }
+    }
 
-        // Extract book information
-        let title = book.get_full_title();
-        let author = book.get_all_authors();
-        let isbn = match book {
-            BookResult::Google(google_book) => google_book.get_isbn_13().or_else(|| google_book.get_isbn_10()),
-            BookResult::OpenLibrary(ol_book) => ol_book.get_best_isbn(),
-        };
+    /// Queues the entry of an offline add in `app.retry_dir`, with its cover
+    /// beside it, for `wcm retry` to submit once Baserow can be reached.
+    async fn queue_offline(&self, prepared: &PreparedBook, cover: Option<Vec<u8>>) -> Result<(), Box<dyn std::error::Error>> {
+        let entry = self.media_entry(prepared, Vec::new()).await?;
+
+        crate::hooks::before_add(&self.config.hooks, &entry).await
+            .map_err(|e| t!("hooks.pre_add_refused", error = e.to_string()).into_owned())?;
+
+        let path = crate::retry::save_offline_entry(Path::new(&self.config.app.retry_dir), &entry, cover.as_deref())
+            .map_err(|e| t!("retry.not_saved", error = e.to_string()).into_owned())?;
+        ui::result(
+            t!("offline.queued", title = &prepared.title, path = path.display()),
+            &serde_json::json!({ "queued": path, "title": prepared.title, "cover": cover.is_some() }),
+        );
+        Ok(())
+    }
+
+    /// The cached cover for an offline add: the book's own, or Open Library's
+    /// for its ISBN, the fallback `handle_cover_image_upload` tries.
+    fn cached_cover(&self, book: &BookResult) -> Option<Vec<u8>> {
+        if !self.covers {
+            return None;
+        }
+        let mut urls: Vec<String> = self.get_cover_image_url(book).into_iter().collect();
+        if let Some(isbn) = book.get_isbn() {
+            urls.push(format!("https://covers.openlibrary.org/b/isbn/{}-L.jpg", isbn));
+        }
+        let cover = urls.iter().find_map(|url| self.offline_cache.as_ref()?.load_cover(url));
+        if cover.is_none() {
+            ui::warn(t!("offline.no_cover"));
+        }
+        cover
+    }
+
+    /// The Baserow entry for a prepared book, with every field the config names.
+    async fn media_entry(
+        &self,
+        prepared: &PreparedBook,
+        cover_images: Vec<crate::baserow::CoverImage>,
+    ) -> Result<crate::baserow::MediaEntry, Box<dyn std::error::Error>> {
+        ui::detail("Preparing Baserow entry with collected data...");
 
         // Convert category names to IDs
-        let category_ids = self.baserow_client.find_category_ids_by_names(selected_categories, available_categories);
+        let category_ids = self.baserow_client.find_category_ids_by_names(&prepared.categories, &prepared.available_categories);
         
         if category_ids.is_empty() {
             return Err("No valid category IDs found for selected categories".into());
         }
 
+        let mut extra = HashMap::new();
+        if let Some(call_number) = &prepared.call_number {
+            extra.insert(self.config.classification.field.clone(), serde_json::Value::from(call_number.as_str()));
+        }
+        let fields = &self.config.baserow.fields;
+        if let Some(series) = &prepared.series {
+            extra.insert(fields.series.clone(), serde_json::Value::from(series.as_str()));
+        }
+        if let Some(volume_number) = prepared.volume_number {
+            extra.insert(fields.volume.clone(), serde_json::Value::from(volume_number));
+        }
+        if let Some(pages) = prepared.pages {
+            extra.insert(fields.pages.clone(), serde_json::Value::from(pages));
+        }
+        if let Some(box_set) = prepared.box_set {
+            extra.insert(fields.box_set.clone(), serde_json::Value::from(vec![box_set]));
+        }
+        if let Some(language) = prepared.language.as_ref().filter(|_| self.config.language_detection.enabled) {
+            extra.insert(fields.language.clone(), serde_json::Value::from(language.code.as_str()));
+        }
+        if let Some(warnings) = &prepared.content_warnings {
+            extra.insert(fields.content_warnings.clone(), serde_json::Value::from(crate::content_warnings::field_value(warnings)));
+        }
+        if let Some(romanized) = self.romanize_title(&prepared.title).await {
+            ui::detail(format!("Romanized title: {}", romanized));
+            extra.insert(fields.romanized_title.clone(), serde_json::Value::from(romanized));
+        }
+        if prepared.read {
+            extra.insert(fields.read_at.clone(), serde_json::Value::from(crate::dates::today()));
+        }
+        let purchase = &prepared.purchase;
+        if let Some(price) = purchase.price {
+            extra.insert(fields.price.clone(), serde_json::Value::from(format!("{:.2}", price)));
+        }
+        if let Some(purchased_at) = &purchase.purchased_at {
+            extra.insert(fields.purchased_at.clone(), serde_json::Value::from(purchased_at.as_str()));
+        }
+        if let Some(store) = &purchase.store {
+            extra.insert(fields.store.clone(), serde_json::Value::from(store.as_str()));
+        }
+        let copy = &prepared.copy;
+        if let Some(condition) = copy.condition {
+            extra.insert(fields.condition.clone(), serde_json::Value::from(condition.name()));
+        }
+        if let Some(edition) = &copy.edition {
+            extra.insert(fields.edition.clone(), serde_json::Value::from(edition.as_str()));
+        }
+        if copy.signed {
+            extra.insert(fields.signed.clone(), serde_json::Value::from(true));
+        }
+        if let Some(provenance) = &copy.provenance {
+            extra.insert(fields.provenance.clone(), serde_json::Value::from(provenance.as_str()));
+        }
+        if let Some(asin) = &self.asin {
+            extra.insert(fields.asin.clone(), serde_json::Value::from(asin.as_str()));
+        }
+        if let Some(issue) = &prepared.issue {
+            if let Some(date) = issue.date_value() {
+                extra.insert(fields.issue_date.clone(), serde_json::Value::from(date));
+            }
+            if let Some(number) = &issue.number {
+                extra.insert(fields.issue_number.clone(), serde_json::Value::from(number.as_str()));
+            }
+        }
+        if self.offline && (self.config.baserow.authors_table_id.is_some() || self.config.baserow.publishers_table_id.is_some()) {
+            ui::warn(t!("offline.no_links"));
+        }
+        if let Some(authors_table_id) = self.config.baserow.authors_table_id.filter(|_| !self.offline) {
+            // The Author text field is still filled in, so a failed link loses nothing
+            let names: Vec<String> = crate::authors::split_author_field(&prepared.author, &self.config.author_format).iter()
+                .map(|name| crate::authors::canonical_name(name))
+                .collect();
+            match crate::linked::resolve(&self.baserow_client, authors_table_id, &names, crate::authors::name_key, 1.0).await {
+                Ok(ids) => {
+                    extra.insert(fields.authors.clone(), serde_json::Value::from(ids));
+                }
+                Err(e) => ui::warn(t!("linked.failed", field = &fields.authors, error = e.to_string())),
+            }
+        }
+        if let (Some(publishers_table_id), Some(publisher)) = (self.config.baserow.publishers_table_id.filter(|_| !self.offline), &prepared.publisher) {
+            let names = [publisher.trim().to_string()];
+            match crate::linked::resolve(&self.baserow_client, publishers_table_id, &names, crate::publishers::publisher_key, crate::publishers::MIN_SIMILARITY).await {
+                Ok(ids) => {
+                    extra.insert(fields.publisher.clone(), serde_json::Value::from(ids));
+                }
+                Err(e) => ui::warn(t!("linked.failed", field = &fields.publisher, error = e.to_string())),
+            }
+        }
+
         // Create the media entry
+        let defaults = &self.config.app.defaults;
         let entry = crate::baserow::MediaEntry {
-            title,
-            author,
-            isbn,
-            synopsis: synopsis.to_string(),
+            title: prepared.title.clone(),
+            author: prepared.author.clone(),
+            isbn: prepared.isbn.clone(),
+            synopsis: prepared.synopsis.clone(),
             category: category_ids,
-            read: false, // Default to not read
-            rating: 0, // Default rating (0 = unrated)
-            media_type: Some(if is_ebook { 3021 } else { 3020 }), // 3021 = Ebook, 3020 = Physical
-            location: vec![], // Empty - to be filled manually by user
+            read: prepared.read,
+            rating: prepared.rating,
+            media_type: Some(match &prepared.issue {
+                Some(_) => defaults.magazine_media_type.unwrap_or(defaults.media_type),
+                None if prepared.is_ebook => defaults.ebook_media_type,
+                None => defaults.media_type,
+            }),
+            location: prepared.location.clone(), // Empty unless picked in the confirmation menu
             cover: cover_images,
-            status: 3028, // Default to "In Place"
+            status: prepared.status,
+            extra,
         };
-
-        // Create the entry in Baserow
-        let created_entry = self.baserow_client.create_media_entry(entry).await?;
-        
-        Ok(created_entry.id)
+        Ok(entry)
     }
 
-    fn show_preflight_confirmation(
+    /// Shows the confirmation summary with a menu to correct any field before the
+    /// book is added. Returns false when the user cancels.
+    async fn show_preflight_confirmation(
         &self,
-        book: &BookResult,
-        selected_categories: &[String],
-        synopsis: &str,
-        is_ebook: bool,
+        prepared: &mut PreparedBook,
+        storages: &mut Option<Vec<crate::baserow::Storage>>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        println!("\n==================================================");
-        println!("               📖 CONFIRMATION SUMMARY");
-        println!("==================================================");
-        
-        // Book details
-        println!("Title:     {}", book.get_full_title());
-        println!("Author:    {}", book.get_all_authors());
+        use dialoguer::{theme::ColorfulTheme, Select};
+
+        if !prepared.possible_duplicates.is_empty() {
+            ui::show("");
+            ui::show(ui::paint(Role::Warning, t!("duplicates.heading")));
+            for duplicate in &prepared.possible_duplicates {
+                ui::show(format!("   {}", t!("duplicates.entry",
+                    id = duplicate.id, title = duplicate.title, author = duplicate.author, reason = duplicate.reason)));
+                if let Some(isbn) = &duplicate.isbn {
+                    ui::show(format!("      {}", t!("duplicates.isbn", value = isbn)));
+                }
+                if let Some(location) = &duplicate.location {
+                    ui::show(format!("      {}", t!("duplicates.location", value = location)));
+                }
+            }
+        }
+
+        if self.edit {
+            match self.edit_as_yaml(prepared)? {
+                Some(submit) => return Ok(submit),
+                None => ui::info(t!("entry_editor.not_saved")),
+            }
+        }
+
+        let fields: Vec<EditableField> = EditableField::ALL.into_iter()
+            .filter(|field| *field != EditableField::Sources || prepared.sources.is_some())
+            .collect();
+        let mut actions = vec![t!("confirm.add").into_owned()];
+        actions.extend(fields.iter().map(|field| t!("confirm.edit", field = field.label()).into_owned()));
+        actions.push(t!("confirm.cancel").into_owned());
+
+        loop {
+            self.print_confirmation_summary(prepared, storages.as_deref());
+
+            let action = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(t!("confirm.prompt"))
+                .items(&actions)
+                .default(0)
+                .interact()?;
+
+            match action {
+                0 => return Ok(true),
+                i if i <= fields.len() => {
+                    self.edit_field(prepared, fields[i - 1], storages).await?;
+                }
+                _ => return Ok(false),
+            }
+        }
+    }
+
+    /// Opens the entry in `$EDITOR` until it is saved without problems. Returns
+    /// whether to submit it, or `None` when the editor could not be started or
+    /// was closed without saving, to fall back to the confirmation menu.
+    fn edit_as_yaml(&self, prepared: &mut PreparedBook) -> Result<Option<bool>, Box<dyn std::error::Error>> {
+        use crate::entry_editor::{self, EditableEntry};
+        use dialoguer::{theme::ColorfulTheme, Confirm, Editor};
+
+        let categories: Vec<String> = prepared.available_categories.iter().filter_map(|category| category.get_name()).collect();
+        let mut text = entry_editor::to_yaml(&EditableEntry::from_prepared(prepared), &categories);
+        loop {
+            let saved = match Editor::new().extension(".yaml").edit(&text) {
+                Ok(Some(saved)) => saved,
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    ui::warn(t!("entry_editor.unavailable", error = e.to_string()));
+                    return Ok(None);
+                }
+            };
+            match entry_editor::parse(&saved, &categories) {
+                Ok(Some(entry)) => {
+                    entry.apply(prepared);
+                    return Ok(Some(true));
+                }
+                Ok(None) => return Ok(Some(false)),
+                Err(errors) => {
+                    for error in &errors {
+                        ui::error(error);
+                    }
+                    let again = Confirm::with_theme(&ColorfulTheme::default())
+                        .with_prompt(t!("entry_editor.edit_again"))
+                        .default(true)
+                        .interact()?;
+                    if !again {
+                        return Ok(Some(false));
+                    }
+                    text = entry_editor::with_errors(&saved, &errors);
+                }
+            }
+        }
+    }
+
+    /// Prompts for a new value of one field. Storage boxes are only fetched the
+    /// first time the location is edited.
+    async fn edit_field(
+        &self,
+        prepared: &mut PreparedBook,
+        field: EditableField,
+        storages: &mut Option<Vec<crate::baserow::Storage>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use dialoguer::{theme::ColorfulTheme, Confirm, Editor, Input, MultiSelect, Select};
+        let theme = ColorfulTheme::default();
+
+        match field {
+            EditableField::Title => {
+                prepared.title = Input::with_theme(&theme)
+                    .with_prompt(t!("prompt.title"))
+                    .with_initial_text(&prepared.title)
+                    .interact_text()?;
+            }
+            EditableField::Author => {
+                prepared.author = Input::with_theme(&theme)
+                    .with_prompt(t!("prompt.author"))
+                    .with_initial_text(&prepared.author)
+                    .interact_text()?;
+            }
+            EditableField::Isbn => {
+                let isbn: String = Input::with_theme(&theme)
+                    .with_prompt(t!("prompt.isbn"))
+                    .with_initial_text(prepared.isbn.clone().unwrap_or_default())
+                    .allow_empty(true)
+                    .interact_text()?;
+                let isbn = isbn.trim();
+                prepared.isbn = (!isbn.is_empty()).then(|| isbn.to_string());
+            }
+            EditableField::Categories => {
+                let names: Vec<String> = prepared.available_categories.iter()
+                    .filter_map(|category| category.get_name())
+                    .collect();
+                let checked: Vec<bool> = names.iter()
+                    .map(|name| prepared.categories.iter().any(|selected| selected.eq_ignore_ascii_case(name)))
+                    .collect();
+                let picked = MultiSelect::with_theme(&theme)
+                    .with_prompt(t!("prompt.categories"))
+                    .items(&names)
+                    .defaults(&checked)
+                    .interact()?;
+                if picked.is_empty() {
+                    ui::show(t!("prompt.keep_categories"));
+                } else {
+                    prepared.categories = picked.into_iter().map(|i| names[i].clone()).collect();
+                }
+            }
+            EditableField::Synopsis => {
+                // Falls back to a one-line prompt when no editor is available
+                let edited = match Editor::new().edit(&prepared.synopsis) {
+                    Ok(edited) => edited,
+                    Err(_) => Some(Input::with_theme(&theme)
+                        .with_prompt(t!("prompt.synopsis"))
+                        .with_initial_text(&prepared.synopsis)
+                        .interact_text()?),
+                };
+                if let Some(synopsis) = edited {
+                    prepared.synopsis = synopsis.trim().to_string();
+                }
+            }
+            EditableField::Type => {
+                let types = [t!("media_type.physical"), t!("media_type.ebook")];
+                prepared.is_ebook = Select::with_theme(&theme)
+                    .with_prompt(t!("prompt.type"))
+                    .items(&types)
+                    .default(if prepared.is_ebook { 1 } else { 0 })
+                    .interact()? == 1;
+            }
+            EditableField::Status => {
+                let names: Vec<Cow<str>> = crate::baserow::STATUS_OPTIONS.iter().map(|(id, _)| status_label(*id)).collect();
+                let current = crate::baserow::STATUS_OPTIONS.iter()
+                    .position(|(id, _)| *id == prepared.status)
+                    .unwrap_or(0);
+                let picked = Select::with_theme(&theme)
+                    .with_prompt(t!("prompt.status"))
+                    .items(&names)
+                    .default(current)
+                    .interact()?;
+                prepared.status = crate::baserow::STATUS_OPTIONS[picked].0;
+            }
+            EditableField::Read => {
+                prepared.read = Confirm::with_theme(&theme)
+                    .with_prompt(t!("prompt.read"))
+                    .default(prepared.read)
+                    .interact()?;
+            }
+            EditableField::Rating => {
+                prepared.rating = Input::with_theme(&theme)
+                    .with_prompt(t!("prompt.rating"))
+                    .with_initial_text(prepared.rating.to_string())
+                    .validate_with(|rating: &u32| if *rating <= 10 { Ok(()) } else { Err(t!("prompt.rating_range")) })
+                    .interact_text()?;
+            }
+            EditableField::Series => {
+                let series: String = Input::with_theme(&theme)
+                    .with_prompt(t!("prompt.series"))
+                    .with_initial_text(prepared.series.clone().unwrap_or_default())
+                    .allow_empty(true)
+                    .interact_text()?;
+                let series = series.trim();
+                prepared.series = (!series.is_empty()).then(|| series.to_string());
+
+                let volume: String = Input::with_theme(&theme)
+                    .with_prompt(t!("prompt.volume_number"))
+                    .with_initial_text(prepared.volume_number.map(|number| number.to_string()).unwrap_or_default())
+                    .allow_empty(true)
+                    .validate_with(|volume: &String| {
+                        if volume.trim().is_empty() || volume.trim().parse::<u32>().is_ok() { Ok(()) } else { Err(t!("prompt.volume_number_invalid")) }
+                    })
+                    .interact_text()?;
+                prepared.volume_number = volume.trim().parse().ok();
+            }
+            EditableField::Location => {
+                if storages.is_none() {
+                    match self.available_storages().await {
+                        Ok(entries) => *storages = Some(entries),
+                        // The location can be set once online, so an offline add goes on without it
+                        Err(e) if self.offline => {
+                            ui::warn(e.to_string());
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                let storages = storages.as_deref().unwrap_or_default();
+
+                let mut names = vec![t!("prompt.no_location").into_owned()];
+                names.extend(storages.iter().map(|storage| {
+                    storage.get_name().unwrap_or_else(|| t!("summary.storage", id = storage.id).into_owned())
+                }));
+                let current = prepared.location.first()
+                    .and_then(|id| storages.iter().position(|storage| storage.id == *id))
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                let picked = Select::with_theme(&theme)
+                    .with_prompt(t!("prompt.location"))
+                    .items(&names)
+                    .default(current)
+                    .max_length(15)
+                    .interact()?;
+                prepared.location = match picked {
+                    0 => Vec::new(),
+                    i => vec![storages[i - 1].id],
+                };
+            }
+            EditableField::Sources => {
+                let Some(records) = prepared.sources.clone() else {
+                    return Ok(());
+                };
+                // Enter on a field switches it to the other catalogue's value and stays on it
+                let mut current = 0;
+                loop {
+                    let mut items: Vec<String> = SourcedField::ALL.iter()
+                        .map(|field| source_item(&records, prepared, *field))
+                        .collect();
+                    items.push(t!("sources.done").into_owned());
+                    current = Select::with_theme(&theme)
+                        .with_prompt(t!("sources.prompt"))
+                        .items(&items)
+                        .default(current)
+                        .interact()?;
+                    let Some(field) = SourcedField::ALL.get(current).copied() else {
+                        break;
+                    };
+                    match records.toggled(field, prepared.sourced_value(field).as_deref()) {
+                        Some((_, value)) => prepared.set_sourced_value(field, value),
+                        None => ui::show(t!("sources.nothing_else", field = field.label())),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_confirmation_summary(&self, prepared: &PreparedBook, storages: Option<&[crate::baserow::Storage]>) {
+        let mut rows = vec![
+            (t!("summary.title").into_owned(), prepared.title.clone()),
+            (t!("summary.author").into_owned(), prepared.author.clone()),
+        ];
         
         // ISBN if available
-        if let Some(isbn) = match book {
-            BookResult::Google(google_book) => google_book.get_isbn_13().or_else(|| google_book.get_isbn_10()),
-            BookResult::OpenLibrary(ol_book) => ol_book.get_best_isbn(),
-        } {
-            println!("ISBN:      {}", isbn);
+        if let Some(isbn) = &prepared.isbn {
+            rows.push((t!("summary.isbn").into_owned(), isbn.clone()));
+        }
+        if let Some(publisher) = &prepared.publisher {
+            rows.push((t!("summary.publisher").into_owned(), publisher.clone()));
+        }
+        if let Some(pages) = prepared.pages {
+            rows.push((t!("summary.pages").into_owned(), pages.to_string()));
         }
         
-        // Media type
-        println!("Type:      {}", if is_ebook { "📱 Ebook" } else { "📚 Physical Book" });
-        
-        // Categories
-        println!("Categories: {}", selected_categories.join(", "));
-        
-        // Synopsis (truncated for display)
-        let display_synopsis = if synopsis.len() > 300 {
-            format!("{}...", &synopsis[..297])
+        let media_type = if prepared.issue.is_some() {
+            t!("media_type.magazine")
+        } else if prepared.is_ebook {
+            t!("media_type.ebook")
         } else {
-            synopsis.to_string()
+            t!("media_type.physical")
         };
-        println!("Synopsis:  {}", display_synopsis);
+        rows.push((t!("summary.type").into_owned(), media_type.into_owned()));
+        if let Some(language) = prepared.language.as_ref().filter(|_| self.config.language_detection.enabled) {
+            rows.push((t!("summary.language").into_owned(), language.name.clone()));
+        }
+        if let Some(issue) = &prepared.issue {
+            rows.push((t!("summary.issue").into_owned(), issue.label()));
+        }
+        rows.push((t!("summary.categories").into_owned(), prepared.categories.join(", ")));
         
-        println!("==================================================");
+        if let Some(call_number) = &prepared.call_number {
+            rows.push((t!("summary.call_number").into_owned(), call_number.clone()));
+        }
+        if let Some(warnings) = &prepared.content_warnings {
+            let warnings = if warnings.is_empty() { t!("content_warnings.none").into_owned() } else { warnings.join("; ") };
+            rows.push((t!("summary.content_warnings").into_owned(), warnings));
+        }
+        let volume = prepared.volume_number.map(|number| format!("#{}", number));
+        match (&prepared.series, volume) {
+            (Some(series), Some(volume)) => rows.push((t!("summary.series").into_owned(), format!("{} {}", series, volume))),
+            (Some(series), None) => rows.push((t!("summary.series").into_owned(), series.clone())),
+            (None, Some(volume)) => rows.push((t!("summary.series").into_owned(), volume)),
+            (None, None) => {}
+        }
         
-        // Get user confirmation
-        use dialoguer::{theme::ColorfulTheme, Confirm};
+        rows.push((t!("summary.status").into_owned(), status_label(prepared.status).into_owned()));
+        if prepared.read {
+            rows.push((t!("summary.read").into_owned(), t!("summary.yes").into_owned()));
+        }
+        if prepared.rating > 0 {
+            rows.push((t!("summary.rating").into_owned(), "*".repeat(prepared.rating as usize)));
+        }
+        if !prepared.purchase.is_empty() {
+            let purchase = &prepared.purchase;
+            let details: Vec<String> = [
+                purchase.price.map(|price| format!("{:.2}", price)),
+                purchase.store.clone(),
+                purchase.purchased_at.clone(),
+            ].into_iter().flatten().collect();
+            rows.push((t!("summary.purchase").into_owned(), details.join(", ")));
+        }
+        if !prepared.copy.is_empty() {
+            let copy = &prepared.copy;
+            let details: Vec<String> = [
+                copy.condition.map(|condition| condition.label().into_owned()),
+                copy.edition.clone(),
+                copy.signed.then(|| t!("summary.signed").into_owned()),
+                copy.provenance.clone(),
+            ].into_iter().flatten().collect();
+            rows.push((t!("summary.copy").into_owned(), details.join(", ")));
+        }
         
-        let confirmation = Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Add this book to your library?")
-            .default(false)
-            .interact()?;
+        if let Some(location_id) = prepared.location.first() {
+            let location = storages.unwrap_or_default().iter()
+                .find(|storage| storage.id == *location_id)
+                .and_then(|storage| storage.get_name())
+                .unwrap_or_else(|| t!("summary.storage", id = location_id).into_owned());
+            rows.push((t!("summary.location").into_owned(), location));
+        }
+        
+        // Synopsis (truncated for display)
+        let display_synopsis = if prepared.synopsis.chars().count() > 300 {
+            crate::text::truncate(&prepared.synopsis, 297)
+        } else {
+            prepared.synopsis.clone()
+        };
+        rows.push((t!("summary.synopsis").into_owned(), display_synopsis));
         
-        Ok(confirmation)
+        ui::show("");
+        ui::show(ui::paint(Role::Heading, t!("summary.heading")));
+        ui::show(ui::fields(&rows));
+        ui::show("");
     }
 
     fn get_cover_image_url(&self, book: &BookResult) -> Option<String> {
+        if !self.covers {
+            return None;
+        }
         match book {
             BookResult::Google(google_book) => {
                 // Get the highest quality image available from Google Books
@@ -573,110 +2811,300 @@ impl CombinedBookSearcher {
                         .replace("http://", "https://")   // Ensure HTTPS
                         .replace("&edge=curl", "");      // Remove edge effects only
                     
-                    if self.config.app.verbose {
-                        println!("Original Google Books URL: {}", base_url);
-                        println!("Cleaned URL: {}", cleaned_url);
-                    }
+                    ui::detail(format!("Original Google Books URL: {}", base_url));
+                    ui::detail(format!("Cleaned URL: {}", cleaned_url));
                     
                     Some(cleaned_url)
                 })
             }
+            BookResult::OpenLibrary(ol_book) if ol_book.cover_url.is_some() => ol_book.cover_url.clone(),
             BookResult::OpenLibrary(ol_book) => {
                 // Generate Open Library cover URL if we have an ISBN
                 if let Some(isbn) = ol_book.get_best_isbn() {
                     let url = format!("https://covers.openlibrary.org/b/isbn/{}-L.jpg", isbn);
-                    if self.config.app.verbose {
-                        println!("Open Library cover URL: {}", url);
-                    }
+                    ui::detail(format!("Open Library cover URL: {}", url));
                     Some(url)
                 } else {
                     None
                 }
             }
+            BookResult::Manual(book) => book.cover_url.clone(),
+        }
+    }
+
+    /// Both catalogues' records of the picked book, found by its ISBN, for choosing
+    /// the source of each field in the confirmation menu. `None` when either
+    /// catalogue does not know the ISBN.
+    async fn source_records(&self, book: &BookResult) -> Option<crate::compare::SourceRecords> {
+        let isbn = book.get_isbn()?;
+        let (google, open_library) = match book {
+            BookResult::Google(item) => {
+                ui::detail("Looking the book up on Open Library to choose sources...");
+                let results = timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.open_library_client, &isbn)).await
+                    .inspect_err(|e| ui::detail(format!("Open Library lookup failed: {}", e)))
+                    .ok()?;
+                let open_library = results.books.into_iter().find_map(|book| match book {
+                    BookResult::OpenLibrary(ol_book) => Some(ol_book),
+                    _ => None,
+                })?;
+                (item.clone(), open_library)
+            }
+            BookResult::OpenLibrary(ol_book) => {
+                ui::detail("Looking the book up on Google Books to choose sources...");
+                let results = timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.google_client, &isbn)).await
+                    .inspect_err(|e| ui::detail(format!("Google Books lookup failed: {}", e)))
+                    .ok()?;
+                let google = results.books.into_iter().find_map(|book| match book {
+                    BookResult::Google(item) => Some(item),
+                    _ => None,
+                })?;
+                (google, ol_book.clone())
+            }
+            BookResult::Manual(_) => return None,
+        };
+        let edition = timing::time(Stage::Metadata, self.open_library_client.get_edition_by_isbn(&isbn)).await
+            .inspect_err(|e| ui::detail(format!("Open Library edition lookup failed: {}", e)))
+            .ok();
+        Some(crate::compare::SourceRecords { google, open_library, edition })
+    }
+
+    /// Takes each field from the catalogue `merge` in the config prefers for it,
+    /// looking the book's ISBN up in the other catalogue when needed. A failed
+    /// lookup leaves the picked result's fields as they are.
+    async fn apply_merge_policy(&self, book: &BookResult) -> BookResult {
+        let policy = &self.config.merge;
+        let Some(isbn) = book.get_isbn().filter(|_| !policy.is_default()) else {
+            return book.clone();
+        };
+
+        let (need_google, need_edition) = crate::merge::lookups_needed(book, policy);
+        let mut others = crate::merge::OtherRecords::default();
+        if need_google {
+            ui::detail("Looking the book up on Google Books for the merge policy...");
+            match timing::time(Stage::Metadata, BookSearcher::search_by_isbn(&self.google_client, &isbn)).await {
+                Ok(results) => others.google = results.books.into_iter().find_map(|book| match book {
+                    BookResult::Google(item) => Some(item),
+                    _ => None,
+                }),
+                Err(e) => ui::detail(format!("Google Books lookup for the merge policy failed: {}", e)),
+            }
+        }
+        if need_edition {
+            ui::detail("Looking the edition up on Open Library for the merge policy...");
+            match timing::time(Stage::Metadata, self.open_library_client.get_edition_by_isbn(&isbn)).await {
+                Ok(edition) => others.edition = Some(edition),
+                Err(e) => ui::detail(format!("Open Library lookup for the merge policy failed: {}", e)),
+            }
+        }
+
+        let largest_cover = if policy.cover == crate::config::CoverSource::Largest && self.covers {
+            self.largest_cover(&crate::merge::cover_candidates(book, &others)).await
+        } else {
+            None
+        };
+        crate::merge::merge(book, policy, &others, largest_cover)
+    }
+
+    /// The URL of the image with the most pixels among `urls`, downloading each
+    /// to read its size.
+    async fn largest_cover(&self, urls: &[String]) -> Option<String> {
+        let mut largest: Option<(u64, &String)> = None;
+        for url in urls {
+            if let Some((width, height)) = self.cover_dimensions(url).await {
+                let area = u64::from(width) * u64::from(height);
+                if largest.is_none_or(|(best, _)| area > best) {
+                    largest = Some((area, url));
+                }
+            }
+        }
+        largest.map(|(_, url)| url.clone())
+    }
+
+    /// The width and height of the image at `url`, or `None` when it cannot be
+    /// downloaded or read.
+    async fn cover_dimensions(&self, url: &str) -> Option<(u32, u32)> {
+        let dimensions = async {
+            let bytes = self.download_cover(url).await?;
+            let reader = image::io::Reader::new(std::io::Cursor::new(bytes)).with_guessed_format()?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(reader.into_dimensions()?)
+        };
+        match timing::time(Stage::Upload, dimensions).await {
+            Ok((width, height)) => {
+                ui::detail(format!("Cover {} is {}x{}", url, width, height));
+                Some((width, height))
+            }
+            Err(e) => {
+                ui::detail(format!("Could not measure {}: {}", url, e));
+                None
+            }
+        }
+    }
+
+    /// Cover URLs for a book already in the library, best first: those of the
+    /// editions found by ISBN, then of the editions found by title and author whose
+    /// title is the book's own, then Open Library's cover for the ISBN.
+    pub async fn find_cover_urls(&self, isbn: Option<&str>, title: &str, author: Option<&str>) -> Vec<String> {
+        let mut urls = Vec::new();
+        if let Some(isbn) = isbn {
+            match self.find_by_isbn(isbn).await {
+                Ok(results) => urls.extend(results.books.iter().filter_map(|book| self.get_cover_image_url(book))),
+                Err(e) => ui::detail(format!("ISBN lookup for the cover failed: {}", e)),
+            }
+        }
+        if urls.is_empty() && !title.trim().is_empty() {
+            // A relaxed search may find other books, so only the same title counts
+            let wanted = crate::duplicates::normalize_title(title);
+            match self.find_by_title_author(title, author).await {
+                Ok(results) => urls.extend(results.books.iter()
+                    .filter(|book| crate::duplicates::normalize_title(&book.get_full_title()) == wanted)
+                    .filter_map(|book| self.get_cover_image_url(book))),
+                Err(e) => ui::detail(format!("Title search for the cover failed: {}", e)),
+            }
+        }
+        if let Some(isbn) = isbn {
+            // `default=false` makes a missing cover a 404 instead of a blank image
+            urls.push(format!("https://covers.openlibrary.org/b/isbn/{}-L.jpg?default=false", isbn));
+        }
+        urls.dedup();
+        urls
+    }
+
+    /// Uploads the first of `urls` that downloads, returning the URL it came from.
+    pub async fn upload_first_cover(&self, urls: &[String]) -> Result<(String, crate::baserow::CoverImage), Box<dyn std::error::Error + Send + Sync>> {
+        let mut last_error: Box<dyn std::error::Error + Send + Sync> = "no cover URL".into();
+        for url in urls {
+            match timing::time(Stage::Upload, self.download_and_upload_image(url, "cover.jpg")).await {
+                Ok(uploaded) => return Ok((url.clone(), uploaded.into())),
+                Err(e) => {
+                    ui::detail(format!("Could not use {}: {}", url, e));
+                    last_error = e;
+                }
+            }
         }
+        Err(last_error)
     }
 
     async fn handle_cover_image_upload(&self, book: &BookResult) -> Vec<crate::baserow::CoverImage> {
+        if !self.covers {
+            ui::detail("Skipping the cover (--no-cover)");
+            return Vec::new();
+        }
+
         // Try primary cover image URL
         if let Some(image_url) = self.get_cover_image_url(book) {
-            if self.config.app.verbose {
-                println!("Found cover image URL: {}", image_url);
-            }
+            ui::detail(format!("Found cover image URL: {}", image_url));
             
             // Try download + direct upload approach
-            match self.download_and_upload_image(&image_url, "cover.jpg").await {
-                Ok(upload_response) => {
-                    return vec![crate::baserow::CoverImage {
-                        name: upload_response.name,
-                    }];
-                }
+            match timing::time(Stage::Upload, self.download_and_upload_image(&image_url, "cover.jpg")).await {
+                Ok(upload_response) => vec![upload_response.into()],
                 Err(e) => {
-                    eprintln!("⚠️  Failed to download/upload primary cover image: {}", e);
+                    ui::warn(format!("Failed to download/upload primary cover image: {}", e));
                     
                     // Try fallback for Google Books using Open Library if we have ISBN
                     if let BookResult::Google(google_book) = book {
                         if let Some(isbn) = google_book.get_isbn_13().or_else(|| google_book.get_isbn_10()) {
                             let fallback_url = format!("https://covers.openlibrary.org/b/isbn/{}-L.jpg", isbn);
-                            if self.config.app.verbose {
-                                println!("Trying Open Library fallback: {}", fallback_url);
-                            }
+                            ui::detail(format!("Trying Open Library fallback: {}", fallback_url));
                             
-                            match self.download_and_upload_image(&fallback_url, "cover-fallback.jpg").await {
+                            match timing::time(Stage::Upload, self.download_and_upload_image(&fallback_url, "cover-fallback.jpg")).await {
                                 Ok(upload_response) => {
-                                    println!("✅ Successfully uploaded cover using Open Library fallback");
-                                    return vec![crate::baserow::CoverImage {
-                                        name: upload_response.name,
-                                    }];
+                                    ui::info(ui::paint(Role::Success, "Successfully uploaded cover using Open Library fallback"));
+                                    return vec![upload_response.into()];
                                 }
                                 Err(fallback_e) => {
-                                    eprintln!("⚠️  Fallback download/upload also failed: {}", fallback_e);
+                                    ui::warn(format!("Fallback download/upload also failed: {}", fallback_e));
                                 }
                             }
                         }
                     }
                     
                     // Both primary and fallback failed
-                    println!("\n==================================================");
-                    println!("📝 IMPORTANT: Please manually upload the cover image");
-                    println!("   Primary URL: {}", image_url);
+                    ui::warn(t!("cover.upload_manually"));
+                    ui::warn(format!("   {}", t!("cover.primary_url", url = image_url)));
                     if let BookResult::Google(google_book) = book {
                         if let Some(isbn) = google_book.get_isbn_13().or_else(|| google_book.get_isbn_10()) {
-                            println!("   Fallback URL: https://covers.openlibrary.org/b/isbn/{}-L.jpg", isbn);
+                            let fallback_url = format!("https://covers.openlibrary.org/b/isbn/{}-L.jpg", isbn);
+                            ui::warn(format!("   {}", t!("cover.fallback_url", url = fallback_url)));
                         }
                     }
-                    println!("==================================================\n");
-                    return vec![];
+                    vec![]
                 }
             }
         } else {
-            println!("\n==================================================");
-            println!("📝 IMPORTANT: No cover image found");
-            println!("   Please manually upload a cover image to your book entry");
-            println!("==================================================\n");
+            ui::warn(t!("cover.not_found"));
+            ui::warn(format!("   {}", t!("cover.not_found_hint")));
             vec![]
         }
     }
 
-    async fn download_and_upload_image(&self, image_url: &str, filename: &str) -> Result<crate::baserow::FileUploadResponse, Box<dyn std::error::Error>> {
-        if self.config.app.verbose {
-            println!("Downloading image from: {}", image_url);
+    /// The image at `url`, kept in the offline cache for `wcm add --offline`,
+    /// which only reads the kept copy.
+    async fn download_cover(&self, url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        if self.offline {
+            return self.offline_cache.as_ref()
+                .and_then(|cache| cache.load_cover(url))
+                .ok_or_else(|| t!("offline.cover_not_cached", url = url).into_owned().into());
         }
-        
-        // Download the image
-        let response = reqwest::get(image_url).await?;
-        
+
+        let response = self.http_client.get(url).send_with_retry().await?;
         if !response.status().is_success() {
             return Err(format!("Failed to download image: HTTP {}", response.status()).into());
         }
+        let bytes = response.bytes().await?.to_vec();
+        if let Some(cache) = &self.offline_cache {
+            if let Err(e) = cache.store_cover(url, &bytes) {
+                ui::detail(format!("Could not write to the cache: {}", e));
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// The categories of the Baserow table, kept in the offline cache for
+    /// `wcm add --offline`, which only reads the kept list.
+    async fn available_categories(&self) -> Result<Vec<crate::baserow::Category>, Box<dyn std::error::Error>> {
+        if self.offline {
+            return self.offline_cache.as_ref()
+                .and_then(OfflineCache::load_categories)
+                .ok_or_else(|| WcmError::NotFound(t!("offline.no_categories").into_owned()).into());
+        }
+
+        let categories = timing::time(Stage::Library, self.baserow_client.fetch_categories()).await?;
+        if let Some(cache) = &self.offline_cache {
+            if let Err(e) = cache.store_categories(&categories) {
+                ui::detail(format!("Could not write to the cache: {}", e));
+            }
+        }
+        Ok(categories)
+    }
+
+    /// The storage rows a book can be put in, from the offline cache when
+    /// offline, as `available_categories`.
+    async fn available_storages(&self) -> Result<Vec<crate::baserow::Storage>, Box<dyn std::error::Error>> {
+        if self.offline {
+            return self.offline_cache.as_ref()
+                .and_then(OfflineCache::load_storages)
+                .ok_or_else(|| WcmError::NotFound(t!("offline.no_storages").into_owned()).into());
+        }
+
+        let storages = timing::time(Stage::Library, self.baserow_client.fetch_storage_entries()).await?;
+        if let Some(cache) = &self.offline_cache {
+            if let Err(e) = cache.store_storages(&storages) {
+                ui::detail(format!("Could not write to the cache: {}", e));
+            }
+        }
+        Ok(storages)
+    }
+
+    async fn download_and_upload_image(&self, image_url: &str, filename: &str) -> Result<crate::baserow::FileUploadResponse, Box<dyn std::error::Error + Send + Sync>> {
+        ui::detail(format!("Downloading image from: {}", image_url));
         
-        let image_data = response.bytes().await?;
+        // Download the image
+        let image_data = self.download_cover(image_url).await?;
         
-        if self.config.app.verbose {
-            println!("Downloaded {} bytes, uploading to Baserow...", image_data.len());
-        }
+        ui::detail(format!("Downloaded {} bytes, uploading to Baserow...", image_data.len()));
         
         // Upload directly to Baserow
-        let upload_response = self.baserow_client.upload_file_direct(image_data.to_vec(), filename).await?;
+        let upload_response = self.baserow_client.upload_file_direct(image_data, filename).await?;
         
         Ok(upload_response)
     }
@@ -0,0 +1,34 @@
+use crate::book_search::{BookResult, ManualBook, PreparedBook};
+use crate::config::EntryDefaults;
+
+/// The entries for the volumes of a box set added as row `parent_id`, in the
+/// order of `titles`. Each volume takes the box set's author, categories, type,
+/// status, read flag, location and content warnings, links back to the box set,
+/// and is recorded as volume 1, 2, ... of the box set's series, or of the box
+/// set itself when it has no series. Volumes get no ISBN, cover or synopsis of
+/// their own.
+pub fn volume_entries(parent: &PreparedBook, parent_id: u64, titles: &[String], defaults: &EntryDefaults) -> Vec<PreparedBook> {
+    let series = parent.series.clone().unwrap_or_else(|| parent.title.clone());
+    titles.iter()
+        .map(|title| title.trim())
+        .filter(|title| !title.is_empty())
+        .enumerate()
+        .map(|(i, title)| {
+            let book = BookResult::Manual(ManualBook {
+                title: title.to_string(),
+                author: parent.author.clone(),
+                ..Default::default()
+            });
+            let mut volume = PreparedBook::new(&book, parent.categories.clone(), String::new(), parent.is_ebook, parent.available_categories.clone(), defaults);
+            volume.publisher = parent.publisher.clone();
+            volume.status = parent.status;
+            volume.read = parent.read;
+            volume.location = parent.location.clone();
+            volume.series = Some(series.clone());
+            volume.volume_number = Some(i as u32 + 1);
+            volume.box_set = Some(parent_id);
+            volume.content_warnings = parent.content_warnings.clone();
+            volume
+        })
+        .collect()
+}
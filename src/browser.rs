@@ -0,0 +1,20 @@
+use std::process::Command;
+
+/// Opens `url` in the default browser, returning once the opener has started.
+pub fn open(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        // The empty argument is the window title `start` would otherwise take from the URL
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command.arg(url).status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("the browser opener exited with {}", status)));
+    }
+    Ok(())
+}
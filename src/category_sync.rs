@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::path::Path;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use crate::baserow::{BaserowClient, BaserowError, Category};
+use crate::duplicates::fold;
+use crate::timing::{self, Stage};
+
+/// A category as the canonical list in `wcm categories sync <file>` gives it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CanonicalCategory {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Earlier names; a row still called one of these is renamed
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Reads the canonical list, a YAML sequence of categories. Two entries with the
+/// same name, or a name also given as an alias, are an error.
+pub fn load(path: &Path) -> Result<Vec<CanonicalCategory>, Box<dyn std::error::Error>> {
+    let categories: Vec<CanonicalCategory> = serde_yaml::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{} is not a category list: {}", path.display(), e))?;
+    let mut seen = HashSet::new();
+    for name in categories.iter().flat_map(|category| std::iter::once(&category.name).chain(&category.aliases)) {
+        if name.trim().is_empty() {
+            return Err(format!("{} has a category without a name", path.display()).into());
+        }
+        if !seen.insert(fold(name)) {
+            return Err(format!("{} lists \"{}\" more than once", path.display(), name).into());
+        }
+    }
+    Ok(categories)
+}
+
+/// One step towards making the categories table match the canonical list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Change {
+    Create { name: String, description: String },
+    Rename { id: u64, from: String, to: String },
+    Describe { id: u64, name: String, description: String },
+    /// A row the list does not mention. Books may link to it, so it is only reported.
+    Unlisted { id: u64, name: String },
+}
+
+/// Compares the canonical list with the table's rows. A row matches a category
+/// when its name is the category's name or one of its aliases, ignoring case and
+/// accents; a matched row whose name is not written exactly as in the list is
+/// renamed, and one whose description differs from a non-empty listed one gets
+/// the listed description.
+pub fn plan(canonical: &[CanonicalCategory], rows: &[Category]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut matched = HashSet::new();
+
+    for category in canonical {
+        let names: Vec<String> = std::iter::once(&category.name).chain(&category.aliases).map(|name| fold(name)).collect();
+        // A row under the current name wins over one under an alias
+        let row = names.iter().find_map(|name| rows.iter()
+            .filter(|row| !matched.contains(&row.id))
+            .find(|row| row.get_name().is_some_and(|existing| fold(&existing) == *name)));
+
+        let Some(row) = row else {
+            changes.push(Change::Create { name: category.name.clone(), description: category.description.clone() });
+            continue;
+        };
+        matched.insert(row.id);
+
+        let existing = row.get_name().unwrap_or_default();
+        if existing != category.name {
+            changes.push(Change::Rename { id: row.id, from: existing, to: category.name.clone() });
+        }
+        if !category.description.is_empty() && row.get_description().unwrap_or_default().trim() != category.description.trim() {
+            changes.push(Change::Describe { id: row.id, name: category.name.clone(), description: category.description.clone() });
+        }
+    }
+
+    for row in rows.iter().filter(|row| !matched.contains(&row.id)) {
+        changes.push(Change::Unlisted { id: row.id, name: row.get_name().unwrap_or_default() });
+    }
+    changes
+}
+
+/// Makes the changes in the categories table `table_id`, stopping at the first
+/// that fails. Unlisted rows are left alone.
+pub async fn apply(client: &BaserowClient, table_id: u64, changes: &[Change]) -> Result<(), BaserowError> {
+    for change in changes {
+        match change {
+            Change::Create { name, description } => {
+                let fields = serde_json::json!({ "Name": name, "Description": description });
+                timing::time(Stage::Create, client.create_row(table_id, &fields)).await?;
+            }
+            Change::Rename { id, to, .. } => {
+                let _: Category = timing::time(Stage::Update, client.update_row(table_id, *id, &serde_json::json!({ "Name": to }))).await?;
+            }
+            Change::Describe { id, description, .. } => {
+                let _: Category = timing::time(Stage::Update, client.update_row(table_id, *id, &serde_json::json!({ "Description": description }))).await?;
+            }
+            Change::Unlisted { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// The changes under their heading, then the unlisted rows under their own,
+/// since a sync leaves those alone.
+pub fn format_changes(changes: &[Change], dry_run: bool) -> String {
+    let (unlisted, changes): (Vec<&Change>, Vec<&Change>) = changes.iter().partition(|change| matches!(change, Change::Unlisted { .. }));
+    let mut lines = Vec::new();
+    if changes.is_empty() {
+        lines.push(t!("categories.in_sync").into_owned());
+    } else {
+        lines.push(if dry_run { t!("categories.dry_run") } else { t!("categories.applied") }.into_owned());
+    }
+    for change in changes {
+        let line = match change {
+            Change::Create { name, .. } => t!("categories.create", name = name),
+            Change::Rename { from, to, .. } => t!("categories.rename", from = from, to = to),
+            Change::Describe { name, .. } => t!("categories.describe", name = name),
+            Change::Unlisted { .. } => continue,
+        };
+        lines.push(format!("  - {}", line));
+    }
+    if !unlisted.is_empty() {
+        lines.push(t!("categories.unlisted_heading").into_owned());
+        for change in unlisted {
+            if let Change::Unlisted { id, name } = change {
+                lines.push(format!("  - {}", t!("categories.unlisted", name = name, id = id)));
+            }
+        }
+    }
+    lines.join("\n")
+}
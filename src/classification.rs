@@ -0,0 +1,191 @@
+use serde_json::Value;
+use crate::book_search::BookResult;
+use crate::config::{ClassificationScheme, Config};
+use crate::http_cache::HttpCache;
+use crate::open_library::OpenLibraryClient;
+use crate::timing::{self, Stage};
+use crate::ui;
+use crate::rate_limit::SendWithRetry;
+
+/// Derives a Dewey or LC call number for a book from library catalogues, falling
+/// back to the LLM when no catalogue has classified it.
+///
+/// OCLC Classify, the usual source for this, was shut down in January 2024, so the
+/// catalogue data comes from Open Library editions and the Library of Congress.
+pub struct Classifier {
+    client: reqwest::Client,
+    open_library_client: OpenLibraryClient,
+    config: Config,
+}
+
+impl Classifier {
+    pub fn new(config: Config) -> Self {
+        let mut open_library_client = OpenLibraryClient::new(config.open_library.base_url.clone());
+        if let Some(cache) = HttpCache::from_config(&config.app) {
+            open_library_client = open_library_client.with_cache(cache);
+        }
+        Self {
+            client: reqwest::Client::new(),
+            open_library_client,
+            config,
+        }
+    }
+
+    /// Uses `client` for the catalogue lookups, including the Open Library ones.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.open_library_client = self.open_library_client.with_http_client(client.clone());
+        self.client = client;
+        self
+    }
+
+    /// Returns the call number for a book, or `None` when no source could classify it.
+    /// Lookup failures are reported but never stop a book from being added.
+    pub async fn call_number(&self, book: &BookResult) -> Option<String> {
+        let scheme = self.config.classification.scheme;
+        let isbn = book.get_isbn();
+
+        let mut class = None;
+        if let Some(isbn) = &isbn {
+            class = match timing::time(Stage::Classification, self.lookup_open_library(isbn)).await {
+                Ok(class) => class,
+                Err(e) => {
+                    ui::detail(format!("Open Library classification lookup failed: {}", e));
+                    None
+                }
+            };
+
+            // The loc.gov JSON API only exposes LC call numbers
+            if class.is_none() && scheme == ClassificationScheme::Lcc {
+                class = match timing::time(Stage::Classification, self.lookup_library_of_congress(isbn)).await {
+                    Ok(class) => class,
+                    Err(e) => {
+                        ui::detail(format!("Library of Congress lookup failed: {}", e));
+                        None
+                    }
+                };
+            }
+        }
+
+        if class.is_none() && self.config.classification.llm_fallback {
+            class = match self.ask_llm(book).await {
+                Ok(class) => class,
+                Err(e) => {
+                    ui::warn(format!("Failed to classify book with LLM: {}", e));
+                    None
+                }
+            };
+        }
+
+        let class = class?;
+        Some(match scheme {
+            // Dewey numbers need an author mark to sort books within a class
+            ClassificationScheme::Dewey => format!("{} {}", class, author_mark(&book.get_all_authors())),
+            // LC call numbers already end in a cutter
+            ClassificationScheme::Lcc => class,
+        })
+    }
+
+    async fn lookup_open_library(&self, isbn: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        ui::detail("Looking up classification on Open Library...");
+
+        let edition = self.open_library_client.get_edition_by_isbn(isbn).await?;
+        let candidates = match self.config.classification.scheme {
+            ClassificationScheme::Dewey => edition.dewey_decimal_class,
+            ClassificationScheme::Lcc => edition.lc_classifications,
+        };
+
+        Ok(candidates.unwrap_or_default().iter().find_map(|raw| self.normalize(raw)))
+    }
+
+    async fn lookup_library_of_congress(&self, isbn: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let url = format!("{}/books/?q={}&fo=json&c=5",
+            self.config.classification.loc_base_url.trim_end_matches('/'),
+            isbn
+        );
+
+        ui::detail(format!("Looking up classification on the Library of Congress: {}", url));
+
+        let response = self.client.get(&url).send_with_retry().await?;
+        if !response.status().is_success() {
+            return Err(format!("Library of Congress API error: {}", response.status()).into());
+        }
+
+        let body: Value = response.json().await?;
+        let results = body.get("results").and_then(Value::as_array).cloned().unwrap_or_default();
+        let call_numbers = results.iter().flat_map(|result| {
+            [result.get("call_number"), result.get("item").and_then(|item| item.get("call_number"))]
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_array)
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        });
+
+        for call_number in call_numbers {
+            if let Some(class) = self.normalize(&call_number) {
+                return Ok(Some(class));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn ask_llm(&self, book: &BookResult) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let scheme = self.config.classification.scheme;
+        ui::info(format!("No catalogue classification found, asking the LLM for a {} number...", scheme.name()));
+
+        let book_info = format!("Title: {}\nAuthor: {}\nPublished: {}",
+            book.get_full_title(),
+            book.get_all_authors(),
+            book.get_published_date().unwrap_or_else(|| "Unknown".to_string())
+        );
+        let llm_provider = crate::llm::LlmProvider::from_config(&self.config)?
+            .with_http_client(self.client.clone());
+        let suggestion = timing::time(Stage::Llm, llm_provider.suggest_classification(&book_info, scheme.name())).await?;
+
+        Ok(self.normalize(&suggestion))
+    }
+
+    fn normalize(&self, raw: &str) -> Option<String> {
+        match self.config.classification.scheme {
+            ClassificationScheme::Dewey => normalize_dewey(raw),
+            ClassificationScheme::Lcc => normalize_lcc(raw),
+        }
+    }
+}
+
+/// Cleans up a Dewey class such as `823/.912` (with segmentation marks) to `823.912`.
+/// Values that are not numeric classes, like `[Fic]` or `B`, are rejected.
+fn normalize_dewey(raw: &str) -> Option<String> {
+    let cleaned: String = raw.split_whitespace().next()?
+        .chars()
+        .filter(|c| *c != '/' && *c != '\'')
+        .collect();
+    let (whole, fraction) = cleaned.split_once('.').unwrap_or((&cleaned, ""));
+
+    let valid = whole.len() == 3
+        && whole.chars().all(|c| c.is_ascii_digit())
+        && fraction.chars().all(|c| c.is_ascii_digit());
+    valid.then(|| cleaned.trim_end_matches('.').to_string())
+}
+
+/// Accepts LC call numbers, which start with one to three class letters and a number.
+fn normalize_lcc(raw: &str) -> Option<String> {
+    let cleaned = raw.trim();
+    let letters = cleaned.chars().take_while(|c| c.is_ascii_uppercase()).count();
+    let valid = (1..=3).contains(&letters)
+        && cleaned[letters..].trim_start().starts_with(|c: char| c.is_ascii_digit());
+    valid.then(|| cleaned.to_string())
+}
+
+/// First three letters of the first author's surname, e.g. `TOL` for J.R.R. Tolkien.
+fn author_mark(authors: &str) -> String {
+    let first_author = authors.split(',').next().unwrap_or_default();
+    let surname = first_author.split_whitespace().last().unwrap_or_default();
+    surname.chars()
+        .filter(|c| c.is_alphabetic())
+        .take(3)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
@@ -0,0 +1,257 @@
+use std::borrow::Cow;
+use rust_i18n::t;
+use crate::book_search::BookResult;
+use crate::duplicates::{normalize_isbn, normalize_title};
+use crate::google_books::BookItem;
+use crate::open_library::{OpenLibraryBook, OpenLibraryBookDetails};
+use crate::text::strip_html;
+use crate::ui::{self, Role};
+
+/// One row of the `--compare` table: a field as each catalogue has it, `None`
+/// where the catalogue leaves it out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparedField {
+    pub name: Cow<'static, str>,
+    pub google: Option<String>,
+    pub open_library: Option<String>,
+}
+
+/// The first Google Books result that Open Library also found, with Open
+/// Library's record of it: one sharing an ISBN, or else one with the same title
+/// once subtitles and punctuation are ignored.
+pub fn pair<'a>(google: &'a [BookResult], open_library: &'a [BookResult]) -> Option<(&'a BookItem, &'a OpenLibraryBook)> {
+    let google: Vec<&BookItem> = google.iter()
+        .filter_map(|book| match book {
+            BookResult::Google(item) => Some(item),
+            _ => None,
+        })
+        .collect();
+    let open_library: Vec<&OpenLibraryBook> = open_library.iter()
+        .filter_map(|book| match book {
+            BookResult::OpenLibrary(ol_book) => Some(ol_book),
+            _ => None,
+        })
+        .collect();
+
+    for item in &google {
+        let isbns: Vec<String> = [item.get_isbn_13(), item.get_isbn_10()].into_iter().flatten()
+            .map(|isbn| normalize_isbn(&isbn))
+            .collect();
+        let same_isbn = open_library.iter().find(|ol_book| {
+            ol_book.isbn.iter().flatten().any(|isbn| isbns.contains(&normalize_isbn(isbn)))
+        });
+        if let Some(ol_book) = same_isbn {
+            return Some((item, ol_book));
+        }
+    }
+    google.iter().find_map(|item| {
+        let title = normalize_title(&item.get_full_title());
+        open_library.iter()
+            .find(|ol_book| !title.is_empty() && normalize_title(&ol_book.get_full_title()) == title)
+            .map(|ol_book| (*item, *ol_book))
+    })
+}
+
+fn word_count(text: Option<String>) -> Option<String> {
+    let words = text?.split_whitespace().count();
+    (words > 0).then(|| t!("compare.words", count = words).into_owned())
+}
+
+fn size((width, height): (u32, u32)) -> String {
+    format!("{}x{}", width, height)
+}
+
+/// ISBN-13 when there is one, as for Google Books, so equal ISBNs look equal.
+fn open_library_isbn(open_library: &OpenLibraryBook) -> Option<String> {
+    open_library.isbn.iter().flatten().find(|isbn| isbn.chars().filter(char::is_ascii_digit).count() == 13).cloned()
+        .or_else(|| open_library.get_best_isbn())
+}
+
+fn open_library_publisher(open_library: &OpenLibraryBook, edition: Option<&OpenLibraryBookDetails>) -> Option<String> {
+    edition.and_then(|edition| edition.publishers.as_ref()?.first().cloned())
+        .or_else(|| open_library.get_primary_publisher())
+}
+
+fn open_library_pages(open_library: &OpenLibraryBook, edition: Option<&OpenLibraryBookDetails>) -> Option<u32> {
+    edition.and_then(|edition| edition.number_of_pages).or(open_library.number_of_pages_median)
+}
+
+fn open_library_description(open_library: &OpenLibraryBook, edition: Option<&OpenLibraryBookDetails>) -> Option<String> {
+    edition.and_then(|edition| edition.get_description()).or_else(|| open_library.description.clone())
+}
+
+/// The fields worth weighing when both catalogues have the book. `edition` is
+/// Open Library's edition record, which has the description and exact page count
+/// a search result lacks; `covers` are the measured cover sizes, Google's first.
+pub fn compare(
+    google: &BookItem,
+    open_library: &OpenLibraryBook,
+    edition: Option<&OpenLibraryBookDetails>,
+    covers: [Option<(u32, u32)>; 2],
+) -> Vec<ComparedField> {
+    let info = &google.volume_info;
+    let field = |name, google: Option<String>, open_library: Option<String>| ComparedField { name, google, open_library };
+    vec![
+        field(t!("compare.title"), Some(google.get_full_title()), Some(open_library.get_full_title())),
+        field(t!("compare.authors"),
+            info.authors.as_ref().map(|authors| authors.join(", ")),
+            open_library.author_name.as_ref().map(|authors| authors.join(", "))),
+        field(t!("compare.published"),
+            info.published_date.clone(),
+            edition.and_then(|edition| edition.publish_date.clone())
+                .or_else(|| open_library.get_latest_publish_year().map(|year| year.to_string()))),
+        field(t!("compare.publisher"), info.publisher.clone(), open_library_publisher(open_library, edition)),
+        field(t!("compare.pages"),
+            info.page_count.filter(|pages| *pages > 0).map(|pages| pages.to_string()),
+            open_library_pages(open_library, edition).map(|pages| pages.to_string())),
+        field(t!("compare.isbn"), google.get_isbn_13().or_else(|| google.get_isbn_10()), open_library_isbn(open_library)),
+        field(t!("compare.description"),
+            word_count(info.description.as_deref().map(strip_html)),
+            word_count(open_library_description(open_library, edition))),
+        field(t!("compare.cover"), covers[0].map(size), covers[1].map(size)),
+    ]
+}
+
+/// The fields as a table with a column per catalogue. Rows where the catalogues
+/// disagree are highlighted.
+pub fn format_table(fields: &[ComparedField]) -> String {
+    let missing = t!("compare.missing");
+    let header = [String::new(), "Google Books".to_string(), "Open Library".to_string()];
+    let rows: Vec<[String; 3]> = fields.iter()
+        .map(|field| [
+            field.name.to_string(),
+            field.google.clone().unwrap_or_else(|| missing.to_string()),
+            field.open_library.clone().unwrap_or_else(|| missing.to_string()),
+        ])
+        .collect();
+
+    let mut widths = [0; 2];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(console::measure_text_width(cell));
+        }
+    }
+    let pad = |cell: &str, width: usize| format!("{}{}", cell, " ".repeat(width - console::measure_text_width(cell)));
+
+    let mut lines = vec![ui::paint(Role::Label, format!("{}  {}  {}", pad(&header[0], widths[0]), pad(&header[1], widths[1]), header[2]))];
+    for (row, field) in rows.iter().zip(fields) {
+        let line = format!("{}  {}  {}", pad(&row[0], widths[0]), pad(&row[1], widths[1]), row[2]);
+        lines.push(if field.google != field.open_library { ui::paint(Role::Warning, line) } else { line });
+    }
+    lines.join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    GoogleBooks,
+    OpenLibrary,
+}
+
+impl Source {
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::GoogleBooks => "Google Books",
+            Source::OpenLibrary => "Open Library",
+        }
+    }
+}
+
+/// Fields of a new entry that can be taken from either catalogue in the
+/// confirmation menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourcedField {
+    Title,
+    Author,
+    Isbn,
+    Publisher,
+    Pages,
+    /// Stored as the synopsis
+    Description,
+    Cover,
+}
+
+impl SourcedField {
+    pub const ALL: [SourcedField; 7] = [
+        SourcedField::Title,
+        SourcedField::Author,
+        SourcedField::Isbn,
+        SourcedField::Publisher,
+        SourcedField::Pages,
+        SourcedField::Description,
+        SourcedField::Cover,
+    ];
+
+    pub fn label(&self) -> Cow<'static, str> {
+        match self {
+            SourcedField::Title => t!("compare.title"),
+            SourcedField::Author => t!("compare.authors"),
+            SourcedField::Isbn => t!("compare.isbn"),
+            SourcedField::Publisher => t!("compare.publisher"),
+            SourcedField::Pages => t!("compare.pages"),
+            SourcedField::Description => t!("compare.description"),
+            SourcedField::Cover => t!("compare.cover"),
+        }
+    }
+}
+
+/// The same book as both catalogues record it, so that each field of the entry
+/// can be taken from either.
+#[derive(Debug, Clone)]
+pub struct SourceRecords {
+    pub google: BookItem,
+    pub open_library: OpenLibraryBook,
+    /// Open Library's edition record, for the description and exact page count
+    pub edition: Option<OpenLibraryBookDetails>,
+}
+
+impl SourceRecords {
+    /// `field` as `source` has it, in the form the entry stores it.
+    pub fn value(&self, field: SourcedField, source: Source) -> Option<String> {
+        let info = &self.google.volume_info;
+        let open_library = &self.open_library;
+        let edition = self.edition.as_ref();
+        let value = match (field, source) {
+            (SourcedField::Title, Source::GoogleBooks) => Some(self.google.get_full_title()),
+            (SourcedField::Title, Source::OpenLibrary) => Some(open_library.get_full_title()),
+            (SourcedField::Author, Source::GoogleBooks) => info.authors.as_ref().map(|authors| authors.join(", ")),
+            (SourcedField::Author, Source::OpenLibrary) => open_library.author_name.as_ref().map(|authors| authors.join(", ")),
+            (SourcedField::Isbn, Source::GoogleBooks) => self.google.get_isbn_13().or_else(|| self.google.get_isbn_10()),
+            (SourcedField::Isbn, Source::OpenLibrary) => open_library_isbn(open_library),
+            (SourcedField::Publisher, Source::GoogleBooks) => info.publisher.clone(),
+            (SourcedField::Publisher, Source::OpenLibrary) => open_library_publisher(open_library, edition),
+            (SourcedField::Pages, Source::GoogleBooks) => info.page_count.filter(|pages| *pages > 0).map(|pages| pages.to_string()),
+            (SourcedField::Pages, Source::OpenLibrary) => open_library_pages(open_library, edition).map(|pages| pages.to_string()),
+            (SourcedField::Description, Source::GoogleBooks) => info.description.as_deref().map(strip_html),
+            (SourcedField::Description, Source::OpenLibrary) => open_library_description(open_library, edition),
+            (SourcedField::Cover, Source::GoogleBooks) => crate::merge::google_cover(&self.google),
+            (SourcedField::Cover, Source::OpenLibrary) => open_library.get_cover_url()
+                .or_else(|| edition?.get_cover_url()),
+        };
+        value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+    }
+
+    /// The catalogue the entry's `current` value of `field` came from, Google
+    /// Books when both agree, `None` when it came from neither, such as a
+    /// generated synopsis or a corrected title.
+    pub fn source_of(&self, field: SourcedField, current: Option<&str>) -> Option<Source> {
+        let current = current?.trim();
+        [Source::GoogleBooks, Source::OpenLibrary].into_iter()
+            .find(|source| self.value(field, *source).as_deref() == Some(current))
+    }
+
+    /// What toggling `field` switches the entry to: the other catalogue's value,
+    /// or Google Books' (failing that Open Library's) when the current value came
+    /// from neither. `None` when there is nothing different to switch to.
+    pub fn toggled(&self, field: SourcedField, current: Option<&str>) -> Option<(Source, String)> {
+        let candidates: &[Source] = match self.source_of(field, current) {
+            Some(Source::GoogleBooks) => &[Source::OpenLibrary],
+            Some(Source::OpenLibrary) => &[Source::GoogleBooks],
+            None => &[Source::GoogleBooks, Source::OpenLibrary],
+        };
+        candidates.iter().find_map(|source| {
+            self.value(field, *source)
+                .filter(|value| Some(value.as_str()) != current.map(str::trim))
+                .map(|value| (*source, value))
+        })
+    }
+}
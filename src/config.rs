@@ -1,71 +1,1352 @@
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Every section and field has a default except the Baserow token and IDs, so wcm
+/// runs from environment variables alone; `missing_required` lists what is unset.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Config {
+    #[serde(default)]
     pub google_books: GoogleBooksConfig,
+    #[serde(default)]
     pub open_library: OpenLibraryConfig,
+    #[serde(default)]
     pub baserow: BaserowConfig,
+    #[serde(default)]
     pub llm: LlmConfig,
+    #[serde(default)]
     pub app: AppConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub telegram: TelegramConfig,
+    #[serde(default)]
+    pub label: LabelConfig,
+    #[serde(default)]
+    pub classification: ClassificationConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub merge: MergeConfig,
+    #[serde(default)]
+    pub author_format: AuthorFormatConfig,
+    #[serde(default)]
+    pub transliteration: TransliterationConfig,
+    #[serde(default)]
+    pub language_detection: LanguageDetectionConfig,
+    #[serde(default)]
+    pub content_warnings: ContentWarningsConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct GoogleBooksConfig {
+    /// Optional; without a key Google Books allows fewer requests
+    #[serde(default)]
     pub api_key: String,
+    #[serde(default = "default_google_books_base_url")]
     pub base_url: String,
 }
 
+fn default_google_books_base_url() -> String {
+    "https://www.googleapis.com/books/v1".to_string()
+}
+
+impl Default for GoogleBooksConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: default_google_books_base_url(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OpenLibraryConfig {
+    #[serde(default = "default_open_library_base_url")]
     pub base_url: String,
 }
 
+fn default_open_library_base_url() -> String {
+    "https://openlibrary.org".to_string()
+}
+
+impl Default for OpenLibraryConfig {
+    fn default() -> Self {
+        Self { base_url: default_open_library_base_url() }
+    }
+}
+
+/// The token and IDs have no sensible default; left unset they are empty and 0,
+/// and startup reports them through `Config::missing_required`.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BaserowConfig {
+    #[serde(default)]
     pub api_token: String,
+    #[serde(default = "default_baserow_base_url")]
     pub base_url: String,
+    #[serde(default)]
     pub database_id: u64,
+    #[serde(default)]
     pub media_table_id: u64,
+    #[serde(default)]
     pub categories_table_id: u64,
+    #[serde(default)]
     pub storage_table_id: u64,
+    #[serde(default)]
     pub storage_view_id: u64,
+    /// View `wcm open` shows media rows in; the table's first view when unset
+    #[serde(default)]
+    pub media_view_id: Option<u64>,
+    /// Table of authors, one row per person with a `Name` field; when set, added
+    /// books are linked to their author rows through `fields.authors`
+    #[serde(default)]
+    pub authors_table_id: Option<u64>,
+    /// Table of publishers with a `Name` field; when set, added books are linked
+    /// to their publisher's row through `fields.publisher`
+    #[serde(default)]
+    pub publishers_table_id: Option<u64>,
+    /// Accept a self-signed or otherwise invalid certificate from `base_url`
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    #[serde(default)]
+    pub fields: MediaFieldsConfig,
+}
+
+fn default_baserow_base_url() -> String {
+    "https://api.baserow.io".to_string()
+}
+
+impl Default for BaserowConfig {
+    fn default() -> Self {
+        Self {
+            api_token: String::new(),
+            base_url: default_baserow_base_url(),
+            database_id: 0,
+            media_table_id: 0,
+            categories_table_id: 0,
+            storage_table_id: 0,
+            storage_view_id: 0,
+            media_view_id: None,
+            authors_table_id: None,
+            publishers_table_id: None,
+            accept_invalid_certs: false,
+            fields: MediaFieldsConfig::default(),
+        }
+    }
+}
+
+/// Names of optional media table fields. They are only written when the book has
+/// a value for them, so tables without these fields keep working.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MediaFieldsConfig {
+    #[serde(default = "default_series_field")]
+    pub series: String,
+    /// Number field holding the book's place in the series
+    #[serde(default = "default_volume_field")]
+    pub volume: String,
+    /// Number field with two decimal places
+    #[serde(default = "default_price_field")]
+    pub price: String,
+    /// Date field
+    #[serde(default = "default_purchased_at_field")]
+    pub purchased_at: String,
+    #[serde(default = "default_store_field")]
+    pub store: String,
+    /// Date field set to the day a book is added as read, counted by `wcm goal`
+    #[serde(default = "default_read_at_field")]
+    pub read_at: String,
+    /// Number field with the book's page count, read by `wcm log` to estimate a finish date
+    #[serde(default = "default_pages_field")]
+    pub pages: String,
+    /// Long text field `wcm quote` appends to
+    #[serde(default = "default_quotes_field")]
+    pub quotes: String,
+    /// Long text field `wcm note` appends to
+    #[serde(default = "default_notes_field")]
+    pub notes: String,
+    /// Link row field to the table `authors_table_id`
+    #[serde(default = "default_authors_field")]
+    pub authors: String,
+    /// Link row field to the table `publishers_table_id`
+    #[serde(default = "default_publisher_field")]
+    pub publisher: String,
+    /// Text field holding the ASIN of books added with `--asin`
+    #[serde(default = "default_asin_field")]
+    pub asin: String,
+    /// Date field for the cover date of magazine issues
+    #[serde(default = "default_issue_date_field")]
+    pub issue_date: String,
+    /// Text field for the issue number of magazine issues
+    #[serde(default = "default_issue_number_field")]
+    pub issue_number: String,
+    /// Link row field to the media table itself, linking the volumes added with
+    /// `--boxset` to the box set's row
+    #[serde(default = "default_box_set_field")]
+    pub box_set: String,
+    /// Text field for the romanized title of books with Thai titles, see `transliteration`
+    #[serde(default = "default_romanized_title_field")]
+    pub romanized_title: String,
+    /// Text field for the detected language of the book, as an ISO 639-1 code
+    #[serde(default = "default_language_field")]
+    pub language: String,
+    /// Long text field for the LLM's content warnings, see `content_warnings`
+    #[serde(default = "default_content_warnings_field")]
+    pub content_warnings: String,
+    /// Text field for `--condition`: new, good or worn
+    #[serde(default = "default_condition_field")]
+    pub condition: String,
+    /// Text field for `--edition-note`
+    #[serde(default = "default_edition_field")]
+    pub edition: String,
+    /// Boolean field set by `--signed`
+    #[serde(default = "default_signed_field")]
+    pub signed: String,
+    /// Text field for `--provenance`
+    #[serde(default = "default_provenance_field")]
+    pub provenance: String,
+    /// Text field with who a book is lent to, set by `wcm loan lend`
+    #[serde(default = "default_borrower_field")]
+    pub borrower: String,
+    /// Date field set to the day a book is lent
+    #[serde(default = "default_loaned_at_field")]
+    pub loaned_at: String,
+    /// Date field with when a lent book is due back, read by `wcm loan overdue`
+    #[serde(default = "default_loan_due_field")]
+    pub loan_due: String,
+    /// Created on field, read by `wcm stats` for the books added each month
+    #[serde(default = "default_created_at_field")]
+    pub created_at: String,
+}
+
+fn default_series_field() -> String {
+    "Series".to_string()
+}
+
+fn default_volume_field() -> String {
+    "Volume".to_string()
+}
+
+fn default_price_field() -> String {
+    "Price".to_string()
+}
+
+fn default_purchased_at_field() -> String {
+    "Purchased".to_string()
+}
+
+fn default_store_field() -> String {
+    "Store".to_string()
+}
+
+fn default_read_at_field() -> String {
+    "Date Read".to_string()
+}
+
+fn default_pages_field() -> String {
+    "Pages".to_string()
+}
+
+fn default_quotes_field() -> String {
+    "Quotes".to_string()
+}
+
+fn default_notes_field() -> String {
+    "Notes".to_string()
+}
+
+fn default_borrower_field() -> String {
+    "Borrower".to_string()
+}
+
+fn default_loaned_at_field() -> String {
+    "Loaned".to_string()
+}
+
+fn default_loan_due_field() -> String {
+    "Due".to_string()
+}
+
+fn default_created_at_field() -> String {
+    "Created on".to_string()
+}
+
+fn default_authors_field() -> String {
+    "Authors".to_string()
+}
+
+fn default_publisher_field() -> String {
+    "Publisher".to_string()
+}
+
+fn default_asin_field() -> String {
+    "ASIN".to_string()
+}
+
+fn default_issue_date_field() -> String {
+    "Issue Date".to_string()
+}
+
+fn default_issue_number_field() -> String {
+    "Issue".to_string()
+}
+
+fn default_box_set_field() -> String {
+    "Box Set".to_string()
+}
+
+fn default_romanized_title_field() -> String {
+    "Title (Romanized)".to_string()
+}
+
+fn default_language_field() -> String {
+    "Language".to_string()
+}
+
+fn default_content_warnings_field() -> String {
+    "Content Warnings".to_string()
+}
+
+fn default_condition_field() -> String {
+    "Condition".to_string()
+}
+
+fn default_edition_field() -> String {
+    "Edition".to_string()
+}
+
+fn default_signed_field() -> String {
+    "Signed".to_string()
+}
+
+fn default_provenance_field() -> String {
+    "Provenance".to_string()
+}
+
+impl Default for MediaFieldsConfig {
+    fn default() -> Self {
+        Self {
+            series: default_series_field(),
+            volume: default_volume_field(),
+            price: default_price_field(),
+            purchased_at: default_purchased_at_field(),
+            store: default_store_field(),
+            read_at: default_read_at_field(),
+            pages: default_pages_field(),
+            quotes: default_quotes_field(),
+            notes: default_notes_field(),
+            authors: default_authors_field(),
+            publisher: default_publisher_field(),
+            asin: default_asin_field(),
+            issue_date: default_issue_date_field(),
+            issue_number: default_issue_number_field(),
+            box_set: default_box_set_field(),
+            romanized_title: default_romanized_title_field(),
+            language: default_language_field(),
+            content_warnings: default_content_warnings_field(),
+            condition: default_condition_field(),
+            edition: default_edition_field(),
+            signed: default_signed_field(),
+            provenance: default_provenance_field(),
+            borrower: default_borrower_field(),
+            loaned_at: default_loaned_at_field(),
+            loan_due: default_loan_due_field(),
+            created_at: default_created_at_field(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LlmConfig {
+    #[serde(default = "default_llm_provider")]
     pub provider: String,
+    #[serde(default)]
     pub openai: OpenAiConfig,
+    #[serde(default)]
     pub anthropic: AnthropicConfig,
+    #[serde(default)]
     pub ollama: OllamaConfig,
+    /// Provider whose embedding model indexes the library for `wcm find
+    /// --semantic`, "ollama" or "openai"; `provider` when unset
+    #[serde(default)]
+    pub embedding_provider: Option<String>,
+    #[serde(default)]
+    pub budget: LlmBudgetConfig,
+}
+
+fn default_llm_provider() -> String {
+    "ollama".to_string()
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_llm_provider(),
+            openai: OpenAiConfig::default(),
+            anthropic: AnthropicConfig::default(),
+            ollama: OllamaConfig::default(),
+            embedding_provider: None,
+            budget: LlmBudgetConfig::default(),
+        }
+    }
+}
+
+/// Monthly cap on what the paid providers, OpenAI and Anthropic, may use.
+/// Without `monthly_tokens` or `monthly_spend` usage is only tracked.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LlmBudgetConfig {
+    /// Prompt and completion tokens per calendar month
+    #[serde(default)]
+    pub monthly_tokens: Option<u64>,
+    /// Spend per calendar month, in the currency of the prices below
+    #[serde(default)]
+    pub monthly_spend: Option<f64>,
+    /// Price of a million prompt tokens
+    #[serde(default)]
+    pub input_price: f64,
+    /// Price of a million completion tokens
+    #[serde(default)]
+    pub output_price: f64,
+    #[serde(default)]
+    pub fallback: BudgetFallback,
+    /// JSON file the tokens used each month are counted in; empty turns tracking off
+    #[serde(default = "default_llm_usage_file")]
+    pub usage_file: String,
+}
+
+fn default_llm_usage_file() -> String {
+    "llm_usage.json".to_string()
+}
+
+impl Default for LlmBudgetConfig {
+    fn default() -> Self {
+        Self {
+            monthly_tokens: None,
+            monthly_spend: None,
+            input_price: 0.0,
+            output_price: 0.0,
+            fallback: BudgetFallback::default(),
+            usage_file: default_llm_usage_file(),
+        }
+    }
+}
+
+/// What wcm uses instead of a paid provider once the budget is spent.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetFallback {
+    /// The local Ollama server from `llm.ollama`
+    #[default]
+    Ollama,
+    /// No LLM: each step goes on as when the LLM is unavailable
+    None,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OpenAiConfig {
+    #[serde(default)]
     pub api_key: String,
+    #[serde(default = "default_openai_model")]
     pub model: String,
+    #[serde(default = "default_openai_base_url")]
     pub base_url: String,
+    #[serde(default = "default_openai_embedding_model")]
+    pub embedding_model: String,
+}
+
+fn default_openai_embedding_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+fn default_openai_model() -> String {
+    "gpt-4".to_string()
+}
+
+fn default_openai_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model: default_openai_model(),
+            base_url: default_openai_base_url(),
+            embedding_model: default_openai_embedding_model(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AnthropicConfig {
+    #[serde(default)]
     pub api_key: String,
+    #[serde(default = "default_anthropic_model")]
     pub model: String,
+    #[serde(default = "default_anthropic_base_url")]
     pub base_url: String,
 }
 
+fn default_anthropic_model() -> String {
+    "claude-3-sonnet-20240229".to_string()
+}
+
+fn default_anthropic_base_url() -> String {
+    "https://api.anthropic.com/v1".to_string()
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model: default_anthropic_model(),
+            base_url: default_anthropic_base_url(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OllamaConfig {
+    #[serde(default = "default_ollama_base_url")]
     pub base_url: String,
+    #[serde(default = "default_ollama_model")]
     pub model: String,
+    /// Accept a self-signed or otherwise invalid certificate from `base_url`
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    #[serde(default = "default_ollama_embedding_model")]
+    pub embedding_model: String,
+}
+
+fn default_ollama_embedding_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+fn default_ollama_base_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_ollama_model() -> String {
+    "gpt-oss:20b".to_string()
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_ollama_base_url(),
+            model: default_ollama_model(),
+            accept_invalid_certs: false,
+            embedding_model: default_ollama_embedding_model(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AppConfig {
+    #[serde(default)]
     pub verbose: bool,
+    #[serde(default = "default_max_search_results")]
     pub max_search_results: usize,
+    #[serde(default = "default_min_synopsis_words")]
     pub min_synopsis_words: usize,
+    #[serde(default = "default_target_synopsis_words")]
     pub target_synopsis_words: usize,
+    /// Directory entries that Baserow failed to create are saved to, for `wcm retry`
+    #[serde(default = "default_retry_dir")]
+    pub retry_dir: String,
+    /// Language for prompts and messages ("en" or "th"); taken from `LANG` when unset
+    #[serde(default)]
+    pub language: Option<String>,
+    /// How many books `wcm import` processes at once
+    #[serde(default = "default_import_jobs")]
+    pub import_jobs: usize,
+    /// How many times a request answered with 429 Too Many Requests is retried
+    #[serde(default = "default_rate_limit_retries")]
+    pub rate_limit_retries: u32,
+    /// Directory Google Books and Open Library responses are cached in; empty turns caching off
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: String,
+    /// Field values new entries start with
+    #[serde(default)]
+    pub defaults: EntryDefaults,
+    /// JSON file the yearly reading targets of `wcm goal` are kept in
+    #[serde(default = "default_goals_file")]
+    pub goals_file: String,
+    /// JSON Lines file the reading sessions of `wcm log` are appended to
+    #[serde(default = "default_sessions_file")]
+    pub sessions_file: String,
+    /// JSON file the perceptual hashes of uploaded covers are kept in, to warn
+    /// about a cover already on another row; empty turns the check off
+    #[serde(default = "default_cover_hashes_file")]
+    pub cover_hashes_file: String,
+    /// JSON file the embeddings of `wcm find --semantic` are kept in
+    #[serde(default = "default_embeddings_file")]
+    pub embeddings_file: String,
+    /// Most books `wcm ask` shows the LLM; a larger library is cut to the ones
+    /// closest to the question in the embeddings index
+    #[serde(default = "default_ask_max_rows")]
+    pub ask_max_rows: usize,
+    /// TrueType font for the text of `wcm stats --chart`; the first of a few
+    /// common system fonts when unset
+    #[serde(default)]
+    pub chart_font: Option<String>,
+    /// JSON file the result list of the last search is kept in, for
+    /// `wcm add --from-last`; empty turns it off
+    #[serde(default = "default_last_search_file")]
+    pub last_search_file: String,
+}
+
+fn default_max_search_results() -> usize {
+    10
+}
+
+fn default_min_synopsis_words() -> usize {
+    50
+}
+
+fn default_target_synopsis_words() -> usize {
+    150
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            max_search_results: default_max_search_results(),
+            min_synopsis_words: default_min_synopsis_words(),
+            target_synopsis_words: default_target_synopsis_words(),
+            retry_dir: default_retry_dir(),
+            language: None,
+            import_jobs: default_import_jobs(),
+            rate_limit_retries: default_rate_limit_retries(),
+            cache_dir: default_cache_dir(),
+            defaults: EntryDefaults::default(),
+            goals_file: default_goals_file(),
+            sessions_file: default_sessions_file(),
+            cover_hashes_file: default_cover_hashes_file(),
+            embeddings_file: default_embeddings_file(),
+            ask_max_rows: default_ask_max_rows(),
+            chart_font: None,
+            last_search_file: default_last_search_file(),
+        }
+    }
+}
+
+/// Field values every new media entry starts with, for libraries whose select
+/// options have other IDs or that catalogue books once they have read them.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EntryDefaults {
+    /// `Media Type` option ID for physical books
+    #[serde(default = "default_media_type")]
+    pub media_type: u64,
+    /// `Media Type` option ID for books added with `--ebook`
+    #[serde(default = "default_ebook_media_type")]
+    pub ebook_media_type: u64,
+    /// `Media Type` option ID for `wcm add magazine`; physical books' when unset
+    #[serde(default)]
+    pub magazine_media_type: Option<u64>,
+    /// `Status` option ID, see `baserow::STATUS_OPTIONS`
+    #[serde(default = "default_status")]
+    pub status: u64,
+    #[serde(default)]
+    pub read: bool,
+    /// 0 leaves the book unrated
+    #[serde(default)]
+    pub rating: u32,
+}
+
+fn default_media_type() -> u64 {
+    3020
+}
+
+fn default_ebook_media_type() -> u64 {
+    3021
+}
+
+fn default_status() -> u64 {
+    3028 // In Place
+}
+
+impl Default for EntryDefaults {
+    fn default() -> Self {
+        Self {
+            media_type: default_media_type(),
+            ebook_media_type: default_ebook_media_type(),
+            magazine_media_type: None,
+            status: default_status(),
+            read: false,
+            rating: 0,
+        }
+    }
+}
+
+fn default_retry_dir() -> String {
+    "failed_entries".to_string()
+}
+
+fn default_import_jobs() -> usize {
+    4
+}
+
+fn default_rate_limit_retries() -> u32 {
+    3
+}
+
+fn default_cache_dir() -> String {
+    "http_cache".to_string()
+}
+
+fn default_goals_file() -> String {
+    "reading_goals.json".to_string()
+}
+
+fn default_sessions_file() -> String {
+    "reading_sessions.jsonl".to_string()
+}
+
+fn default_cover_hashes_file() -> String {
+    "cover_hashes.json".to_string()
+}
+
+fn default_embeddings_file() -> String {
+    "embeddings.json".to_string()
+}
+
+fn default_ask_max_rows() -> usize {
+    300
+}
+
+fn default_last_search_file() -> String {
+    "last_search.json".to_string()
+}
+
+/// Settings for the HTTP client shared by every API client.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HttpConfig {
+    /// Seconds a whole request may take; LLM answers and Telegram's 30 second long polls need room
+    #[serde(default = "default_http_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_http_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_http_user_agent")]
+    pub user_agent: String,
+    /// Proxy for every request, e.g. "http://proxy.lan:3128"; `HTTP_PROXY`/`HTTPS_PROXY` apply when unset
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// PEM file of extra root certificates, e.g. a home LAN's internal CA
+    #[serde(default)]
+    pub ca_bundle: Option<String>,
+}
+
+fn default_http_timeout_secs() -> u64 {
+    120
+}
+
+fn default_http_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_http_user_agent() -> String {
+    format!("wcm/{} (wattanit-collections-manager)", env!("CARGO_PKG_VERSION"))
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_http_timeout_secs(),
+            connect_timeout_secs: default_http_connect_timeout_secs(),
+            user_agent: default_http_user_agent(),
+            proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ServerConfig {
+    pub bind: String,
+    pub auth_token: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:8080".to_string(),
+            auth_token: None,
+        }
+    }
+}
+
+/// Maintenance tasks `wcm daemon` runs on a schedule.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DaemonConfig {
+    /// JSON file the daemon keeps its state in, read by `wcm daemon status`
+    #[serde(default = "default_daemon_status_file")]
+    pub status_file: String,
+    #[serde(default)]
+    pub tasks: Vec<DaemonTask>,
+    /// Address to serve `/metrics` on while the daemon runs, e.g. "127.0.0.1:9464";
+    /// no metrics endpoint when unset
+    #[serde(default)]
+    pub metrics_bind: Option<String>,
+}
+
+fn default_daemon_status_file() -> String {
+    "daemon_status.json".to_string()
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            status_file: default_daemon_status_file(),
+            tasks: Vec::new(),
+            metrics_bind: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DaemonTask {
+    pub task: DaemonTaskKind,
+    /// Cron expression in UTC, e.g. "0 3 * * *", see `schedule::Schedule`
+    pub schedule: String,
+    /// Most books to handle per run, for `covers_backfill` and `enrich`
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Milliseconds between books for `covers_backfill`
+    #[serde(default = "default_daemon_delay_ms")]
+    pub delay_ms: u64,
+    /// Leave covers to `covers_backfill` when running `enrich`
+    #[serde(default)]
+    pub synopsis_only: bool,
+}
+
+fn default_daemon_delay_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonTaskKind {
+    /// `wcm covers backfill`
+    CoversBackfill,
+    /// `wcm enrich --all`, without prompts
+    Enrich,
+    /// `wcm label --all --changed-since <last run>`, overwriting the PNG labels
+    Labels,
+}
+
+impl std::fmt::Display for DaemonTaskKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DaemonTaskKind::CoversBackfill => write!(f, "covers_backfill"),
+            DaemonTaskKind::Enrich => write!(f, "enrich"),
+            DaemonTaskKind::Labels => write!(f, "labels"),
+        }
+    }
+}
+
+/// Which catalogue each field is taken from when Google Books and Open Library
+/// both know the book, e.g. `description: google_books`. Fields left at
+/// `selected` come from the search result that was picked, as before.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct MergeConfig {
+    /// Used for the synopsis and the LLM's choice of categories
+    #[serde(default)]
+    pub description: FieldSource,
+    /// Passed to the LLM with the description when it picks categories
+    #[serde(default)]
+    pub subjects: FieldSource,
+    #[serde(default)]
+    pub cover: CoverSource,
+}
+
+impl MergeConfig {
+    /// Whether every field comes from the picked result, so no other catalogue
+    /// needs to be asked.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldSource {
+    #[default]
+    Selected,
+    GoogleBooks,
+    OpenLibrary,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverSource {
+    #[default]
+    Selected,
+    GoogleBooks,
+    OpenLibrary,
+    /// Whichever catalogue's image has the most pixels
+    Largest,
+}
+
+/// How names in the Author field of new entries are written. Catalogues give
+/// names in whatever order and spelling their source used; the defaults keep
+/// them that way.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct AuthorFormatConfig {
+    #[serde(default)]
+    pub order: NameOrder,
+    /// Drops accents from Latin letters ("Gabriel Garcia Marquez"); other
+    /// scripts are left alone
+    #[serde(default)]
+    pub strip_diacritics: bool,
+    /// Keeps one of the names that are the same person spelled alike, such as
+    /// "J.R.R. Tolkien" and "Tolkien, J. R. R."
+    #[serde(default)]
+    pub dedupe: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameOrder {
+    /// As the catalogue gave it
+    #[default]
+    AsGiven,
+    /// "Ursula K. Le Guin"
+    FirstLast,
+    /// "Le Guin, Ursula K.", with names separated by semicolons
+    LastFirst,
+}
+
+/// Romanized titles for books with Thai titles, written to
+/// `baserow.fields.romanized_title`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct TransliterationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub method: TransliterationMethod,
+}
+
+/// Brief content warnings from the LLM for new books, shown in the confirmation
+/// summary and written to `baserow.fields.content_warnings`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ContentWarningsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// The kinds of content to warn about
+    #[serde(default = "default_content_warning_kinds")]
+    pub kinds: Vec<String>,
+}
+
+fn default_content_warning_kinds() -> Vec<String> {
+    ["violence", "sexual content", "strong language", "drug use", "self-harm", "abuse", "frightening scenes"]
+        .into_iter().map(String::from).collect()
+}
+
+impl Default for ContentWarningsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kinds: default_content_warning_kinds(),
+        }
+    }
+}
+
+/// Shell commands run around each new entry, for integrations wcm does not have
+/// built in. Each gets the entry as JSON on stdin.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct HooksConfig {
+    /// Run with the entry about to be created; a non-zero exit cancels the add
+    #[serde(default)]
+    pub pre_add: Option<String>,
+    /// Run with the row as Baserow created it, its ID also in `WCM_ROW_ID`
+    #[serde(default)]
+    pub post_add: Option<String>,
+    /// Seconds a hook may run before it is stopped
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            pre_add: None,
+            post_add: None,
+            timeout_secs: default_hook_timeout_secs(),
+        }
+    }
+}
+
+/// Detecting the language of new books from their title and description.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct LanguageDetectionConfig {
+    /// Write the language to `baserow.fields.language` and warn when it is not
+    /// the one asked for with `--lang`
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Generate synopses in the book's language rather than in English; needs
+    /// `enabled`
+    #[serde(default)]
+    pub synopsis_in_book_language: bool,
+}
+
+impl Default for LanguageDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            synopsis_in_book_language: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransliterationMethod {
+    /// Royal Thai General System romanization from the LLM, falling back to
+    /// `Simple` when the LLM is unavailable
+    #[default]
+    Llm,
+    /// Letter-by-letter transcription, offline and instant but rough
+    Simple,
+}
+
+/// Terminal styles, as dotted `console` style strings such as "cyan.bold" or "yellow".
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ThemeConfig {
+    /// Section headings such as the confirmation summary title
+    #[serde(default = "default_theme_heading")]
+    pub heading: String,
+    /// Field names in summaries
+    #[serde(default = "default_theme_label")]
+    pub label: String,
+    #[serde(default = "default_theme_success")]
+    pub success: String,
+    #[serde(default = "default_theme_warning")]
+    pub warning: String,
+    #[serde(default = "default_theme_error")]
+    pub error: String,
+}
+
+fn default_theme_heading() -> String {
+    "cyan.bold".to_string()
+}
+
+fn default_theme_label() -> String {
+    "bold".to_string()
+}
+
+fn default_theme_success() -> String {
+    "green".to_string()
+}
+
+fn default_theme_warning() -> String {
+    "yellow".to_string()
+}
+
+fn default_theme_error() -> String {
+    "red.bold".to_string()
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            heading: default_theme_heading(),
+            label: default_theme_label(),
+            success: default_theme_success(),
+            warning: default_theme_warning(),
+            error: default_theme_error(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct TelegramConfig {
+    #[serde(default)]
+    pub bot_token: String,
+    #[serde(default)]
+    pub allowed_chat_ids: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LabelConfig {
+    /// Print a Code128 barcode of the storage ID on every storage label
+    #[serde(default)]
+    pub barcode: bool,
+    /// Number of book titles to list on storage labels, so a box label doubles as a packing list
+    #[serde(default)]
+    pub contents: u32,
+    /// Sheet layout used for PDF output, either a built-in template or one from `sheets`
+    #[serde(default = "default_sheet")]
+    pub sheet: String,
+    /// Custom sheet layouts, in addition to the built-in Avery templates
+    #[serde(default)]
+    pub sheets: Vec<SheetLayout>,
+    /// Directory PNG labels are written to
+    #[serde(default = "default_output_dir")]
+    pub output_dir: String,
+    /// Storage table field holding the row's last modified time, used by --changed-since
+    #[serde(default = "default_modified_field")]
+    pub modified_field: String,
+    /// Logo or bookplate image composited onto every label
+    #[serde(default)]
+    pub logo: Option<LogoConfig>,
+    /// Printer used by `wcm label --print`
+    #[serde(default)]
+    pub printer: PrinterConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct PrinterConfig {
+    #[serde(default)]
+    pub driver: PrinterDriver,
+    /// CUPS queue name; the system default printer when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Extra `-o` options passed to `lp`, e.g. `media=Custom.62x29mm`
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub brother_ql: BrotherQlConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrinterDriver {
+    #[default]
+    Cups,
+    BrotherQl,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BrotherQlConfig {
+    /// Printer device file, or `tcp://host:port` for network models
+    #[serde(default = "default_brother_ql_device")]
+    pub device: String,
+    /// Width of the continuous tape loaded in the printer, in mm
+    #[serde(default = "default_brother_ql_tape")]
+    pub tape: u32,
+}
+
+impl Default for BrotherQlConfig {
+    fn default() -> Self {
+        Self {
+            device: default_brother_ql_device(),
+            tape: default_brother_ql_tape(),
+        }
+    }
+}
+
+fn default_brother_ql_device() -> String {
+    "/dev/usb/lp0".to_string()
+}
+
+fn default_brother_ql_tape() -> u32 {
+    62
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LogoConfig {
+    pub path: String,
+    #[serde(default)]
+    pub position: LogoPosition,
+    /// Logo height as a fraction of the label height
+    #[serde(default = "default_logo_size")]
+    pub size: f32,
+    /// Gap between the logo and the label edge, in pixels
+    #[serde(default = "default_logo_margin")]
+    pub margin: u32,
+    /// 1.0 is fully opaque; lower values give a watermark, like a faint ex-libris stamp
+    #[serde(default = "default_logo_opacity")]
+    pub opacity: f32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogoPosition {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+fn default_logo_size() -> f32 {
+    0.2
+}
+
+fn default_logo_margin() -> u32 {
+    10
+}
+
+fn default_logo_opacity() -> f32 {
+    1.0
+}
+
+impl Default for LabelConfig {
+    fn default() -> Self {
+        Self {
+            barcode: false,
+            contents: 0,
+            sheet: default_sheet(),
+            sheets: Vec::new(),
+            output_dir: default_output_dir(),
+            modified_field: default_modified_field(),
+            logo: None,
+            printer: PrinterConfig::default(),
+        }
+    }
+}
+
+impl LabelConfig {
+    /// Looks up a sheet layout by name (case-insensitive), preferring custom layouts.
+    pub fn find_sheet(&self, name: &str) -> Option<SheetLayout> {
+        self.sheets.iter()
+            .find(|sheet| sheet.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .or_else(|| SheetLayout::builtin(name))
+    }
+}
+
+fn default_sheet() -> String {
+    "L7160".to_string()
+}
+
+fn default_output_dir() -> String {
+    ".".to_string()
+}
+
+fn default_modified_field() -> String {
+    "Last modified".to_string()
+}
+
+/// Layout of an adhesive label sheet. All measurements are in millimetres.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SheetLayout {
+    pub name: String,
+    pub page_width: f32,
+    pub page_height: f32,
+    pub columns: u32,
+    pub rows: u32,
+    pub label_width: f32,
+    pub label_height: f32,
+    /// Distance from the top edge of the page to the top of the first row
+    pub margin_top: f32,
+    /// Distance from the left edge of the page to the left of the first column
+    pub margin_left: f32,
+    /// Distance between the left edges of neighbouring columns
+    pub pitch_x: f32,
+    /// Distance between the top edges of neighbouring rows
+    pub pitch_y: f32,
+}
+
+const INCH: f32 = 25.4;
+
+impl SheetLayout {
+    pub const BUILTIN_NAMES: &'static [&'static str] = &["5160", "5163", "L7160", "L7163", "L7165"];
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        let layout = |name: &str, page: (f32, f32), grid: (u32, u32), label: (f32, f32), margin: (f32, f32), pitch: (f32, f32)| Self {
+            name: name.to_string(),
+            page_width: page.0,
+            page_height: page.1,
+            columns: grid.0,
+            rows: grid.1,
+            label_width: label.0,
+            label_height: label.1,
+            margin_top: margin.0,
+            margin_left: margin.1,
+            pitch_x: pitch.0,
+            pitch_y: pitch.1,
+        };
+        let letter = (8.5 * INCH, 11.0 * INCH);
+        let a4 = (210.0, 297.0);
+
+        match name.to_uppercase().as_str() {
+            // US Letter, 30 address labels
+            "5160" => Some(layout("5160", letter, (3, 10), (2.625 * INCH, INCH), (0.5 * INCH, 0.1875 * INCH), (2.75 * INCH, INCH))),
+            // US Letter, 10 shipping labels
+            "5163" => Some(layout("5163", letter, (2, 5), (4.0 * INCH, 2.0 * INCH), (0.5 * INCH, 0.15625 * INCH), (4.1875 * INCH, 2.0 * INCH))),
+            // A4, 21 address labels
+            "L7160" => Some(layout("L7160", a4, (3, 7), (63.5, 38.1), (15.15, 7.25), (66.04, 38.1))),
+            // A4, 14 parcel labels
+            "L7163" => Some(layout("L7163", a4, (2, 7), (99.1, 38.1), (15.15, 4.65), (101.6, 38.1))),
+            // A4, 8 parcel labels
+            "L7165" => Some(layout("L7165", a4, (2, 4), (99.1, 67.7), (13.1, 4.65), (101.6, 67.7))),
+            _ => None,
+        }
+    }
+
+    pub fn labels_per_page(&self) -> u32 {
+        self.columns * self.rows
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ClassificationConfig {
+    /// Look up a call number for every book that is added
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub scheme: ClassificationScheme,
+    /// Media table field the call number is stored in (and read from for spine labels)
+    #[serde(default = "default_call_number_field")]
+    pub field: String,
+    /// Ask the LLM when no catalogue has a classification for the book
+    #[serde(default = "default_true")]
+    pub llm_fallback: bool,
+    #[serde(default = "default_loc_base_url")]
+    pub loc_base_url: String,
+}
+
+impl Default for ClassificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scheme: ClassificationScheme::default(),
+            field: default_call_number_field(),
+            llm_fallback: true,
+            loc_base_url: default_loc_base_url(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassificationScheme {
+    #[default]
+    Dewey,
+    Lcc,
+}
+
+impl ClassificationScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClassificationScheme::Dewey => "Dewey Decimal",
+            ClassificationScheme::Lcc => "Library of Congress",
+        }
+    }
+}
+
+fn default_call_number_field() -> String {
+    "Call Number".to_string()
+}
+
+fn default_loc_base_url() -> String {
+    "https://www.loc.gov".to_string()
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Config {
@@ -131,9 +1412,54 @@ impl Config {
             cfg.llm.provider = provider;
         }
         
+        if let Ok(token) = std::env::var("WCM_SERVER_TOKEN") {
+            cfg.server.auth_token = Some(token);
+        }
+        
+        if let Ok(token) = std::env::var("TELEGRAM_BOT_TOKEN") {
+            cfg.telegram.bot_token = token;
+        }
+
+        if let Ok(chat_ids) = std::env::var("TELEGRAM_ALLOWED_CHAT_IDS") {
+            cfg.telegram.allowed_chat_ids = chat_ids.split(',')
+                .filter_map(|id| id.trim().parse().ok())
+                .collect();
+        }
+        
         Ok(cfg)
     }
     
+    /// Required settings that are unset or still a placeholder, as (setting,
+    /// environment variable) pairs, so startup can name every one at once.
+    pub fn missing_required(&self) -> Vec<(&'static str, &'static str)> {
+        let unset = |value: &str| value.trim().is_empty() || value.contains("your_");
+        let mut missing = Vec::new();
+
+        if unset(&self.baserow.api_token) {
+            missing.push(("baserow.api_token", "BASEROW_API_TOKEN"));
+        }
+        let ids = [
+            ("baserow.database_id", "BASEROW_DATABASE_ID", self.baserow.database_id),
+            ("baserow.media_table_id", "BASEROW_MEDIA_TABLE_ID", self.baserow.media_table_id),
+            ("baserow.categories_table_id", "BASEROW_CATEGORIES_TABLE_ID", self.baserow.categories_table_id),
+            ("baserow.storage_table_id", "BASEROW_STORAGE_TABLE_ID", self.baserow.storage_table_id),
+            ("baserow.storage_view_id", "BASEROW_STORAGE_VIEW_ID", self.baserow.storage_view_id),
+        ];
+        for (setting, variable, id) in ids {
+            if id == 0 {
+                missing.push((setting, variable));
+            }
+        }
+
+        match self.llm.provider.as_str() {
+            "openai" if unset(&self.llm.openai.api_key) => missing.push(("llm.openai.api_key", "OPENAI_API_KEY")),
+            "anthropic" if unset(&self.llm.anthropic.api_key) => missing.push(("llm.anthropic.api_key", "ANTHROPIC_API_KEY")),
+            _ => {}
+        }
+
+        missing
+    }
+
     pub fn validate(&self) -> Result<(), String> {
         // Check required API keys based on selected LLM provider
         match self.llm.provider.as_str() {
@@ -164,6 +1490,15 @@ impl Config {
         if self.baserow.api_token.contains("your_") {
             return Err("Baserow API token not configured".to_string());
         }
+
+        if self.app.import_jobs == 0 {
+            return Err("app.import_jobs must be at least 1".to_string());
+        }
+
+        for (i, task) in self.daemon.tasks.iter().enumerate() {
+            crate::schedule::Schedule::parse(&task.schedule)
+                .map_err(|e| format!("daemon.tasks[{}].schedule: {}", i, e))?;
+        }
         
         Ok(())
     }
@@ -0,0 +1,490 @@
+use serde::Serialize;
+use rust_i18n::t;
+use crate::baserow::{BaserowClient, BaserowError, MEDIA_FIELDS};
+use crate::config::{Config, SheetLayout};
+use crate::llm::LlmProvider;
+use crate::ui::{self, Role};
+
+const PROVIDERS: &[&str] = &["ollama", "openai", "anthropic"];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// One line of the `wcm config check` report: a setting, what is wrong with it
+/// (or that it is fine), and how to fix it.
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub setting: String,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    pub fn ok(setting: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { setting: setting.into(), severity: Severity::Ok, message: message.into(), fix: None }
+    }
+
+    pub fn warning(setting: impl Into<String>, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { setting: setting.into(), severity: Severity::Warning, message: message.into(), fix: Some(fix.into()) }
+    }
+
+    pub fn error(setting: impl Into<String>, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { setting: setting.into(), severity: Severity::Error, message: message.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// Checks the whole configuration, reporting every problem rather than stopping
+/// at the first like `Config::validate`. Unless `offline`, it also contacts the
+/// services to see that the URLs answer, the Baserow tables exist, and the fields
+/// wcm writes to are there.
+pub async fn run(config: &Config, offline: bool) -> Vec<Finding> {
+    let mut findings = check_settings(config);
+    if !offline {
+        findings.extend(check_services(config).await);
+    }
+    findings
+}
+
+fn is_placeholder(value: &str) -> bool {
+    value.trim().is_empty() || value.contains("your_")
+}
+
+/// Checks that need no network: placeholders, URLs, IDs, names and files.
+pub fn check_settings(config: &Config) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    findings.push(if is_placeholder(&config.baserow.api_token) {
+        Finding::error("baserow.api_token", t!("config_check.placeholder"), t!("config_check.baserow_token_fix"))
+    } else {
+        Finding::ok("baserow.api_token", t!("config_check.set"))
+    });
+
+    let ids = [
+        ("baserow.database_id", config.baserow.database_id),
+        ("baserow.media_table_id", config.baserow.media_table_id),
+        ("baserow.categories_table_id", config.baserow.categories_table_id),
+        ("baserow.storage_table_id", config.baserow.storage_table_id),
+        ("baserow.storage_view_id", config.baserow.storage_view_id),
+    ];
+    for (setting, id) in ids {
+        if id == 0 {
+            findings.push(Finding::error(setting, t!("config_check.id_zero"), t!("config_check.id_fix")));
+        }
+    }
+
+    findings.push(if is_placeholder(&config.google_books.api_key) {
+        Finding::warning("google_books.api_key", t!("config_check.google_key_missing"), t!("config_check.google_key_fix"))
+    } else {
+        Finding::ok("google_books.api_key", t!("config_check.set"))
+    });
+
+    let provider = config.llm.provider.as_str();
+    if PROVIDERS.contains(&provider) {
+        findings.push(Finding::ok("llm.provider", provider));
+    } else {
+        let fix = match closest(provider, PROVIDERS.iter().copied()) {
+            Some(suggestion) => t!("config_check.did_you_mean", suggestion = suggestion),
+            None => t!("config_check.provider_fix"),
+        };
+        findings.push(Finding::error("llm.provider", t!("config_check.provider_unknown", provider = provider), fix));
+    }
+
+    if let Some(embedding_provider) = &config.llm.embedding_provider {
+        if !["ollama", "openai"].contains(&embedding_provider.as_str()) {
+            findings.push(Finding::error(
+                "llm.embedding_provider",
+                t!("config_check.provider_unknown", provider = embedding_provider),
+                t!("config_check.embedding_provider_fix"),
+            ));
+        }
+    }
+
+    let (key, key_fix, model) = match provider {
+        "openai" => (Some(&config.llm.openai.api_key), "OPENAI_API_KEY", &config.llm.openai.model),
+        "anthropic" => (Some(&config.llm.anthropic.api_key), "ANTHROPIC_API_KEY", &config.llm.anthropic.model),
+        _ => (None, "", &config.llm.ollama.model),
+    };
+    if let Some(key) = key {
+        let setting = format!("llm.{}.api_key", provider);
+        findings.push(if is_placeholder(key) {
+            Finding::error(&setting, t!("config_check.placeholder"), t!("config_check.llm_key_fix", setting = &setting, variable = key_fix))
+        } else {
+            Finding::ok(&setting, t!("config_check.set"))
+        });
+    }
+    if PROVIDERS.contains(&provider) && model.trim().is_empty() {
+        let setting = format!("llm.{}.model", provider);
+        findings.push(Finding::error(&setting, t!("config_check.model_missing"), t!("config_check.model_fix", setting = &setting)));
+    }
+
+    let mut urls = vec![
+        ("baserow.base_url".to_string(), config.baserow.base_url.as_str()),
+        ("google_books.base_url".to_string(), config.google_books.base_url.as_str()),
+        ("open_library.base_url".to_string(), config.open_library.base_url.as_str()),
+    ];
+    if let Some(url) = llm_base_url(config) {
+        urls.push((format!("llm.{}.base_url", provider), url));
+    }
+    for (setting, url) in urls {
+        let valid = reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+        if !valid {
+            findings.push(Finding::error(setting, t!("config_check.url_invalid", url = url), t!("config_check.url_fix")));
+        }
+    }
+
+    let budget = &config.llm.budget;
+    if budget.monthly_spend.is_some() && budget.input_price == 0.0 && budget.output_price == 0.0 {
+        findings.push(Finding::warning("llm.budget.monthly_spend", t!("config_check.budget_unpriced"), t!("config_check.budget_price_fix")));
+    }
+
+    if config.app.import_jobs == 0 {
+        findings.push(Finding::error("app.import_jobs", t!("config_check.import_jobs_zero"), t!("config_check.import_jobs_fix")));
+    }
+
+    if config.label.find_sheet(&config.label.sheet).is_none() {
+        let names: Vec<&str> = SheetLayout::BUILTIN_NAMES.iter().copied()
+            .chain(config.label.sheets.iter().map(|sheet| sheet.name.as_str()))
+            .collect();
+        findings.push(Finding::error(
+            "label.sheet",
+            t!("config_check.sheet_unknown", sheet = &config.label.sheet),
+            t!("config_check.sheet_fix", sheets = names.join(", ")),
+        ));
+    }
+
+    if let Some(logo) = &config.label.logo {
+        if !std::path::Path::new(&logo.path).exists() {
+            findings.push(Finding::error("label.logo.path", t!("config_check.file_missing", path = &logo.path), t!("config_check.logo_fix")));
+        }
+    }
+
+    for (setting, command) in [("hooks.pre_add", &config.hooks.pre_add), ("hooks.post_add", &config.hooks.post_add)] {
+        // Only a program given as a path can be checked; a bare name is looked up on PATH by the shell
+        let program = command.as_deref().and_then(|command| command.split_whitespace().next());
+        if let Some(program) = program.filter(|program| program.contains('/')) {
+            if !std::path::Path::new(program).exists() {
+                findings.push(Finding::error(setting, t!("config_check.file_missing", path = program), t!("config_check.hook_fix", setting = setting)));
+            }
+        }
+    }
+
+    if let Err(e) = crate::http::build_client(&config.http) {
+        findings.push(Finding::error("http", e.to_string(), t!("config_check.http_fix")));
+    }
+
+    if !config.telegram.bot_token.is_empty() && !config.telegram.bot_token.contains("your_") && config.telegram.allowed_chat_ids.is_empty() {
+        findings.push(Finding::warning("telegram.allowed_chat_ids", t!("config_check.telegram_open"), t!("config_check.telegram_fix")));
+    }
+
+    let local = ["127.0.0.1:", "localhost:", "[::1]:"].iter().any(|prefix| config.server.bind.starts_with(prefix));
+    if !local && config.server.auth_token.is_none() {
+        findings.push(Finding::warning("server.auth_token", t!("config_check.server_open", bind = &config.server.bind), t!("config_check.server_fix")));
+    }
+
+    findings
+}
+
+fn llm_base_url(config: &Config) -> Option<&str> {
+    match config.llm.provider.as_str() {
+        "ollama" => Some(&config.llm.ollama.base_url),
+        "openai" => Some(&config.llm.openai.base_url),
+        "anthropic" => Some(&config.llm.anthropic.base_url),
+        _ => None,
+    }
+}
+
+/// Checks that contact the services: reachability, Baserow tables and fields,
+/// and the Ollama model.
+pub async fn check_services(config: &Config) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    // A broken `http` section is already reported; the checks still run without it
+    let http_client = crate::http::build_client(&config.http).unwrap_or_default();
+
+    let baserow_http_client = if config.baserow.accept_invalid_certs {
+        crate::http::build_client_accepting_invalid_certs(&config.http).unwrap_or_default()
+    } else {
+        http_client.clone()
+    };
+    let baserow_client = BaserowClient::new(config.baserow.clone()).with_http_client(baserow_http_client);
+    if !is_placeholder(&config.baserow.api_token) {
+        findings.extend(check_baserow(config, &baserow_client).await);
+    }
+
+    for (setting, url) in [
+        ("google_books.base_url", &config.google_books.base_url),
+        ("open_library.base_url", &config.open_library.base_url),
+    ] {
+        findings.push(check_reachable(&http_client, setting, url).await);
+    }
+
+    if let Ok(provider) = LlmProvider::configured(config) {
+        match provider.with_http_client(http_client.clone()) {
+            LlmProvider::Ollama(ollama) => findings.push(check_ollama(&ollama).await),
+            _ => {
+                if let Some(url) = llm_base_url(config) {
+                    let setting = format!("llm.{}.base_url", config.llm.provider);
+                    findings.push(check_reachable(&http_client, &setting, url).await);
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+async fn check_reachable(client: &reqwest::Client, setting: &str, url: &str) -> Finding {
+    ui::detail(format!("Checking that {} answers...", url));
+    // A plain send: even a 429 shows the URL answers
+    match client.get(url).send().await {
+        Ok(response) => Finding::ok(setting, t!("config_check.reachable", status = response.status().as_u16())),
+        Err(e) => Finding::error(setting, t!("config_check.unreachable", url = url, error = e), NetworkProblem::of(&e).fix()),
+    }
+}
+
+/// Why a request got no answer, which decides the fix to suggest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkProblem {
+    /// The host name did not resolve
+    Dns,
+    /// The server's certificate was not trusted, or the handshake failed
+    Tls,
+    /// Nothing listens on the port
+    Refused,
+    Timeout,
+    Other,
+}
+
+impl NetworkProblem {
+    /// Classifies an error from its message and those of its causes, which is
+    /// where hyper and the TLS library say what went wrong.
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| message.contains(word));
+        if mentions(&["dns error", "failed to lookup address", "name or service not known", "no such host"]) {
+            NetworkProblem::Dns
+        } else if mentions(&["certificate", "tls", "ssl", "handshake"]) {
+            NetworkProblem::Tls
+        } else if mentions(&["connection refused"]) {
+            NetworkProblem::Refused
+        } else if mentions(&["timed out", "timeout"]) {
+            NetworkProblem::Timeout
+        } else {
+            NetworkProblem::Other
+        }
+    }
+
+    pub fn of(error: &reqwest::Error) -> Self {
+        if error.is_timeout() {
+            return NetworkProblem::Timeout;
+        }
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        Self::classify(&message)
+    }
+
+    pub fn fix(self) -> std::borrow::Cow<'static, str> {
+        match self {
+            NetworkProblem::Dns => t!("config_check.dns_fix"),
+            NetworkProblem::Tls => t!("config_check.tls_fix"),
+            NetworkProblem::Refused => t!("config_check.refused_fix"),
+            NetworkProblem::Timeout => t!("config_check.timeout_fix"),
+            NetworkProblem::Other => t!("config_check.unreachable_fix"),
+        }
+    }
+}
+
+/// A Baserow table to look up, with the fields wcm needs in it and the ones it can
+/// do without, each with the setting that names it.
+struct TableCheck<'a> {
+    setting: &'a str,
+    id: u64,
+    required: Vec<&'a str>,
+    optional: Vec<(&'a str, &'a str)>,
+}
+
+async fn check_baserow(config: &Config, client: &BaserowClient) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let mut media_fields = MEDIA_FIELDS.to_vec();
+    if config.classification.enabled {
+        media_fields.push(&config.classification.field);
+    }
+    let mut tables = vec![
+        TableCheck {
+            setting: "baserow.media_table_id",
+            id: config.baserow.media_table_id,
+            required: media_fields,
+            // Only used by the flags and commands that fill them in
+            optional: vec![
+                ("baserow.fields.series", &config.baserow.fields.series),
+                ("baserow.fields.volume", &config.baserow.fields.volume),
+                ("baserow.fields.price", &config.baserow.fields.price),
+                ("baserow.fields.purchased_at", &config.baserow.fields.purchased_at),
+                ("baserow.fields.store", &config.baserow.fields.store),
+                ("baserow.fields.read_at", &config.baserow.fields.read_at),
+                ("baserow.fields.pages", &config.baserow.fields.pages),
+                ("baserow.fields.quotes", &config.baserow.fields.quotes),
+                ("baserow.fields.notes", &config.baserow.fields.notes),
+                ("baserow.fields.asin", &config.baserow.fields.asin),
+                ("baserow.fields.issue_date", &config.baserow.fields.issue_date),
+                ("baserow.fields.issue_number", &config.baserow.fields.issue_number),
+                ("baserow.fields.box_set", &config.baserow.fields.box_set),
+                ("baserow.fields.romanized_title", &config.baserow.fields.romanized_title),
+                ("baserow.fields.language", &config.baserow.fields.language),
+                ("baserow.fields.content_warnings", &config.baserow.fields.content_warnings),
+                ("baserow.fields.condition", &config.baserow.fields.condition),
+                ("baserow.fields.edition", &config.baserow.fields.edition),
+                ("baserow.fields.signed", &config.baserow.fields.signed),
+                ("baserow.fields.provenance", &config.baserow.fields.provenance),
+                ("baserow.fields.borrower", &config.baserow.fields.borrower),
+                ("baserow.fields.loaned_at", &config.baserow.fields.loaned_at),
+                ("baserow.fields.loan_due", &config.baserow.fields.loan_due),
+                ("baserow.fields.created_at", &config.baserow.fields.created_at),
+            ],
+        },
+        TableCheck { setting: "baserow.categories_table_id", id: config.baserow.categories_table_id, required: vec!["Name"], optional: Vec::new() },
+        TableCheck {
+            setting: "baserow.storage_table_id",
+            id: config.baserow.storage_table_id,
+            required: vec!["Name"],
+            // Only `wcm label --all --changed-since` reads the modified field
+            optional: vec![("label.modified_field", &config.label.modified_field)],
+        },
+    ];
+    if let Some(authors_table_id) = config.baserow.authors_table_id {
+        tables[0].required.push(&config.baserow.fields.authors);
+        tables.push(TableCheck { setting: "baserow.authors_table_id", id: authors_table_id, required: vec!["Name"], optional: Vec::new() });
+    }
+    if let Some(publishers_table_id) = config.baserow.publishers_table_id {
+        tables[0].required.push(&config.baserow.fields.publisher);
+        tables.push(TableCheck { setting: "baserow.publishers_table_id", id: publishers_table_id, required: vec!["Name"], optional: Vec::new() });
+    }
+
+    for TableCheck { setting, id: table_id, required, optional } in tables {
+        ui::detail(format!("Checking Baserow table {}...", table_id));
+        let fields = match client.fetch_fields(table_id).await {
+            Ok(fields) => fields,
+            Err(BaserowError::AuthenticationFailed) => {
+                findings.push(Finding::error("baserow.api_token", t!("config_check.token_rejected"), t!("config_check.baserow_token_fix")));
+                return findings;
+            }
+            Err(BaserowError::NotFound) => {
+                findings.push(Finding::error(setting, t!("config_check.table_not_found", id = table_id), t!("config_check.table_fix")));
+                continue;
+            }
+            Err(BaserowError::RequestFailed(e)) => {
+                findings.push(Finding::error(
+                    "baserow.base_url",
+                    t!("config_check.unreachable", url = &config.baserow.base_url, error = e),
+                    NetworkProblem::of(&e).fix(),
+                ));
+                return findings;
+            }
+            Err(e) => {
+                findings.push(Finding::error(setting, e.to_string(), t!("config_check.table_fix")));
+                continue;
+            }
+        };
+        findings.push(Finding::ok(setting, t!("config_check.table_found", count = fields.len())));
+
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+        let table = setting.trim_start_matches("baserow.").trim_end_matches("_table_id");
+        for field in required {
+            if !names.contains(&field) {
+                let setting = if field == config.classification.field.as_str() { "classification.field" } else { setting };
+                findings.push(Finding::error(setting, t!("config_check.field_missing", table = table, field = field), field_fix(table, field, &names)));
+            }
+        }
+        for (setting, field) in optional {
+            if !names.contains(&field) {
+                findings.push(Finding::warning(setting, t!("config_check.field_missing", table = table, field = field), field_fix(table, field, &names)));
+            }
+        }
+    }
+
+    findings
+}
+
+fn field_fix(table: &str, field: &str, names: &[&str]) -> String {
+    match closest(field, names.iter().copied()) {
+        Some(similar) => t!("config_check.field_rename_fix", similar = similar, field = field).to_string(),
+        None => t!("config_check.field_add_fix", table = table, field = field).to_string(),
+    }
+}
+
+async fn check_ollama(ollama: &crate::llm::OllamaClient) -> Finding {
+    let model = ollama.model();
+    ui::detail("Listing the models on the Ollama server...");
+    match ollama.installed_models().await {
+        Ok(models) => {
+            let installed = models.iter().any(|name| name == model || *name == format!("{}:latest", model));
+            if installed {
+                Finding::ok("llm.ollama.model", t!("config_check.model_installed", model = model))
+            } else {
+                Finding::error(
+                    "llm.ollama.model",
+                    t!("config_check.model_not_installed", model = model),
+                    t!("config_check.model_pull_fix", model = model, models = models.join(", ")),
+                )
+            }
+        }
+        Err(e) => Finding::error("llm.ollama.base_url", e.to_string(), NetworkProblem::classify(&e.to_string()).fix()),
+    }
+}
+
+/// The candidate closest to `value` ignoring case, when it is close enough to be
+/// a likely typo.
+fn closest<'a>(value: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    candidates
+        .map(|candidate| (candidate, strsim::normalized_levenshtein(&value, &candidate.to_lowercase())))
+        .filter(|(_, similarity)| *similarity >= 0.6)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate)
+}
+
+/// Formats the findings as a table, with each fix on the line below its problem.
+pub fn format_report(findings: &[Finding]) -> String {
+    let status = |severity: Severity| match severity {
+        Severity::Ok => (t!("config_check.ok").to_string(), Role::Success),
+        Severity::Warning => (t!("config_check.warning").to_string(), Role::Warning),
+        Severity::Error => (t!("config_check.error").to_string(), Role::Error),
+    };
+    let setting_width = findings.iter().map(|finding| finding.setting.len()).max().unwrap_or(0);
+    let status_width = [Severity::Ok, Severity::Warning, Severity::Error].iter()
+        .map(|severity| console::measure_text_width(&status(*severity).0))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for finding in findings {
+        let (label, role) = status(finding.severity);
+        let padding = " ".repeat(status_width - console::measure_text_width(&label));
+        lines.push(format!("{:setting_width$}  {}{}  {}", finding.setting, ui::paint(role, label), padding, finding.message));
+        if let Some(fix) = &finding.fix {
+            lines.push(format!("{:indent$}{}", "", t!("config_check.fix", fix = fix), indent = setting_width + status_width + 4));
+        }
+    }
+
+    let errors = findings.iter().filter(|finding| finding.severity == Severity::Error).count();
+    let warnings = findings.iter().filter(|finding| finding.severity == Severity::Warning).count();
+    lines.push(String::new());
+    lines.push(if errors == 0 && warnings == 0 {
+        ui::paint(Role::Success, t!("config_check.all_good"))
+    } else {
+        t!("config_check.summary", errors = errors, warnings = warnings).to_string()
+    });
+    lines.join("\n")
+}
@@ -0,0 +1,64 @@
+/// Written to the field when the LLM found nothing to warn about, so a checked
+/// book can be told apart from one that was never checked.
+pub const NONE: &str = "None";
+
+/// What the LLM is told about the book: everything a reader would see on the
+/// entry.
+pub fn describe(title: &str, author: &str, categories: &[String], synopsis: &str) -> String {
+    let mut info = format!("Title: {}\nAuthor: {}", title, author);
+    if !categories.is_empty() {
+        info.push_str(&format!("\nCategories: {}", categories.join(", ")));
+    }
+    if !synopsis.trim().is_empty() {
+        info.push_str(&format!("\nSynopsis: {}", synopsis.trim()));
+    }
+    info
+}
+
+/// Reads `Kind | detail` lines into warnings such as "Violence: battle scenes",
+/// keeping only the kinds that were asked about, each once. A response of
+/// `NONE` gives no warnings. `None` when the response is neither.
+pub fn parse_response(response: &str, kinds: &[String]) -> Option<Vec<String>> {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut seen: Vec<&String> = Vec::new();
+    let mut said_none = false;
+    for line in response.lines() {
+        let line = line.trim().trim_start_matches(['-', '*']).trim();
+        if line.trim_matches(|c: char| !c.is_alphabetic()).eq_ignore_ascii_case("none") {
+            said_none = true;
+            continue;
+        }
+        let (kind, detail) = line.split_once('|').unwrap_or((line, ""));
+        let kind = kind.trim().trim_matches('*').trim();
+        let Some(known) = kinds.iter().find(|known| known.eq_ignore_ascii_case(kind)) else { continue };
+        if seen.contains(&known) {
+            continue;
+        }
+        seen.push(known);
+        let mut warning = capitalize(known);
+        let detail = detail.trim();
+        if !detail.is_empty() {
+            warning.push_str(": ");
+            warning.push_str(detail);
+        }
+        warnings.push(warning);
+    }
+    (!warnings.is_empty() || said_none).then_some(warnings)
+}
+
+/// The warnings as written to the field.
+pub fn field_value(warnings: &[String]) -> String {
+    if warnings.is_empty() {
+        NONE.to_string()
+    } else {
+        warnings.join("; ")
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use image::DynamicImage;
+use crate::baserow::MediaRow;
+
+/// Serializes updates to the hashes file, which parallel import jobs share.
+static HASHES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Hashes at most this many bits apart are the same picture: one cover
+/// resized, recompressed or with a slightly different crop, but not another
+/// edition's design.
+pub const SAME_COVER_DISTANCE: u32 = 4;
+
+/// A 64-bit difference hash of the image: shrunk to 9x8 grey pixels, one bit
+/// for each pair of neighbours in a row saying whether it gets brighter. It
+/// stays the same through the resizing and recompression catalogues and
+/// Baserow apply, where a hash of the file's bytes would not.
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.grayscale().resize_exact(9, 8, image::imageops::FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x + 1, y)[0] > small.get_pixel(x, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+/// The hash of an image file's contents.
+pub fn hash_bytes(bytes: &[u8]) -> Result<u64, image::ImageError> {
+    Ok(dhash(&image::load_from_memory(bytes)?))
+}
+
+/// How many bits two hashes differ in.
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// The cover hashes of media rows by row ID, kept in `app.cover_hashes_file`
+/// as `{"42": "f0e4c2d0c8a0b0b0"}`. A missing file has no hashes.
+pub fn load(path: &Path) -> Result<BTreeMap<u64, String>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let hashes = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{} is not a cover hashes file: {}", path.display(), e))?;
+    Ok(hashes)
+}
+
+/// Stores the hash of the cover attached to `row_id`, replacing any earlier one.
+pub fn record(path: &Path, row_id: u64, hash: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = HASHES_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut hashes = load(path)?;
+    hashes.insert(row_id, format!("{:016x}", hash));
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Written to a temporary file first so a reader never sees half a file
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, serde_json::to_string_pretty(&hashes)?)?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+/// The rows whose cover is the same picture as `hash`, closest first.
+pub fn same_cover(hashes: &BTreeMap<u64, String>, hash: u64) -> Vec<u64> {
+    let mut matches: Vec<(u32, u64)> = hashes.iter()
+        .filter_map(|(id, stored)| u64::from_str_radix(stored, 16).ok().map(|stored| (distance(stored, hash), *id)))
+        .filter(|(distance, _)| *distance <= SAME_COVER_DISTANCE)
+        .collect();
+    matches.sort();
+    matches.into_iter().map(|(_, id)| id).collect()
+}
+
+/// Where Baserow serves the first file of a row's `Cover` field.
+pub fn cover_url(row: &MediaRow) -> Option<String> {
+    row.fields.get("Cover")?.as_array()?.first()?.get("url")?.as_str().map(str::to_string)
+}
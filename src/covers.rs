@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::{BaserowClient, MediaRow};
+use crate::book_search::CombinedBookSearcher;
+use crate::timing::{self, Stage};
+use crate::ui::{self, Role};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillStatus {
+    Uploaded,
+    /// A cover was found but not uploaded, because of `--dry-run`
+    Found,
+    NotFound,
+    Failed,
+}
+
+/// What `wcm covers backfill` did for one row.
+#[derive(Debug, Serialize)]
+pub struct BackfillItem {
+    pub id: u64,
+    pub title: String,
+    pub status: BackfillStatus,
+    /// Where the cover came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BackfillItem {
+    fn new(row: &MediaRow, status: BackfillStatus) -> Self {
+        Self { id: row.id, title: row.get_text("Title").unwrap_or_default(), status, url: None, error: None }
+    }
+}
+
+pub fn has_cover(row: &MediaRow) -> bool {
+    row.fields.get("Cover").and_then(|cover| cover.as_array()).is_some_and(|files| !files.is_empty())
+}
+
+/// The rows without a cover, in table order, up to `limit`.
+pub fn missing_covers(rows: Vec<MediaRow>, limit: Option<usize>) -> Vec<MediaRow> {
+    rows.into_iter()
+        .filter(|row| !has_cover(row))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// Looks up and uploads a cover for each row, one row at a time and waiting
+/// `delay` between rows so the catalogues and Baserow are not flooded. With
+/// `dry_run` the covers are only looked up.
+pub async fn backfill(
+    searcher: &CombinedBookSearcher,
+    client: &BaserowClient,
+    rows: &[MediaRow],
+    delay: Duration,
+    dry_run: bool,
+) -> Vec<BackfillItem> {
+    let mut items = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(delay).await;
+        }
+        let item = backfill_row(searcher, client, row, dry_run).await;
+        match item.status {
+            BackfillStatus::Uploaded => ui::info(t!("covers.uploaded", number = i + 1, total = rows.len(), title = &item.title)),
+            BackfillStatus::Found => ui::info(t!("covers.found", number = i + 1, total = rows.len(), title = &item.title, url = item.url.as_deref().unwrap_or_default())),
+            BackfillStatus::NotFound => ui::warn(t!("covers.not_found", number = i + 1, total = rows.len(), title = &item.title)),
+            BackfillStatus::Failed => ui::warn(t!("covers.failed", number = i + 1, total = rows.len(), title = &item.title, error = item.error.as_deref().unwrap_or_default())),
+        }
+        items.push(item);
+    }
+    items
+}
+
+/// Looks up a cover for one row and, unless `dry_run`, uploads it and sets it on
+/// the row.
+pub async fn backfill_row(searcher: &CombinedBookSearcher, client: &BaserowClient, row: &MediaRow, dry_run: bool) -> BackfillItem {
+    let isbn = row.get_text("ISBN").map(|isbn| crate::duplicates::normalize_isbn(&isbn)).filter(|isbn| !isbn.is_empty());
+    let title = row.get_text("Title").unwrap_or_default();
+    let author = row.get_text("Author");
+    let urls = searcher.find_cover_urls(isbn.as_deref(), &title, author.as_deref()).await;
+    if urls.is_empty() {
+        return BackfillItem::new(row, BackfillStatus::NotFound);
+    }
+
+    if dry_run {
+        let mut item = BackfillItem::new(row, BackfillStatus::Found);
+        item.url = urls.into_iter().next();
+        return item;
+    }
+
+    let (url, cover) = match searcher.upload_first_cover(&urls).await {
+        Ok(uploaded) => uploaded,
+        // Only the Open Library guess was left and it has no cover either
+        Err(_) if urls.len() == 1 && isbn.is_some() => return BackfillItem::new(row, BackfillStatus::NotFound),
+        Err(e) => {
+            let mut item = BackfillItem::new(row, BackfillStatus::Failed);
+            item.error = Some(e.to_string());
+            return item;
+        }
+    };
+    match timing::time(Stage::Update, client.update_media_row(row.id, &serde_json::json!({ "Cover": [&cover] }))).await {
+        Ok(_) => {
+            if let Some(uploaded) = &cover.url {
+                searcher.record_cover_hash(row.id, uploaded).await;
+            }
+            let mut item = BackfillItem::new(row, BackfillStatus::Uploaded);
+            item.url = Some(url);
+            item
+        }
+        Err(e) => {
+            let mut item = BackfillItem::new(row, BackfillStatus::Failed);
+            item.error = Some(e.to_string());
+            item
+        }
+    }
+}
+
+/// The rows with a cover but no stored cover hash, in table order, up to `limit`.
+pub fn unhashed_covers(rows: Vec<MediaRow>, hashes: &BTreeMap<u64, String>, limit: Option<usize>) -> Vec<MediaRow> {
+    rows.into_iter()
+        .filter(|row| has_cover(row) && !hashes.contains_key(&row.id))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// What `wcm covers hash` did.
+#[derive(Debug, Default, Serialize)]
+pub struct HashSummary {
+    pub hashed: usize,
+    /// Covers that could not be downloaded or read
+    pub failed: usize,
+}
+
+/// Hashes the covers already attached to `rows`, so that covers uploaded
+/// before hashes were kept are compared against new books too.
+pub async fn hash_covers(searcher: &CombinedBookSearcher, rows: &[MediaRow]) -> HashSummary {
+    let mut summary = HashSummary::default();
+    for (i, row) in rows.iter().enumerate() {
+        let title = row.get_text("Title").unwrap_or_default();
+        let hashed = match crate::cover_hashes::cover_url(row) {
+            Some(url) => searcher.record_cover_hash(row.id, &url).await,
+            None => false,
+        };
+        if hashed {
+            summary.hashed += 1;
+            ui::detail(format!("[{}/{}] {}: hashed", i + 1, rows.len(), title));
+        } else {
+            summary.failed += 1;
+            ui::warn(t!("covers.hash_failed", number = i + 1, total = rows.len(), title = title));
+        }
+    }
+    summary
+}
+
+pub fn format_summary(items: &[BackfillItem]) -> String {
+    let groups = [
+        (BackfillStatus::Uploaded, t!("covers.summary_uploaded")),
+        (BackfillStatus::Found, t!("covers.summary_found")),
+        (BackfillStatus::NotFound, t!("covers.summary_not_found")),
+        (BackfillStatus::Failed, t!("covers.summary_failed")),
+    ];
+
+    let mut lines = vec![String::new(), ui::paint(Role::Heading, t!("covers.summary_heading"))];
+    for (status, heading) in groups {
+        let group: Vec<&BackfillItem> = items.iter().filter(|item| item.status == status).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        lines.push(ui::paint(Role::Label, format!("{} ({})", heading, group.len())));
+        for item in group {
+            lines.push(match status {
+                BackfillStatus::Failed => format!("  #{}  {}  {}", item.id, item.title, item.error.as_deref().unwrap_or_default()),
+                _ => format!("  #{}  {}", item.id, item.title),
+            });
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,316 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use crate::baserow::BaserowClient;
+use crate::book_search::CombinedBookSearcher;
+use crate::config::{Config, DaemonTask, DaemonTaskKind};
+use crate::covers::{self, BackfillStatus};
+use crate::dates;
+use crate::enrich::{self, EnrichOptions, EnrichStatus};
+use crate::error::EXIT_CANCELLED;
+use crate::label::LabelGenerator;
+use crate::metrics;
+use crate::schedule::Schedule;
+use crate::ui::{self, Role};
+
+/// What the daemon writes to `daemon.status_file` whenever a task starts or
+/// finishes, for `wcm daemon status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    /// `YYYY-MM-DD HH:MM` UTC, as are the other times
+    pub started_at: String,
+    /// Set when the daemon shut down cleanly
+    #[serde(default)]
+    pub stopped_at: Option<String>,
+    pub tasks: Vec<TaskStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub task: DaemonTaskKind,
+    pub schedule: String,
+    #[serde(default)]
+    pub running: bool,
+    #[serde(default)]
+    pub last_run: Option<String>,
+    /// Summary of the last run, or its error
+    #[serde(default)]
+    pub last_result: Option<String>,
+    #[serde(default)]
+    pub last_ok: Option<bool>,
+    /// When the last successful run started, in minutes since the epoch; `labels`
+    /// redraws the boxes changed since then
+    #[serde(default)]
+    pub last_success: Option<u64>,
+    #[serde(default)]
+    pub next_run: Option<String>,
+}
+
+impl DaemonStatus {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    fn save(&self, path: &Path) {
+        // Written to a temporary file first so `wcm daemon status` never reads half a file
+        let temporary = path.with_extension("tmp");
+        let written = serde_json::to_string_pretty(self).map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(&temporary, json))
+            .and_then(|()| std::fs::rename(&temporary, path));
+        if let Err(e) = written {
+            ui::warn(t!("daemon.status_not_saved", path = path.display(), error = e));
+        }
+    }
+}
+
+/// Whether the process that wrote a status file is still running.
+#[cfg(unix)]
+pub fn is_running(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn is_running(_pid: u32) -> bool {
+    true
+}
+
+/// Runs the tasks in `daemon.tasks` on their schedules until Ctrl-C or SIGTERM.
+/// A task that is running when the signal comes is finished first; a second
+/// signal quits at once.
+pub struct Daemon {
+    searcher: CombinedBookSearcher,
+    client: BaserowClient,
+    label_generator: LabelGenerator,
+    config: Config,
+}
+
+impl Daemon {
+    pub fn new(searcher: CombinedBookSearcher, client: BaserowClient, label_generator: LabelGenerator, config: Config) -> Self {
+        Self { searcher, client, label_generator, config }
+    }
+
+    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let tasks = &self.config.daemon.tasks;
+        if tasks.is_empty() {
+            return Err(t!("daemon.no_tasks").into());
+        }
+        let schedules = tasks.iter()
+            .map(|task| Schedule::parse(&task.schedule))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let status_path = PathBuf::from(&self.config.daemon.status_file);
+        // Keep the last successful runs of an earlier daemon, so `labels` carries on
+        // from where it stopped
+        let previous = DaemonStatus::load(&status_path).ok();
+        let mut status = DaemonStatus {
+            pid: std::process::id(),
+            started_at: dates::now(),
+            stopped_at: None,
+            tasks: tasks.iter().map(|task| TaskStatus {
+                task: task.task,
+                schedule: task.schedule.clone(),
+                running: false,
+                last_run: None,
+                last_result: None,
+                last_ok: None,
+                last_success: previous.as_ref()
+                    .and_then(|previous| previous.tasks.iter().find(|old| old.task == task.task))
+                    .and_then(|old| old.last_success),
+                next_run: None,
+            }).collect(),
+        };
+
+        if let Some(bind) = &self.config.daemon.metrics_bind {
+            // Bound here so a taken port stops the daemon before any task runs
+            let listener = tokio::net::TcpListener::bind(bind).await?;
+            let auth_token = self.config.server.auth_token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = metrics::serve(listener, auth_token).await {
+                    ui::warn(t!("daemon.metrics_failed", error = e));
+                }
+            });
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopping = Arc::new(Notify::new());
+        spawn_signal_handler(stop.clone(), stopping.clone());
+
+        let mut next: Vec<Option<u64>> = schedules.iter().map(|schedule| schedule.next_after(dates::now_minutes())).collect();
+        ui::info(t!("daemon.started", count = tasks.len(), path = status_path.display()));
+        loop {
+            for (task, next_run) in status.tasks.iter_mut().zip(&next) {
+                task.next_run = next_run.map(dates::format_minutes);
+            }
+            status.save(&status_path);
+
+            let Some(due) = next.iter().flatten().min().copied() else {
+                return Err(t!("daemon.never_due").into());
+            };
+            let wait = due.saturating_sub(dates::now_minutes()) * 60;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(wait)) => {}
+                _ = stopping.notified() => {}
+            }
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            for i in 0..tasks.len() {
+                if next[i].is_none_or(|minute| minute > dates::now_minutes()) {
+                    continue;
+                }
+                let started = dates::now_minutes();
+                status.tasks[i].running = true;
+                status.tasks[i].last_run = Some(dates::format_minutes(started));
+                status.save(&status_path);
+
+                ui::info(ui::paint(Role::Heading, t!("daemon.running", task = tasks[i].task)));
+                let result = self.run_task(&tasks[i], status.tasks[i].last_success).await;
+                metrics::record_task_run(&tasks[i].task.to_string(), result.is_ok());
+                let task_status = &mut status.tasks[i];
+                task_status.running = false;
+                match result {
+                    Ok(summary) => {
+                        ui::info(t!("daemon.finished", task = tasks[i].task, summary = &summary));
+                        task_status.last_ok = Some(true);
+                        task_status.last_success = Some(started);
+                        task_status.last_result = Some(summary);
+                    }
+                    Err(e) => {
+                        ui::warn(t!("daemon.failed", task = tasks[i].task, error = e.to_string()));
+                        task_status.last_ok = Some(false);
+                        task_status.last_result = Some(e.to_string());
+                    }
+                }
+                next[i] = schedules[i].next_after(dates::now_minutes());
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+            }
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        status.stopped_at = Some(dates::now());
+        for task in &mut status.tasks {
+            task.next_run = None;
+        }
+        status.save(&status_path);
+        ui::info(t!("daemon.stopped"));
+        Ok(())
+    }
+
+    /// Runs one task, returning a one-line summary of what it did.
+    async fn run_task(&self, task: &DaemonTask, last_success: Option<u64>) -> Result<String, Box<dyn std::error::Error>> {
+        match task.task {
+            DaemonTaskKind::CoversBackfill => {
+                let rows = covers::missing_covers(self.client.fetch_all_media().await?, task.limit);
+                let items = covers::backfill(&self.searcher, &self.client, &rows, Duration::from_millis(task.delay_ms), false).await;
+                let count = |status| items.iter().filter(|item| item.status == status).count();
+                Ok(t!("daemon.covers_summary",
+                    uploaded = count(BackfillStatus::Uploaded),
+                    not_found = count(BackfillStatus::NotFound),
+                    failed = count(BackfillStatus::Failed)).into_owned())
+            }
+            DaemonTaskKind::Enrich => {
+                let options = EnrichOptions {
+                    min_words: self.config.app.min_synopsis_words,
+                    synopsis_only: task.synopsis_only,
+                    dry_run: false,
+                    confirm: false,
+                };
+                let rows: Vec<_> = enrich::incomplete_rows(self.client.fetch_all_media().await?, options.min_words, options.synopsis_only)
+                    .into_iter()
+                    .take(task.limit.unwrap_or(usize::MAX))
+                    .collect();
+                let items = enrich::run(&self.searcher, &self.client, &rows, options, false).await;
+                let count = |status| items.iter().filter(|item| item.status == status).count();
+                Ok(t!("daemon.enrich_summary",
+                    updated = count(EnrichStatus::Updated),
+                    unchanged = count(EnrichStatus::Complete),
+                    failed = count(EnrichStatus::Failed)).into_owned())
+            }
+            DaemonTaskKind::Labels => {
+                let baserow = &self.config.baserow;
+                let mut storages = self.label_generator.fetch_storages(&[], &[]).await?;
+                if let Some(last_success) = last_success {
+                    // Dates are all the modified field offers, so boxes changed on the day of the last run are redrawn
+                    let since = dates::format_days((last_success / 1440) as i64);
+                    storages = LabelGenerator::filter_changed_since(storages, &self.config.label.modified_field, &since)?;
+                }
+                let output_dir = PathBuf::from(&self.config.label.output_dir);
+                let written = self.label_generator
+                    .save_storage_labels(&storages, baserow.storage_table_id, baserow.database_id, baserow.storage_view_id, &output_dir)
+                    .await?;
+                Ok(t!("daemon.labels_summary", count = written.len(), dir = output_dir.display()).into_owned())
+            }
+        }
+    }
+}
+
+fn spawn_signal_handler(stop: Arc<AtomicBool>, stopping: Arc<Notify>) {
+    tokio::spawn(async move {
+        loop {
+            shutdown_signal().await;
+            if stop.swap(true, Ordering::SeqCst) {
+                std::process::exit(EXIT_CANCELLED);
+            }
+            ui::warn(t!("daemon.stopping"));
+            stopping.notify_one();
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+pub fn format_status(status: &DaemonStatus) -> String {
+    let state = match &status.stopped_at {
+        Some(stopped_at) => t!("daemon.state_stopped", time = stopped_at),
+        None if is_running(status.pid) => t!("daemon.state_running", pid = status.pid, time = &status.started_at),
+        None => t!("daemon.state_gone", pid = status.pid),
+    };
+    let mut lines = vec![ui::paint(Role::Heading, state)];
+    for task in &status.tasks {
+        lines.push(String::new());
+        lines.push(ui::paint(Role::Label, format!("{} ({})", task.task, task.schedule)));
+        if task.running {
+            lines.push(format!("  {}", t!("daemon.task_running", time = task.last_run.as_deref().unwrap_or_default())));
+        } else if let Some(last_run) = &task.last_run {
+            let result = task.last_result.as_deref().unwrap_or_default();
+            let role = if task.last_ok == Some(false) { Role::Warning } else { Role::Success };
+            lines.push(format!("  {}", t!("daemon.task_last_run", time = last_run, result = ui::paint(role, result))));
+        } else {
+            lines.push(format!("  {}", t!("daemon.task_not_run")));
+        }
+        if let Some(next_run) = &task.next_run {
+            lines.push(format!("  {}", t!("daemon.task_next_run", time = next_run)));
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,82 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn today() -> String {
+    format_days((unix_seconds() / 86_400) as i64)
+}
+
+/// The current time in UTC as `YYYY-MM-DD HH:MM`.
+pub fn now() -> String {
+    format_minutes(now_minutes())
+}
+
+/// Minutes since 1970-01-01 00:00 UTC.
+pub fn now_minutes() -> u64 {
+    unix_seconds() / 60
+}
+
+/// `YYYY-MM-DD HH:MM` for a time in minutes since 1970-01-01 00:00 UTC.
+pub fn format_minutes(minutes: u64) -> String {
+    format!("{} {:02}:{:02}", format_days((minutes / 1440) as i64), minutes % 1440 / 60, minutes % 60)
+}
+
+fn unix_seconds() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or_default()
+}
+
+/// Days since 1970-01-01 for a `YYYY-MM-DD` date, or `None` if it is not one,
+/// such as February 30th. Timestamps such as Baserow's `2025-03-01T10:00:00Z`
+/// are read by their date.
+pub fn parse_days(date: &str) -> Option<i64> {
+    let mut parts = date.get(..10)?.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    // A day past the end of the month comes back as a day of the next one
+    let days = days_from_civil(year, month, day);
+    (civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// The `YYYY-MM-DD` date `days` days after 1970-01-01.
+pub fn format_days(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn is_leap_year(year: u32) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// 1 for January 1st, from a `YYYY-MM-DD` date.
+pub fn day_of_year(date: &str) -> u32 {
+    let year = date.get(..4).and_then(|year| year.parse().ok()).unwrap_or(1970);
+    match (parse_days(date), parse_days(&format!("{:04}-01-01", year))) {
+        (Some(days), Some(new_year)) => (days - new_year + 1) as u32,
+        _ => 1,
+    }
+}
+
+// Howard Hinnant's civil calendar algorithms
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,143 @@
+use serde::Serialize;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Instant;
+use rust_i18n::t;
+use crate::baserow::BaserowClient;
+use crate::config::Config;
+use crate::google_books::GoogleBooksClient;
+use crate::llm::LlmProvider;
+use crate::open_library::OpenLibraryClient;
+use crate::ui::{self, Role};
+use crate::web_search::WebSearchClient;
+
+/// ISBN looked up by the catalogue checks (Homer's Odyssey, Penguin Classics).
+const SAMPLE_ISBN: &str = "9780140449136";
+
+/// The outcome of one check run by `wcm test --all`.
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub service: String,
+    pub ok: bool,
+    pub latency_ms: u128,
+    pub detail: String,
+}
+
+async fn check<F, E>(service: impl Into<String>, call: F) -> CheckResult
+where
+    F: Future<Output = Result<String, E>>,
+    E: Display,
+{
+    let service = service.into();
+    ui::detail(format!("Checking {}...", service));
+
+    let started = Instant::now();
+    let outcome = call.await;
+    let latency_ms = started.elapsed().as_millis();
+
+    match outcome {
+        Ok(detail) => CheckResult { service, ok: true, latency_ms, detail },
+        Err(e) => CheckResult { service, ok: false, latency_ms, detail: e.to_string() },
+    }
+}
+
+/// Checks every service the configuration depends on, one after the other so the
+/// latencies do not affect each other. Nothing is printed except verbose details.
+pub async fn run_all(config: &Config, baserow_client: &BaserowClient, http_client: &reqwest::Client) -> Vec<CheckResult> {
+    let google_client = GoogleBooksClient::new(
+        config.google_books.api_key.clone(),
+        config.google_books.base_url.clone(),
+    ).with_http_client(http_client.clone());
+    let open_library_client = OpenLibraryClient::new(config.open_library.base_url.clone())
+        .with_http_client(http_client.clone());
+    let web_search_client = WebSearchClient::new().with_http_client(http_client.clone());
+
+    let mut results = Vec::new();
+
+    results.push(check("Google Books", async {
+        google_client.search_by_isbn(SAMPLE_ISBN).await
+            .map(|response| t!("test.results", count = response.total_items).to_string())
+    }).await);
+
+    results.push(check("Open Library", async {
+        open_library_client.search_by_isbn(SAMPLE_ISBN).await
+            .map(|response| t!("test.results", count = response.num_found).to_string())
+    }).await);
+
+    results.push(check("Web search (DuckDuckGo)", async {
+        web_search_client.test_connection().await
+            .map(|count| t!("test.results", count = count).to_string())
+    }).await);
+
+    results.push(check(format!("LLM ({})", config.llm.provider), async {
+        let provider = LlmProvider::configured(config)?.with_http_client(http_client.clone());
+        provider.test_connection().await
+            .map(|report| t!("test.llm_answered", model = report.model).to_string())
+    }).await);
+
+    results.push(check("Baserow read", async {
+        baserow_client.fetch_media_page(1, 1).await
+            .map(|page| t!("test.rows", count = page.count.unwrap_or(page.results.len() as u32)).to_string())
+    }).await);
+
+    results.push(check("Baserow write", async {
+        baserow_client.check_write_access().await
+            .map(|_| t!("test.row_created").to_string())
+    }).await);
+
+    results.push(check("Baserow file upload", async {
+        let upload = baserow_client.upload_file(sample_png()?, "wcm-test.png").await?;
+        Ok::<String, Box<dyn std::error::Error>>(upload.name)
+    }).await);
+
+    results
+}
+
+/// A 1x1 white PNG for the upload check.
+fn sample_png() -> Result<Vec<u8>, image::ImageError> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(1, 1, image::Rgb([255, 255, 255])))
+        .write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+    Ok(bytes.into_inner())
+}
+
+/// Formats the results as a table with one row per service.
+pub fn format_table(results: &[CheckResult]) -> String {
+    let rows: Vec<[String; 4]> = results.iter()
+        .map(|result| [
+            result.service.clone(),
+            if result.ok { t!("test.pass").to_string() } else { t!("test.fail").to_string() },
+            format!("{} ms", result.latency_ms),
+            result.detail.clone(),
+        ])
+        .collect();
+    let header = [t!("test.service"), t!("test.status"), t!("test.latency"), t!("test.detail")]
+        .map(|cell| cell.to_string());
+
+    let mut widths = [0; 3];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(console::measure_text_width(cell));
+        }
+    }
+    let pad = |cell: &str, width: usize| format!("{}{}", cell, " ".repeat(width - console::measure_text_width(cell)));
+
+    let mut lines = vec![ui::paint(Role::Label, format!(
+        "{}  {}  {}  {}",
+        pad(&header[0], widths[0]),
+        pad(&header[1], widths[1]),
+        pad(&header[2], widths[2]),
+        header[3],
+    ))];
+    for (row, result) in rows.iter().zip(results) {
+        let role = if result.ok { Role::Success } else { Role::Error };
+        lines.push(format!(
+            "{}  {}  {}  {}",
+            pad(&row[0], widths[0]),
+            ui::paint(role, pad(&row[1], widths[1])),
+            pad(&row[2], widths[2]),
+            row[3],
+        ));
+    }
+    lines.join("\n")
+}
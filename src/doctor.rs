@@ -0,0 +1,146 @@
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use rust_i18n::t;
+use crate::config::Config;
+use crate::config_check::{self, Finding, Severity};
+use crate::ui::{self, Role};
+
+/// Checks everything wcm depends on, for `wcm doctor`: where the configuration
+/// comes from and whether it parses, every finding of `wcm config check`, that
+/// the data files can be written, and whether prompts can be answered. `loaded`
+/// is the result of `Config::load`; when it failed, the remaining checks run
+/// against the defaults.
+pub async fn run(loaded: Result<Config, String>, offline: bool) -> Vec<Finding> {
+    let mut findings = vec![Finding::ok(
+        "wcm",
+        t!("doctor.version", version = env!("CARGO_PKG_VERSION"), os = std::env::consts::OS, arch = std::env::consts::ARCH),
+    )];
+    findings.extend(check_config_files());
+
+    let config = match loaded {
+        Ok(config) => {
+            findings.push(Finding::ok("config", t!("doctor.config_parsed")));
+            findings.extend(config_check::run(&config, offline).await);
+            config
+        }
+        Err(e) => {
+            findings.push(Finding::error("config", t!("doctor.config_invalid", error = e), t!("doctor.config_invalid_fix")));
+            Config::default()
+        }
+    };
+
+    findings.extend(check_data_paths(&config));
+    findings.extend(check_terminal());
+    findings
+}
+
+/// Where the settings come from: config.yaml in the working directory, and the
+/// nearest .env file.
+fn check_config_files() -> Vec<Finding> {
+    let dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let mut findings = Vec::new();
+    let config_file = dir.join("config.yaml");
+    findings.push(if config_file.exists() {
+        Finding::ok("config.yaml", t!("doctor.found", path = config_file.display()))
+    } else {
+        Finding::warning("config.yaml", t!("doctor.no_config_file", dir = dir.display()), t!("doctor.no_config_file_fix"))
+    });
+    if let Ok(env_file) = dotenvy::dotenv() {
+        findings.push(Finding::ok(".env", t!("doctor.found", path = env_file.display())));
+    }
+    findings
+}
+
+/// The files and directories wcm writes to, with the setting naming each and
+/// whether it is a directory. Empty settings, which turn a feature off, are left
+/// out.
+pub fn data_paths(config: &Config) -> Vec<(&'static str, &str, bool)> {
+    [
+        ("app.retry_dir", config.app.retry_dir.as_str(), true),
+        ("app.cache_dir", config.app.cache_dir.as_str(), true),
+        ("app.goals_file", config.app.goals_file.as_str(), false),
+        ("app.sessions_file", config.app.sessions_file.as_str(), false),
+        ("app.cover_hashes_file", config.app.cover_hashes_file.as_str(), false),
+        ("app.embeddings_file", config.app.embeddings_file.as_str(), false),
+        ("app.last_search_file", config.app.last_search_file.as_str(), false),
+        ("daemon.status_file", config.daemon.status_file.as_str(), false),
+        ("label.output_dir", config.label.output_dir.as_str(), true),
+    ]
+    .into_iter()
+    .filter(|(_, path, _)| !path.trim().is_empty())
+    .collect()
+}
+
+fn check_data_paths(config: &Config) -> Vec<Finding> {
+    data_paths(config).into_iter()
+        .map(|(setting, path, is_dir)| {
+            let path = Path::new(path);
+            let dir = if is_dir { path } else { path.parent().unwrap_or(Path::new("")) };
+            check_writable(setting, dir)
+        })
+        .collect()
+}
+
+/// Whether a file can be created in `dir`. A directory that does not exist yet
+/// is created on first use, so its nearest existing parent is tried instead.
+pub fn check_writable(setting: &str, dir: &Path) -> Finding {
+    // Absolute, so the report says where the files go whichever directory it was run from
+    let dir: PathBuf = std::env::current_dir().map(|cwd| cwd.join(dir)).unwrap_or_else(|_| dir.to_path_buf())
+        .components()
+        .filter(|component| *component != std::path::Component::CurDir)
+        .collect();
+    let existing = dir.ancestors().find(|ancestor| ancestor.exists()).unwrap_or(Path::new("."));
+    if !existing.is_dir() {
+        return Finding::error(setting, t!("doctor.not_a_directory", path = existing.display()), t!("doctor.not_writable_fix", setting = setting));
+    }
+
+    let probe = existing.join(format!(".wcm-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Finding::ok(setting, t!("doctor.writable", path = dir.display()))
+        }
+        Err(e) => Finding::error(
+            setting,
+            t!("doctor.not_writable", path = existing.display(), error = e),
+            t!("doctor.not_writable_fix", setting = setting),
+        ),
+    }
+}
+
+/// Whether the interactive prompts can be answered: they read the keyboard from
+/// stdin and draw on stderr.
+fn check_terminal() -> Vec<Finding> {
+    let interactive = std::io::stdin().is_terminal() && console::Term::stderr().is_term();
+    vec![if interactive {
+        Finding::ok("terminal", t!("doctor.interactive"))
+    } else {
+        Finding::warning("terminal", t!("doctor.not_interactive"), t!("doctor.not_interactive_fix"))
+    }]
+}
+
+/// The findings that need fixing, errors before warnings, each group in the
+/// order it was found.
+pub fn prioritized(findings: &[Finding]) -> Vec<&Finding> {
+    let mut problems: Vec<&Finding> = findings.iter()
+        .filter(|finding| finding.severity != Severity::Ok && finding.fix.is_some())
+        .collect();
+    problems.sort_by_key(|finding| finding.severity != Severity::Error);
+    problems
+}
+
+/// The `wcm config check` table of every finding, then the numbered list of
+/// fixes, most important first.
+pub fn format_report(findings: &[Finding]) -> String {
+    let mut report = config_check::format_report(findings);
+    let problems = prioritized(findings);
+    if !problems.is_empty() {
+        report.push_str("\n\n");
+        report.push_str(&ui::paint(Role::Label, t!("doctor.fix_list")));
+        for (number, finding) in problems.iter().enumerate() {
+            let fix = finding.fix.as_deref().unwrap_or_default();
+            report.push_str(&format!("\n{:>3}. {}: {}", number + 1, finding.setting, fix));
+        }
+    }
+    report
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::book_search::BookResult;
+use rust_i18n::t;
+
+/// Normalized titles at least this similar count as the same work.
+const TITLE_SIMILARITY_THRESHOLD: f64 = 0.9;
+
+/// A library row that looks like the book being added.
+#[derive(Debug, Clone, Serialize)]
+pub struct PossibleDuplicate {
+    pub id: u64,
+    pub title: String,
+    pub author: String,
+    pub isbn: Option<String>,
+    pub location: Option<String>,
+    pub reason: String,
+}
+
+impl PossibleDuplicate {
+    pub(crate) fn new(row: &MediaRow, reason: &str) -> Self {
+        Self {
+            id: row.id,
+            title: row.get_text("Title").unwrap_or_default(),
+            author: row.get_text("Author").unwrap_or_default(),
+            isbn: row.get_text("ISBN"),
+            location: row.get_text("Location"),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Finds rows with the same ISBN, or a matching title and author. Titles are compared
+/// without subtitles, accents, punctuation, or a leading article, so other editions
+/// of the same book are caught too.
+pub fn find_possible_duplicates(book: &BookResult, rows: &[MediaRow]) -> Vec<PossibleDuplicate> {
+    let isbn = book.get_isbn().map(|isbn| normalize_isbn(&isbn));
+    let title = normalize_title(&book.get_full_title());
+    let surnames = author_surnames(&book.get_all_authors());
+
+    rows.iter()
+        .filter_map(|row| {
+            let row_isbn = row.get_text("ISBN").map(|isbn| normalize_isbn(&isbn));
+            if isbn.is_some() && row_isbn == isbn {
+                return Some(PossibleDuplicate::new(row, &t!("duplicates.same_isbn")));
+            }
+
+            let row_title = normalize_title(&row.get_text("Title")?);
+            if title.is_empty() || strsim::normalized_levenshtein(&title, &row_title) < TITLE_SIMILARITY_THRESHOLD {
+                return None;
+            }
+
+            // An unknown author on either side is not evidence against a match
+            let row_surnames = author_surnames(&row.get_text("Author").unwrap_or_default());
+            let same_author = surnames.is_empty()
+                || row_surnames.is_empty()
+                || surnames.iter().any(|surname| row_surnames.contains(surname));
+
+            same_author.then(|| PossibleDuplicate::new(row, &t!("duplicates.similar")))
+        })
+        .collect()
+}
+
+/// An ISBN without punctuation, with an ISBN-10 written as its ISBN-13, so both
+/// forms of one book compare equal.
+pub(crate) fn normalize_isbn(isbn: &str) -> String {
+    let cleaned = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase();
+    match cleaned.len() {
+        10 => crate::barcode::isbn_to_ean13(&cleaned).unwrap_or(cleaned),
+        _ => cleaned,
+    }
+}
+
+/// Lowercases and strips accents, punctuation, and extra whitespace. Only the
+/// accents of Latin letters go: in Thai and other scripts the marks are vowels
+/// and tones, and dropping them would make different words equal.
+pub(crate) fn fold(text: &str) -> String {
+    let folded: String = crate::authors::strip_latin_diacritics(text).chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() || unicode_normalization::char::is_combining_mark(c) { c } else { ' ' })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub(crate) fn normalize_title(title: &str) -> String {
+    let main_title = title
+        .split([':', '(', '['])
+        .next()
+        .unwrap_or(title)
+        .split(" - ")
+        .next()
+        .unwrap_or(title);
+    let folded = fold(main_title);
+
+    for article in ["the ", "a ", "an "] {
+        if let Some(rest) = folded.strip_prefix(article) {
+            return rest.to_string();
+        }
+    }
+    folded
+}
+
+/// Last names of each author in an author list, whichever order each name is
+/// written in, so rows stored under any `author_format` match.
+fn author_surnames(authors: &str) -> Vec<String> {
+    crate::authors::split_authors(authors).iter()
+        .filter_map(|author| crate::authors::surname_key(author))
+        .collect()
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use crate::baserow::MediaRow;
+use crate::config::Config;
+use crate::duplicates::fold;
+use crate::llm::{LlmError, LlmProvider};
+use crate::timing::{self, Stage};
+use crate::ui;
+
+/// Texts sent to the embedding model in one request.
+const BATCH_SIZE: usize = 32;
+
+/// The embedding model of `llm.embedding_provider`, or of `llm.provider` when
+/// that is unset.
+#[derive(Debug, Clone)]
+pub struct Embedder {
+    provider: LlmProvider,
+    model: String,
+}
+
+impl Embedder {
+    pub fn from_config(config: &Config) -> Result<Self, LlmError> {
+        let mut config = config.clone();
+        if let Some(provider) = &config.llm.embedding_provider {
+            config.llm.provider = provider.clone();
+        }
+        let model = match config.llm.provider.as_str() {
+            "openai" => config.llm.openai.embedding_model.clone(),
+            _ => config.llm.ollama.embedding_model.clone(),
+        };
+        Ok(Self { provider: LlmProvider::configured(&config)?, model })
+    }
+
+    /// Uses `client` for every request, as `LlmProvider::with_http_client`.
+    pub fn with_http_client(self, client: reqwest::Client) -> Self {
+        Self { provider: self.provider.with_http_client(client), ..self }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The vector of each of `texts`, in order, sent in batches.
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(BATCH_SIZE) {
+            vectors.extend(self.provider.embed(&self.model, batch).await?);
+        }
+        Ok(vectors)
+    }
+}
+
+/// What is embedded for a book: its title, author, categories and synopsis.
+pub fn book_text(row: &MediaRow) -> String {
+    let mut text = row.get_text("Title").unwrap_or_default();
+    if let Some(author) = row.get_text("Author") {
+        text.push_str(&format!(" by {}", author));
+    }
+    if let Some(categories) = row.get_text("Category") {
+        text.push_str(&format!(". {}", categories));
+    }
+    if let Some(synopsis) = row.get_text("Synopsis") {
+        text.push_str(&format!(". {}", synopsis.trim()));
+    }
+    text
+}
+
+/// A short fingerprint of `text` (64-bit FNV-1a), so a book is embedded again
+/// only when its text changed.
+pub fn digest(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+/// How alike two vectors point, from -1 to 1; 0 when either is empty.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if a.len() != b.len() || norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedBook {
+    /// `digest` of the text the vector was made from
+    pub digest: String,
+    pub vector: Vec<f32>,
+}
+
+/// The local vector store: one vector per media row, all from `model`. Kept in
+/// `app.embeddings_file`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Index {
+    pub model: String,
+    pub books: BTreeMap<u64, IndexedBook>,
+}
+
+impl Index {
+    /// The index at `path`, or an empty one when there is no file yet.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let index = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{} is not an embeddings file: {}", path.display(), e))?;
+        Ok(index)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Drops the vectors of rows no longer in `rows`, and every vector when
+    /// they came from another model, since vectors of different models cannot
+    /// be compared.
+    pub fn prune(&mut self, model: &str, rows: &[MediaRow]) {
+        if self.model != model {
+            self.model = model.to_string();
+            self.books.clear();
+        }
+        let ids: std::collections::HashSet<u64> = rows.iter().map(|row| row.id).collect();
+        self.books.retain(|id, _| ids.contains(id));
+    }
+
+    /// The rows that are new or whose text changed since they were embedded,
+    /// with the text to embed.
+    pub fn stale<'a>(&self, rows: &'a [MediaRow]) -> Vec<(&'a MediaRow, String)> {
+        rows.iter()
+            .map(|row| (row, book_text(row)))
+            .filter(|(row, text)| self.books.get(&row.id).is_none_or(|book| book.digest != digest(text)))
+            .collect()
+    }
+
+    /// The `count` indexed rows closest to `query`, closest first, with their
+    /// cosine similarity.
+    pub fn nearest(&self, query: &[f32], count: usize) -> Vec<(u64, f32)> {
+        let mut scored: Vec<(u64, f32)> = self.books.iter()
+            .map(|(id, book)| (*id, cosine(query, &book.vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(count);
+        scored
+    }
+}
+
+/// Brings the index at `path` up to date with `rows`, embedding the books that
+/// are new or changed. The index is saved after each batch, so an interrupted
+/// run keeps what it did.
+pub async fn refresh(path: &Path, embedder: &Embedder, rows: &[MediaRow]) -> Result<Index, Box<dyn std::error::Error>> {
+    let mut index = Index::load(path)?;
+    index.prune(embedder.model(), rows);
+    let stale = index.stale(rows);
+    if !stale.is_empty() {
+        ui::info(t!("find.indexing", count = stale.len(), model = embedder.model()));
+    }
+    for (done, batch) in stale.chunks(BATCH_SIZE).enumerate() {
+        let texts: Vec<String> = batch.iter().map(|(_, text)| text.clone()).collect();
+        let vectors = timing::time(Stage::Llm, embedder.embed(&texts)).await?;
+        for ((row, text), vector) in batch.iter().zip(vectors) {
+            index.books.insert(row.id, IndexedBook { digest: digest(text), vector });
+        }
+        index.save(path)?;
+        ui::detail(format!("Embedded {}/{} books", (done * BATCH_SIZE + batch.len()), stale.len()));
+    }
+    if stale.is_empty() {
+        // Pruning alone may have changed it
+        index.save(path)?;
+    }
+    Ok(index)
+}
+
+/// A book `wcm find` matched, with how close it is to the query for a semantic
+/// search.
+#[derive(Debug, Clone, Serialize)]
+pub struct FoundBook {
+    pub id: u64,
+    pub title: String,
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+}
+
+impl FoundBook {
+    fn new(row: &MediaRow, score: Option<f32>) -> Self {
+        Self {
+            id: row.id,
+            title: row.get_text("Title").unwrap_or_default(),
+            author: row.get_text("Author").unwrap_or_default(),
+            score,
+        }
+    }
+}
+
+/// The rows closest in meaning to the query vector, closest first.
+pub fn semantic_matches(index: &Index, rows: &[MediaRow], query: &[f32], count: usize) -> Vec<FoundBook> {
+    index.nearest(query, count).into_iter()
+        .filter_map(|(id, score)| rows.iter().find(|row| row.id == id).map(|row| FoundBook::new(row, Some(score))))
+        .collect()
+}
+
+/// The rows whose title, author, categories or synopsis contain every word of
+/// `query`, ignoring case and accents, in table order.
+pub fn keyword_matches(rows: &[MediaRow], query: &str, count: usize) -> Vec<FoundBook> {
+    let query = fold(query);
+    let words: Vec<&str> = query.split_whitespace().collect();
+    rows.iter()
+        .filter(|row| {
+            let text = fold(&book_text(row));
+            let text_words: Vec<&str> = text.split_whitespace().collect();
+            !words.is_empty() && words.iter().all(|word| text_words.iter().any(|text_word| text_word.starts_with(word)))
+        })
+        .take(count)
+        .map(|row| FoundBook::new(row, None))
+        .collect()
+}
+
+/// The matches as a numbered list, with the similarity of semantic matches.
+pub fn format_matches(matches: &[FoundBook]) -> String {
+    if matches.is_empty() {
+        return t!("find.none").to_string();
+    }
+    matches.iter().enumerate()
+        .map(|(i, found)| {
+            let line = t!("find.match", number = i + 1, title = &found.title, author = &found.author, id = found.id).to_string();
+            match found.score {
+                Some(score) => format!("{}  {}", line, ui::paint(ui::Role::Label, format!("{:.2}", score))),
+                None => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,198 @@
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::{BaserowClient, MediaRow};
+use crate::book_search::CombinedBookSearcher;
+use crate::covers::{self, BackfillStatus};
+use crate::timing::{self, Stage};
+use crate::ui::{self, Role};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichStatus {
+    Updated,
+    /// Listed but left alone, because of `--dry-run`
+    WouldUpdate,
+    /// The new synopsis was turned down at the `--confirm` prompt
+    Declined,
+    /// Nothing to do: the synopsis is long enough and there is a cover
+    Complete,
+    Failed,
+}
+
+/// What `wcm enrich` did for one row.
+#[derive(Debug, Serialize)]
+pub struct EnrichItem {
+    pub id: u64,
+    pub title: String,
+    pub status: EnrichStatus,
+    /// Words in the synopsis before, and after when it was replaced
+    pub words_before: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words_after: Option<usize>,
+    /// What happened to a missing cover, unless `--synopsis-only`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover: Option<BackfillStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What to do to each row.
+#[derive(Debug, Clone, Copy)]
+pub struct EnrichOptions {
+    /// Synopses with fewer words are regenerated
+    pub min_words: usize,
+    /// Leave covers alone
+    pub synopsis_only: bool,
+    /// Only list what would change, without the LLM
+    pub dry_run: bool,
+    /// Ask before writing each new synopsis
+    pub confirm: bool,
+}
+
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn synopsis(row: &MediaRow) -> String {
+    row.get_text("Synopsis").unwrap_or_default()
+}
+
+/// Whether a row's synopsis is missing or shorter than `min_words`.
+pub fn needs_synopsis(row: &MediaRow, min_words: usize) -> bool {
+    word_count(&synopsis(row)) < min_words
+}
+
+/// The rows `wcm enrich --all` works on, in table order: those whose synopsis is
+/// too short and, unless `synopsis_only`, those without a cover.
+pub fn incomplete_rows(rows: Vec<MediaRow>, min_words: usize, synopsis_only: bool) -> Vec<MediaRow> {
+    rows.into_iter()
+        .filter(|row| needs_synopsis(row, min_words) || (!synopsis_only && !covers::has_cover(row)))
+        .collect()
+}
+
+/// Regenerates the synopsis of each row that needs it, and fills a missing cover,
+/// one row at a time. `force` regenerates the synopsis whatever its length, for a
+/// book named on the command line. Stops early when a `--confirm` prompt is
+/// dismissed with Esc.
+pub async fn run(
+    searcher: &CombinedBookSearcher,
+    client: &BaserowClient,
+    rows: &[MediaRow],
+    options: EnrichOptions,
+    force: bool,
+) -> Vec<EnrichItem> {
+    let mut items = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let title = row.get_text("Title").unwrap_or_default();
+        ui::info(ui::paint(Role::Label, t!("enrich.row", number = i + 1, total = rows.len(), title = &title)));
+        let mut item = EnrichItem {
+            id: row.id,
+            title,
+            status: EnrichStatus::Complete,
+            words_before: word_count(&synopsis(row)),
+            words_after: None,
+            cover: None,
+            error: None,
+        };
+
+        if force || needs_synopsis(row, options.min_words) {
+            match enrich_synopsis(searcher, client, row, options).await {
+                Ok(Some((status, words_after))) => {
+                    item.status = status;
+                    item.words_after = words_after;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    item.status = EnrichStatus::Failed;
+                    item.error = Some(e.to_string());
+                }
+            }
+        }
+        if !options.synopsis_only && !covers::has_cover(row) {
+            let cover = covers::backfill_row(searcher, client, row, options.dry_run).await;
+            if item.status == EnrichStatus::Complete && cover.status != BackfillStatus::NotFound {
+                item.status = match cover.status {
+                    BackfillStatus::Uploaded => EnrichStatus::Updated,
+                    BackfillStatus::Found => EnrichStatus::WouldUpdate,
+                    _ => EnrichStatus::Failed,
+                };
+            }
+            item.error = item.error.take().or(cover.error);
+            item.cover = Some(cover.status);
+        }
+        items.push(item);
+    }
+    items
+}
+
+/// Returns the row's new status and synopsis length, or `None` when the user
+/// stopped at the prompt.
+async fn enrich_synopsis(
+    searcher: &CombinedBookSearcher,
+    client: &BaserowClient,
+    row: &MediaRow,
+    options: EnrichOptions,
+) -> Result<Option<(EnrichStatus, Option<usize>)>, Box<dyn std::error::Error>> {
+    let existing = synopsis(row);
+    if options.dry_run {
+        ui::info(t!("enrich.would_regenerate", words = word_count(&existing)));
+        return Ok(Some((EnrichStatus::WouldUpdate, None)));
+    }
+
+    let title = row.get_text("Title").unwrap_or_default();
+    let author = row.get_text("Author").unwrap_or_default();
+    let generated = searcher.generate_synopsis(&title, &author, &existing).await?;
+
+    if options.confirm {
+        if !existing.trim().is_empty() {
+            ui::show(ui::paint(Role::Heading, t!("enrich.current")));
+            ui::show(&existing);
+        }
+        ui::show(ui::paint(Role::Heading, t!("enrich.generated")));
+        ui::show(&generated);
+        let accepted = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(t!("enrich.confirm"))
+            .default(true)
+            .interact_opt()?;
+        match accepted {
+            Some(true) => {}
+            Some(false) => return Ok(Some((EnrichStatus::Declined, None))),
+            None => return Ok(None),
+        }
+    }
+
+    timing::time(Stage::Update, client.update_media_row(row.id, &serde_json::json!({ "Synopsis": generated }))).await?;
+    Ok(Some((EnrichStatus::Updated, Some(word_count(&generated)))))
+}
+
+pub fn format_summary(items: &[EnrichItem]) -> String {
+    let groups = [
+        (EnrichStatus::Updated, t!("enrich.summary_updated")),
+        (EnrichStatus::WouldUpdate, t!("enrich.summary_would_update")),
+        (EnrichStatus::Declined, t!("enrich.summary_declined")),
+        (EnrichStatus::Complete, t!("enrich.summary_complete")),
+        (EnrichStatus::Failed, t!("enrich.summary_failed")),
+    ];
+
+    let mut lines = vec![String::new(), ui::paint(Role::Heading, t!("enrich.summary_heading"))];
+    for (status, heading) in groups {
+        let group: Vec<&EnrichItem> = items.iter().filter(|item| item.status == status).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        lines.push(ui::paint(Role::Label, format!("{} ({})", heading, group.len())));
+        for item in group {
+            let mut line = format!("  #{}  {}", item.id, item.title);
+            if let Some(words_after) = item.words_after {
+                line.push_str(&format!("  ({} -> {} words)", item.words_before, words_after));
+            }
+            if let Some(error) = &item.error {
+                line.push_str(&format!("  {}", error));
+            }
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
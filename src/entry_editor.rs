@@ -0,0 +1,153 @@
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use crate::baserow::STATUS_OPTIONS;
+use crate::book_search::PreparedBook;
+use crate::identifiers::is_valid_isbn;
+
+/// Marks the comment lines listing the problems of the last save, so they are
+/// replaced rather than piled up when the file is opened again.
+const ERROR_PREFIX: &str = "# ERROR: ";
+
+/// The fields of a prepared entry that `wcm add --edit` opens in the editor, as
+/// YAML. Status is written by its option name; fields left out of the file are
+/// emptied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EditableEntry {
+    pub title: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub isbn: Option<String>,
+    #[serde(default)]
+    pub publisher: Option<String>,
+    #[serde(default)]
+    pub pages: Option<u32>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub synopsis: String,
+    #[serde(default)]
+    pub ebook: bool,
+    pub status: String,
+    #[serde(default)]
+    pub read: bool,
+    /// 0 to 10, 0 for unrated
+    #[serde(default)]
+    pub rating: u32,
+    #[serde(default)]
+    pub series: Option<String>,
+    #[serde(default)]
+    pub volume: Option<u32>,
+    #[serde(default)]
+    pub call_number: Option<String>,
+}
+
+impl EditableEntry {
+    pub fn from_prepared(prepared: &PreparedBook) -> Self {
+        Self {
+            title: prepared.title.clone(),
+            author: prepared.author.clone(),
+            isbn: prepared.isbn.clone(),
+            publisher: prepared.publisher.clone(),
+            pages: prepared.pages,
+            categories: prepared.categories.clone(),
+            synopsis: prepared.synopsis.clone(),
+            ebook: prepared.is_ebook,
+            status: STATUS_OPTIONS.iter()
+                .find(|(id, _)| *id == prepared.status)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_default(),
+            read: prepared.read,
+            rating: prepared.rating,
+            series: prepared.series.clone(),
+            volume: prepared.volume_number,
+            call_number: prepared.call_number.clone(),
+        }
+    }
+
+    /// Writes the entry back into `prepared`. Only call it on an entry `parse`
+    /// accepted.
+    pub fn apply(self, prepared: &mut PreparedBook) {
+        let trimmed = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        prepared.title = self.title.trim().to_string();
+        prepared.author = self.author.trim().to_string();
+        prepared.isbn = trimmed(self.isbn);
+        prepared.publisher = trimmed(self.publisher);
+        prepared.pages = self.pages;
+        prepared.categories = self.categories;
+        prepared.synopsis = self.synopsis.trim().to_string();
+        prepared.is_ebook = self.ebook;
+        if let Some((id, _)) = STATUS_OPTIONS.iter().find(|(_, name)| name.eq_ignore_ascii_case(self.status.trim())) {
+            prepared.status = *id;
+        }
+        prepared.read = self.read;
+        prepared.rating = self.rating;
+        prepared.series = trimmed(self.series);
+        prepared.volume_number = self.volume;
+        prepared.call_number = trimmed(self.call_number);
+    }
+}
+
+/// The entry as the file opened in the editor: comments with the categories
+/// and statuses to choose from, then the YAML.
+pub fn to_yaml(entry: &EditableEntry, categories: &[String]) -> String {
+    let statuses: Vec<&str> = STATUS_OPTIONS.iter().map(|(_, name)| *name).collect();
+    format!(
+        "# {}\n# {}\n# {}\n\n{}",
+        t!("entry_editor.instructions"),
+        t!("entry_editor.categories", categories = categories.join(", ")),
+        t!("entry_editor.statuses", statuses = statuses.join(", ")),
+        serde_yaml::to_string(entry).unwrap_or_default(),
+    )
+}
+
+/// The saved file with `errors` as comments at the top, in place of those of
+/// the previous save, to open again.
+pub fn with_errors(text: &str, errors: &[String]) -> String {
+    let mut lines: Vec<String> = errors.iter().map(|error| format!("{}{}", ERROR_PREFIX, error.replace('\n', " "))).collect();
+    lines.extend(text.lines().filter(|line| !line.starts_with(ERROR_PREFIX)).map(str::to_string));
+    lines.join("\n") + "\n"
+}
+
+/// Reads the saved file back: `None` when it was emptied, which cancels the
+/// add, or every problem found. Categories are matched ignoring case and come
+/// back with the name in `categories`.
+pub fn parse(text: &str, categories: &[String]) -> Result<Option<EditableEntry>, Vec<String>> {
+    if text.lines().all(|line| line.trim().is_empty() || line.trim_start().starts_with('#')) {
+        return Ok(None);
+    }
+    let mut entry: EditableEntry = serde_yaml::from_str(text)
+        .map_err(|e| vec![t!("entry_editor.invalid_yaml", error = e.to_string()).into_owned()])?;
+
+    let mut errors = Vec::new();
+    if entry.title.trim().is_empty() {
+        errors.push(t!("entry_editor.title_required").into_owned());
+    }
+    if let Some(isbn) = entry.isbn.as_deref().map(str::trim).filter(|isbn| !isbn.is_empty()) {
+        if !is_valid_isbn(isbn) {
+            errors.push(t!("entry_editor.invalid_isbn", isbn = isbn).into_owned());
+        }
+    }
+    if entry.categories.is_empty() {
+        errors.push(t!("entry_editor.category_required").into_owned());
+    }
+    for category in entry.categories.iter_mut() {
+        match categories.iter().find(|name| name.eq_ignore_ascii_case(category.trim())) {
+            Some(name) => *category = name.clone(),
+            None => errors.push(t!("entry_editor.unknown_category", category = category.as_str()).into_owned()),
+        }
+    }
+    if !STATUS_OPTIONS.iter().any(|(_, name)| name.eq_ignore_ascii_case(entry.status.trim())) {
+        errors.push(t!("entry_editor.unknown_status", status = entry.status.as_str()).into_owned());
+    }
+    if entry.rating > 10 {
+        errors.push(t!("entry_editor.rating_range", rating = entry.rating).into_owned());
+    }
+
+    if errors.is_empty() {
+        Ok(Some(entry))
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::baserow::BaserowError;
+use crate::llm::LlmError;
+use rust_i18n::t;
+
+/// Process exit codes, so wrapper scripts can tell failures apart.
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid command line arguments (clap uses the same code for its own errors).
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_CONFIG: i32 = 3;
+pub const EXIT_NOT_FOUND: i32 = 4;
+pub const EXIT_CANCELLED: i32 = 5;
+pub const EXIT_NETWORK: i32 = 6;
+pub const EXIT_LLM: i32 = 7;
+pub const EXIT_BASEROW_REJECTED: i32 = 8;
+
+/// The kinds of failure a command can end with, each with its own exit code.
+#[derive(Debug)]
+pub enum WcmError {
+    Config(String),
+    NotFound(String),
+    Cancelled,
+    Network(reqwest::Error),
+    Llm(LlmError),
+    Baserow(BaserowError),
+    Other(Box<dyn std::error::Error>),
+}
+
+impl WcmError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WcmError::Config(_) => EXIT_CONFIG,
+            WcmError::NotFound(_) => EXIT_NOT_FOUND,
+            WcmError::Cancelled => EXIT_CANCELLED,
+            WcmError::Network(_) => EXIT_NETWORK,
+            WcmError::Llm(_) => EXIT_LLM,
+            WcmError::Baserow(BaserowError::RequestFailed(_)) => EXIT_NETWORK,
+            WcmError::Baserow(BaserowError::NotFound) => EXIT_NOT_FOUND,
+            WcmError::Baserow(_) => EXIT_BASEROW_REJECTED,
+            WcmError::Other(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+impl std::fmt::Display for WcmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WcmError::Config(msg) => write!(f, "{}", t!("error.config", message = msg)),
+            WcmError::NotFound(msg) => write!(f, "{}", msg),
+            WcmError::Cancelled => write!(f, "{}", t!("error.cancelled")),
+            WcmError::Network(e) => write!(f, "{}", t!("error.network", message = e)),
+            WcmError::Llm(e) => write!(f, "{}", e),
+            WcmError::Baserow(e) => write!(f, "{}", t!("error.baserow", message = e)),
+            WcmError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for WcmError {}
+
+impl From<BaserowError> for WcmError {
+    fn from(error: BaserowError) -> Self {
+        WcmError::Baserow(error)
+    }
+}
+
+impl From<LlmError> for WcmError {
+    fn from(error: LlmError) -> Self {
+        WcmError::Llm(error)
+    }
+}
+
+impl From<reqwest::Error> for WcmError {
+    fn from(error: reqwest::Error) -> Self {
+        WcmError::Network(error)
+    }
+}
+
+impl From<std::io::Error> for WcmError {
+    fn from(error: std::io::Error) -> Self {
+        WcmError::Other(Box::new(error))
+    }
+}
+
+/// Recovers the kind of failure from the boxed errors most functions return.
+impl From<Box<dyn std::error::Error>> for WcmError {
+    fn from(error: Box<dyn std::error::Error>) -> Self {
+        let error = match error.downcast::<WcmError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<BaserowError>() {
+            Ok(error) => return WcmError::Baserow(*error),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<LlmError>() {
+            Ok(error) => return WcmError::Llm(*error),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<reqwest::Error>() {
+            Ok(error) => return WcmError::Network(*error),
+            Err(error) => error,
+        };
+        match error.downcast::<config::ConfigError>() {
+            Ok(error) => WcmError::Config(error.to_string()),
+            Err(error) => WcmError::Other(error),
+        }
+    }
+}
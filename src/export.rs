@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use serde::Serialize;
+use serde_json::Value;
+use crate::baserow::MediaRow;
+
+/// Fields that lead every file, in this order; the rest follow alphabetically.
+const LEADING_FIELDS: [&str; 3] = ["Title", "Author", "ISBN"];
+
+/// Written as the body of the file rather than in the front matter.
+const BODY_FIELD: &str = "Synopsis";
+
+/// Baserow's position of the row in the table, which changes when rows are
+/// moved and says nothing about the book.
+const SKIPPED_FIELDS: [&str; 1] = ["order"];
+
+/// Longest slug kept in a file name, so long subtitles do not make unwieldy paths.
+const MAX_SLUG_LEN: usize = 60;
+
+/// What `wcm export --format markdown-tree` did to the output directory.
+#[derive(Debug, Default, Serialize)]
+pub struct ExportSummary {
+    pub written: usize,
+    pub unchanged: usize,
+    /// Files of rows no longer in the table
+    pub removed: usize,
+}
+
+/// The file a row is exported to: its title as ASCII words joined by hyphens,
+/// then the row ID, which keeps the name unique and stable while the title is.
+pub fn file_name(row: &MediaRow) -> String {
+    let title = deunicode::deunicode(&row.get_text("Title").unwrap_or_default()).to_lowercase();
+    let mut slug = String::new();
+    for word in title.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    if slug.is_empty() {
+        slug.push_str("untitled");
+    }
+    format!("{}-{}.md", slug, row.id)
+}
+
+/// Whether a file name has the shape `file_name` gives, so other files kept
+/// in the directory, such as a README, are never removed.
+fn is_exported_name(name: &str) -> bool {
+    name.strip_suffix(".md")
+        .and_then(|stem| stem.rsplit_once('-'))
+        .is_some_and(|(_, id)| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// A field value for the front matter: numbers and booleans as they are, select
+/// and link fields as their values, file fields as their file names. `None` for
+/// empty values.
+fn field_value(value: &Value) -> Option<serde_yaml::Value> {
+    let text = match value {
+        Value::Number(n) => return serde_yaml::to_value(n).ok(),
+        Value::Bool(b) => return Some((*b).into()),
+        Value::String(s) => s.trim().to_string(),
+        Value::Object(o) => o.get("value")?.as_str()?.trim().to_string(),
+        Value::Array(items) => items.iter()
+            .filter_map(|item| item.get("value").or_else(|| item.get("visible_name")).and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Null => return None,
+    };
+    Some(text).filter(|text| !text.is_empty()).map(Into::into)
+}
+
+/// A row as Markdown: YAML front matter with the row ID and every non-empty
+/// field, then the synopsis. The same row always renders to the same text,
+/// whatever order Baserow sent its fields in.
+pub fn render(row: &MediaRow) -> String {
+    let mut names: Vec<&String> = row.fields.keys()
+        .filter(|name| name.as_str() != BODY_FIELD && !SKIPPED_FIELDS.contains(&name.as_str()))
+        .collect();
+    names.sort_by_key(|name| (LEADING_FIELDS.iter().position(|leading| leading == name).unwrap_or(LEADING_FIELDS.len()), name.as_str()));
+
+    let mut front_matter = serde_yaml::Mapping::new();
+    front_matter.insert("id".into(), row.id.into());
+    for name in names {
+        if let Some(value) = field_value(&row.fields[name]) {
+            front_matter.insert(name.as_str().into(), value);
+        }
+    }
+    let yaml = serde_yaml::to_string(&front_matter).unwrap_or_default();
+
+    let mut text = format!("---\n{}---\n", yaml);
+    if let Some(synopsis) = row.get_text(BODY_FIELD) {
+        text.push('\n');
+        text.push_str(synopsis.trim());
+        text.push('\n');
+    }
+    text
+}
+
+/// A template key for a field name: lowercase ASCII with underscores, so
+/// "Media Type" can be written `media_type` as well as `book["Media Type"]`.
+pub fn field_key(name: &str) -> String {
+    let key: String = deunicode::deunicode(name).to_lowercase().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    key.split('_').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("_")
+}
+
+/// A row as a template sees it: `id`, then every non-empty field under its
+/// Baserow name and under its `field_key`, with values as in the front matter
+/// of `render`.
+pub fn template_book(row: &MediaRow) -> BTreeMap<String, serde_yaml::Value> {
+    let mut book = BTreeMap::new();
+    for (name, value) in &row.fields {
+        if SKIPPED_FIELDS.contains(&name.as_str()) {
+            continue;
+        }
+        if let Some(value) = field_value(value) {
+            book.insert(field_key(name), value.clone());
+            book.insert(name.clone(), value);
+        }
+    }
+    book.insert("id".to_string(), row.id.into());
+    book
+}
+
+/// Renders a MiniJinja template with every row in `books`, in table order, and
+/// their number in `count`, e.g. for a shelf sign or a page of a website.
+/// `name` is only used in error messages.
+pub fn render_template(name: &str, source: &str, rows: &[MediaRow]) -> Result<String, minijinja::Error> {
+    let mut env = minijinja::Environment::new();
+    env.add_template(name, source)?;
+    let books: Vec<BTreeMap<String, serde_yaml::Value>> = rows.iter().map(template_book).collect();
+    env.get_template(name)?.render(minijinja::context! { books => books, count => rows.len() })
+}
+
+/// Writes one Markdown file per row into `dir`, leaving files whose content has
+/// not changed untouched and removing the files of rows that are gone, so
+/// committing the directory records exactly what changed in the library.
+pub fn write_tree(rows: &[MediaRow], dir: &Path) -> std::io::Result<ExportSummary> {
+    std::fs::create_dir_all(dir)?;
+    let mut summary = ExportSummary::default();
+    let mut kept = HashSet::new();
+    for row in rows {
+        let name = file_name(row);
+        let path = dir.join(&name);
+        let text = render(row);
+        if std::fs::read_to_string(&path).is_ok_and(|existing| existing == text) {
+            summary.unchanged += 1;
+        } else {
+            std::fs::write(&path, text)?;
+            summary.written += 1;
+        }
+        kept.insert(name);
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let stale = path.file_name().and_then(|name| name.to_str())
+            .is_some_and(|name| is_exported_name(name) && !kept.contains(name));
+        if stale && path.is_file() {
+            std::fs::remove_file(&path)?;
+            summary.removed += 1;
+        }
+    }
+    Ok(summary)
+}
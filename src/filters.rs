@@ -0,0 +1,109 @@
+use crate::book_search::BookResult;
+
+/// Two- and three-letter codes of the languages the filters understand. Google
+/// Books uses ISO 639-1 codes and Open Library MARC codes.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "eng"),
+    ("th", "tha"),
+    ("fr", "fre"),
+    ("de", "ger"),
+    ("es", "spa"),
+    ("it", "ita"),
+    ("pt", "por"),
+    ("nl", "dut"),
+    ("ru", "rus"),
+    ("ja", "jpn"),
+    ("zh", "chi"),
+    ("ko", "kor"),
+    ("vi", "vie"),
+    ("la", "lat"),
+    ("el", "gre"),
+];
+
+/// A language code as ISO 639-1 when it is a MARC code the filters know,
+/// otherwise lowercased as it is.
+pub fn iso_code(code: &str) -> String {
+    let code = code.trim().to_lowercase();
+    LANGUAGES.iter()
+        .find(|(_, marc)| *marc == code)
+        .map(|(iso, _)| iso.to_string())
+        .unwrap_or(code)
+}
+
+/// Narrows title and series searches, for titles with dozens of editions. Each
+/// filter is sent to the APIs where they support it and checked on every result
+/// either way; a result that does not say its year, publisher, or language is
+/// dropped when filtering on it.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    pub year: Option<u32>,
+    pub publisher: Option<String>,
+    /// ISO 639-1 or MARC code, e.g. `en` or `eng`
+    pub lang: Option<String>,
+}
+
+impl SearchFilters {
+    pub fn is_empty(&self) -> bool {
+        self.year.is_none() && self.publisher.is_none() && self.lang.is_none()
+    }
+
+    /// The language as a two-letter code, for Google Books' `langRestrict`.
+    pub fn lang_iso(&self) -> Option<String> {
+        self.lang.as_deref().map(iso_code)
+    }
+
+    /// The language as a MARC code, for Open Library's `language`.
+    pub fn lang_marc(&self) -> Option<String> {
+        let lang = self.lang.as_deref()?.to_lowercase();
+        Some(LANGUAGES.iter()
+            .find(|(iso, _)| *iso == lang)
+            .map(|(_, marc)| marc.to_string())
+            .unwrap_or(lang))
+    }
+
+    pub fn matches(&self, book: &BookResult) -> bool {
+        if let Some(year) = self.year {
+            let year = year.to_string();
+            let same_year = match book {
+                BookResult::Google(book) => book.volume_info.published_date.as_deref()
+                    .is_some_and(|date| date.starts_with(&year)),
+                BookResult::OpenLibrary(book) => book.publish_year.iter().flatten()
+                    .chain(book.first_publish_year.iter())
+                    .any(|published| published.to_string() == year),
+                BookResult::Manual(_) => true,
+            };
+            if !same_year {
+                return false;
+            }
+        }
+
+        if let Some(publisher) = &self.publisher {
+            let publisher = publisher.to_lowercase();
+            let same_publisher = match book {
+                BookResult::Google(book) => book.volume_info.publisher.iter()
+                    .any(|name| name.to_lowercase().contains(&publisher)),
+                BookResult::OpenLibrary(book) => book.publisher.iter().flatten()
+                    .any(|name| name.to_lowercase().contains(&publisher)),
+                BookResult::Manual(_) => true,
+            };
+            if !same_publisher {
+                return false;
+            }
+        }
+
+        if let (Some(iso), Some(marc)) = (self.lang_iso(), self.lang_marc()) {
+            let same_lang = match book {
+                BookResult::Google(book) => book.volume_info.language.as_deref()
+                    .is_some_and(|lang| lang.eq_ignore_ascii_case(&iso)),
+                BookResult::OpenLibrary(book) => book.language.iter().flatten()
+                    .any(|lang| lang.eq_ignore_ascii_case(&marc)),
+                BookResult::Manual(_) => true,
+            };
+            if !same_lang {
+                return false;
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::dates::{day_of_year, is_leap_year};
+
+const BAR_WIDTH: usize = 30;
+
+/// Yearly reading targets, kept in a small JSON file (`app.goals_file`) as
+/// `{"2025": 52}`. A missing file has no goals.
+pub fn load(path: &Path) -> Result<BTreeMap<u32, u32>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let goals = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{} is not a goals file: {}", path.display(), e))?;
+    Ok(goals)
+}
+
+/// Sets the target for `year`, replacing any earlier one.
+pub fn set(path: &Path, year: u32, target: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let mut goals = load(path)?;
+    goals.insert(year, target);
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&goals)?)?;
+    Ok(())
+}
+
+/// How far the library is towards a year's target.
+#[derive(Debug, Serialize)]
+pub struct GoalProgress {
+    pub year: u32,
+    pub target: u32,
+    /// Rows marked read with a read date in the year
+    pub read: u32,
+    /// Rows marked read without a read date, which count towards no year
+    pub undated: u32,
+    /// Books that should be read by today to stay on pace; only for the current year
+    pub expected: Option<u32>,
+}
+
+/// Counts the rows marked read whose `read_at_field` date falls in `year`.
+/// `today` is a `YYYY-MM-DD` date, used for the pace of the current year.
+pub fn progress(rows: &[MediaRow], read_at_field: &str, year: u32, target: u32, today: &str) -> GoalProgress {
+    let prefix = format!("{}-", year);
+    let mut read = 0;
+    let mut undated = 0;
+    for row in rows.iter().filter(|row| row.fields.get("Read").and_then(|v| v.as_bool()).unwrap_or(false)) {
+        match row.get_text(read_at_field).filter(|date| !date.is_empty()) {
+            Some(date) if date.starts_with(&prefix) => read += 1,
+            Some(_) => {}
+            None => undated += 1,
+        }
+    }
+
+    let expected = today.starts_with(&prefix).then(|| {
+        let days = if is_leap_year(year) { 366 } else { 365 };
+        (target as u64 * day_of_year(today) as u64 / days) as u32
+    });
+    GoalProgress { year, target, read, undated, expected }
+}
+
+pub fn format_progress(progress: &GoalProgress) -> String {
+    let ratio = if progress.target == 0 { 1.0 } else { (progress.read as f64 / progress.target as f64).min(1.0) };
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    let mut lines = vec![
+        t!("goal.heading", year = progress.year).into_owned(),
+        format!("[{}{}] {}/{} ({:.0}%)",
+            "#".repeat(filled), "-".repeat(BAR_WIDTH - filled), progress.read, progress.target, ratio * 100.0),
+    ];
+
+    if progress.read >= progress.target {
+        lines.push(t!("goal.reached").into_owned());
+    } else if let Some(expected) = progress.expected {
+        if progress.read >= expected {
+            lines.push(t!("goal.on_track", ahead = progress.read - expected).into_owned());
+        } else {
+            lines.push(t!("goal.behind", behind = expected - progress.read).into_owned());
+        }
+    }
+    if progress.undated > 0 {
+        lines.push(t!("goal.undated", count = progress.undated).into_owned());
+    }
+    lines.join("\n")
+}
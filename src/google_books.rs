@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use dialoguer::{Select, theme::ColorfulTheme};
 use crate::config::Config;
+use crate::filters::SearchFilters;
+use crate::http_cache::{self, HttpCache};
+use crate::ui::{self, Role};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct GoogleBooksResponse {
@@ -62,6 +65,15 @@ pub struct VolumeInfo {
     pub info_link: Option<String>,
     #[serde(rename = "canonicalVolumeLink")]
     pub canonical_volume_link: Option<String>,
+    #[serde(rename = "seriesInfo")]
+    pub series_info: Option<SeriesInfo>,
+}
+
+/// Where a volume sits in its series, when Google Books knows.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SeriesInfo {
+    #[serde(rename = "bookDisplayNumber")]
+    pub book_display_number: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -104,6 +116,8 @@ pub struct GoogleBooksClient {
     client: reqwest::Client,
     api_key: String,
     base_url: String,
+    filters: SearchFilters,
+    cache: Option<HttpCache>,
 }
 
 impl GoogleBooksClient {
@@ -112,9 +126,29 @@ impl GoogleBooksClient {
             client: reqwest::Client::new(),
             api_key,
             base_url,
+            filters: SearchFilters::default(),
+            cache: None,
         }
     }
 
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Restricts title and series searches to a publisher and language.
+    pub fn with_filters(mut self, filters: SearchFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Keeps responses in `cache` and revalidates them instead of fetching them again.
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     pub async fn search_by_isbn(&self, isbn: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
         let url = if self.api_key.contains("your_") || self.api_key.is_empty() {
             // Try without API key for basic usage
@@ -123,21 +157,9 @@ impl GoogleBooksClient {
             format!("{}/volumes?q=isbn:{}&key={}", self.base_url, isbn, self.api_key)
         };
 
-        println!("Making request to: {}", url.replace(&self.api_key, "***"));
-
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        ui::detail(format!("Making request to: {}", url.replace(&self.api_key, "***")));
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
-            return Err(format!("Google Books API error: {} - {}", status, error_text).into());
-        }
-
-        let books_response: GoogleBooksResponse = response.json().await?;
-        Ok(books_response)
+        self.get_volumes(&url).await
     }
 
     pub async fn search_by_title_author(
@@ -145,60 +167,79 @@ impl GoogleBooksClient {
         title: &str,
         author: &str,
     ) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
-        let query = format!("intitle:\"{}\" inauthor:\"{}\"", title, author);
-        let url = if self.api_key.contains("your_") || self.api_key.is_empty() {
+        self.search_volumes(&format!("intitle:\"{}\" inauthor:\"{}\"", title, author)).await
+    }
+
+    pub async fn search_by_title(&self, title: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        self.search_volumes(&format!("intitle:\"{}\"", title)).await
+    }
+
+    /// Searches for the words anywhere in a volume, without the `intitle:` syntax.
+    pub async fn search_by_keywords(&self, keywords: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        self.search_volumes(keywords).await
+    }
+
+    /// Searches for volumes filed under a subject, as `wcm similar` does to find
+    /// related books.
+    pub async fn search_by_subject(&self, subject: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        self.search_volumes(&format!("subject:\"{}\"", subject)).await
+    }
+
+    pub async fn search_by_author(&self, author: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        self.search_volumes(&format!("inauthor:\"{}\"", author)).await
+    }
+
+    /// Searches for volumes of a series, asking for the most results a page allows
+    /// since a series can be long.
+    pub async fn search_by_series(&self, series: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        self.search_volumes_page(&format!("\"{}\"", series), Some(40)).await
+    }
+
+    async fn search_volumes(&self, query: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        self.search_volumes_page(query, None).await
+    }
+
+    async fn search_volumes_page(&self, query: &str, max_results: Option<u32>) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        let query = match &self.filters.publisher {
+            Some(publisher) => format!("{} inpublisher:\"{}\"", query, publisher),
+            None => query.to_string(),
+        };
+        let query = query.as_str();
+        let mut url = if self.api_key.contains("your_") || self.api_key.is_empty() {
             format!(
                 "{}/volumes?q={}",
                 self.base_url,
-                urlencoding::encode(&query)
+                urlencoding::encode(query)
             )
         } else {
             format!(
                 "{}/volumes?q={}&key={}",
                 self.base_url,
-                urlencoding::encode(&query),
+                urlencoding::encode(query),
                 self.api_key
             )
         };
-
-        println!("Making request to: {}", url.replace(&self.api_key, "***"));
-
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
-            return Err(format!("Google Books API error: {} - {}", status, error_text).into());
+        if let Some(max_results) = max_results {
+            url.push_str(&format!("&maxResults={}", max_results));
+        }
+        if let Some(lang) = self.filters.lang_iso() {
+            url.push_str(&format!("&langRestrict={}", urlencoding::encode(&lang)));
         }
 
-        let books_response: GoogleBooksResponse = response.json().await?;
-        Ok(books_response)
+        ui::detail(format!("Making request to: {}", url.replace(&self.api_key, "***")));
+
+        self.get_volumes(&url).await
     }
 
-    #[allow(dead_code)]
-    pub async fn search_by_title(&self, title: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
-        let query = format!("intitle:{}", title);
-        let url = format!(
-            "{}/volumes?q={}&key={}",
-            self.base_url,
-            urlencoding::encode(&query),
-            self.api_key
-        );
-
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(format!("Google Books API error: {}", response.status()).into());
+    /// GETs `url` through the cache and parses the volume list.
+    async fn get_volumes(&self, url: &str) -> Result<GoogleBooksResponse, Box<dyn std::error::Error>> {
+        let response = http_cache::get(&self.client, self.cache.as_ref(), url).await?;
+
+        if !response.status.is_success() {
+            return Err(format!("Google Books API error: {} - {}", response.status, response.body).into());
         }
 
-        let books_response: GoogleBooksResponse = response.json().await?;
-        Ok(books_response)
+        Ok(serde_json::from_str(&response.body)?)
     }
 }
 
@@ -248,48 +289,46 @@ impl BookItem {
 }
 
 pub fn display_google_book_info(book: &BookItem, _config: &Config) {
-    println!("\n=== Book Information (Google Books) ===");
-    println!("Title: {}", book.get_full_title());
-    println!("Author(s): {}", book.get_all_authors());
+    let mut rows = Vec::new();
+    rows.push(("Title".to_string(), book.get_full_title()));
+    rows.push(("Author(s)".to_string(), book.get_all_authors()));
     
     if let Some(publisher) = &book.volume_info.publisher {
-        println!("Publisher: {}", publisher);
+        rows.push(("Publisher".to_string(), publisher.to_string()));
     }
     
     if let Some(date) = &book.volume_info.published_date {
-        println!("Published: {}", date);
+        rows.push(("Published".to_string(), date.to_string()));
     }
     
     if let Some(page_count) = book.volume_info.page_count {
-        println!("Pages: {}", page_count);
+        rows.push(("Pages".to_string(), page_count.to_string()));
     }
     
     if let Some(isbn13) = book.get_isbn_13() {
-        println!("ISBN-13: {}", isbn13);
+        rows.push(("ISBN-13".to_string(), isbn13));
     }
     
     if let Some(isbn10) = book.get_isbn_10() {
-        println!("ISBN-10: {}", isbn10);
+        rows.push(("ISBN-10".to_string(), isbn10));
     }
     
     if let Some(description) = &book.volume_info.description {
-        let desc = if description.len() > 1000 {
-            format!("{}...", &description[..1000])
-        } else {
-            description.clone()
-        };
-        println!("Description: {}", desc);
+        rows.push(("Description".to_string(), crate::text::truncate(&crate::text::strip_html(description), 1000)));
     }
     
     if let Some(cover_url) = book.get_best_cover_image() {
-        println!("Cover Image: {}", cover_url);
+        rows.push(("Cover Image".to_string(), cover_url));
     }
     
     if let Some(categories) = &book.volume_info.categories {
-        println!("Categories: {}", categories.join(", "));
+        rows.push(("Categories".to_string(), categories.join(", ")));
     }
     
-    println!("========================================\n");
+    ui::info("");
+    ui::info(ui::paint(Role::Heading, "Book Information (Google Books)"));
+    ui::info(ui::fields(&rows));
+    ui::info("");
 }
 
 #[allow(dead_code)]
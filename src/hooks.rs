@@ -0,0 +1,98 @@
+use std::fmt;
+use std::process::Stdio;
+use std::time::Duration;
+use rust_i18n::t;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use crate::baserow::{CreatedEntry, MediaEntry};
+use crate::config::HooksConfig;
+use crate::ui;
+
+#[derive(Debug)]
+pub enum HookError {
+    /// The shell could not be started
+    Spawn(std::io::Error),
+    /// The command exited with a non-zero status
+    Failed { code: Option<i32>, output: String },
+    TimedOut(Duration),
+}
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HookError::Spawn(e) => write!(f, "could not run the hook: {}", e),
+            HookError::Failed { code: Some(code), output } if !output.is_empty() => write!(f, "the hook exited with {}: {}", code, output),
+            HookError::Failed { code: Some(code), .. } => write!(f, "the hook exited with {}", code),
+            HookError::Failed { code: None, .. } => write!(f, "the hook was killed by a signal"),
+            HookError::TimedOut(timeout) => write!(f, "the hook did not finish within {}s", timeout.as_secs()),
+        }
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Runs `command` through the shell with `input` as JSON on stdin and the hook's
+/// name in `WCM_HOOK`, and returns what it printed on stdout. Its stderr goes
+/// straight to wcm's, so a hook can report progress; a hook still running after
+/// `timeout` is killed.
+pub async fn run(hook: &str, command: &str, input: &impl Serialize, env: &[(&str, String)], timeout: Duration) -> Result<String, HookError> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .env("WCM_HOOK", hook)
+        .envs(env.iter().map(|(name, value)| (*name, value.as_str())))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(HookError::Spawn)?;
+
+    let json = serde_json::to_vec(input).unwrap_or_default();
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input may exit before reading it all
+        let _ = stdin.write_all(&json).await;
+    }
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output.map_err(HookError::Spawn)?,
+        Err(_) => return Err(HookError::TimedOut(timeout)),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        Err(HookError::Failed { code: output.status.code(), output: stdout })
+    }
+}
+
+/// Runs `hooks.pre_add` with the entry about to be created. An error means the
+/// entry must not be created.
+pub async fn before_add(config: &HooksConfig, entry: &MediaEntry) -> Result<(), HookError> {
+    let Some(command) = config.pre_add.as_deref().filter(|command| !command.trim().is_empty()) else {
+        return Ok(());
+    };
+    ui::detail(format!("Running the pre-add hook: {}", command));
+    let output = run("pre_add", command, entry, &[], Duration::from_secs(config.timeout_secs)).await?;
+    if !output.is_empty() {
+        ui::detail(output);
+    }
+    Ok(())
+}
+
+/// Runs `hooks.post_add` with the row Baserow created. The entry already
+/// exists, so a failure is only a warning.
+pub async fn after_add(config: &HooksConfig, created: &CreatedEntry) {
+    let Some(command) = config.post_add.as_deref().filter(|command| !command.trim().is_empty()) else {
+        return;
+    };
+    ui::detail(format!("Running the post-add hook: {}", command));
+    let env = [("WCM_ROW_ID", created.id.to_string())];
+    match run("post_add", command, created, &env, Duration::from_secs(config.timeout_secs)).await {
+        Ok(output) if !output.is_empty() => ui::detail(output),
+        Ok(_) => {}
+        Err(e) => ui::warn(t!("hooks.post_add_failed", id = created.id, error = e.to_string())),
+    }
+}
@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::time::Duration;
+use reqwest::{Certificate, ClientBuilder};
+use crate::config::HttpConfig;
+
+/// Builds the `reqwest::Client` every API client shares, so connections to the
+/// same host are pooled across lookups and the timeouts, user agent, proxy, and
+/// CA bundle in `http` apply to every request.
+pub fn build_client(config: &HttpConfig) -> Result<reqwest::Client, Box<dyn Error>> {
+    Ok(builder(config)?.build()?)
+}
+
+/// Like `build_client`, but accepting any certificate, including self-signed and
+/// expired ones. Only for the self-hosted Baserow and Ollama servers, and only when
+/// their `accept_invalid_certs` option asks for it.
+pub fn build_client_accepting_invalid_certs(config: &HttpConfig) -> Result<reqwest::Client, Box<dyn Error>> {
+    Ok(builder(config)?.danger_accept_invalid_certs(true).build()?)
+}
+
+fn builder(config: &HttpConfig) -> Result<ClientBuilder, Box<dyn Error>> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .user_agent(config.user_agent.as_str());
+
+    if let Some(proxy) = config.proxy.as_deref().filter(|proxy| !proxy.is_empty()) {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    // Trusted in addition to the system roots, for servers behind an internal CA
+    if let Some(path) = config.ca_bundle.as_deref().filter(|path| !path.is_empty()) {
+        let pem = std::fs::read(path).map_err(|e| format!("cannot read http.ca_bundle {}: {}", path, e))?;
+        let certificates = Certificate::from_pem_bundle(&pem)?;
+        if certificates.is_empty() {
+            return Err(format!("http.ca_bundle {} contains no PEM certificates", path).into());
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use reqwest::header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use crate::config::AppConfig;
+use crate::rate_limit::SendWithRetry;
+use crate::ui;
+use rust_i18n::t;
+
+/// A stored response with the validators the server sent along with it.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+/// The status and body of a GET, read from the network or from the cache.
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+/// Metadata responses kept on disk, one JSON file per URL. Stored responses are
+/// always revalidated with `If-None-Match`/`If-Modified-Since`, so a lookup still
+/// costs a request, but an unchanged response comes back as an empty 304 that
+/// most APIs do not count against the quota. Offline, stored responses are
+/// returned as they are and nothing is requested.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+    offline: bool,
+}
+
+impl HttpCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), offline: false }
+    }
+
+    /// Serves every lookup from the stored responses, for `wcm add --offline`.
+    /// A URL that was never stored fails at once instead of being requested.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// The cache in `app.cache_dir`, or none when it is set to an empty string.
+    pub fn from_config(app: &AppConfig) -> Option<Self> {
+        (!app.cache_dir.is_empty()).then(|| Self::new(&app.cache_dir))
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", stable_hash(&redact(url))))
+    }
+
+    fn load(&self, url: &str) -> Option<Entry> {
+        let entry: Entry = serde_json::from_str(&std::fs::read_to_string(self.path_for(url)).ok()?).ok()?;
+        // A hash collision would otherwise serve another URL's response
+        (entry.url == redact(url)).then_some(entry)
+    }
+
+    fn store(&self, entry: &Entry) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path_for(&entry.url), serde_json::to_string(entry)?)
+    }
+}
+
+/// A 64-bit FNV-1a hash of `text`, for file names that must stay the same from
+/// one build of wcm to the next, which `DefaultHasher` does not promise.
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+/// GETs `url`, through `cache` when there is one: a stored copy is sent for
+/// revalidation and returned on 304 Not Modified, and a new successful response
+/// replaces it. An offline cache only returns the stored copy.
+pub async fn get(client: &reqwest::Client, cache: Option<&HttpCache>, url: &str) -> Result<CachedResponse, Box<dyn std::error::Error>> {
+    let cached = cache.and_then(|cache| cache.load(url));
+    if cache.is_some_and(|cache| cache.offline) {
+        let entry = cached.ok_or_else(|| t!("offline.not_cached", url = redact(url)).into_owned())?;
+        crate::metrics::record_cache(true);
+        ui::detail("Offline, using the cached response");
+        return Ok(CachedResponse { status: StatusCode::OK, body: entry.body });
+    }
+
+    let mut request = client.get(url);
+    if let Some(entry) = &cached {
+        if let Some(etag) = &entry.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send_with_retry().await?;
+    let status = response.status();
+    if cache.is_some() && status != StatusCode::NOT_MODIFIED {
+        crate::metrics::record_cache(false);
+    }
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = cached {
+            crate::metrics::record_cache(true);
+            ui::detail("Not modified since the last lookup, using the cached response");
+            return Ok(CachedResponse { status: StatusCode::OK, body: entry.body });
+        }
+    }
+
+    let header = |name: HeaderName| response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    let body = response.text().await?;
+
+    if let Some(cache) = cache {
+        // Kept even without validators, so an offline add can use it
+        if status.is_success() {
+            let entry = Entry { url: redact(url), etag, last_modified, body };
+            if let Err(e) = cache.store(&entry) {
+                ui::detail(format!("Could not write to the cache: {}", e));
+            }
+            return Ok(CachedResponse { status, body: entry.body });
+        }
+    }
+    Ok(CachedResponse { status, body })
+}
+
+/// The URL without its API key, so the key is not written to disk and changing
+/// it keeps the cache.
+fn redact(url: &str) -> String {
+    match url.split_once('?') {
+        Some((base, query)) => {
+            let params: Vec<&str> = query.split('&').filter(|param| !param.starts_with("key=")).collect();
+            format!("{}?{}", base, params.join("&"))
+        }
+        None => url.to_string(),
+    }
+}
@@ -0,0 +1,26 @@
+/// Languages with a translation in `locales/`.
+pub const LANGUAGES: &[&str] = &["en", "th"];
+
+/// Sets the language prompts, confirmations and errors are shown in for the rest
+/// of the run.
+pub fn init(configured: Option<&str>) {
+    rust_i18n::set_locale(resolve(configured));
+}
+
+/// Picks `app.language` when set, otherwise the system locale from `LC_ALL`,
+/// `LC_MESSAGES` or `LANG` (so `th_TH.UTF-8` selects Thai), and English when
+/// there is no translation for either.
+pub fn resolve(configured: Option<&str>) -> &'static str {
+    let requested = configured.map(str::to_string).or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+    });
+
+    requested
+        .and_then(|locale| {
+            let language = locale.split(['_', '-', '.', '@']).next().unwrap_or_default().to_lowercase();
+            LANGUAGES.iter().find(|supported| **supported == language).copied()
+        })
+        .unwrap_or("en")
+}
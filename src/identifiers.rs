@@ -0,0 +1,88 @@
+/// A catalogue number other than an ISBN that a book can be looked up by, for
+/// older books printed before ISBNs or without one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogueId {
+    /// Library of Congress Control Number, normalized (see `normalize_lccn`)
+    Lccn(String),
+    /// OCLC (WorldCat) number, digits only
+    Oclc(String),
+}
+
+impl std::fmt::Display for CatalogueId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CatalogueId::Lccn(lccn) => write!(f, "LCCN {}", lccn),
+            CatalogueId::Oclc(oclc) => write!(f, "OCLC {}", oclc),
+        }
+    }
+}
+
+/// Normalizes an LCCN the way the Library of Congress does: spaces removed, letters
+/// lowercased, and a hyphenated serial padded to six digits, so "n 78-890351" and
+/// "78-5" become "n78890351" and "78000005". Returns `None` when there are no digits.
+pub fn normalize_lccn(lccn: &str) -> Option<String> {
+    let compact: String = lccn.split('/').next().unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let normalized = match compact.split_once('-') {
+        Some((prefix, serial)) if serial.chars().all(|c| c.is_ascii_digit()) => format!("{}{:0>6}", prefix, serial),
+        _ => compact,
+    };
+    let digits = normalized.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then_some(normalized)
+}
+
+/// Strips the prefixes OCLC numbers are often written with ("ocm", "ocn", "on",
+/// "(OCoLC)") and leading zeros. Returns `None` when no digits are left.
+pub fn normalize_oclc(oclc: &str) -> Option<String> {
+    let oclc = oclc.trim();
+    let oclc = oclc.strip_prefix("(OCoLC)").unwrap_or(oclc);
+    let digits = oclc.trim_start_matches(|c: char| c.is_ascii_alphabetic()).trim_start_matches('0');
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then(|| digits.to_string())
+}
+
+/// Uppercases an Amazon ASIN and checks it is ten letters and digits. Returns
+/// `None` for anything else.
+pub fn normalize_asin(asin: &str) -> Option<String> {
+    let asin = asin.trim().to_uppercase();
+    (asin.len() == 10 && asin.chars().all(|c| c.is_ascii_alphanumeric())).then_some(asin)
+}
+
+/// The ISBN-10 an ASIN is, when it is one: Amazon uses the ISBN-10 as the ASIN of
+/// printed books, while Kindle editions get a `B0...` ASIN of their own.
+pub fn asin_as_isbn(asin: &str) -> Option<String> {
+    let chars: Vec<char> = asin.chars().collect();
+    if chars.len() != 10 || !chars[..9].iter().all(char::is_ascii_digit) {
+        return None;
+    }
+    let sum: u32 = chars[..9].iter().zip((2..=10).rev()).map(|(c, weight)| c.to_digit(10).unwrap_or(0) * weight).sum();
+    let check = match (11 - sum % 11) % 11 {
+        10 => 'X',
+        digit => char::from_digit(digit, 10)?,
+    };
+    (chars[9].to_ascii_uppercase() == check).then(|| asin.to_uppercase())
+}
+
+/// Whether `isbn` is an ISBN-10 or ISBN-13 with the right check digit. Hyphens
+/// and spaces are ignored.
+pub fn is_valid_isbn(isbn: &str) -> bool {
+    let chars: Vec<char> = isbn.chars().filter(|c| *c != '-' && !c.is_whitespace()).collect();
+    let digit = |c: &char| c.to_digit(10);
+    match chars.len() {
+        10 => {
+            let check = match chars[9] {
+                'X' | 'x' => Some(10),
+                c => digit(&c),
+            };
+            let digits: Option<Vec<u32>> = chars[..9].iter().map(digit).chain(std::iter::once(check)).collect();
+            digits.is_some_and(|digits| digits.iter().zip((1..=10).rev()).map(|(d, weight)| d * weight).sum::<u32>() % 11 == 0)
+        }
+        13 => {
+            let digits: Option<Vec<u32>> = chars.iter().map(digit).collect();
+            digits.is_some_and(|digits| digits.iter().enumerate().map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 }).sum::<u32>() % 10 == 0)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,505 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use rust_i18n::t;
+use crate::baserow::MediaRow;
+use crate::book_search::{AddedBook, CombinedBookSearcher};
+use crate::duplicates::normalize_isbn;
+use crate::error::EXIT_CANCELLED;
+use crate::ui::{self, Role};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Added,
+    /// Added by an earlier run, according to the checkpoint
+    AlreadyAdded,
+    /// Not added because the library seems to have the book already
+    Duplicate,
+    /// Neither Google Books nor Open Library knows the ISBN
+    NotFound,
+    Failed,
+    /// Not started because the import was interrupted
+    Interrupted,
+}
+
+/// One book to import: an ISBN from a list or a CSV row, or the title and author
+/// of a CSV row without an ISBN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportEntry {
+    Isbn(String),
+    TitleAuthor { title: String, author: String },
+}
+
+impl std::fmt::Display for ImportEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportEntry::Isbn(isbn) => write!(f, "{}", isbn),
+            ImportEntry::TitleAuthor { title, author } => write!(f, "{} / {}", title, author),
+        }
+    }
+}
+
+/// What happened to one entry of an import.
+#[derive(Debug, Serialize)]
+pub struct ImportItem {
+    /// The ISBN, or `title / author` for rows searched by title
+    pub entry: String,
+    pub status: ImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Library rows a skipped duplicate matched
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_of: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportItem {
+    fn new(entry: String, status: ImportStatus) -> Self {
+        Self { entry, status, id: None, title: None, duplicate_of: Vec::new(), error: None }
+    }
+
+    pub fn is_added(&self) -> bool {
+        matches!(self.status, ImportStatus::Added | ImportStatus::AlreadyAdded)
+    }
+}
+
+/// One book the checkpoint records as added. Books are matched by entry and by
+/// which occurrence of that entry in the list they are, so editing other lines of
+/// the list between runs does not shift them.
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointRecord {
+    #[serde(alias = "isbn")]
+    entry: String,
+    occurrence: usize,
+    id: u64,
+    title: String,
+}
+
+/// The books an import has added so far, kept as one JSON line per book next to
+/// the import file so an interrupted import can be resumed without adding them twice.
+pub struct Checkpoint {
+    path: PathBuf,
+    added: HashMap<(String, usize), CheckpointRecord>,
+    file: std::fs::File,
+}
+
+impl Checkpoint {
+    /// `isbns.txt` is checkpointed in `isbns.txt.checkpoint`.
+    pub fn path_for(input: &Path) -> PathBuf {
+        let mut name = input.as_os_str().to_owned();
+        name.push(".checkpoint");
+        PathBuf::from(name)
+    }
+
+    /// Opens the checkpoint at `path`, reading what an earlier run recorded when
+    /// `resume` is set and starting a new one otherwise.
+    pub fn open(path: PathBuf, resume: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut added = HashMap::new();
+        if resume && path.exists() {
+            // A line cut short by a crash is skipped; that book is simply tried again
+            for line in std::fs::read_to_string(&path)?.lines() {
+                if let Ok(record) = serde_json::from_str::<CheckpointRecord>(line) {
+                    added.insert((record.entry.clone(), record.occurrence), record);
+                }
+            }
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(resume)
+            .write(true)
+            .truncate(!resume)
+            .open(&path)?;
+        Ok(Self { path, added, file })
+    }
+
+    fn record(&mut self, record: CheckpointRecord) -> std::io::Result<()> {
+        writeln!(self.file, "{}", serde_json::to_string(&record)?)?;
+        self.file.sync_data()?;
+        self.added.insert((record.entry.clone(), record.occurrence), record);
+        Ok(())
+    }
+
+    /// Deletes the checkpoint once every book has been added.
+    pub fn remove(self) -> std::io::Result<()> {
+        drop(self.file);
+        std::fs::remove_file(&self.path)
+    }
+}
+
+/// Reads the ISBNs to import, one per line. Blank lines and lines starting with
+/// `#` are skipped, and separators such as hyphens are removed.
+pub fn read_isbns(path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let isbns = std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.chars().filter(|c| c.is_ascii_alphanumeric()).collect())
+        .collect();
+    Ok(isbns)
+}
+
+/// Which CSV columns hold the fields an import uses. Each field defaults to the
+/// column of the same name; headers are matched ignoring case and surrounding spaces.
+#[derive(Debug, Clone)]
+pub struct ColumnMap {
+    isbn: String,
+    title: String,
+    author: String,
+}
+
+impl Default for ColumnMap {
+    fn default() -> Self {
+        Self { isbn: "isbn".to_string(), title: "title".to_string(), author: "author".to_string() }
+    }
+}
+
+impl ColumnMap {
+    /// Parses a mapping such as `isbn=Barcode,title=Name`. Fields left out keep
+    /// their default column.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut map = Self::default();
+        for pair in spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let Some((field, column)) = pair.split_once('=') else {
+                return Err(t!("import.map_invalid", pair = pair).into_owned());
+            };
+            let column = column.trim().to_string();
+            match field.trim().to_lowercase().as_str() {
+                "isbn" => map.isbn = column,
+                "title" => map.title = column,
+                "author" => map.author = column,
+                other => return Err(t!("import.map_unknown_field", field = other).into_owned()),
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Reads the books to import from a CSV file with a header row. Rows with an ISBN
+/// are looked up by it, rows without one by title and author; rows with neither
+/// are skipped with a warning.
+pub fn read_csv(path: &Path, map: &ColumnMap) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let find = |column: &str| headers.iter().position(|header| header.eq_ignore_ascii_case(column.trim()));
+    let isbn_column = find(&map.isbn);
+    let title_columns = find(&map.title).zip(find(&map.author));
+    if isbn_column.is_none() && title_columns.is_none() {
+        return Err(t!("import.columns_missing",
+            isbn = map.isbn, title = map.title, author = map.author,
+            columns = headers.iter().collect::<Vec<&str>>().join(", ")).into());
+    }
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let cell = |column: Option<usize>| column.and_then(|column| record.get(column)).unwrap_or_default();
+
+        // Exports often decorate ISBNs, e.g. LibraryThing's "[0345391802]"
+        let isbn: String = cell(isbn_column).chars().filter(|c| c.is_ascii_digit() || *c == 'X' || *c == 'x').collect();
+        let title = cell(title_columns.map(|(title, _)| title));
+        let author = cell(title_columns.map(|(_, author)| author));
+        if !isbn.is_empty() {
+            entries.push(ImportEntry::Isbn(isbn));
+        } else if !title.is_empty() && !author.is_empty() {
+            entries.push(ImportEntry::TitleAuthor { title: title.to_string(), author: author.to_string() });
+        } else {
+            let line = record.position().map(|position| position.line()).unwrap_or_default();
+            ui::warn(t!("import.row_skipped", line = line));
+        }
+    }
+    Ok(entries)
+}
+
+/// How one book of an import ended, before it is turned into an `ImportItem`.
+enum Outcome {
+    Added(AddedBook),
+    Duplicate(String, Vec<u64>),
+    NotFound,
+    /// The error is kept as text so the task's output can cross threads
+    Failed(String),
+}
+
+/// ISBNs of the books the run has added or is adding, with the row each became
+/// once it is created. `resolved` wakes the jobs waiting on a book in progress.
+#[derive(Default)]
+struct Claims {
+    ids: Mutex<HashMap<String, Option<u64>>>,
+    resolved: tokio::sync::Notify,
+}
+
+type Claimed = Arc<Claims>;
+
+/// Adds every entry without prompting, running at most `jobs` books at once.
+/// The best match is taken for each entry, as in the HTTP server, and books that
+/// match a row of `library`, or have the ISBN of a book the run already added,
+/// are skipped; pass `None` to add them anyway. Books
+/// the checkpoint already lists are skipped and each newly added book is recorded
+/// in it. Results are printed as each book finishes and returned in the order of
+/// `entries`.
+///
+/// Ctrl-C stops new books from starting and waits for the ones in progress, so
+/// every row that was created is in the checkpoint; a second Ctrl-C exits at once.
+pub async fn run(
+    searcher: Arc<CombinedBookSearcher>,
+    entries: Vec<ImportEntry>,
+    is_ebook: bool,
+    jobs: usize,
+    library: Option<Arc<Vec<MediaRow>>>,
+    checkpoint: &mut Checkpoint,
+) -> Vec<ImportItem> {
+    let total = entries.len();
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let mut tasks = JoinSet::new();
+    let mut items: Vec<Option<ImportItem>> = (0..total).map(|_| None).collect();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let claimed = Claimed::default();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let key = entry.to_string();
+        let occurrence = occurrences.entry(key.clone()).or_insert(0);
+        *occurrence += 1;
+        let occurrence = *occurrence;
+
+        if let Some(record) = checkpoint.added.get(&(key.clone(), occurrence)) {
+            let mut item = ImportItem::new(key, ImportStatus::AlreadyAdded);
+            item.id = Some(record.id);
+            item.title = Some(record.title.clone());
+            items[index] = Some(item);
+            continue;
+        }
+
+        let searcher = searcher.clone();
+        let permits = permits.clone();
+        let interrupted = interrupted.clone();
+        let library = library.clone();
+        let claimed = claimed.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let outcome = if interrupted.load(Ordering::Relaxed) {
+                None
+            } else {
+                Some(add_entry(&searcher, &entry, is_ebook, library.as_deref(), &claimed).await)
+            };
+            (index, key, occurrence, outcome)
+        });
+    }
+
+    let skipped = items.iter().flatten().count();
+    if skipped > 0 {
+        ui::info(t!("import.resuming", count = skipped));
+    }
+
+    let mut done = skipped;
+    let mut ctrl_c_pressed = 0;
+    loop {
+        let joined = tokio::select! {
+            joined = tasks.join_next() => match joined {
+                Some(joined) => joined,
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => {
+                ctrl_c_pressed += 1;
+                if ctrl_c_pressed > 1 {
+                    std::process::exit(EXIT_CANCELLED);
+                }
+                interrupted.store(true, Ordering::Relaxed);
+                ui::warn(t!("import.interrupting"));
+                continue;
+            }
+        };
+
+        let (index, key, occurrence, outcome) = match joined {
+            Ok(finished) => finished,
+            Err(e) => {
+                ui::error(t!("import.task_failed", error = e));
+                continue;
+            }
+        };
+
+        let Some(outcome) = outcome else {
+            items[index] = Some(ImportItem::new(key, ImportStatus::Interrupted));
+            continue;
+        };
+        done += 1;
+        let progress = format!("[{}/{}]", done, total);
+
+        let item = match outcome {
+            Outcome::Added(added) => {
+                ui::info(format!("{} {}", progress, ui::paint(Role::Success,
+                    t!("import.added", entry = key, title = added.title, id = added.id))));
+                let record = CheckpointRecord { entry: key.clone(), occurrence, id: added.id, title: added.title.clone() };
+                if let Err(e) = checkpoint.record(record) {
+                    ui::error(t!("import.checkpoint_failed", error = e));
+                }
+                let mut item = ImportItem::new(key, ImportStatus::Added);
+                item.id = Some(added.id);
+                item.title = Some(added.title);
+                item
+            }
+            Outcome::Duplicate(title, rows) => {
+                ui::warn(format!("{} {}", progress, t!("import.duplicate", entry = key, title = title, ids = format_ids(&rows))));
+                let mut item = ImportItem::new(key, ImportStatus::Duplicate);
+                item.title = Some(title);
+                item.duplicate_of = rows;
+                item
+            }
+            Outcome::NotFound => {
+                ui::warn(format!("{} {}", progress, t!("import.failed", entry = key, error = t!("import.not_found"))));
+                ImportItem::new(key, ImportStatus::NotFound)
+            }
+            Outcome::Failed(error) => {
+                ui::warn(format!("{} {}", progress, t!("import.failed", entry = key, error = error)));
+                let mut item = ImportItem::new(key, ImportStatus::Failed);
+                item.error = Some(error);
+                item
+            }
+        };
+        items[index] = Some(item);
+    }
+
+    items.into_iter().flatten().collect()
+}
+
+async fn add_entry(searcher: &CombinedBookSearcher, entry: &ImportEntry, is_ebook: bool, library: Option<&Vec<MediaRow>>, claimed: &Claimed) -> Outcome {
+    let results = match entry {
+        ImportEntry::Isbn(isbn) => searcher.find_by_isbn(isbn).await.map_err(|e| e.to_string()),
+        ImportEntry::TitleAuthor { title, author } => searcher.find_by_title_author(title, Some(author)).await.map_err(|e| e.to_string()),
+    };
+    let results = match results {
+        Ok(results) => results,
+        Err(e) => return Outcome::Failed(e),
+    };
+    let Some(book) = results.books.into_iter().next() else {
+        return Outcome::NotFound;
+    };
+
+    if let Some(library) = library {
+        let duplicates = crate::duplicates::find_possible_duplicates(&book, library);
+        if !duplicates.is_empty() {
+            return Outcome::Duplicate(book.get_full_title(), duplicates.iter().map(|row| row.id).collect());
+        }
+    }
+    // The library was loaded before the run, so books added since are checked
+    // here; the ISBN is claimed before adding so parallel jobs cannot both add it
+    let isbn = book.get_isbn().map(|isbn| normalize_isbn(&isbn)).filter(|isbn| !isbn.is_empty() && library.is_some());
+    if let Some(isbn) = &isbn {
+        // A job still adding the ISBN may yet fail, so wait to see which row it becomes
+        loop {
+            let resolved = claimed.resolved.notified();
+            tokio::pin!(resolved);
+            resolved.as_mut().enable();
+            {
+                let mut ids = claimed.ids.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                match ids.get(isbn) {
+                    Some(Some(id)) => return Outcome::Duplicate(book.get_full_title(), vec![*id]),
+                    Some(None) => {}
+                    None => {
+                        ids.insert(isbn.clone(), None);
+                        break;
+                    }
+                }
+            }
+            resolved.await;
+        }
+    }
+
+    let outcome = match searcher.add_book_unattended(&book, is_ebook).await {
+        Ok(added) => Outcome::Added(added),
+        Err(e) => Outcome::Failed(e.to_string()),
+    };
+    if let Some(isbn) = isbn {
+        let mut ids = claimed.ids.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match &outcome {
+            Outcome::Added(added) => {
+                ids.insert(isbn, Some(added.id));
+            }
+            // A later entry with the ISBN may still add it
+            _ => {
+                ids.remove(&isbn);
+            }
+        }
+        drop(ids);
+        claimed.resolved.notify_waiters();
+    }
+    outcome
+}
+
+fn format_ids(ids: &[u64]) -> String {
+    ids.iter().map(|id| format!("#{}", id)).collect::<Vec<String>>().join(", ")
+}
+
+/// Formats the outcome of an import grouped by status, so what was added,
+/// skipped and left over can be checked afterwards.
+pub fn format_summary(items: &[ImportItem]) -> String {
+    let groups = [
+        (ImportStatus::Added, t!("import.summary_added")),
+        (ImportStatus::AlreadyAdded, t!("import.summary_already_added")),
+        (ImportStatus::Duplicate, t!("import.summary_duplicates")),
+        (ImportStatus::NotFound, t!("import.summary_not_found")),
+        (ImportStatus::Failed, t!("import.summary_failed")),
+        (ImportStatus::Interrupted, t!("import.summary_interrupted")),
+    ];
+
+    let mut lines = vec![String::new(), ui::paint(Role::Heading, t!("import.summary_heading"))];
+    let mut totals = Vec::new();
+    for (status, heading) in groups {
+        let group: Vec<&ImportItem> = items.iter().filter(|item| item.status == status).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        lines.push(ui::paint(Role::Label, format!("{} ({})", heading, group.len())));
+        for item in &group {
+            let title = item.title.as_deref().unwrap_or_default();
+            lines.push(match status {
+                ImportStatus::Added | ImportStatus::AlreadyAdded =>
+                    format!("  {}  #{}  {}", item.entry, item.id.unwrap_or_default(), title),
+                ImportStatus::Duplicate =>
+                    format!("  {}  {}  ({})", item.entry, title, format_ids(&item.duplicate_of)),
+                ImportStatus::Failed =>
+                    format!("  {}  {}", item.entry, item.error.as_deref().unwrap_or_default()),
+                ImportStatus::NotFound | ImportStatus::Interrupted => format!("  {}", item.entry),
+            });
+        }
+        totals.push(format!("{} {}", group.len(), heading.to_lowercase()));
+    }
+
+    lines.push(String::new());
+    lines.push(totals.join(", "));
+    lines.join("\n")
+}
+
+/// Writes the import results to `path`, as CSV when it ends in `.csv` and as
+/// JSON otherwise.
+pub fn write_report(path: &Path, items: &[ImportItem]) -> Result<(), Box<dyn std::error::Error>> {
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(["entry", "status", "id", "title", "duplicate_of", "error"])?;
+        for item in items {
+            let status = serde_json::to_value(item.status)?;
+            writer.write_record([
+                item.entry.as_str(),
+                status.as_str().unwrap_or_default(),
+                &item.id.map(|id| id.to_string()).unwrap_or_default(),
+                item.title.as_deref().unwrap_or_default(),
+                &item.duplicate_of.iter().map(u64::to_string).collect::<Vec<String>>().join(" "),
+                item.error.as_deref().unwrap_or_default(),
+            ])?;
+        }
+        writer.flush()?;
+    } else {
+        std::fs::write(path, serde_json::to_string_pretty(items)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::time::Duration;
+use dialoguer::{theme::ColorfulTheme, Select};
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::{BaserowClient, MediaRow};
+use crate::book_search::{BookResult, CombinedBookSearcher};
+use crate::duplicates::find_possible_duplicates;
+use crate::timing::{self, Stage};
+use crate::ui::{self, Role};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IsbnStatus {
+    Updated,
+    /// An ISBN was found but not saved, because of `--dry-run`
+    Proposed,
+    /// Every proposal was turned down
+    Skipped,
+    NotFound,
+    Failed,
+}
+
+/// What `wcm isbn backfill` did for one row.
+#[derive(Debug, Serialize)]
+pub struct IsbnItem {
+    pub id: u64,
+    pub title: String,
+    pub status: IsbnStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IsbnItem {
+    fn new(row: &MediaRow, status: IsbnStatus) -> Self {
+        Self { id: row.id, title: row.get_text("Title").unwrap_or_default(), status, isbn: None, error: None }
+    }
+}
+
+/// An edition found for a row without an ISBN.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Proposal {
+    pub isbn: String,
+    pub title: String,
+    pub author: String,
+    pub publisher: Option<String>,
+    pub year: Option<String>,
+}
+
+/// The rows with an empty ISBN field, in table order, up to `limit`.
+pub fn missing_isbns(rows: Vec<MediaRow>, limit: Option<usize>) -> Vec<MediaRow> {
+    rows.into_iter()
+        .filter(|row| row.get_text("ISBN").is_none())
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// The editions among `books` that could be the row's book, one per ISBN, in the
+/// order found. An edition counts when `wcm add` would warn that it duplicates
+/// the row: the same title, ignoring subtitles, and an author in common.
+pub fn proposals(row: &MediaRow, books: &[BookResult]) -> Vec<Proposal> {
+    let mut proposals: Vec<Proposal> = Vec::new();
+    for book in books {
+        let Some(isbn) = book.get_isbn() else { continue };
+        if proposals.iter().any(|proposal| proposal.isbn == isbn)
+            || find_possible_duplicates(book, std::slice::from_ref(row)).is_empty() {
+            continue;
+        }
+        proposals.push(Proposal {
+            isbn,
+            title: book.get_full_title(),
+            author: book.get_all_authors(),
+            publisher: book.get_publisher(),
+            year: book.get_published_date(),
+        });
+    }
+    proposals
+}
+
+fn describe(proposal: &Proposal) -> String {
+    let details: Vec<&str> = [proposal.publisher.as_deref(), proposal.year.as_deref()].into_iter().flatten().collect();
+    let mut line = format!("{}  {} by {}", proposal.isbn, proposal.title, proposal.author);
+    if !details.is_empty() {
+        line.push_str(&format!(" ({})", details.join(", ")));
+    }
+    line
+}
+
+/// Searches by title and author for each row and, unless `dry_run`, asks which
+/// of the editions found to take the ISBN from. Rows are handled one at a time,
+/// `delay` apart; Esc at a prompt stops the run.
+pub async fn backfill(
+    searcher: &CombinedBookSearcher,
+    client: &BaserowClient,
+    rows: &[MediaRow],
+    delay: Duration,
+    dry_run: bool,
+) -> Result<Vec<IsbnItem>, Box<dyn std::error::Error>> {
+    let mut items = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(delay).await;
+        }
+        let title = row.get_text("Title").unwrap_or_default();
+        let author = row.get_text("Author");
+        ui::info(ui::paint(Role::Label, t!("isbn_backfill.row",
+            number = i + 1, total = rows.len(), title = &title, author = author.as_deref().unwrap_or("?"))));
+
+        let found = match searcher.find_by_title_author(&title, author.as_deref()).await {
+            Ok(results) => proposals(row, &results.books),
+            Err(e) => {
+                let mut item = IsbnItem::new(row, IsbnStatus::Failed);
+                item.error = Some(e.to_string());
+                items.push(item);
+                continue;
+            }
+        };
+        if found.is_empty() {
+            ui::warn(t!("isbn_backfill.none"));
+            items.push(IsbnItem::new(row, IsbnStatus::NotFound));
+            continue;
+        }
+
+        let item = if dry_run {
+            ui::info(t!("isbn_backfill.proposed", edition = describe(&found[0])));
+            let mut item = IsbnItem::new(row, IsbnStatus::Proposed);
+            item.isbn = Some(found[0].isbn.clone());
+            item
+        } else {
+            let mut choices: Vec<String> = found.iter().map(describe).collect();
+            choices.push(t!("isbn_backfill.skip").into_owned());
+            let picked = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(t!("isbn_backfill.prompt"))
+                .items(&choices)
+                .default(0)
+                .interact_opt()?;
+            match picked {
+                None => break,
+                Some(index) if index == found.len() => IsbnItem::new(row, IsbnStatus::Skipped),
+                Some(index) => {
+                    let isbn = &found[index].isbn;
+                    match timing::time(Stage::Update, client.update_media_row(row.id, &serde_json::json!({ "ISBN": isbn }))).await {
+                        Ok(_) => {
+                            let mut item = IsbnItem::new(row, IsbnStatus::Updated);
+                            item.isbn = Some(isbn.clone());
+                            item
+                        }
+                        Err(e) => {
+                            let mut item = IsbnItem::new(row, IsbnStatus::Failed);
+                            item.error = Some(e.to_string());
+                            item
+                        }
+                    }
+                }
+            }
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+pub fn format_summary(items: &[IsbnItem]) -> String {
+    let groups = [
+        (IsbnStatus::Updated, t!("isbn_backfill.summary_updated")),
+        (IsbnStatus::Proposed, t!("isbn_backfill.summary_proposed")),
+        (IsbnStatus::Skipped, t!("isbn_backfill.summary_skipped")),
+        (IsbnStatus::NotFound, t!("isbn_backfill.summary_not_found")),
+        (IsbnStatus::Failed, t!("isbn_backfill.summary_failed")),
+    ];
+
+    let mut lines = vec![String::new(), ui::paint(Role::Heading, t!("isbn_backfill.summary_heading"))];
+    for (status, heading) in groups {
+        let group: Vec<&IsbnItem> = items.iter().filter(|item| item.status == status).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        lines.push(ui::paint(Role::Label, format!("{} ({})", heading, group.len())));
+        for item in group {
+            let detail = item.isbn.as_deref().or(item.error.as_deref()).unwrap_or_default();
+            lines.push(format!("  #{}  {}  {}", item.id, item.title, detail).trim_end().to_string());
+        }
+    }
+    lines.join("\n")
+}
@@ -1,12 +1,43 @@
-use image::{ImageBuffer, Rgb, RgbImage, imageops};
+use flate2::{write::ZlibEncoder, Compression};
+use image::{ImageBuffer, Rgb, RgbImage, RgbaImage, imageops};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref};
 use qrcode::QrCode;
-use crate::baserow::{BaserowClient, Storage};
-use std::path::Path;
+use crate::barcode;
+use crate::baserow::{self, BaserowClient, MediaRow, Storage};
+use crate::config::{LogoConfig, LogoPosition, SheetLayout};
+use crate::error::WcmError;
+use crate::ui;
+use rust_i18n::t;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+// Spine labels are 1.5 x 1 inch at 300 DPI
+const SPINE_LABEL_WIDTH: u32 = 450;
+const SPINE_LABEL_HEIGHT: u32 = 300;
+const SPINE_LABEL_MARGIN: u32 = 15;
+
+// Keeps the artwork clear of the die-cut edge when the printer feed is slightly off
+const LABEL_PADDING_MM: f32 = 1.5;
+
 pub struct LabelGenerator {
     baserow_client: BaserowClient,
     baserow_base_url: String,
+    barcode: bool,
+    contents: u32,
+    logo: Option<(RgbaImage, LogoConfig)>,
+    overwrite: bool,
+    call_number_field: String,
+}
+
+/// The books listed on a storage label that doubles as a packing list.
+#[derive(Debug, Clone, Default)]
+pub struct StorageContents {
+    pub titles: Vec<String>,
+    /// Number of books in the box, which can be more than the titles listed
+    pub total: u32,
 }
 
 impl LabelGenerator {
@@ -14,19 +45,113 @@ impl LabelGenerator {
         Self {
             baserow_client,
             baserow_base_url,
+            barcode: false,
+            contents: 0,
+            logo: None,
+            overwrite: false,
+            call_number_field: "Call Number".to_string(),
+        }
+    }
+
+    /// Adds a Code128 barcode of the storage ID under the label text, for USB barcode scanners.
+    pub fn with_barcode(mut self, barcode: bool) -> Self {
+        self.barcode = barcode;
+        self
+    }
+
+    /// Lists up to `count` book titles from the box on each storage label (0 disables).
+    pub fn with_contents(mut self, count: u32) -> Self {
+        self.contents = count;
+        self
+    }
+
+    /// Replaces existing files instead of writing `name-1.png`, `name-2.png`, ...
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Sets the media table field spine labels read the call number from.
+    pub fn with_call_number_field(mut self, field: String) -> Self {
+        self.call_number_field = field;
+        self
+    }
+
+    /// Picks the absolute path a label file is written to, numbering it when the
+    /// requested name is taken and overwriting is off.
+    fn output_path(&self, requested: &Path) -> std::io::Result<PathBuf> {
+        let requested = std::path::absolute(requested)?;
+        if self.overwrite || !requested.exists() {
+            return Ok(requested);
+        }
+
+        let stem = requested.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let extension = requested.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        let mut n = 1;
+        loop {
+            let candidate = requested.with_file_name(format!("{}-{}{}", stem, n, extension));
+            if !candidate.exists() {
+                return Ok(candidate);
+            }
+            n += 1;
+        }
+    }
+
+    fn save_label(&self, image: &RgbImage, requested: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let path = self.output_path(requested)?;
+        image.save(&path)?;
+        Ok(path)
+    }
+
+    /// Loads the logo image to composite onto every label.
+    pub fn with_logo(mut self, logo: Option<&LogoConfig>) -> Result<Self, Box<dyn std::error::Error>> {
+        self.logo = match logo {
+            Some(config) => {
+                let image = image::open(&config.path)
+                    .map_err(|e| format!("Failed to load label logo '{}': {}", config.path, e))?;
+                Some((image.to_rgba8(), config.clone()))
+            }
+            None => None,
+        };
+        Ok(self)
+    }
+
+    fn apply_logo(&self, img: &mut RgbImage) {
+        let Some((logo, config)) = &self.logo else { return };
+
+        let target_height = ((img.height() as f32 * config.size.clamp(0.01, 1.0)) as u32).max(1);
+        let target_width = ((logo.width() as f32 * target_height as f32 / logo.height() as f32) as u32)
+            .clamp(1, img.width());
+        let logo = imageops::resize(logo, target_width, target_height, imageops::FilterType::Lanczos3);
+
+        let right = img.width().saturating_sub(logo.width() + config.margin);
+        let bottom = img.height().saturating_sub(logo.height() + config.margin);
+        let (x, y) = match config.position {
+            LogoPosition::TopLeft => (config.margin, config.margin),
+            LogoPosition::TopRight => (right, config.margin),
+            LogoPosition::BottomLeft => (config.margin, bottom),
+            LogoPosition::BottomRight => (right, bottom),
+            LogoPosition::Center => ((img.width() - logo.width()) / 2, img.height().saturating_sub(logo.height()) / 2),
+        };
+
+        let opacity = config.opacity.clamp(0.0, 1.0);
+        for (lx, ly, pixel) in logo.enumerate_pixels() {
+            let (px, py) = (x + lx, y + ly);
+            if px >= img.width() || py >= img.height() {
+                continue;
+            }
+            let alpha = pixel[3] as f32 / 255.0 * opacity;
+            let base = img.get_pixel_mut(px, py);
+            for channel in 0..3 {
+                base[channel] = (pixel[channel] as f32 * alpha + base[channel] as f32 * (1.0 - alpha)).round() as u8;
+            }
         }
     }
 
     pub fn generate_qr_code(&self, storage_id: u64, storage_table_id: u64, database_id: u64, storage_view_id: u64) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, Box<dyn std::error::Error>> {
-        let storage_url = format!("{}/database/{}/table/{}/{}/row/{}", 
-            self.baserow_base_url.trim_end_matches('/'), 
-            database_id,
-            storage_table_id,
-            storage_view_id,  // This is the view ID (e.g., 3153)
-            storage_id
-        );
+        let storage_url = baserow::row_url(&self.baserow_base_url, database_id, storage_table_id, Some(storage_view_id), storage_id);
         
-        println!("Generating QR code for URL: {}", storage_url);
+        ui::detail(format!("Generating QR code for URL: {}", storage_url));
         
         let code = QrCode::new(&storage_url)?;
         
@@ -67,7 +192,7 @@ impl LabelGenerator {
         Ok(rgb_image)
     }
 
-    pub fn create_label(&self, storage: &Storage, storage_table_id: u64, database_id: u64, storage_view_id: u64) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    pub fn create_label(&self, storage: &Storage, contents: &StorageContents, storage_table_id: u64, database_id: u64, storage_view_id: u64) -> Result<RgbImage, Box<dyn std::error::Error>> {
         // Create a 600x300 pixel label
         let width = 600;
         let height = 300;
@@ -88,16 +213,181 @@ impl LabelGenerator {
         let storage_name = storage.get_name().unwrap_or_else(|| format!("Storage {}", storage.id));
         let storage_id_text = format!("ID: {}", storage.id);
         
-        // Draw storage name (larger, centered)
-        self.draw_text(&mut img, &storage_name, 50, 80)?;
-        
-        // Draw storage ID below the name
-        self.draw_text(&mut img, &storage_id_text, 50, 140)?;
+        if contents.titles.is_empty() {
+            // Draw storage name (larger, centered)
+            self.draw_text(&mut img, &storage_name, 50, 80)?;
+            
+            // Draw storage ID below the name
+            self.draw_text(&mut img, &storage_id_text, 50, 140)?;
+        } else {
+            // Packing list layout: the name moves up and doubles in size to stand out from the titles
+            let storage_name: String = storage_name.chars().take(16).collect();
+            self.draw_text_scaled(&mut img, &storage_name, 30, 20, 2)?;
+            self.draw_text(&mut img, &storage_id_text, 30, 52)?;
+            self.draw_contents(&mut img, contents, 30, 76, if self.barcode { 185 } else { 290 })?;
+        }
+
+        if self.barcode {
+            let modules = barcode::encode_code128(&storage.id.to_string())
+                .ok_or("Storage ID cannot be encoded as Code128")?;
+            let barcode_image = barcode::render_barcode(&modules, 310, 80);
+            imageops::overlay(&mut img, &barcode_image, 40, 190);
+        }
         
+        self.apply_logo(&mut img);
+
+        Ok(img)
+    }
+
+    fn draw_contents(&self, img: &mut RgbImage, contents: &StorageContents, x: u32, top: u32, bottom: u32) -> Result<(), Box<dyn std::error::Error>> {
+        const LINE_HEIGHT: u32 = 15;
+        // Titles stop short of the QR code at x=380
+        const MAX_CHARS: usize = 33;
+
+        let available_lines = ((bottom - top) / LINE_HEIGHT) as usize;
+        let mut shown = contents.titles.len().min(available_lines);
+        let mut remaining = contents.total as usize - shown.min(contents.total as usize);
+        if remaining > 0 && shown == available_lines {
+            // Make room for the "more" line
+            shown -= 1;
+            remaining += 1;
+        }
+
+        for (i, title) in contents.titles.iter().take(shown).enumerate() {
+            let line: String = format!("- {}", title).chars().take(MAX_CHARS).collect();
+            self.draw_text_scaled(img, &line, x, top + i as u32 * LINE_HEIGHT, 1)?;
+        }
+        if remaining > 0 {
+            self.draw_text_scaled(img, &format!("  and {} more", remaining), x, top + shown as u32 * LINE_HEIGHT, 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Looks up the books stored in a box when labels list their contents.
+    pub async fn fetch_contents(&self, storage: &Storage) -> Result<StorageContents, Box<dyn std::error::Error>> {
+        if self.contents == 0 {
+            return Ok(StorageContents::default());
+        }
+
+        let response = self.baserow_client.fetch_media_in_storage(storage.id, self.contents).await?;
+        let titles: Vec<String> = response.results.iter()
+            .filter_map(|media| media.get_text("Title"))
+            .take(self.contents as usize)
+            .collect();
+        let total = response.count.unwrap_or(titles.len() as u32);
+
+        Ok(StorageContents { titles, total })
+    }
+
+    /// Renders a storage label, fetching its contents first when that is enabled.
+    pub async fn storage_label(&self, storage: &Storage, storage_table_id: u64, database_id: u64, storage_view_id: u64) -> Result<RgbImage, Box<dyn std::error::Error>> {
+        let contents = self.fetch_contents(storage).await?;
+        self.create_label(storage, &contents, storage_table_id, database_id, storage_view_id)
+    }
+
+    pub fn create_isbn_label(&self, isbn: &str) -> Result<RgbImage, Box<dyn std::error::Error>> {
+        let ean = barcode::isbn_to_ean13(isbn)
+            .ok_or_else(|| format!("'{}' is not a valid ISBN", isbn))?;
+        let modules = barcode::encode_ean13(&ean)
+            .ok_or_else(|| format!("'{}' is not a valid ISBN", isbn))?;
+
+        let width = 600;
+        let height = 300;
+        let mut img = ImageBuffer::from_pixel(width, height, Rgb([255, 255, 255]));
+
+        self.draw_text(&mut img, &format!("ISBN: {}", ean), 50, 50)?;
+
+        let barcode_image = barcode::render_barcode(&modules, 500, 180);
+        let x = (width - barcode_image.width()) / 2;
+        imageops::overlay(&mut img, &barcode_image, x as i64, 90);
+
+        self.apply_logo(&mut img);
+
+        Ok(img)
+    }
+
+    pub fn generate_isbn_label(&self, isbn: &str, output_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let label_image = self.create_isbn_label(isbn)?;
+        let output_path = self.save_label(&label_image, output_path)?;
+
+        ui::info(format!("ISBN label generated for {} -> {}", isbn, output_path.display()));
+
+        Ok(output_path)
+    }
+
+    /// Renders a spine/jacket label for one book: call number in large type, then title,
+    /// author, and the storage box it belongs in.
+    pub fn create_spine_label(&self, media: &MediaRow) -> Result<RgbImage, Box<dyn std::error::Error>> {
+        let mut img = ImageBuffer::from_pixel(SPINE_LABEL_WIDTH, SPINE_LABEL_HEIGHT, Rgb([255, 255, 255]));
+        let usable_width = SPINE_LABEL_WIDTH - 2 * SPINE_LABEL_MARGIN;
+
+        // Call numbers are stacked one part per line, the way libraries print them
+        let mut lines: Vec<(String, u32)> = Vec::new();
+        if let Some(call_number) = media.get_text(&self.call_number_field) {
+            for part in call_number.split_whitespace().take(3) {
+                lines.push((part.to_string(), 3));
+            }
+        }
+        let title = media.get_text("Title").unwrap_or_else(|| format!("Media {}", media.id));
+        for line in wrap_text(&title, max_chars(usable_width, 2)).into_iter().take(2) {
+            lines.push((line, 2));
+        }
+        if let Some(author) = media.get_text("Author") {
+            lines.push((author, 2));
+        }
+        if let Some(location) = media.get_text("Location") {
+            lines.push((format!("Box: {}", location), 2));
+        }
+
+        let mut y = SPINE_LABEL_MARGIN;
+        for (text, scale) in lines {
+            let line_height = 12 * scale + 2 * scale;
+            if y + line_height > SPINE_LABEL_HEIGHT - SPINE_LABEL_MARGIN {
+                break;
+            }
+            let text: String = text.chars().take(max_chars(usable_width, scale)).collect();
+            let text_width = text.chars().count() as u32 * 10 * scale;
+            let x = SPINE_LABEL_MARGIN + usable_width.saturating_sub(text_width) / 2;
+            self.draw_text_scaled(&mut img, &text, x, y, scale)?;
+            y += line_height;
+        }
+
+        self.apply_logo(&mut img);
+
         Ok(img)
     }
 
+    pub async fn generate_spine_label(&self, media_id: u64, output_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        ui::detail(format!("Looking up media by ID: {}", media_id));
+
+        let media = self.baserow_client.fetch_media_row(media_id).await?;
+        let label_image = self.create_spine_label(&media)?;
+        let output_path = self.save_label(&label_image, output_path)?;
+
+        ui::info(format!("Spine label generated for '{}' (ID: {}) -> {}",
+                   media.get_text("Title").unwrap_or_default(), media.id, output_path.display()));
+
+        Ok(output_path)
+    }
+
+    pub async fn create_spine_labels(&self, media_ids: &[u64]) -> Result<Vec<RgbImage>, Box<dyn std::error::Error>> {
+        let mut labels = Vec::new();
+        for media_id in media_ids {
+            let media = self.baserow_client.fetch_media_row(*media_id).await?;
+            labels.push(self.create_spine_label(&media)?);
+        }
+        Ok(labels)
+    }
+
     fn draw_text(&self, img: &mut RgbImage, text: &str, x: u32, y: u32) -> Result<(), Box<dyn std::error::Error>> {
+        // Limit text length so it stays clear of the QR code
+        let text: String = text.chars().take(26).collect();
+        self.draw_text_scaled(img, &text, x, y, 1)
+    }
+
+    /// Draws text with the bitmap font, each font pixel becoming a `scale` x `scale` block.
+    fn draw_text_scaled(&self, img: &mut RgbImage, text: &str, x: u32, y: u32, scale: u32) -> Result<(), Box<dyn std::error::Error>> {
         // Use a simple embedded font data for basic text rendering
         // This is a minimal font implementation for the label
         
@@ -105,21 +395,25 @@ impl LabelGenerator {
         
         // Simple bitmap font - each character is 8x12 pixels
         let font_data = self.get_simple_font_data();
+        let put_block = |img: &mut RgbImage, px: u32, py: u32| {
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let (bx, by) = (px * scale + dx, py * scale + dy);
+                    if x + bx < img.width() && y + by < img.height() {
+                        img.put_pixel(x + bx, y + by, text_color);
+                    }
+                }
+            }
+        };
         
         for (i, ch) in text.chars().enumerate() {
-            if i > 25 { break; } // Limit text length
-            
-            let char_x = x + (i as u32 * 10); // 8 pixels width + 2 spacing
+            let char_x = i as u32 * 10; // 8 pixels width + 2 spacing
             
             if let Some(char_bitmap) = font_data.get(&ch) {
                 for (row, &byte) in char_bitmap.iter().enumerate() {
                     for bit in 0..8 {
                         if (byte >> (7 - bit)) & 1 == 1 {
-                            let px = char_x + bit;
-                            let py = y + row as u32;
-                            if px < img.width() && py < img.height() {
-                                img.put_pixel(px, py, text_color);
-                            }
+                            put_block(img, char_x + bit, row as u32);
                         }
                     }
                 }
@@ -127,10 +421,8 @@ impl LabelGenerator {
                 // Draw a simple rectangle for unknown characters
                 for dx in 0..8 {
                     for dy in 0..12 {
-                        let px = char_x + dx;
-                        let py = y + dy;
-                        if px < img.width() && py < img.height() && (dx == 0 || dx == 7 || dy == 0 || dy == 11) {
-                            img.put_pixel(px, py, text_color);
+                        if dx == 0 || dx == 7 || dy == 0 || dy == 11 {
+                            put_block(img, char_x + dx, dy);
                         }
                     }
                 }
@@ -218,43 +510,224 @@ impl LabelGenerator {
         font.insert('.', vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00]);
         font.insert(':', vec![0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00]);
         font.insert('/', vec![0x00, 0x02, 0x04, 0x08, 0x10, 0x10, 0x20, 0x40, 0x80, 0x00, 0x00, 0x00]);
+        font.insert(',', vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x08, 0x10]);
+        font.insert('\'', vec![0x00, 0x18, 0x18, 0x08, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        font.insert('(', vec![0x00, 0x04, 0x08, 0x10, 0x10, 0x10, 0x10, 0x10, 0x08, 0x04, 0x00, 0x00]);
+        font.insert(')', vec![0x00, 0x20, 0x10, 0x08, 0x08, 0x08, 0x08, 0x08, 0x10, 0x20, 0x00, 0x00]);
+        font.insert('&', vec![0x00, 0x30, 0x48, 0x48, 0x30, 0x32, 0x4C, 0x44, 0x4A, 0x31, 0x00, 0x00]);
+        font.insert('!', vec![0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00]);
+        font.insert('?', vec![0x00, 0x3C, 0x42, 0x02, 0x04, 0x08, 0x08, 0x00, 0x08, 0x08, 0x00, 0x00]);
         
         font
     }
 
-    pub async fn generate_label_by_id(&self, storage_id: u64, storage_table_id: u64, database_id: u64, storage_view_id: u64, output_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Looking up storage by ID: {}", storage_id);
+    pub async fn generate_label_by_id(&self, storage_id: u64, storage_table_id: u64, database_id: u64, storage_view_id: u64, output_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        ui::detail(format!("Looking up storage by ID: {}", storage_id));
         
         let storage = self.baserow_client.find_storage_by_id(storage_id).await?
-            .ok_or_else(|| format!("Storage with ID {} not found", storage_id))?;
+            .ok_or_else(|| WcmError::NotFound(t!("error.storage_id_not_found", id = storage_id).into_owned()))?;
         
         let storage_name = storage.get_name().unwrap_or_else(|| format!("Storage {}", storage.id));
-        println!("Found storage: {}", storage_name);
+        ui::detail(format!("Found storage: {}", storage_name));
         
-        let label_image = self.create_label(&storage, storage_table_id, database_id, storage_view_id)?;
-        label_image.save(output_path)?;
+        let label_image = self.storage_label(&storage, storage_table_id, database_id, storage_view_id).await?;
+        let output_path = self.save_label(&label_image, output_path)?;
         
-        println!("Label generated for storage '{}' (ID: {}) -> {}", 
-                 storage_name, storage.id, output_path.display());
+        ui::info(format!("Label generated for storage '{}' (ID: {}) -> {}",
+                   storage_name, storage.id, output_path.display()));
         
-        Ok(())
+        Ok(output_path)
     }
 
-    pub async fn generate_label_by_name(&self, storage_name: &str, storage_table_id: u64, database_id: u64, storage_view_id: u64, output_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Looking up storage by name: {}", storage_name);
+    pub async fn generate_label_by_name(&self, storage_name: &str, storage_table_id: u64, database_id: u64, storage_view_id: u64, output_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        ui::detail(format!("Looking up storage by name: {}", storage_name));
         
         let storage = self.baserow_client.find_storage_by_name(storage_name).await?
-            .ok_or_else(|| format!("Storage with name '{}' not found", storage_name))?;
+            .ok_or_else(|| WcmError::NotFound(t!("error.storage_name_not_found", name = storage_name).into_owned()))?;
         
         let found_name = storage.get_name().unwrap_or_else(|| format!("Storage {}", storage.id));
-        println!("Found storage: {} (ID: {})", found_name, storage.id);
+        ui::detail(format!("Found storage: {} (ID: {})", found_name, storage.id));
         
-        let label_image = self.create_label(&storage, storage_table_id, database_id, storage_view_id)?;
-        label_image.save(output_path)?;
+        let label_image = self.storage_label(&storage, storage_table_id, database_id, storage_view_id).await?;
+        let output_path = self.save_label(&label_image, output_path)?;
         
-        println!("Label generated for storage '{}' (ID: {}) -> {}", 
-                 found_name, storage.id, output_path.display());
+        ui::info(format!("Label generated for storage '{}' (ID: {}) -> {}",
+                   found_name, storage.id, output_path.display()));
         
-        Ok(())
+        Ok(output_path)
+    }
+
+    /// Looks up several storage boxes at once, or every storage box when none are named.
+    pub async fn fetch_storages(&self, storage_ids: &[u64], storage_names: &[String]) -> Result<Vec<Storage>, Box<dyn std::error::Error>> {
+        let storage_entries = self.baserow_client.fetch_storage_entries().await?;
+        if storage_ids.is_empty() && storage_names.is_empty() {
+            return Ok(storage_entries);
+        }
+
+        let mut selected = Vec::new();
+        for id in storage_ids {
+            let storage = storage_entries.iter().find(|storage| storage.id == *id)
+                .ok_or_else(|| WcmError::NotFound(t!("error.storage_id_not_found", id = id).into_owned()))?;
+            selected.push(storage.clone());
+        }
+        for name in storage_names {
+            let storage = storage_entries.iter()
+                .find(|storage| storage.get_name().map(|n| n.eq_ignore_ascii_case(name)).unwrap_or(false))
+                .ok_or_else(|| WcmError::NotFound(t!("error.storage_name_not_found", name = name).into_owned()))?;
+            selected.push(storage.clone());
+        }
+
+        Ok(selected)
+    }
+
+    /// Keeps the storage boxes modified at or after `since` (`YYYY-MM-DD` or an ISO 8601 UTC
+    /// timestamp). Rows without a value in `modified_field` are kept, so nothing is silently missed.
+    pub fn filter_changed_since(storages: Vec<Storage>, modified_field: &str, since: &str) -> Result<Vec<Storage>, Box<dyn std::error::Error>> {
+        let is_date = since.len() >= 10 && since.char_indices().take(10)
+            .all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() });
+        if !is_date {
+            return Err(format!("'{}' is not a date, expected YYYY-MM-DD", since).into());
+        }
+        if !storages.is_empty() && storages.iter().all(|storage| storage.get_timestamp(modified_field).is_none()) {
+            return Err(format!("The storage table has no '{}' field. Add a \"Last modified\" field in Baserow or set label.modified_field", modified_field).into());
+        }
+
+        // ISO 8601 UTC timestamps order the same way as strings
+        Ok(storages.into_iter()
+            .filter(|storage| storage.get_timestamp(modified_field).map(|modified| modified >= since).unwrap_or(true))
+            .collect())
+    }
+
+    /// Writes one `storage_label_<id>.png` per storage box into `output_dir`.
+    pub async fn save_storage_labels(&self, storages: &[Storage], storage_table_id: u64, database_id: u64, storage_view_id: u64, output_dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(output_dir)?;
+
+        let mut written = Vec::new();
+        for storage in storages {
+            let output_path = output_dir.join(format!("storage_label_{}.png", storage.id));
+            let label_image = self.storage_label(storage, storage_table_id, database_id, storage_view_id).await?;
+            let output_path = self.save_label(&label_image, &output_path)?;
+
+            let storage_name = storage.get_name().unwrap_or_else(|| format!("Storage {}", storage.id));
+            ui::info(format!("Label generated for storage '{}' (ID: {}) -> {}",
+                       storage_name, storage.id, output_path.display()));
+            written.push(output_path);
+        }
+
+        ui::info(format!("{} storage label(s) written to {}", storages.len(), output_dir.display()));
+        Ok(written)
     }
-}
\ No newline at end of file
+
+    /// Lays labels out on adhesive sheets and writes them as a PDF, adding pages as needed.
+    /// The first `skip` positions are left empty so partly used sheets can be reused.
+    pub fn write_label_sheet(&self, labels: &[RgbImage], layout: &SheetLayout, skip: u32, output_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let per_page = layout.labels_per_page() as usize;
+        if per_page == 0 {
+            return Err(format!("Sheet layout '{}' has no label positions", layout.name).into());
+        }
+
+        let page_width = layout.page_width * POINTS_PER_MM;
+        let page_height = layout.page_height * POINTS_PER_MM;
+        let positions = (skip as usize..skip as usize + labels.len()).collect::<Vec<_>>();
+        let page_count = positions.last().map(|last| last / per_page + 1).unwrap_or(0);
+
+        let mut next_id = Ref::new(1);
+        let catalog_id = next_id.bump();
+        let page_tree_id = next_id.bump();
+        let page_ids: Vec<Ref> = (0..page_count).map(|_| next_id.bump()).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id).kids(page_ids.iter().copied()).count(page_count as i32);
+
+        for (page_index, page_id) in page_ids.iter().enumerate() {
+            let content_id = next_id.bump();
+            let mut content = Content::new();
+            let mut images = Vec::new();
+
+            for (label, position) in labels.iter().zip(&positions) {
+                if position / per_page != page_index {
+                    continue;
+                }
+                let slot = (position % per_page) as u32;
+                let column = slot % layout.columns;
+                let row = slot / layout.columns;
+
+                // PDF coordinates start at the bottom-left corner of the page
+                let cell_x = layout.margin_left + column as f32 * layout.pitch_x;
+                let cell_y = layout.page_height - layout.margin_top - row as f32 * layout.pitch_y - layout.label_height;
+
+                let available_width = layout.label_width - 2.0 * LABEL_PADDING_MM;
+                let available_height = layout.label_height - 2.0 * LABEL_PADDING_MM;
+                let scale = (available_width / label.width() as f32).min(available_height / label.height() as f32);
+                let width = label.width() as f32 * scale;
+                let height = label.height() as f32 * scale;
+                let x = cell_x + (layout.label_width - width) / 2.0;
+                let y = cell_y + (layout.label_height - height) / 2.0;
+
+                let image_id = next_id.bump();
+                let name = format!("Im{}", images.len() + 1);
+                content.save_state();
+                content.transform([width * POINTS_PER_MM, 0.0, 0.0, height * POINTS_PER_MM, x * POINTS_PER_MM, y * POINTS_PER_MM]);
+                content.x_object(Name(name.as_bytes()));
+                content.restore_state();
+                images.push((name, image_id, label));
+            }
+
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, page_width, page_height));
+            page.parent(page_tree_id);
+            page.contents(content_id);
+            {
+                let mut resources = page.resources();
+                let mut x_objects = resources.x_objects();
+                for (name, image_id, _) in &images {
+                    x_objects.pair(Name(name.as_bytes()), *image_id);
+                }
+            }
+            page.finish();
+
+            pdf.stream(content_id, &content.finish());
+
+            for (_, image_id, label) in images {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(label.as_raw())?;
+                let data = encoder.finish()?;
+
+                let mut image = pdf.image_xobject(image_id, &data);
+                image.filter(Filter::FlateDecode);
+                image.width(label.width() as i32);
+                image.height(label.height() as i32);
+                image.color_space().device_rgb();
+                image.bits_per_component(8);
+                image.finish();
+            }
+        }
+
+        let output_path = self.output_path(output_path)?;
+        std::fs::write(&output_path, pdf.finish())?;
+
+        ui::info(format!("{} label(s) written on {} page(s) of {} -> {}",
+                   labels.len(), page_count, layout.name, output_path.display()));
+
+        Ok(output_path)
+    }
+}
+
+fn max_chars(width: u32, scale: u32) -> usize {
+    (width / (10 * scale)) as usize
+}
+
+fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= max_chars => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
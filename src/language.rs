@@ -0,0 +1,97 @@
+use serde::Serialize;
+use whatlang::Lang;
+use crate::book_search::BookResult;
+use crate::filters::iso_code;
+use crate::text::strip_html;
+
+/// A book's language, as an ISO 639-1 code where there is one, e.g. `th`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Language {
+    pub code: String,
+    /// English name, for the LLM prompt and the summary
+    pub name: String,
+}
+
+/// ISO 639-1 codes of the languages whatlang detects that have one.
+const ISO_639_1: &[(Lang, &str)] = &[
+    (Lang::Eng, "en"),
+    (Lang::Tha, "th"),
+    (Lang::Fra, "fr"),
+    (Lang::Deu, "de"),
+    (Lang::Spa, "es"),
+    (Lang::Ita, "it"),
+    (Lang::Por, "pt"),
+    (Lang::Nld, "nl"),
+    (Lang::Rus, "ru"),
+    (Lang::Jpn, "ja"),
+    (Lang::Cmn, "zh"),
+    (Lang::Kor, "ko"),
+    (Lang::Vie, "vi"),
+    (Lang::Lat, "la"),
+    (Lang::Ell, "el"),
+];
+
+fn from_lang(lang: Lang) -> Language {
+    let code = ISO_639_1.iter()
+        .find(|(known, _)| *known == lang)
+        .map(|(_, code)| code.to_string())
+        .unwrap_or_else(|| lang.code().to_string());
+    Language { code, name: lang.eng_name().to_string() }
+}
+
+/// The language of `text`, when whatlang is confident about it. Short Latin
+/// text such as most titles is rarely enough to tell; a script used by only one
+/// language, such as Thai, always is.
+pub fn detect(text: &str) -> Option<Language> {
+    whatlang::detect(text)
+        .filter(|info| info.is_reliable())
+        .map(|info| from_lang(info.lang()))
+}
+
+/// A language given as a code by a catalogue or on the command line.
+pub fn from_code(code: &str) -> Option<Language> {
+    let code = iso_code(code);
+    if code.is_empty() {
+        return None;
+    }
+    let name = ISO_639_1.iter()
+        .find(|(_, iso)| *iso == code)
+        .map(|(lang, _)| lang.eng_name().to_string())
+        .unwrap_or_else(|| code.clone());
+    Some(Language { code, name })
+}
+
+/// The language of a book's title, or failing that of its title and
+/// description together, so a description in another language, as for a
+/// translation described in English, does not outweigh the title.
+pub fn text_language(title: &str, description: Option<&str>) -> Option<Language> {
+    detect(title).or_else(|| {
+        let description = description.filter(|description| !description.trim().is_empty())?;
+        detect(&format!("{}\n{}", title, description))
+    })
+}
+
+/// The language a book is written in: as `text_language` detects it, failing
+/// that the language the catalogue gives.
+pub fn book_language(book: &BookResult) -> Option<Language> {
+    let description = match book {
+        BookResult::Google(item) => item.volume_info.description.as_deref().map(strip_html),
+        BookResult::OpenLibrary(ol_book) => ol_book.description.as_deref().map(strip_html),
+        BookResult::Manual(_) => None,
+    };
+    if let Some(language) = text_language(&book.get_full_title(), description.as_deref()) {
+        return Some(language);
+    }
+    let stated = match book {
+        BookResult::Google(item) => item.volume_info.language.clone(),
+        BookResult::OpenLibrary(ol_book) => ol_book.language.as_ref().and_then(|languages| languages.first().cloned()),
+        BookResult::Manual(_) => None,
+    };
+    stated.as_deref().and_then(from_code)
+}
+
+/// Whether `language` is the one asked for with `--lang`, given as an ISO
+/// 639-1 or MARC code.
+pub fn is_preferred(language: &Language, preferred: &str) -> bool {
+    iso_code(preferred) == language.code
+}
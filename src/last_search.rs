@@ -0,0 +1,75 @@
+use std::path::Path;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use crate::book_search::BookResult;
+
+/// One book of a saved result list, with the series and volume number it was
+/// found under by `wcm search --series`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedResult {
+    pub book: BookResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u32>,
+}
+
+impl SavedResult {
+    pub fn new(book: BookResult) -> Self {
+        Self { book, series: None, volume: None }
+    }
+}
+
+/// The result list of the last search, kept in `app.last_search_file` so
+/// `wcm add --from-last` can add from it without searching again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastSearch {
+    /// What was searched for, as shown to the user
+    pub query: String,
+    pub results: Vec<SavedResult>,
+}
+
+impl LastSearch {
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Err(t!("last_search.none").into_owned().into());
+        }
+        let search = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{} is not a saved search: {}", path.display(), e))?;
+        Ok(search)
+    }
+
+    /// The `number`th result, counting from 1 as the list is printed.
+    pub fn pick(&self, number: usize) -> Result<&SavedResult, String> {
+        number.checked_sub(1)
+            .and_then(|index| self.results.get(index))
+            .ok_or_else(|| t!("last_search.out_of_range", number = number, count = self.results.len(), query = &self.query).into_owned())
+    }
+}
+
+/// The results as a numbered list, the numbers `--from-last` takes.
+pub fn format_results(search: &LastSearch) -> String {
+    let mut lines = vec![t!("last_search.heading", count = search.results.len(), query = &search.query).into_owned()];
+    for (i, result) in search.results.iter().enumerate() {
+        let book = &result.book;
+        let mut line = format!("{:>3}. {}", i + 1, t!("select.item",
+            title = book.get_full_title(),
+            author = book.get_all_authors(),
+            year = book.get_published_date().unwrap_or_else(|| t!("select.unknown_year").into_owned())
+        ));
+        if let Some(isbn) = book.get_isbn() {
+            line.push_str(&format!(" [{}]", isbn));
+        }
+        lines.push(line);
+    }
+    lines.push(t!("last_search.hint").into_owned());
+    lines.join("\n")
+}
@@ -0,0 +1,70 @@
+rust_i18n::i18n!("locales", fallback = "en");
+
+pub mod config;
+pub mod config_check;
+pub mod doctor;
+pub mod hooks;
+pub mod embeddings;
+pub mod ask;
+pub mod error;
+pub mod ui;
+pub mod timing;
+pub mod metrics;
+pub mod http;
+pub mod rate_limit;
+pub mod http_cache;
+pub mod offline;
+pub mod i18n;
+pub mod text;
+pub mod google_books;
+pub mod open_library;
+pub mod book_search;
+pub mod merge;
+pub mod compare;
+pub mod baserow;
+pub mod web_search;
+pub mod llm;
+pub mod llm_budget;
+pub mod label;
+pub mod classification;
+pub mod duplicates;
+pub mod filters;
+pub mod series;
+pub mod server;
+pub mod barcode;
+pub mod printer;
+pub mod retry;
+pub mod import;
+pub mod diagnostics;
+pub mod dates;
+pub mod goals;
+pub mod stats;
+pub mod library;
+pub mod sessions;
+pub mod loans;
+pub mod recommend;
+pub mod next;
+pub mod similar;
+pub mod browser;
+pub mod linked;
+pub mod authors;
+pub mod publishers;
+pub mod category_sync;
+pub mod identifiers;
+pub mod entry_editor;
+pub mod last_search;
+pub mod periodicals;
+pub mod boxset;
+pub mod transliteration;
+pub mod language;
+pub mod content_warnings;
+pub mod cover_hashes;
+pub mod covers;
+pub mod enrich;
+pub mod isbn_backfill;
+pub mod export;
+pub mod schedule;
+pub mod daemon;
+pub mod loc;
+pub mod telegram;
+pub mod mcp;
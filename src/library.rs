@@ -0,0 +1,85 @@
+use dialoguer::{theme::ColorfulTheme, Select};
+use rust_i18n::t;
+use crate::baserow::{BaserowClient, BaserowError, MediaRow, Storage};
+use crate::duplicates::{fold, normalize_isbn};
+use crate::error::WcmError;
+use crate::timing::{self, Stage};
+
+/// Finds the media row a command such as `wcm log <book>` refers to: a row ID, an
+/// ISBN, or part of the title or romanized title (ignoring case and accents).
+/// When several titles match, the user picks one.
+pub async fn find_book(client: &BaserowClient, reference: &str) -> Result<MediaRow, WcmError> {
+    let reference = reference.trim();
+    let isbn = normalize_isbn(reference);
+    let is_isbn = matches!(isbn.len(), 10 | 13) && isbn[..isbn.len() - 1].chars().all(|c| c.is_ascii_digit());
+
+    if !is_isbn {
+        if let Ok(id) = reference.parse::<u64>() {
+            return match timing::time(Stage::Library, client.fetch_media_row(id)).await {
+                Err(BaserowError::NotFound) => Err(not_found(reference)),
+                result => result.map_err(WcmError::from),
+            };
+        }
+    }
+
+    let rows = timing::time(Stage::Library, client.fetch_all_media()).await?;
+    let mut matches: Vec<MediaRow> = if is_isbn {
+        rows.into_iter()
+            .filter(|row| row.get_text("ISBN").is_some_and(|row_isbn| normalize_isbn(&row_isbn) == isbn))
+            .collect()
+    } else {
+        let wanted = fold(reference);
+        let romanized = &client.fields().romanized_title;
+        rows.into_iter()
+            .filter(|row| ["Title", romanized.as_str()].into_iter()
+                .any(|field| row.get_text(field).is_some_and(|title| fold(&title).contains(&wanted))))
+            .collect()
+    };
+
+    match matches.len() {
+        0 => Err(not_found(reference)),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let items: Vec<String> = matches.iter().map(|row| t!("book_ref.item",
+                title = row.get_text("Title").unwrap_or_default(),
+                author = row.get_text("Author").unwrap_or_default(),
+                id = row.id
+            ).into_owned()).collect();
+            let picked = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(t!("book_ref.prompt", count = matches.len()))
+                .items(&items)
+                .default(0)
+                .interact_opt()
+                .map_err(|e| WcmError::Other(Box::new(e)))?
+                .ok_or(WcmError::Cancelled)?;
+            Ok(matches.remove(picked))
+        }
+    }
+}
+
+/// Finds a storage by row ID or by name, ignoring case.
+pub async fn find_storage(client: &BaserowClient, reference: &str) -> Result<Storage, WcmError> {
+    let reference = reference.trim();
+    if let Ok(id) = reference.parse::<u64>() {
+        if let Some(storage) = timing::time(Stage::Library, client.find_storage_by_id(id)).await? {
+            return Ok(storage);
+        }
+    }
+    timing::time(Stage::Library, client.find_storage_by_name(reference)).await?
+        .ok_or_else(|| WcmError::NotFound(t!("error.storage_name_not_found", name = reference).into_owned()))
+}
+
+/// Adds `entry` to the end of a long text field of `row`, after a blank line,
+/// keeping what is already there.
+pub async fn append_to_field(client: &BaserowClient, row: &MediaRow, field: &str, entry: &str) -> Result<MediaRow, WcmError> {
+    let text = match row.get_text(field).filter(|text| !text.trim().is_empty()) {
+        Some(existing) => format!("{}\n\n{}", existing.trim_end(), entry),
+        None => entry.to_string(),
+    };
+    let updated = timing::time(Stage::Update, client.update_media_row(row.id, &serde_json::json!({ field: text }))).await?;
+    Ok(updated)
+}
+
+fn not_found(reference: &str) -> WcmError {
+    WcmError::NotFound(t!("error.no_book_matching", book = reference).into_owned())
+}
@@ -0,0 +1,46 @@
+use rust_i18n::t;
+use crate::baserow::{BaserowClient, BaserowError, NamedRow};
+use crate::timing::{self, Stage};
+use crate::ui;
+
+/// The row of `rows` whose name has the same `key` as `name`, or failing that the
+/// one whose key is most alike, if at least `min_similarity` (0 to 1) alike.
+pub fn find_row<'a>(rows: &'a [NamedRow], name: &str, key: impl Fn(&str) -> String, min_similarity: f64) -> Option<&'a NamedRow> {
+    let wanted = key(name);
+    let keyed: Vec<(&NamedRow, String)> = rows.iter()
+        .filter_map(|row| row.get_name().map(|existing| (row, key(existing))))
+        .collect();
+    if let Some((row, _)) = keyed.iter().find(|(_, existing)| *existing == wanted) {
+        return Some(row);
+    }
+    keyed.into_iter()
+        .map(|(row, existing)| (row, strsim::normalized_levenshtein(&wanted, &existing)))
+        .filter(|(_, similarity)| *similarity >= min_similarity)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(row, _)| row)
+}
+
+/// Resolves `names` to rows of a linked table such as the authors table, comparing
+/// names by `key` so spelling variants land on one row (see `find_row`). Names
+/// with no row are created as given. Returns the row IDs in the order of `names`,
+/// without repeats.
+pub async fn resolve(client: &BaserowClient, table_id: u64, names: &[String], key: impl Fn(&str) -> String, min_similarity: f64) -> Result<Vec<u64>, BaserowError> {
+    let mut rows: Vec<NamedRow> = timing::time(Stage::Library, client.fetch_all_rows(table_id)).await?;
+    let mut ids = Vec::new();
+    for name in names.iter().filter(|name| !key(name).is_empty()) {
+        let id = match find_row(&rows, name, &key, min_similarity) {
+            Some(row) => row.id,
+            None => {
+                let fields = serde_json::json!({ "Name": name });
+                let created = timing::time(Stage::Create, client.create_row(table_id, &fields)).await?;
+                ui::info(t!("linked.created", name = name));
+                rows.push(NamedRow { id: created.id, fields: serde_json::from_value(fields).unwrap_or_default() });
+                created.id
+            }
+        };
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
@@ -1,7 +1,10 @@
-use reqwest;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use rust_i18n::t;
 use serde::{Deserialize, Serialize};
-use crate::config::{Config, LlmConfig};
+use crate::config::{BudgetFallback, Config, HttpConfig, LlmConfig};
 use crate::baserow::Category;
+use crate::rate_limit::SendWithRetry;
 
 #[derive(Debug, Clone)]
 pub enum LlmProvider {
@@ -15,6 +18,7 @@ pub struct OllamaClient {
     client: reqwest::Client,
     base_url: String,
     model: String,
+    accept_invalid_certs: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +27,8 @@ pub struct OpenAiClient {
     api_key: String,
     base_url: String,
     model: String,
+    /// Where the tokens of each completion are counted, see `llm_budget`
+    usage_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -49,6 +55,47 @@ pub struct OllamaRequest {
 pub struct OllamaResponse {
     pub response: String,
     pub done: bool,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    pub eval_count: Option<u32>,
+}
+
+/// The model list from Ollama's `/api/tags`.
+#[derive(Debug, Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+/// A batch of texts to embed, in the shape both Ollama's `/api/embed` and
+/// OpenAI's `/embeddings` take.
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbeddings {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddings {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -68,6 +115,44 @@ pub struct OpenAiMessage {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OpenAiResponse {
     pub choices: Vec<OpenAiChoice>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OpenAiUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+/// A model's answer with what the provider reported about it.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    pub model: String,
+    pub prompt_tokens: Option<u32>,
+    pub completion_tokens: Option<u32>,
+}
+
+/// A book the LLM suggests reading, with why.
+#[derive(Debug, Clone, Serialize)]
+pub struct BookSuggestion {
+    pub title: String,
+    pub author: String,
+    pub reason: String,
+}
+
+/// What `wcm test --llm` found out about the configured provider.
+#[derive(Debug, Serialize)]
+pub struct LlmTestReport {
+    pub provider: String,
+    pub model: String,
+    pub latency_ms: u128,
+    pub prompt_tokens: Option<u32>,
+    pub completion_tokens: Option<u32>,
+    pub response: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -82,6 +167,8 @@ pub enum LlmError {
     #[allow(dead_code)]
     ModelNotAvailable,
     ConfigurationError(String),
+    /// The paid provider's monthly budget is spent and `llm.budget.fallback` is "none"
+    BudgetExceeded(String),
 }
 
 impl std::fmt::Display for LlmError {
@@ -91,22 +178,60 @@ impl std::fmt::Display for LlmError {
             LlmError::InvalidResponse(msg) => write!(f, "Invalid LLM response: {}", msg),
             LlmError::ModelNotAvailable => write!(f, "LLM model not available"),
             LlmError::ConfigurationError(msg) => write!(f, "LLM configuration error: {}", msg),
+            LlmError::BudgetExceeded(reason) => write!(f, "{}", t!("llm_budget.exceeded", reason = reason)),
         }
     }
 }
 
 impl std::error::Error for LlmError {}
 
+impl LlmError {
+    /// Whether `error` is the `BudgetExceeded` from `LlmProvider::from_config`, so
+    /// callers can go on without the LLM rather than fail.
+    pub fn is_budget_exceeded(error: &(dyn std::error::Error + 'static)) -> bool {
+        matches!(error.downcast_ref::<LlmError>(), Some(LlmError::BudgetExceeded(_)))
+    }
+}
+
 impl From<reqwest::Error> for LlmError {
     fn from(error: reqwest::Error) -> Self {
         LlmError::RequestFailed(error)
     }
 }
 
+/// Set once the budget warning has been shown, so an import warns only once.
+static BUDGET_WARNED: AtomicBool = AtomicBool::new(false);
+
 impl LlmProvider {
+    /// The configured provider, or its fallback once a paid provider has used
+    /// this month's `llm.budget`.
     pub fn from_config(config: &Config) -> Result<Self, LlmError> {
+        let provider = Self::configured(config)?;
+        if !provider.is_paid() {
+            return Ok(provider);
+        }
+        let Some(reason) = budget_exceeded(config)? else {
+            return Ok(provider);
+        };
+
+        let fallback = config.llm.budget.fallback;
+        if !BUDGET_WARNED.swap(true, Ordering::Relaxed) {
+            crate::ui::warn(match fallback {
+                BudgetFallback::Ollama => t!("llm_budget.using_ollama", reason = &reason, model = &config.llm.ollama.model),
+                BudgetFallback::None => t!("llm_budget.using_none", reason = &reason),
+            });
+        }
+        match fallback {
+            BudgetFallback::Ollama => Ok(LlmProvider::Ollama(OllamaClient::new(&config.llm, &config.http)?)),
+            BudgetFallback::None => Err(LlmError::BudgetExceeded(reason)),
+        }
+    }
+
+    /// The provider `llm.provider` names, whatever the budget. For checking the
+    /// setup and for embeddings, which must stay with one model.
+    pub fn configured(config: &Config) -> Result<Self, LlmError> {
         match config.llm.provider.as_str() {
-            "ollama" => Ok(LlmProvider::Ollama(OllamaClient::new(&config.llm)?)),
+            "ollama" => Ok(LlmProvider::Ollama(OllamaClient::new(&config.llm, &config.http)?)),
             "openai" => Ok(LlmProvider::OpenAi(OpenAiClient::new(&config.llm)?)),
             "anthropic" => Ok(LlmProvider::Anthropic(AnthropicClient::new(&config.llm)?)),
             provider => Err(LlmError::ConfigurationError(format!(
@@ -116,6 +241,17 @@ impl LlmProvider {
         }
     }
 
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(self, client: reqwest::Client) -> Self {
+        match self {
+            // An Ollama server with a self-signed certificate keeps the client that accepts it
+            LlmProvider::Ollama(inner) if inner.accept_invalid_certs => LlmProvider::Ollama(inner),
+            LlmProvider::Ollama(inner) => LlmProvider::Ollama(OllamaClient { client, ..inner }),
+            LlmProvider::OpenAi(inner) => LlmProvider::OpenAi(OpenAiClient { client, ..inner }),
+            LlmProvider::Anthropic(inner) => LlmProvider::Anthropic(AnthropicClient { client, ..inner }),
+        }
+    }
+
     pub async fn select_categories(
         &self,
         book_info: &str,
@@ -135,8 +271,9 @@ impl LlmProvider {
         &self,
         book_info: &str,
         target_words: usize,
+        language: Option<&str>,
     ) -> Result<String, LlmError> {
-        let prompt = create_synopsis_prompt(book_info, target_words);
+        let prompt = create_synopsis_prompt(book_info, target_words, language);
         
         let response = match self {
             LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
@@ -156,19 +293,216 @@ impl LlmProvider {
         
         Ok(cleaned_response.to_string())
     }
+
+    /// Sends a tiny fixed prompt and checks that the answer is the expected one,
+    /// so a wrong key or a model Ollama has not pulled shows up before a real add.
+    pub async fn test_connection(&self) -> Result<LlmTestReport, LlmError> {
+        let prompt = "Reply with the single word OK.";
+
+        let started = std::time::Instant::now();
+        let completion = match self {
+            LlmProvider::Ollama(client) => client.complete(prompt).await?,
+            LlmProvider::OpenAi(client) => client.complete(prompt).await?,
+            LlmProvider::Anthropic(client) => client.complete(prompt).await?,
+        };
+        let latency_ms = started.elapsed().as_millis();
+
+        let response = completion.text.trim();
+        if !response.to_lowercase().contains("ok") {
+            return Err(LlmError::InvalidResponse(format!("Expected \"OK\", got: {:?}", response)));
+        }
+
+        Ok(LlmTestReport {
+            provider: self.name().to_string(),
+            model: completion.model,
+            latency_ms,
+            prompt_tokens: completion.prompt_tokens,
+            completion_tokens: completion.completion_tokens,
+            response: response.to_string(),
+        })
+    }
+
+    /// Whether the provider bills per token, which `llm.budget` caps.
+    pub fn is_paid(&self) -> bool {
+        !matches!(self, LlmProvider::Ollama(_))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LlmProvider::Ollama(_) => "ollama",
+            LlmProvider::OpenAi(_) => "openai",
+            LlmProvider::Anthropic(_) => "anthropic",
+        }
+    }
+
+    /// Embeds each of `texts` with the embedding model `model`, in order.
+    pub async fn embed(&self, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let vectors = match self {
+            LlmProvider::Ollama(client) => client.embed(model, texts).await?,
+            LlmProvider::OpenAi(client) => client.embed(model, texts).await?,
+            LlmProvider::Anthropic(_) => return Err(LlmError::ConfigurationError(
+                "Anthropic has no embedding models; set llm.embedding_provider to ollama or openai".to_string()
+            )),
+        };
+        if vectors.len() != texts.len() {
+            return Err(LlmError::InvalidResponse(format!("asked for {} embeddings, got {}", texts.len(), vectors.len())));
+        }
+        Ok(vectors)
+    }
+
+    /// Suggests up to `count` books, not in the library, for someone who liked the
+    /// books described in `library`.
+    pub async fn recommend_books(&self, library: &str, count: usize) -> Result<Vec<BookSuggestion>, LlmError> {
+        let prompt = create_recommendation_prompt(library, count);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        parse_suggestions(&response, count)
+    }
+
+    /// Suggests up to `count` books comparable to the one described in `book`.
+    pub async fn similar_books(&self, book: &str, count: usize) -> Result<Vec<BookSuggestion>, LlmError> {
+        let prompt = create_similar_prompt(book, count);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        parse_suggestions(&response, count)
+    }
+
+    /// Orders `candidates`, books the rules in `wcm next` scored the same, for a
+    /// reader who recently read the books in `recent`. Returns indexes into
+    /// `candidates`, best first, each with a short reason.
+    pub async fn pick_next_book(&self, recent: &str, candidates: &[String]) -> Result<Vec<(usize, String)>, LlmError> {
+        let prompt = create_pick_next_prompt(recent, candidates);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        parse_picks(&response, candidates.len())
+    }
+
+    /// Asks the LLM for a classification number, as a last resort when no catalogue has one.
+    pub async fn suggest_classification(
+        &self,
+        book_info: &str,
+        scheme_name: &str,
+    ) -> Result<String, LlmError> {
+        let prompt = create_classification_prompt(book_info, scheme_name);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        response.lines()
+            .map(|line| line.trim().trim_matches('*').trim())
+            .find(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .ok_or_else(|| LlmError::InvalidResponse("Empty classification response".to_string()))
+    }
+
+    /// Asks the LLM which of `kinds` of content a book has, as brief warnings.
+    /// An empty list means the LLM found none of them.
+    pub async fn content_warnings(&self, book_info: &str, kinds: &[String]) -> Result<Vec<String>, LlmError> {
+        let prompt = create_content_warnings_prompt(book_info, kinds);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        crate::content_warnings::parse_response(&response, kinds)
+            .ok_or_else(|| LlmError::InvalidResponse(format!("No content warnings in the response: {}", response.trim())))
+    }
+
+    /// Asks the LLM to romanize a Thai title.
+    pub async fn transliterate_title(&self, title: &str) -> Result<String, LlmError> {
+        let prompt = create_transliteration_prompt(title);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        crate::transliteration::parse_response(&response)
+            .ok_or_else(|| LlmError::InvalidResponse("No romanized title in the response".to_string()))
+    }
+
+    /// Answers a question about the reader's books from `catalog`, one line per
+    /// book. `partial` says the catalog is only the books closest to the question.
+    pub async fn answer_question(&self, question: &str, catalog: &str, partial: bool) -> Result<String, LlmError> {
+        let prompt = create_question_prompt(question, catalog, partial);
+
+        let response = match self {
+            LlmProvider::Ollama(client) => client.generate_text(&prompt).await?,
+            LlmProvider::OpenAi(client) => client.generate_text(&prompt).await?,
+            LlmProvider::Anthropic(client) => client.generate_text(&prompt).await?,
+        };
+
+        let answer = response.trim();
+        if answer.is_empty() {
+            return Err(LlmError::InvalidResponse("Empty answer".to_string()));
+        }
+        Ok(answer.to_string())
+    }
 }
 
 impl OllamaClient {
-    pub fn new(config: &LlmConfig) -> Result<Self, LlmError> {
-        let client = reqwest::Client::new();
+    pub fn new(config: &LlmConfig, http: &HttpConfig) -> Result<Self, LlmError> {
+        let accept_invalid_certs = config.ollama.accept_invalid_certs;
+        let client = if accept_invalid_certs {
+            crate::http::build_client_accepting_invalid_certs(http)
+                .map_err(|e| LlmError::ConfigurationError(e.to_string()))?
+        } else {
+            reqwest::Client::new()
+        };
         Ok(Self {
             client,
             base_url: config.ollama.base_url.clone(),
             model: config.ollama.model.clone(),
+            accept_invalid_certs,
         })
     }
 
     pub async fn generate_response(&self, prompt: &str) -> Result<String, LlmError> {
+        self.complete(prompt).await.map(|completion| completion.text)
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Names of the models pulled on the server, e.g. "llama3:latest".
+    pub async fn installed_models(&self) -> Result<Vec<String>, LlmError> {
+        let response = self.client
+            .get(format!("{}/api/tags", self.base_url))
+            .send_with_retry()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(LlmError::InvalidResponse(format!("Ollama API returned status: {}", response.status())));
+        }
+
+        let tags: OllamaTags = response.json().await
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        Ok(tags.models.into_iter().map(|model| model.name).collect())
+    }
+
+    pub async fn complete(&self, prompt: &str) -> Result<Completion, LlmError> {
         let request = OllamaRequest {
             model: self.model.clone(),
             prompt: prompt.to_string(),
@@ -176,27 +510,57 @@ impl OllamaClient {
         };
 
         let response = self.client
-            .post(&format!("{}/api/generate", self.base_url))
+            .post(format!("{}/api/generate", self.base_url))
             .json(&request)
-            .send()
+            .send_with_retry()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
             return Err(LlmError::InvalidResponse(format!(
-                "Ollama API returned status: {}",
-                response.status()
-            )));
+                "Ollama API returned status: {} {}",
+                status,
+                error_text
+            ).trim_end().to_string()));
         }
 
         let ollama_response: OllamaResponse = response.json().await
             .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
 
-        Ok(ollama_response.response)
+        Ok(Completion {
+            text: ollama_response.response,
+            model: ollama_response.model.unwrap_or_else(|| self.model.clone()),
+            prompt_tokens: ollama_response.prompt_eval_count,
+            completion_tokens: ollama_response.eval_count,
+        })
     }
 
     pub async fn generate_text(&self, prompt: &str) -> Result<String, LlmError> {
         self.generate_response(prompt).await
     }
+
+    pub async fn embed(&self, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let response = self.client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&EmbeddingRequest { model, input: texts })
+            .send_with_retry()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LlmError::InvalidResponse(format!(
+                "Ollama API returned status: {} {}",
+                status,
+                error_text
+            ).trim_end().to_string()));
+        }
+
+        let embeddings: OllamaEmbeddings = response.json().await
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        Ok(embeddings.embeddings)
+    }
 }
 
 impl OpenAiClient {
@@ -213,10 +577,15 @@ impl OpenAiClient {
             api_key: config.openai.api_key.clone(),
             base_url: config.openai.base_url.clone(),
             model: config.openai.model.clone(),
+            usage_file: Some(&config.budget.usage_file).filter(|file| !file.is_empty()).map(PathBuf::from),
         })
     }
 
     pub async fn generate_response(&self, prompt: &str) -> Result<String, LlmError> {
+        self.complete(prompt).await.map(|completion| completion.text)
+    }
+
+    pub async fn complete(&self, prompt: &str) -> Result<Completion, LlmError> {
         let request = OpenAiRequest {
             model: self.model.clone(),
             messages: vec![OpenAiMessage {
@@ -228,25 +597,40 @@ impl OpenAiClient {
         };
 
         let response = self.client
-            .post(&format!("{}/chat/completions", self.base_url))
+            .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .json(&request)
-            .send()
+            .send_with_retry()
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
             return Err(LlmError::InvalidResponse(format!(
-                "OpenAI API returned status: {}",
-                response.status()
-            )));
+                "OpenAI API returned status: {} {}",
+                status,
+                error_text
+            ).trim_end().to_string()));
         }
 
         let openai_response: OpenAiResponse = response.json().await
             .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
 
+        if let (Some(path), Some(usage)) = (&self.usage_file, &openai_response.usage) {
+            let month = crate::llm_budget::month_of(&crate::dates::today());
+            if let Err(e) = crate::llm_budget::record(path, &month, usage.prompt_tokens, usage.completion_tokens) {
+                crate::ui::detail(format!("Could not record LLM usage in {}: {}", path.display(), e));
+            }
+        }
+
         if let Some(choice) = openai_response.choices.first() {
-            Ok(choice.message.content.clone())
+            Ok(Completion {
+                text: choice.message.content.clone(),
+                model: openai_response.model.clone().unwrap_or_else(|| self.model.clone()),
+                prompt_tokens: openai_response.usage.as_ref().map(|usage| usage.prompt_tokens),
+                completion_tokens: openai_response.usage.as_ref().map(|usage| usage.completion_tokens),
+            })
         } else {
             Err(LlmError::InvalidResponse("No response from OpenAI".to_string()))
         }
@@ -255,6 +639,31 @@ impl OpenAiClient {
     pub async fn generate_text(&self, prompt: &str) -> Result<String, LlmError> {
         self.generate_response(prompt).await
     }
+
+    pub async fn embed(&self, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let response = self.client
+            .post(format!("{}/embeddings", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&EmbeddingRequest { model, input: texts })
+            .send_with_retry()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LlmError::InvalidResponse(format!(
+                "OpenAI API returned status: {} {}",
+                status,
+                error_text
+            ).trim_end().to_string()));
+        }
+
+        let mut embeddings: OpenAiEmbeddings = response.json().await
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        // The API may answer out of order; `index` says which input each is for
+        embeddings.data.sort_by_key(|embedding| embedding.index);
+        Ok(embeddings.data.into_iter().map(|embedding| embedding.embedding).collect())
+    }
 }
 
 impl AnthropicClient {
@@ -274,7 +683,11 @@ impl AnthropicClient {
         })
     }
 
-    pub async fn generate_response(&self, _prompt: &str) -> Result<String, LlmError> {
+    pub async fn generate_response(&self, prompt: &str) -> Result<String, LlmError> {
+        self.complete(prompt).await.map(|completion| completion.text)
+    }
+
+    pub async fn complete(&self, _prompt: &str) -> Result<Completion, LlmError> {
         // Placeholder for Anthropic implementation
         // Would need to implement Claude API calls here
         Err(LlmError::ConfigurationError(
@@ -287,6 +700,18 @@ impl AnthropicClient {
     }
 }
 
+/// Why this month's usage of the paid providers is over `llm.budget`, or
+/// `None` while it is within it or nothing caps it.
+fn budget_exceeded(config: &Config) -> Result<Option<String>, LlmError> {
+    let budget = &config.llm.budget;
+    if budget.usage_file.is_empty() || (budget.monthly_tokens.is_none() && budget.monthly_spend.is_none()) {
+        return Ok(None);
+    }
+    let usage = crate::llm_budget::this_month(std::path::Path::new(&budget.usage_file))
+        .map_err(|e| LlmError::ConfigurationError(e.to_string()))?;
+    Ok(usage.over_budget(budget))
+}
+
 fn create_category_selection_prompt(book_info: &str, categories: &[Category]) -> String {
     let category_list = categories
         .iter()
@@ -316,7 +741,10 @@ RESPONSE FORMAT: Category1, Category2, Category3, Category4, Category5"#,
     )
 }
 
-fn create_synopsis_prompt(book_info: &str, target_words: usize) -> String {
+fn create_synopsis_prompt(book_info: &str, target_words: usize, language: Option<&str>) -> String {
+    let language = language
+        .map(|language| format!("\n6. Write the synopsis in {}, the language of the book", language))
+        .unwrap_or_default();
     format!(
         r#"Based on the book information provided, write a comprehensive synopsis of approximately {} words.
 
@@ -328,15 +756,218 @@ INSTRUCTIONS:
 2. Include main themes, plot elements (without major spoilers), and key characters
 3. Target length: approximately {} words
 4. Write in an informative yet engaging style suitable for a library catalog
-5. Focus on what makes this book unique and interesting to potential readers
+5. Focus on what makes this book unique and interesting to potential readers{}
 
 SYNOPSIS:"#,
         target_words,
         book_info,
-        target_words
+        target_words,
+        language
+    )
+}
+
+fn create_classification_prompt(book_info: &str, scheme_name: &str) -> String {
+    format!(
+        r#"You are a cataloguing librarian. Assign the {} classification number for the book described below.
+
+BOOK INFORMATION:
+{}
+
+INSTRUCTIONS:
+1. Use the class a library catalogue record for this book would most likely carry
+2. Return ONLY the class number on a single line, with no explanation
+3. Do not add an author mark or publication year
+
+CLASS NUMBER:"#,
+        scheme_name,
+        book_info
+    )
+}
+
+fn create_content_warnings_prompt(book_info: &str, kinds: &[String]) -> String {
+    format!(
+        r#"You are a librarian helping a parent decide which books are suitable for children. Say which of these kinds of content the book below contains: {}.
+
+BOOK INFORMATION:
+{}
+
+INSTRUCTIONS:
+1. Use what you know of the book itself, not only the information above
+2. List only kinds from the list above that the book clearly contains, one per line, as: Kind | a few words on what and how strong
+3. Do not mention kinds the book does not contain
+4. If it contains none of them, reply with the single word NONE
+
+CONTENT WARNINGS:"#,
+        kinds.join(", "),
+        book_info
     )
 }
 
+fn create_transliteration_prompt(title: &str) -> String {
+    format!(
+        r#"Romanize this Thai book title using the Royal Thai General System of Transcription (RTGS).
+
+TITLE:
+{}
+
+INSTRUCTIONS:
+1. Transcribe the pronunciation, not the spelling letter by letter
+2. Separate words with spaces and capitalize them as an English title would be
+3. Keep any part of the title that is not in Thai script as it is
+4. Return ONLY the romanized title on a single line, with no explanation
+
+ROMANIZED TITLE:"#,
+        title
+    )
+}
+
+fn create_question_prompt(question: &str, catalog: &str, partial: bool) -> String {
+    let scope = if partial {
+        "These are the books from the reader's library most relevant to the question; the library has others"
+    } else {
+        "This is the reader's whole library"
+    };
+    format!(
+        r#"You are a librarian answering a question about the reader's own books. {}, one per line with its row number first:
+
+{}
+
+QUESTION:
+{}
+
+INSTRUCTIONS:
+1. Answer from the books listed above only; never add books that are not in the list
+2. When the question asks for books, list each matching one as: Title by Author (#row)
+3. Check every condition in the question, such as read or unread, page count, category, status and location, against each book's fields
+4. If no book matches, say so plainly; do not guess at fields a book does not list
+5. Be brief, and answer in the language of the question
+
+ANSWER:"#,
+        scope,
+        catalog,
+        question
+    )
+}
+
+fn create_recommendation_prompt(library: &str, count: usize) -> String {
+    format!(
+        r#"You are a librarian recommending books. These are books the reader owns and rated highly or read recently:
+
+{}
+
+INSTRUCTIONS:
+1. Suggest {} books the reader would enjoy, based on the authors, subjects and styles above
+2. Do not suggest any book in the list above
+3. Prefer variety: not more than two books by the same author
+4. Write one book per line as: Title | Author | One sentence on why it fits this reader
+5. Return ONLY those lines, with no numbering or other text
+
+SUGGESTIONS:"#,
+        library,
+        count
+    )
+}
+
+/// Reads `Title | Author | Reason` lines, skipping anything else the model wrote.
+fn parse_suggestions(response: &str, count: usize) -> Result<Vec<BookSuggestion>, LlmError> {
+    let suggestions: Vec<BookSuggestion> = response.lines()
+        .filter_map(|line| {
+            let line = strip_list_marker(line.trim());
+            let mut parts = line.splitn(3, '|').map(|part| part.trim().trim_matches('*').trim());
+            let title = parts.next().filter(|title| !title.is_empty())?;
+            let author = parts.next().filter(|author| !author.is_empty())?;
+            Some(BookSuggestion {
+                title: title.trim_matches('"').to_string(),
+                author: author.to_string(),
+                reason: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .take(count)
+        .collect();
+
+    if suggestions.is_empty() {
+        return Err(LlmError::InvalidResponse(format!("No suggestions in the response: {}", response.trim())));
+    }
+    Ok(suggestions)
+}
+
+fn create_similar_prompt(book: &str, count: usize) -> String {
+    format!(
+        r#"You are a librarian. A reader wants books comparable to this one:
+
+{}
+
+INSTRUCTIONS:
+1. Suggest {} other books a reader of this one would enjoy: similar subject, style or audience
+2. Do not suggest other editions or translations of the same book
+3. Write one book per line as: Title | Author | One sentence on what it shares with the book above
+4. Return ONLY those lines, with no numbering or other text
+
+SUGGESTIONS:"#,
+        book,
+        count
+    )
+}
+
+fn create_pick_next_prompt(recent: &str, candidates: &[String]) -> String {
+    let numbered: Vec<String> = candidates.iter()
+        .enumerate()
+        .map(|(i, candidate)| format!("{}. {}", i + 1, candidate))
+        .collect();
+    let recent = if recent.is_empty() { "(nothing recorded)" } else { recent };
+    format!(
+        r#"You are helping a reader choose their next book from their own unread shelf.
+
+BOOKS THEY READ MOST RECENTLY:
+{}
+
+CANDIDATES:
+{}
+
+INSTRUCTIONS:
+1. Rank every candidate, best next read first, favouring a change from the recent books
+2. Write one candidate per line as: Number | One short sentence on why
+3. Return ONLY those lines, with no other text
+
+RANKING:"#,
+        recent,
+        numbered.join("\n")
+    )
+}
+
+/// Reads `Number | Reason` lines into indexes into the `count` candidates,
+/// skipping numbers out of range or already given.
+fn parse_picks(response: &str, count: usize) -> Result<Vec<(usize, String)>, LlmError> {
+    let mut picks: Vec<(usize, String)> = Vec::new();
+    for line in response.lines() {
+        let mut parts = line.splitn(2, '|');
+        let number = parts.next().unwrap_or_default().trim().trim_matches(|c: char| !c.is_ascii_digit());
+        let Ok(number) = number.parse::<usize>() else { continue };
+        if number == 0 || number > count || picks.iter().any(|(index, _)| *index == number - 1) {
+            continue;
+        }
+        picks.push((number - 1, parts.next().unwrap_or_default().trim().to_string()));
+    }
+
+    if picks.is_empty() {
+        return Err(LlmError::InvalidResponse(format!("No ranking in the response: {}", response.trim())));
+    }
+    Ok(picks)
+}
+
+/// Drops a leading `-`, `*`, `1.` or `1)` the model may have put before a line,
+/// leaving titles such as "1984" alone.
+fn strip_list_marker(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix(['-', '*']) {
+        return rest.trim_start();
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match line[digits..].strip_prefix(['.', ')']) {
+        Some(rest) if digits > 0 && rest.starts_with(' ') => rest.trim_start(),
+        _ => line,
+    }
+}
+
 fn parse_category_response(response: &str, available_categories: &[Category]) -> Result<Vec<String>, LlmError> {
     let available_names: Vec<String> = available_categories
         .iter()
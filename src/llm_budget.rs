@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use crate::config::LlmBudgetConfig;
+
+/// Serializes updates to the usage file, which the jobs of an import share.
+static USAGE_LOCK: Mutex<()> = Mutex::new(());
+
+/// What the paid providers used in one calendar month.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonthUsage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl MonthUsage {
+    pub fn tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// The spend at the prices in `budget`, which are per million tokens.
+    pub fn spend(&self, budget: &LlmBudgetConfig) -> f64 {
+        (self.prompt_tokens as f64 * budget.input_price + self.completion_tokens as f64 * budget.output_price) / 1_000_000.0
+    }
+
+    /// Why this month's usage is over `budget`, or `None` while it is within it.
+    pub fn over_budget(&self, budget: &LlmBudgetConfig) -> Option<String> {
+        if let Some(cap) = budget.monthly_tokens.filter(|cap| self.tokens() >= *cap) {
+            return Some(t!("llm_budget.tokens", used = self.tokens(), cap = cap).into_owned());
+        }
+        if let Some(cap) = budget.monthly_spend.filter(|cap| self.spend(budget) >= *cap) {
+            return Some(t!("llm_budget.spend", used = format!("{:.2}", self.spend(budget)), cap = format!("{:.2}", cap)).into_owned());
+        }
+        None
+    }
+}
+
+/// Usage by month, as `YYYY-MM`, the way `llm.budget.usage_file` keeps it.
+pub type UsageLog = BTreeMap<String, MonthUsage>;
+
+/// The month `YYYY-MM-DD` falls in.
+pub fn month_of(date: &str) -> String {
+    date.get(..7).unwrap_or(date).to_string()
+}
+
+/// The usage kept in `path`; empty when there is no file yet.
+pub fn load(path: &Path) -> Result<UsageLog, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(UsageLog::new());
+    }
+    let log = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{} is not an LLM usage file: {}", path.display(), e))?;
+    Ok(log)
+}
+
+/// This month's usage from `path`.
+pub fn this_month(path: &Path) -> Result<MonthUsage, Box<dyn std::error::Error>> {
+    Ok(load(path)?.get(&month_of(&crate::dates::today())).copied().unwrap_or_default())
+}
+
+/// Adds one request and its tokens to `month` in `path`.
+pub fn record(path: &Path, month: &str, prompt_tokens: u32, completion_tokens: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = USAGE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut log = load(path)?;
+    let usage = log.entry(month.to_string()).or_default();
+    usage.requests += 1;
+    usage.prompt_tokens += u64::from(prompt_tokens);
+    usage.completion_tokens += u64::from(completion_tokens);
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&log)?)?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::{BaserowClient, MediaRow};
+use crate::config::MediaFieldsConfig;
+use crate::dates;
+use crate::error::WcmError;
+use crate::timing::{self, Stage};
+use crate::ui::{self, Role};
+
+/// `Status` option of a book that is lent out, see `baserow::STATUS_OPTIONS`.
+pub const ON_LOAN: u64 = 3030;
+/// `Status` option a returned book goes back to.
+pub const IN_PLACE: u64 = 3028;
+
+/// A book that is lent out, as `wcm loan` reports it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Loan {
+    pub id: u64,
+    pub title: String,
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub borrower: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loaned_at: Option<String>,
+    /// `YYYY-MM-DD`, when the loan has a due date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+}
+
+impl Loan {
+    fn new(row: &MediaRow, fields: &MediaFieldsConfig) -> Self {
+        Self {
+            id: row.id,
+            title: row.get_text("Title").unwrap_or_default(),
+            author: row.get_text("Author").unwrap_or_default(),
+            borrower: row.get_text(&fields.borrower),
+            loaned_at: row.get_text(&fields.loaned_at).and_then(|date| date.get(..10).map(str::to_string)),
+            due: row.get_text(&fields.loan_due).and_then(|date| date.get(..10).map(str::to_string)),
+        }
+    }
+
+    /// Days past the due date on `today`; 0 or less while it is not overdue,
+    /// `None` without a due date.
+    pub fn days_overdue(&self, today: &str) -> Option<i64> {
+        Some(dates::parse_days(today)? - dates::parse_days(self.due.as_deref()?)?)
+    }
+}
+
+/// Whether the row's `Status` is On Loan.
+pub fn is_on_loan(row: &MediaRow) -> bool {
+    row.fields.get("Status")
+        .and_then(|status| status.get("id").or(Some(status)))
+        .and_then(|id| id.as_u64())
+        == Some(ON_LOAN)
+}
+
+/// Every book that is lent out, in table order.
+pub fn on_loan(rows: &[MediaRow], fields: &MediaFieldsConfig) -> Vec<Loan> {
+    rows.iter().filter(|row| is_on_loan(row)).map(|row| Loan::new(row, fields)).collect()
+}
+
+/// The loans whose due date is before `today`, the longest overdue first.
+pub fn overdue(loans: &[Loan], today: &str) -> Vec<Loan> {
+    let mut overdue: Vec<Loan> = loans.iter()
+        .filter(|loan| loan.days_overdue(today).is_some_and(|days| days > 0))
+        .cloned()
+        .collect();
+    overdue.sort_by_key(|loan| std::cmp::Reverse(loan.days_overdue(today)));
+    overdue
+}
+
+/// Marks `row` as lent to `borrower` from `today`, due back on `due` if given.
+/// The borrower and dates are only written to the fields the table has.
+pub async fn lend(client: &BaserowClient, row: &MediaRow, fields: &MediaFieldsConfig, borrower: &str, due: Option<&str>, today: &str) -> Result<MediaRow, WcmError> {
+    let mut update = serde_json::json!({ "Status": ON_LOAN });
+    set_if_present(&mut update, row, &fields.borrower, borrower.into());
+    set_if_present(&mut update, row, &fields.loaned_at, today.into());
+    if let Some(due) = due {
+        set_if_present(&mut update, row, &fields.loan_due, due.into());
+    }
+    Ok(timing::time(Stage::Update, client.update_media_row(row.id, &update)).await?)
+}
+
+/// Puts `row` back in place and clears its borrower and dates.
+pub async fn give_back(client: &BaserowClient, row: &MediaRow, fields: &MediaFieldsConfig) -> Result<MediaRow, WcmError> {
+    let mut update = serde_json::json!({ "Status": IN_PLACE });
+    set_if_present(&mut update, row, &fields.borrower, "".into());
+    set_if_present(&mut update, row, &fields.loaned_at, serde_json::Value::Null);
+    set_if_present(&mut update, row, &fields.loan_due, serde_json::Value::Null);
+    Ok(timing::time(Stage::Update, client.update_media_row(row.id, &update)).await?)
+}
+
+/// Sets `field` in `update` when the table has it. Baserow returns every field
+/// of a row, so a field missing from `row` is missing from the table.
+fn set_if_present(update: &mut serde_json::Value, row: &MediaRow, field: &str, value: serde_json::Value) {
+    if row.fields.contains_key(field) {
+        update[field] = value;
+    }
+}
+
+/// The overdue loans as a list, with how long each is overdue.
+pub fn format_overdue(overdue: &[Loan], today: &str) -> String {
+    if overdue.is_empty() {
+        return t!("loan.none_overdue").to_string();
+    }
+    let mut lines = vec![ui::paint(Role::Label, t!("loan.overdue_heading", count = overdue.len()))];
+    for loan in overdue {
+        let borrower = loan.borrower.clone().unwrap_or_else(|| t!("loan.unknown_borrower").to_string());
+        lines.push(t!(
+            "loan.overdue_item",
+            title = &loan.title,
+            id = loan.id,
+            borrower = borrower,
+            due = loan.due.as_deref().unwrap_or_default(),
+            days = loan.days_overdue(today).unwrap_or_default(),
+        ).to_string());
+    }
+    lines.join("\n")
+}
+
+/// An iCalendar feed with an all-day event on the due date of every loan that
+/// has one. Each event's UID is stable for the row and due date, so a calendar
+/// subscribed to the file updates the events instead of duplicating them.
+/// `stamp` is the current time as `dates::now` gives it.
+pub fn to_ical(loans: &[Loan], stamp: &str) -> String {
+    let stamp = format!("{}T{}00Z", stamp.get(..10).unwrap_or_default().replace('-', ""), stamp.get(11..16).unwrap_or("00:00").replace(':', ""));
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//wcm//Loans {}//EN", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(&t!("loan.calendar_name"))),
+    ];
+    for loan in loans {
+        let Some(due) = loan.due.as_deref().and_then(dates::parse_days) else {
+            continue;
+        };
+        let summary = match &loan.borrower {
+            Some(borrower) => t!("loan.event_summary_borrower", title = &loan.title, borrower = borrower),
+            None => t!("loan.event_summary", title = &loan.title),
+        };
+        let mut description = t!("loan.event_description", title = &loan.title, author = &loan.author, id = loan.id).to_string();
+        if let Some(loaned_at) = &loan.loaned_at {
+            description.push('\n');
+            description.push_str(&t!("loan.event_lent_on", date = loaned_at));
+        }
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:wcm-loan-{}-{}@wcm", loan.id, ical_date(due)),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", ical_date(due)),
+            format!("DTEND;VALUE=DATE:{}", ical_date(due + 1)),
+            format!("SUMMARY:{}", escape(&summary)),
+            format!("DESCRIPTION:{}", escape(&description)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+fn ical_date(days: i64) -> String {
+    dates::format_days(days).replace('-', "")
+}
+
+/// Escapes the characters RFC 5545 reserves in text values.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Splits a content line into lines of at most 75 bytes, each continuation
+/// starting with a space, without breaking a UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,71 @@
+use serde_json::Value;
+use crate::authors::canonical_name;
+use crate::book_search::ManualBook;
+use crate::rate_limit::SendWithRetry;
+use crate::ui;
+
+/// Looks books up in the Library of Congress catalogue through the loc.gov JSON
+/// API (`classification.loc_base_url`).
+pub struct LocClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl LocClient {
+    pub fn new(base_url: String) -> Self {
+        Self { client: reqwest::Client::new(), base_url }
+    }
+
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Fetches the catalogue record for a normalized LCCN, or `None` when the
+    /// Library of Congress has no item under it.
+    pub async fn find_by_lccn(&self, lccn: &str) -> Result<Option<ManualBook>, Box<dyn std::error::Error>> {
+        let url = format!("{}/item/{}/?fo=json", self.base_url.trim_end_matches('/'), lccn);
+
+        ui::detail(format!("Making Library of Congress request to: {}", url));
+
+        let response = self.client.get(&url).send_with_retry().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Library of Congress API error: {}", response.status()).into());
+        }
+
+        let body: Value = response.json().await?;
+        Ok(book_from_item(body.get("item").unwrap_or(&Value::Null)))
+    }
+}
+
+/// Reads the title and first contributor of a loc.gov item record. Contributor
+/// names come as "Tolkien, J. R. R. (John Ronald Reuel), 1892-1973", so the
+/// fuller form and dates are dropped before the name is turned around.
+pub fn book_from_item(item: &Value) -> Option<ManualBook> {
+    let title = item.get("title").and_then(Value::as_str)?
+        .trim()
+        .trim_end_matches(['/', '.', ' '])
+        .to_string();
+    let author = ["contributor_names", "contributors"].iter()
+        .find_map(|field| item.get(*field).and_then(Value::as_array).and_then(|names| names.first()).and_then(Value::as_str))
+        .map(clean_contributor)
+        .unwrap_or_default();
+    Some(ManualBook { title, author, isbn: None, cover_url: None })
+}
+
+fn clean_contributor(name: &str) -> String {
+    let name = name.split(" (").next().unwrap_or(name);
+    let parts: Vec<&str> = name.split(", ")
+        .take_while(|part| !part.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    let name = parts.join(", ");
+    let name = name.trim_end_matches(',');
+    // A closing period after a full word, not after an initial
+    let last_word = name.rsplit([' ', ',']).next().unwrap_or_default();
+    let name = if last_word.len() > 2 { name.trim_end_matches('.') } else { name };
+    canonical_name(name)
+}
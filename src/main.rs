@@ -1,20 +1,27 @@
-use clap::{Parser, Subcommand};
-
-mod config;
-mod google_books;
-mod open_library;
-mod book_search;
-mod baserow;
-mod web_search;
-mod llm;
-mod label;
-
-use config::Config;
-use google_books::GoogleBooksClient;
-use open_library::OpenLibraryClient;
-use book_search::CombinedBookSearcher;
-use baserow::BaserowClient;
-use label::LabelGenerator;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rust_i18n::t;
+
+use wcm::config::{Config, SheetLayout};
+use wcm::error::{WcmError, EXIT_CANCELLED, EXIT_CONFIG, EXIT_FAILURE, EXIT_USAGE};
+use wcm::google_books::GoogleBooksClient;
+use wcm::i18n;
+use wcm::identifiers::CatalogueId;
+use wcm::filters::SearchFilters;
+use wcm::http_cache::HttpCache;
+use wcm::import::{Checkpoint, ColumnMap, ImportEntry, ImportStatus};
+use wcm::open_library::OpenLibraryClient;
+use wcm::book_search::{CombinedBookSearcher, Condition, CopyDetails, Purchase};
+use wcm::sessions::Session;
+use wcm::baserow::{BaserowClient, Storage};
+use wcm::label::LabelGenerator;
+use wcm::server;
+use wcm::llm::LlmProvider;
+use wcm::ui::{self, Role, Verbosity};
+use wcm::telegram;
+use wcm::timing;
+use wcm::mcp::McpServer;
+
+rust_i18n::i18n!("locales", fallback = "en");
 
 #[derive(Parser)]
 #[command(name = "wcm")]
@@ -23,62 +30,676 @@ use label::LabelGenerator;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[arg(short, long, global = true, help = "Show request URLs and lookup details")]
+    verbose: bool,
+
+    #[arg(short, long, global = true, conflicts_with = "verbose", help = "Only print warnings, errors, prompts and results")]
+    quiet: bool,
+
+    #[arg(long, global = true, help = "Print command results as JSON on stdout (everything else goes to stderr)")]
+    json: bool,
+
+    #[arg(long, global = true, help = "Disable colored output (also set by the NO_COLOR environment variable)")]
+    no_color: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Add {
-        #[arg(long, help = "Add book by ISBN")]
-        isbn: Option<String>,
-        
-        #[arg(long, help = "Book title")]
+    Add(Box<AddArgs>),
+    Test {
+        #[arg(long, help = "Test Baserow connection")]
+        baserow: bool,
+
+        #[arg(long, help = "Send a tiny prompt to the configured LLM and report the model, latency and token usage")]
+        llm: bool,
+
+        #[arg(long, help = "Check every configured service and print a pass/fail table with latencies")]
+        all: bool,
+    },
+    #[command(about = "Find the volumes of a series and add the ones you pick, or list the books matching a title")]
+    Search {
+        #[arg(long, required_unless_present = "title", conflicts_with = "title", help = "Series name, e.g. \"Discworld\"")]
+        series: Option<String>,
+
+        #[arg(long, help = "List the books with this title, to add one later with wcm add --from-last")]
         title: Option<String>,
-        
-        #[arg(long, help = "Book author")]
+
+        #[arg(long, requires = "title", help = "Book author, narrows a --title search")]
         author: Option<String>,
-        
-        #[arg(long, help = "Mark as ebook (default: physical book)")]
+
+        #[arg(long, help = "Mark the added volumes as ebooks (default: physical book)")]
         ebook: bool,
+
+        #[arg(long, help = "Only show editions published in this year")]
+        year: Option<u32>,
+
+        #[arg(long, help = "Only show editions from this publisher (part of the name is enough)")]
+        publisher: Option<String>,
+
+        #[arg(long, value_name = "CODE", help = "Only show editions in this language, e.g. en or eng")]
+        lang: Option<String>,
     },
-    Test {
-        #[arg(long, help = "Test Baserow connection")]
-        baserow: bool,
+    #[command(about = "Add every ISBN listed in a file or CSV spreadsheet, without prompting")]
+    Import {
+        #[arg(help = "File with one ISBN per line (blank lines and lines starting with # are skipped), or a .csv file with a header row")]
+        file: std::path::PathBuf,
+
+        #[arg(long, value_name = "FIELD=COLUMN,...", help = "CSV columns holding the isbn, title and author, e.g. \"isbn=Barcode,title=Name\" (default: columns named isbn, title and author); implies CSV")]
+        map: Option<String>,
+
+        #[arg(long, help = "Mark every book as an ebook (default: physical book)")]
+        ebook: bool,
+
+        #[arg(short, long, value_name = "N", help = "Books to process at once (default: app.import_jobs from config)")]
+        jobs: Option<usize>,
+
+        #[arg(long, help = "Continue an interrupted import, skipping the books its checkpoint lists as added")]
+        resume: bool,
+
+        #[arg(long, help = "Add books even if the library seems to have them already")]
+        allow_duplicates: bool,
+
+        #[arg(long, help = "Do not look up or upload covers, e.g. to backfill them later")]
+        no_cover: bool,
+
+        #[arg(long, value_name = "FILE", help = "Also write the results to FILE, as CSV if it ends in .csv and JSON otherwise")]
+        report: Option<std::path::PathBuf>,
+    },
+    #[command(about = "Resubmit entries that Baserow failed to create")]
+    Retry {
+        #[arg(help = "Saved entry to submit (default: every entry in app.retry_dir)")]
+        files: Vec<std::path::PathBuf>,
     },
     Label {
-        #[arg(long, help = "Generate label by storage ID")]
-        storage_id: Option<u64>,
+        #[arg(long, help = "Generate label by storage ID (repeat for several labels)")]
+        storage_id: Vec<u64>,
         
-        #[arg(long, help = "Generate label by storage name")]
-        storage_name: Option<String>,
+        #[arg(long, help = "Generate label by storage name (repeat for several labels)")]
+        storage_name: Vec<String>,
+
+        #[arg(long, help = "Generate an EAN-13 barcode label for a book's ISBN (repeat for several labels)")]
+        isbn: Vec<String>,
+
+        #[arg(long, help = "Generate a spine label for a book by its media table row ID (repeat for several labels)")]
+        media_id: Vec<u64>,
+
+        #[arg(long, help = "Add a Code128 barcode of the storage ID (default: label.barcode from config)")]
+        barcode: bool,
+
+        #[arg(long, value_name = "N", help = "List the first N book titles in the box on storage labels (default: label.contents from config)")]
+        contents: Option<u32>,
+
+        #[arg(long, help = "Lay the labels out on a PDF label sheet instead of one PNG per label")]
+        pdf: Option<std::path::PathBuf>,
+
+        #[arg(long, help = "Sheet layout for --pdf, e.g. 5160 or L7160 (default: label.sheet from config)")]
+        sheet: Option<String>,
+
+        #[arg(long, default_value_t = 0, help = "Leave the first N positions of the sheet empty")]
+        skip: u32,
+
+        #[arg(long, help = "Label every storage box")]
+        all: bool,
+
+        #[arg(long, visible_alias = "output", help = "Directory for PNG labels (default: label.output_dir from config)")]
+        output_dir: Option<std::path::PathBuf>,
+
+        #[arg(long, help = "Overwrite existing label files instead of adding a numbered suffix")]
+        force: bool,
+
+        #[arg(long, help = "With --all, only storage boxes modified on or after this date (YYYY-MM-DD)")]
+        changed_since: Option<String>,
+
+        #[arg(long, help = "Send the labels to the printer configured under label.printer")]
+        print: bool,
+    },
+    Serve {
+        #[arg(long, help = "Address to listen on (default: server.bind from config)")]
+        bind: Option<String>,
+    },
+    Bot {
+        #[command(subcommand)]
+        platform: BotPlatform,
+    },
+    #[command(about = "Run a Model Context Protocol server on stdio for LLM assistants")]
+    Mcp,
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    #[command(about = "Check the configuration, services, data directories and terminal, and list the fixes to make")]
+    Doctor {
+        #[arg(long, help = "Skip the checks that contact services")]
+        offline: bool,
+    },
+    #[command(about = "Log a reading session and show the progress through the book")]
+    Log {
+        #[arg(help = "Media row ID, ISBN, or part of the title")]
+        book: String,
+
+        #[arg(long, required_unless_present = "minutes", help = "Pages read")]
+        pages: Option<u32>,
+
+        #[arg(long, help = "Minutes spent reading")]
+        minutes: Option<u32>,
+
+        #[arg(long, value_parser = parse_date, help = "Day of the session, YYYY-MM-DD (default: today)")]
+        date: Option<String>,
+    },
+    #[command(about = "Add a memorable quote to a book's quotes field")]
+    Quote {
+        #[arg(help = "Media row ID, ISBN, or part of the title")]
+        book: String,
+
+        #[arg(help = "The quote")]
+        text: String,
+
+        #[arg(long, help = "Page the quote is on")]
+        page: Option<String>,
+    },
+    #[command(about = "Add a timestamped note to a book's notes field")]
+    Note {
+        #[arg(help = "Media row ID, ISBN, or part of the title")]
+        book: String,
+
+        #[arg(help = "The note")]
+        text: String,
+    },
+    #[command(about = "Lend books out, see which are overdue and export their due dates")]
+    Loan {
+        #[command(subcommand)]
+        action: LoanAction,
+    },
+    #[command(about = "Pick what to read next from your unread books")]
+    Next {
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=20), help = "Number of books to suggest")]
+        count: u32,
+
+        #[arg(long, help = "Prefer short books")]
+        busy: bool,
+
+        #[arg(long, help = "Rank by the rules alone, without asking the LLM to break ties")]
+        no_llm: bool,
+    },
+    #[command(about = "Open a book's or storage's row in Baserow in the browser")]
+    Open {
+        #[arg(help = "Row ID, ISBN, or part of the title")]
+        book: Option<String>,
+
+        #[arg(long, value_name = "ID_OR_NAME", conflicts_with = "book", help = "Open a storage instead, by row ID or name")]
+        storage: Option<String>,
+
+        #[arg(long, help = "Print the URL instead of opening it")]
+        print: bool,
+    },
+    #[command(about = "Find books comparable to one in the library")]
+    Similar {
+        #[arg(help = "Row ID, ISBN, or part of the title")]
+        book: String,
+
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=50), help = "Number of books to show")]
+        count: u32,
+
+        #[arg(long, help = "Use the catalogues alone, without asking the LLM")]
+        no_llm: bool,
+    },
+    #[command(about = "Find books in the library by words, or by meaning with --semantic")]
+    Find {
+        #[arg(help = "Words to look for, or with --semantic a description such as \"melancholy sci-fi about memory\"")]
+        query: String,
+
+        #[arg(long, help = "Rank books by how close their synopsis is in meaning, using the embedding model")]
+        semantic: bool,
+
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=50), help = "Number of books to show")]
+        count: u32,
+    },
+    #[command(about = "Ask the LLM a question about the books in the library")]
+    Ask {
+        #[arg(help = "The question, e.g. \"which unread History books under 300 pages do I own?\"")]
+        question: String,
+    },
+    #[command(about = "Ask the LLM for new books based on the ones you rated highly or read recently")]
+    Recommend {
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..=20), help = "Number of suggestions")]
+        count: u32,
+
+        #[arg(long, value_name = "STARS", default_value_t = 7, value_parser = clap::value_parser!(u32).range(1..=10), help = "Lowest rating that counts as highly rated")]
+        min_rating: u32,
+
+        #[arg(long, help = "Look each suggestion up in Google Books and Open Library, dropping any that cannot be found")]
+        check: bool,
+    },
+    #[command(about = "Set a yearly reading target and follow the progress towards it")]
+    Goal {
+        #[command(subcommand)]
+        action: GoalAction,
+    },
+    #[command(about = "Show a year's additions, reading and categories, optionally as a chart")]
+    Stats {
+        #[arg(help = "Year (default: the current year)")]
+        year: Option<u32>,
+        #[arg(long, value_name = "FILE", help = "Also draw the year as a PNG chart")]
+        chart: Option<std::path::PathBuf>,
     },
+    #[command(about = "Manage the categories table")]
+    Categories {
+        #[command(subcommand)]
+        action: CategoriesAction,
+    },
+    #[command(about = "Regenerate short synopses with the LLM and fill in missing covers")]
+    Enrich {
+        #[arg(required_unless_present = "all", conflicts_with = "all", help = "Row ID, ISBN, or part of the title; its synopsis is regenerated whatever its length")]
+        book: Option<String>,
+
+        #[arg(long, help = "Every book whose synopsis is missing or too short, or that has no cover")]
+        all: bool,
+
+        #[arg(long, help = "Leave covers alone")]
+        synopsis_only: bool,
+
+        #[arg(long, value_name = "N", help = "Regenerate synopses shorter than N words (default: app.min_synopsis_words)")]
+        min_words: Option<usize>,
+
+        #[arg(long, help = "Only list the books that would change, without asking the LLM")]
+        dry_run: bool,
+
+        #[arg(long, conflicts_with = "dry_run", help = "Show each new synopsis and ask before saving it")]
+        confirm: bool,
+    },
+    #[command(about = "Manage the ISBNs of books already in the library")]
+    Isbn {
+        #[command(subcommand)]
+        action: IsbnAction,
+    },
+    #[command(about = "Manage book covers")]
+    Covers {
+        #[command(subcommand)]
+        action: CoversAction,
+    },
+    #[command(about = "Write the whole media table to plain-text files, e.g. to keep its history in git")]
+    Export {
+        #[arg(long, value_enum, default_value = "markdown-tree", help = "Output format: one Markdown file per book")]
+        format: ExportFormat,
+
+        #[arg(short, long, value_name = "PATH", help = "Directory to write the files to, default catalog; files of deleted rows are removed. With --template, the file to write instead of stdout")]
+        output: Option<std::path::PathBuf>,
+
+        #[arg(long, value_name = "FILE", help = "Render every book through a MiniJinja template instead, e.g. shelf.md.j2")]
+        template: Option<std::path::PathBuf>,
+    },
+    #[command(about = "Run the maintenance tasks in daemon.tasks on their schedules until stopped")]
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonAction>,
+    },
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct AddArgs {
+    #[command(subcommand)]
+    kind: Option<AddKind>,
+
+    #[arg(long, help = "Add book by ISBN")]
+    isbn: Option<String>,
+    
+    #[arg(long, help = "Book title")]
+    title: Option<String>,
+    
+    #[arg(long, help = "Book author (optional with --title; narrows the search)")]
+    author: Option<String>,
+    
+    #[arg(long, conflicts_with_all = ["isbn", "title", "author"], value_parser = parse_lccn, help = "Library of Congress Control Number, for books without an ISBN")]
+    lccn: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["isbn", "title", "author", "lccn"], value_parser = parse_oclc, help = "OCLC (WorldCat) number, for books without an ISBN")]
+    oclc: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["isbn", "title", "author", "lccn", "oclc"], value_parser = parse_asin, help = "Amazon ASIN, for Kindle ebooks without an ISBN (implies --ebook unless the ASIN is an ISBN-10)")]
+    asin: Option<String>,
+
+    #[arg(long, help = "Mark as ebook (default: physical book)")]
+    ebook: bool,
+
+    #[arg(long, conflicts_with_all = ["isbn", "lccn", "oclc", "asin"], help = "Enter every field by hand, without looking the book up (--title and --author prefill the form)")]
+    manual: bool,
+
+    #[arg(long, value_name = "FILE", help = "Write the confirmed entry to FILE instead of adding it to Baserow")]
+    save_draft: Option<std::path::PathBuf>,
+
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["isbn", "title", "author", "lccn", "oclc", "asin", "manual", "save_draft"], help = "Review and submit an entry saved with --save-draft")]
+    from_draft: Option<std::path::PathBuf>,
+
+    #[arg(long, conflicts_with_all = ["isbn", "manual", "from_draft"], help = "Only show editions published in this year")]
+    year: Option<u32>,
+
+    #[arg(long, conflicts_with_all = ["isbn", "manual", "from_draft"], help = "Only show editions from this publisher (part of the name is enough)")]
+    publisher: Option<String>,
+
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["isbn", "manual", "from_draft"], help = "Only show editions in this language, e.g. en or eng")]
+    lang: Option<String>,
+
+    #[arg(long = "category", value_name = "NAME", conflicts_with = "from_draft", help = "File the book under this category instead of asking the LLM (repeat for several)")]
+    categories: Vec<String>,
+
+    #[arg(long, value_name = "TEXT", conflicts_with = "from_draft", help = "Store this synopsis as is, without the length check or LLM generation")]
+    synopsis: Option<String>,
+
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["synopsis", "from_draft"], help = "Store the synopsis in FILE as is, e.g. the publisher's blurb")]
+    synopsis_file: Option<std::path::PathBuf>,
+
+    #[arg(long, help = "Do not look up or upload a cover")]
+    no_cover: bool,
+
+    #[arg(long, value_name = "NAME", conflicts_with = "from_draft", help = "Record the book as part of this series")]
+    series: Option<String>,
+
+    #[arg(long, value_name = "N", conflicts_with = "from_draft", help = "Record the book's volume number in its series")]
+    volume_number: Option<u32>,
+
+    #[arg(long, value_name = "AMOUNT", conflicts_with = "from_draft", value_parser = parse_price, help = "Record what the book cost, e.g. 12.50")]
+    price: Option<f64>,
+
+    #[arg(long, value_name = "DATE", conflicts_with = "from_draft", value_parser = parse_date, help = "Record when the book was bought (YYYY-MM-DD)")]
+    purchased_at: Option<String>,
+
+    #[arg(long, value_name = "NAME", conflicts_with = "from_draft", help = "Record the shop the book was bought from")]
+    store: Option<String>,
+
+    #[arg(long, value_name = "CONDITION", conflicts_with = "from_draft", value_parser = parse_condition, help = "Record the copy's condition: new, good or worn")]
+    condition: Option<Condition>,
+
+    #[arg(long, value_name = "TEXT", conflicts_with = "from_draft", help = "Record the printing or edition, e.g. \"First edition, third printing\"")]
+    edition_note: Option<String>,
+
+    #[arg(long, conflicts_with = "from_draft", help = "Mark the copy as signed")]
+    signed: bool,
+
+    #[arg(long, value_name = "TEXT", conflicts_with = "from_draft", help = "Record where the copy came from, e.g. \"Gift from Grandma\" or \"Secondhand shop\"")]
+    provenance: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["lccn", "oclc", "asin", "manual", "from_draft"], help = "Search Google Books and Open Library both and show their records side by side before choosing")]
+    compare: bool,
+
+    #[arg(long, conflicts_with_all = ["asin", "manual", "save_draft", "from_draft"], help = "Add the book as a box set, then an entry for each volume in it linked to the box set")]
+    boxset: bool,
+
+    #[arg(long = "volume", value_name = "TITLE", requires = "boxset", help = "Title of a volume in the box set, in order (repeat for each; asked for when not given)")]
+    volumes: Vec<String>,
+
+    #[arg(long, conflicts_with = "from_draft", help = "Mark the book as already read")]
+    read: bool,
+
+    #[arg(long, value_name = "STARS", conflicts_with = "from_draft", value_parser = clap::value_parser!(u32).range(0..=10), help = "Rate the book, 1 to 10 stars (0 for unrated)")]
+    rating: Option<u32>,
+
+    #[arg(long, help = "Review the entry as YAML in $EDITOR instead of the confirmation menu")]
+    edit: bool,
+
+    #[arg(long, value_name = "N", conflicts_with_all = ["isbn", "title", "author", "lccn", "oclc", "asin", "manual", "from_draft", "boxset"], value_parser = clap::value_parser!(u32).range(1..), help = "Add the Nth result of the last search, without searching again")]
+    from_last: Option<u32>,
+
+    #[arg(long, conflicts_with_all = ["from_draft", "boxset"], help = "Work from the local caches only and queue the entry for wcm retry")]
+    offline: bool,
+}
+
+#[derive(Subcommand)]
+enum AddKind {
+    #[command(about = "Add one issue of a magazine or other periodical, without an ISBN lookup")]
+    Magazine {
+        #[arg(long, help = "Name of the magazine, e.g. Wired")]
+        title: String,
+
+        #[arg(long, value_name = "DATE", required_unless_present = "number", value_parser = parse_issue_date, help = "Cover date of the issue: YYYY, YYYY-MM or YYYY-MM-DD")]
+        issue: Option<String>,
+
+        #[arg(long, value_name = "NUMBER", help = "Issue number as printed, e.g. 142")]
+        number: Option<String>,
+
+        #[arg(long, help = "Publisher, stored as the author")]
+        publisher: Option<String>,
+
+        #[arg(long, value_name = "URL", help = "Download the cover from this URL")]
+        cover: Option<String>,
+
+        #[arg(long, value_name = "FILE", conflicts_with = "cover", help = "Upload this scan or photo as the cover")]
+        cover_file: Option<std::path::PathBuf>,
+
+        #[arg(long = "category", value_name = "NAME", help = "File the issue under this category (repeat for several; asked for when not given)")]
+        categories: Vec<String>,
+
+        #[arg(long, value_name = "TEXT", help = "Store this description of the issue")]
+        synopsis: Option<String>,
+
+        #[arg(long, value_name = "AMOUNT", value_parser = parse_price, help = "Record what the issue cost, e.g. 6.99")]
+        price: Option<f64>,
+
+        #[arg(long, value_name = "DATE", value_parser = parse_date, help = "Record when the issue was bought (YYYY-MM-DD)")]
+        purchased_at: Option<String>,
+
+        #[arg(long, value_name = "NAME", help = "Record the shop the issue was bought from")]
+        store: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    #[command(about = "Check every setting and report each problem with a suggested fix")]
+    Check {
+        #[arg(long, help = "Only check the settings themselves, without contacting any service")]
+        offline: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GoalAction {
+    #[command(about = "Set how many books to read in a year")]
+    Set {
+        #[arg(help = "Year, e.g. 2025")]
+        year: u32,
+        #[arg(help = "Number of books")]
+        target: u32,
+    },
+    #[command(about = "Show the books read so far against the year's target")]
+    Status {
+        #[arg(help = "Year (default: the current year)")]
+        year: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LoanAction {
+    #[command(about = "Mark a book as lent to someone, optionally with a due date")]
+    Lend {
+        #[arg(help = "Media row ID, ISBN, or part of the title")]
+        book: String,
+        #[arg(long, value_name = "NAME", help = "Who the book is lent to")]
+        to: String,
+        #[arg(long, value_name = "DATE", value_parser = parse_date, help = "When the book is due back (YYYY-MM-DD)")]
+        due: Option<String>,
+        #[arg(long, value_name = "N", conflicts_with = "due", help = "Due back N days from today")]
+        days: Option<u32>,
+    },
+    #[command(about = "Mark a lent book as back in place")]
+    Return {
+        #[arg(help = "Media row ID, ISBN, or part of the title")]
+        book: String,
+    },
+    #[command(about = "List the lent books past their due date")]
+    Overdue,
+    #[command(about = "Write the due dates of lent books as an iCalendar (.ics) feed")]
+    ExportIcal {
+        #[arg(long, short, value_name = "FILE", help = "Write the feed to this file instead of stdout")]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CategoriesAction {
+    #[command(about = "Create, rename and describe categories to match a canonical YAML list")]
+    Sync {
+        #[arg(help = "YAML list of categories, each with a name, description and optional aliases")]
+        file: std::path::PathBuf,
+        #[arg(long, help = "Show the changes without making them")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CoversAction {
+    #[command(about = "Find and upload covers for every book without one")]
+    Backfill {
+        #[arg(long, value_name = "N", help = "Only process the first N books without a cover")]
+        limit: Option<usize>,
+        #[arg(long, value_name = "MS", default_value_t = 1000, help = "Milliseconds to wait between books")]
+        delay_ms: u64,
+        #[arg(long, help = "Only look the covers up, without uploading them")]
+        dry_run: bool,
+    },
+    #[command(about = "Hash the covers already in the library, so new books with the same cover are flagged as possible duplicates")]
+    Hash {
+        #[arg(long, value_name = "N", help = "Only process the first N covers without a hash")]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IsbnAction {
+    #[command(about = "Search by title and author for books without an ISBN and pick one for each")]
+    Backfill {
+        #[arg(long, value_name = "N", help = "Only process the first N books without an ISBN")]
+        limit: Option<usize>,
+        #[arg(long, value_name = "MS", default_value_t = 1000, help = "Milliseconds to wait between books")]
+        delay_ms: u64,
+        #[arg(long, help = "Only show the best match for each book, without saving anything")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    MarkdownTree,
+}
+
+#[derive(Subcommand)]
+enum DaemonAction {
+    #[command(about = "Show when each task last ran, how it went and when it runs next")]
+    Status,
+}
+
+#[derive(Subcommand)]
+enum BotPlatform {
+    #[command(about = "Run a Telegram bot that adds books from chat messages and barcode photos")]
+    Telegram,
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let verbosity = if cli.quiet {
+        Verbosity::Quiet
+    } else if cli.verbose {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+    ui::init(verbosity, cli.json);
+    if cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        ui::disable_colors();
+    }
+    // Until the config is loaded, messages follow the system locale
+    i18n::init(None);
     
+    // Handled before the configuration must load, since a broken one is what it reports
+    if let Commands::Doctor { offline } = &cli.command {
+        let loaded = Config::load().map_err(|e| e.to_string());
+        if let Ok(config) = &loaded {
+            ui::set_theme(&config.theme);
+            if config.app.language.is_some() {
+                i18n::init(config.app.language.as_deref());
+            }
+        }
+        ui::info(t!("doctor.running"));
+        let findings = wcm::doctor::run(loaded, *offline).await;
+        ui::result(wcm::doctor::format_report(&findings), &findings);
+        if findings.iter().any(|finding| finding.severity == wcm::config_check::Severity::Error) {
+            std::process::exit(EXIT_CONFIG);
+        }
+        return;
+    }
+
     // Load configuration
     let config = match Config::load() {
         Ok(config) => config,
         Err(e) => {
-            eprintln!("Error loading configuration: {}", e);
-            eprintln!("Make sure config.yaml exists or required environment variables are set.");
-            std::process::exit(1);
+            ui::error(t!("error.loading_config", error = e));
+            ui::error(t!("error.config_hint"));
+            std::process::exit(EXIT_CONFIG);
         }
     };
     
-    // Validate configuration
-    if let Err(e) = config.validate() {
-        eprintln!("Configuration validation failed: {}", e);
-        eprintln!("Please check your config.yaml or .env file.");
-        std::process::exit(1);
+    // Validate configuration; `config check` reports every problem itself
+    let checking_config = matches!(cli.command, Commands::Config { .. });
+    let missing = config.missing_required();
+    if !missing.is_empty() && !checking_config {
+        ui::error(t!("error.missing_settings"));
+        for (setting, variable) in missing {
+            ui::error(t!("error.missing_setting", variable = variable, setting = setting));
+        }
+        std::process::exit(EXIT_CONFIG);
+    }
+    if let Err(e) = config.validate().or_else(|e| if checking_config { Ok(()) } else { Err(e) }) {
+        ui::error(t!("error.invalid_config", error = e));
+        ui::error(t!("error.invalid_config_hint"));
+        std::process::exit(EXIT_CONFIG);
     }
     
-    if config.app.verbose {
-        println!("Configuration loaded successfully");
-        println!("LLM Provider: {}", config.llm.provider);
+    ui::set_theme(&config.theme);
+    wcm::rate_limit::set_max_retries(config.app.rate_limit_retries);
+    if config.app.language.is_some() {
+        i18n::init(config.app.language.as_deref());
     }
 
+    // app.verbose turns on verbose output unless --quiet was given
+    if config.app.verbose && !cli.quiet {
+        ui::init(Verbosity::Verbose, cli.json);
+    }
+    ui::detail("Configuration loaded successfully");
+    ui::detail(format!("LLM Provider: {}", config.llm.provider));
+
+    // Handled before the clients are built, so a broken `http` section is reported too
+    if let Commands::Config { action: ConfigAction::Check { offline } } = &cli.command {
+        ui::info(t!("config_check.running"));
+        let findings = wcm::config_check::run(&config, *offline).await;
+        ui::result(wcm::config_check::format_report(&findings), &findings);
+        if findings.iter().any(|finding| finding.severity == wcm::config_check::Severity::Error) {
+            std::process::exit(EXIT_CONFIG);
+        }
+        return;
+    }
+    // Reads the status file only, so it works while the daemon holds the services
+    if let Commands::Daemon { action: Some(DaemonAction::Status) } = &cli.command {
+        let path = std::path::Path::new(&config.daemon.status_file);
+        match wcm::daemon::DaemonStatus::load(path) {
+            Ok(status) => ui::result(wcm::daemon::format_status(&status), &status),
+            Err(e) => exit_with_error(&t!("daemon.no_status", path = path.display()), e),
+        }
+        return;
+    }
+
+    // One HTTP client for every service, so connections are pooled and the
+    // settings in `http` apply everywhere
+    let http_client = match wcm::http::build_client(&config.http) {
+        Ok(client) => client,
+        Err(e) => {
+            ui::error(t!("error.invalid_config", error = e));
+            ui::error(t!("error.invalid_config_hint"));
+            std::process::exit(EXIT_CONFIG);
+        }
+    };
+
     // Create API clients
     let google_client = GoogleBooksClient::new(
         config.google_books.api_key.clone(),
@@ -87,68 +708,1070 @@ async fn main() {
     let open_library_client = OpenLibraryClient::new(
         config.open_library.base_url.clone(),
     );
-    let baserow_client = BaserowClient::new(config.baserow.clone());
+    let offline = matches!(&cli.command, Commands::Add(add) if add.offline);
+    let (google_client, open_library_client) = match HttpCache::from_config(&config.app) {
+        Some(cache) => {
+            let cache = cache.with_offline(offline);
+            (google_client.with_cache(cache.clone()), open_library_client.with_cache(cache))
+        }
+        None if offline => {
+            ui::error(t!("offline.no_cache_dir"));
+            std::process::exit(EXIT_CONFIG);
+        }
+        None => (google_client, open_library_client),
+    };
+    let baserow_http_client = if config.baserow.accept_invalid_certs {
+        match wcm::http::build_client_accepting_invalid_certs(&config.http) {
+            Ok(client) => client,
+            Err(e) => {
+                ui::error(t!("error.invalid_config", error = e));
+                ui::error(t!("error.invalid_config_hint"));
+                std::process::exit(EXIT_CONFIG);
+            }
+        }
+    } else {
+        http_client.clone()
+    };
+    let baserow_client = BaserowClient::new(config.baserow.clone()).with_http_client(baserow_http_client);
 
     // Create combined searcher and label generator
-    let searcher = CombinedBookSearcher::new(google_client, open_library_client, baserow_client.clone(), config.clone());
-    let label_generator = LabelGenerator::new(baserow_client.clone(), config.baserow.base_url.clone());
+    let searcher = CombinedBookSearcher::new(google_client, open_library_client, baserow_client.clone(), config.clone())
+        .with_http_client(http_client.clone());
+    let label_generator = LabelGenerator::new(baserow_client.clone(), config.baserow.base_url.clone())
+        .with_barcode(config.label.barcode)
+        .with_contents(config.label.contents)
+        .with_call_number_field(config.classification.field.clone());
 
     match &cli.command {
-        Commands::Add { isbn, title, author, ebook } => {
-            if let Some(isbn_value) = isbn {
-                if config.app.verbose {
-                    println!("Adding {} by ISBN: {}", if *ebook { "ebook" } else { "book" }, isbn_value);
+        Commands::Add(add) => match add.as_ref() {
+            AddArgs { kind: Some(AddKind::Magazine { title, issue, number, publisher, cover, cover_file, categories, synopsis, price, purchased_at, store }), offline, .. } => {
+                let issue = wcm::periodicals::Issue {
+                    date: issue.clone(),
+                    number: number.as_deref().map(str::trim).filter(|number| !number.is_empty()).map(str::to_string),
+                };
+                let searcher = searcher
+                    .with_categories(categories.clone())
+                    .with_synopsis(synopsis.clone())
+                    .with_purchase(Purchase { price: *price, purchased_at: purchased_at.clone(), store: store.clone() })
+                    .with_offline(*offline);
+                ui::detail(format!("Adding magazine issue: {}", wcm::periodicals::issue_title(title, &issue)));
+                if let Err(e) = searcher.add_magazine(title, issue, publisher.as_deref(), cover.as_deref(), cover_file.as_deref()).await {
+                    exit_with_error(&t!("error.add_magazine"), e);
                 }
-                if let Err(e) = add_book_by_isbn(isbn_value, &searcher, *ebook).await {
-                    eprintln!("Error adding book by ISBN: {}", e);
-                    std::process::exit(1);
+                timing::report();
+            }
+            AddArgs { kind: None, isbn, title, author, lccn, oclc, asin, ebook, manual, save_draft, from_draft, year, publisher, lang, categories, synopsis, synopsis_file, no_cover, series, volume_number, price, purchased_at, store, condition, edition_note, signed, provenance, compare, boxset, volumes, read, rating, edit, from_last, offline } => {
+                let synopsis = match synopsis_file {
+                    Some(path) => match std::fs::read_to_string(path) {
+                        Ok(text) => Some(text),
+                        Err(e) => exit_with_error(&t!("error.reading_file", file = path.display()), e),
+                    },
+                    None => synopsis.clone(),
+                };
+                let filters = SearchFilters { year: *year, publisher: publisher.clone(), lang: lang.clone() };
+                let mut defaults = config.app.defaults.clone();
+                defaults.read |= *read;
+                defaults.rating = rating.unwrap_or(defaults.rating);
+                let searcher = searcher.with_draft_output(save_draft.clone()).with_filters(filters)
+                    .with_categories(categories.clone())
+                    .with_synopsis(synopsis)
+                    .with_covers(!*no_cover)
+                    .with_series(series.clone(), *volume_number)
+                    .with_asin(asin.clone())
+                    .with_compare(*compare)
+                    .with_edit(*edit)
+                    .with_offline(*offline)
+                    .with_boxset(boxset.then(|| volumes.clone()))
+                    .with_purchase(Purchase { price: *price, purchased_at: purchased_at.clone(), store: store.clone() })
+                    .with_copy_details(CopyDetails { condition: *condition, edition: edition_note.clone(), signed: *signed, provenance: provenance.clone() })
+                    .with_entry_defaults(defaults);
+                if let Some(path) = from_draft {
+                    if let Err(e) = searcher.add_from_draft(path).await {
+                        exit_with_error(&t!("error.add_from_draft"), e);
+                    }
+                } else if let Some(number) = from_last {
+                    if let Err(e) = searcher.add_from_last(*number as usize, *ebook).await {
+                        exit_with_error(&t!("error.add_from_last"), e);
+                    }
+                } else if *manual {
+                    if let Err(e) = searcher.add_manually(title.as_deref(), author.as_deref(), *ebook).await {
+                        exit_with_error(&t!("error.add_manually"), e);
+                    }
+                } else if let Some(isbn_value) = isbn {
+                    ui::detail(format!("Adding {} by ISBN: {}", if *ebook { "ebook" } else { "book" }, isbn_value));
+                    if let Err(e) = add_book_by_isbn(isbn_value, &searcher, *ebook).await {
+                        exit_with_error(&t!("error.add_by_isbn"), e);
+                    }
+                } else if let Some(asin_value) = asin {
+                    // An ASIN that is an ISBN-10 is a printed book's
+                    let is_ebook = *ebook || wcm::identifiers::asin_as_isbn(asin_value).is_none();
+                    ui::detail(format!("Adding {} by ASIN: {}", if is_ebook { "ebook" } else { "book" }, asin_value));
+                    if let Err(e) = searcher.search_by_asin(asin_value, is_ebook).await {
+                        exit_with_error(&t!("error.add_by_asin"), e);
+                    }
+                } else if let Some(id) = lccn.clone().map(CatalogueId::Lccn).or_else(|| oclc.clone().map(CatalogueId::Oclc)) {
+                    ui::detail(format!("Adding {} by {}", if *ebook { "ebook" } else { "book" }, id));
+                    if let Err(e) = searcher.search_by_catalogue_id(&id, *ebook).await {
+                        exit_with_error(&t!("error.add_by_catalogue_id"), e);
+                    }
+                } else if let Some(title_value) = title {
+                    ui::detail(format!("Adding {} by title: '{}' and author: '{}'", if *ebook { "ebook" } else { "book" }, title_value, author.as_deref().unwrap_or("any")));
+                    if let Err(e) = add_book_by_title_author(title_value, author.as_deref(), &searcher, *ebook).await {
+                        exit_with_error(&t!("error.add_by_title"), e);
+                    }
+                } else {
+                    ui::error(t!("error.add_usage"));
+                    std::process::exit(EXIT_USAGE);
+                }
+                timing::report();
+            }
+        },
+        Commands::Search { series, title, author, ebook, year, publisher, lang } => {
+            let filters = SearchFilters { year: *year, publisher: publisher.clone(), lang: lang.clone() };
+            let searcher = searcher.with_filters(filters);
+            match (series, title) {
+                (Some(series), _) => {
+                    if let Err(e) = searcher.add_series(series, *ebook).await {
+                        exit_with_error(&t!("error.search_series"), e);
+                    }
+                }
+                (None, Some(title)) => match searcher.search_and_remember(title, author.as_deref()).await {
+                    Ok(search) => ui::result(wcm::last_search::format_results(&search), &search),
+                    Err(e) => exit_with_error(&t!("error.search_title"), e),
+                },
+                (None, None) => unreachable!("clap requires --series or --title"),
+            }
+            timing::report();
+        }
+        Commands::Test { baserow, llm, all } => {
+            if *all {
+                ui::info(t!("test.running"));
+                let results = wcm::diagnostics::run_all(&config, &baserow_client, &http_client).await;
+                ui::result(wcm::diagnostics::format_table(&results), &results);
+
+                let failed = results.iter().filter(|result| !result.ok).count();
+                if failed > 0 {
+                    ui::error(t!("test.failed", failed = failed, total = results.len()));
+                    std::process::exit(EXIT_FAILURE);
                 }
-            } else if let (Some(title_value), Some(author_value)) = (title, author) {
-                if config.app.verbose {
-                    println!("Adding {} by title: '{}' and author: '{}'", if *ebook { "ebook" } else { "book" }, title_value, author_value);
+            } else {
+                if *baserow {
+                    if let Err(e) = baserow_client.test_connection().await {
+                        exit_with_error(&t!("error.connection_test"), e);
+                    }
+                    ui::result(t!("result.connection_ok"), &serde_json::json!({ "baserow": "ok" }));
                 }
-                if let Err(e) = add_book_by_title_author(title_value, author_value, &searcher, *ebook).await {
-                    eprintln!("Error adding book by title/author: {}", e);
-                    std::process::exit(1);
+                if *llm {
+                    test_llm(&config, &http_client).await;
                 }
+            }
+        }
+        Commands::Import { file, map, ebook, jobs, resume, allow_duplicates, no_cover, report } => {
+            let is_csv = map.is_some() || file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+            let entries = if is_csv {
+                let map = match map.as_deref().map(ColumnMap::parse).transpose() {
+                    Ok(map) => map.unwrap_or_default(),
+                    Err(e) => {
+                        ui::error(e);
+                        std::process::exit(EXIT_USAGE);
+                    }
+                };
+                wcm::import::read_csv(file, &map)
             } else {
-                eprintln!("Error: Please provide either --isbn OR both --title and --author");
-                std::process::exit(1);
+                wcm::import::read_isbns(file).map(|isbns| isbns.into_iter().map(ImportEntry::Isbn).collect())
+            };
+            let entries = match entries {
+                Ok(entries) => entries,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = file.display()), e),
+            };
+
+            let checkpoint_path = Checkpoint::path_for(file);
+            if checkpoint_path.exists() && !*resume {
+                ui::error(t!("import.checkpoint_exists", path = checkpoint_path.display()));
+                std::process::exit(EXIT_USAGE);
+            }
+            let mut checkpoint = match Checkpoint::open(checkpoint_path.clone(), *resume) {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = checkpoint_path.display()), e),
+            };
+
+            let library = if *allow_duplicates {
+                None
+            } else {
+                ui::detail("Loading the library to check for duplicates...");
+                match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                    Ok(rows) => Some(std::sync::Arc::new(rows)),
+                    Err(e) => {
+                        ui::warn(t!("import.duplicates_unchecked", error = e));
+                        None
+                    }
+                }
+            };
+
+            let jobs = jobs.unwrap_or(config.app.import_jobs).max(1);
+            ui::info(t!("import.starting", count = entries.len(), jobs = jobs));
+
+            let items = wcm::import::run(std::sync::Arc::new(searcher.with_covers(!*no_cover)), entries, *ebook, jobs, library, &mut checkpoint).await;
+            ui::result(wcm::import::format_summary(&items), &items);
+            if let Some(report) = report {
+                match wcm::import::write_report(report, &items) {
+                    Ok(()) => ui::info(t!("import.report_written", path = report.display())),
+                    Err(e) => ui::error(t!("import.report_failed", path = report.display(), error = e)),
+                }
+            }
+            timing::report();
+
+            let interrupted = items.iter().any(|item| item.status == ImportStatus::Interrupted);
+            let failed = items.iter().any(|item| matches!(item.status, ImportStatus::Failed | ImportStatus::NotFound));
+            if failed || interrupted {
+                let file = match map {
+                    Some(map) => format!("{} --map \"{}\"", file.display(), map),
+                    None => file.display().to_string(),
+                };
+                ui::info(t!("import.resume_hint", file = file));
+                std::process::exit(if interrupted { EXIT_CANCELLED } else { EXIT_FAILURE });
+            }
+            if let Err(e) = checkpoint.remove() {
+                ui::warn(t!("import.checkpoint_failed", error = e));
+            }
+        }
+        Commands::Retry { files } => {
+            let files = if files.is_empty() {
+                match wcm::retry::pending_entries(std::path::Path::new(&config.app.retry_dir)) {
+                    Ok(files) => files,
+                    Err(e) => {
+                        exit_with_error(&t!("error.reading_dir", dir = config.app.retry_dir), e);
+                    }
+                }
+            } else {
+                files.clone()
+            };
+            if files.is_empty() {
+                ui::info(t!("retry.nothing", dir = config.app.retry_dir));
+                return;
+            }
+
+            let mut failed = 0;
+            let mut exit_code = 0;
+            for file in &files {
+                match wcm::retry::retry_entry(&baserow_client, file, &config.hooks).await {
+                    Ok(id) => ui::result(
+                        t!("result.retried", file = file.display(), id = id),
+                        &serde_json::json!({ "file": file, "id": id }),
+                    ),
+                    Err(e) => {
+                        let e = WcmError::from(e);
+                        ui::error(format!("{}: {}", t!("retry.failed", file = file.display()), e));
+                        exit_code = e.exit_code();
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                ui::error(t!("retry.still_waiting", failed = failed, total = files.len()));
+                std::process::exit(exit_code);
+            }
+        }
+        Commands::Label { storage_id, storage_name, isbn, media_id, barcode, contents, pdf, sheet, skip, all, output_dir, force, changed_since, print } => {
+            let label_generator = match label_generator
+                .with_barcode(config.label.barcode || *barcode)
+                .with_contents(contents.unwrap_or(config.label.contents))
+                .with_overwrite(*force)
+                .with_logo(config.label.logo.as_ref())
+            {
+                Ok(label_generator) => label_generator,
+                Err(e) => {
+                    exit_with_error(&t!("error.generic"), e);
+                }
+            };
+            let output_dir = output_dir.clone().unwrap_or_else(|| config.label.output_dir.clone().into());
+            if storage_id.is_empty() && storage_name.is_empty() && isbn.is_empty() && media_id.is_empty() && !*all {
+                ui::error(t!("error.label_usage"));
+                std::process::exit(EXIT_USAGE);
+            }
+            if changed_since.is_some() && !*all {
+                ui::error(t!("error.changed_since_usage"));
+                std::process::exit(EXIT_USAGE);
+            }
+
+            let storages = if *all || (pdf.is_some() && !(storage_id.is_empty() && storage_name.is_empty())) {
+                let (ids, names) = if *all { (&[][..], &[][..]) } else { (&storage_id[..], &storage_name[..]) };
+                match select_storages(&label_generator, ids, names, changed_since.as_deref(), &config.label.modified_field).await {
+                    Ok(storages) => storages,
+                    Err(e) => {
+                        exit_with_error(&t!("error.looking_up_storage"), e);
+                    }
+                }
+            } else {
+                Vec::new()
+            };
+
+            let mut written = Vec::new();
+            if let Some(pdf_path) = pdf {
+                let sheet_name = sheet.clone().unwrap_or_else(|| config.label.sheet.clone());
+                let Some(layout) = config.label.find_sheet(&sheet_name) else {
+                    ui::error(t!("error.unknown_sheet", name = sheet_name, builtin = SheetLayout::BUILTIN_NAMES.join(", ")));
+                    std::process::exit(EXIT_USAGE);
+                };
+                match generate_label_sheet(&label_generator, &config, &storages, isbn, media_id, &layout, *skip, pdf_path).await {
+                    Ok(path) => written.push(path),
+                    Err(e) => {
+                        exit_with_error(&t!("error.label_sheet"), e);
+                    }
+                }
+            } else {
+                if let Err(e) = std::fs::create_dir_all(&output_dir) {
+                    exit_with_error(&t!("error.creating_dir", dir = output_dir.display()), e);
+                }
+                if *all {
+                    match label_generator.save_storage_labels(&storages, config.baserow.storage_table_id, config.baserow.database_id, config.baserow.storage_view_id, &output_dir).await {
+                        Ok(paths) => written.extend(paths),
+                        Err(e) => {
+                            exit_with_error(&t!("error.storage_labels"), e);
+                        }
+                    }
+                } else {
+                    for id in storage_id {
+                        let output_path = output_dir.join(format!("storage_label_{}.png", id));
+                        match label_generator.generate_label_by_id(*id, config.baserow.storage_table_id, config.baserow.database_id, config.baserow.storage_view_id, &output_path).await {
+                            Ok(path) => written.push(path),
+                            Err(e) => {
+                                exit_with_error(&t!("error.label_by_id"), e);
+                            }
+                        }
+                    }
+                    for name in storage_name {
+                        let safe_name = name.replace([' ', '/'], "_");
+                        let output_path = output_dir.join(format!("storage_label_{}.png", safe_name));
+                        match label_generator.generate_label_by_name(name, config.baserow.storage_table_id, config.baserow.database_id, config.baserow.storage_view_id, &output_path).await {
+                            Ok(path) => written.push(path),
+                            Err(e) => {
+                                exit_with_error(&t!("error.label_by_name"), e);
+                            }
+                        }
+                    }
+                }
+                for isbn in isbn {
+                    let safe_isbn: String = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+                    let output_path = output_dir.join(format!("isbn_label_{}.png", safe_isbn));
+                    match label_generator.generate_isbn_label(isbn, &output_path) {
+                        Ok(path) => written.push(path),
+                        Err(e) => {
+                            exit_with_error(&t!("error.isbn_label"), e);
+                        }
+                    }
+                }
+                for id in media_id {
+                    let output_path = output_dir.join(format!("spine_label_{}.png", id));
+                    match label_generator.generate_spine_label(*id, &output_path).await {
+                        Ok(path) => written.push(path),
+                        Err(e) => {
+                            exit_with_error(&t!("error.spine_label"), e);
+                        }
+                    }
+                }
+            }
+
+            if *print {
+                for path in &written {
+                    if let Err(e) = wcm::printer::print_label(path, &config.label.printer) {
+                        exit_with_error(&t!("error.printing", path = path.display()), e);
+                    }
+                }
+            }
+            ui::result(t!("result.labels_written", count = written.len()), &serde_json::json!({ "files": written }));
+        }
+        Commands::Serve { bind } => {
+            let bind = bind.clone().unwrap_or_else(|| config.server.bind.clone());
+            let state = server::AppState::new(searcher, baserow_client, config);
+            if let Err(e) = server::serve(state, &bind).await {
+                exit_with_error(&t!("error.server"), e);
+            }
+        }
+        Commands::Log { book, pages, minutes, date } => {
+            let row = match wcm::library::find_book(&baserow_client, book).await {
+                Ok(row) => row,
+                Err(e) => exit_with_error(&t!("error.finding_book"), e),
+            };
+            let today = wcm::dates::today();
+            let session = Session {
+                book_id: row.id,
+                title: row.get_text("Title").unwrap_or_default(),
+                date: date.clone().unwrap_or_else(|| today.clone()),
+                pages: *pages,
+                minutes: *minutes,
+            };
+            let path = std::path::Path::new(&config.app.sessions_file);
+            let sessions = wcm::sessions::append(path, &session).and_then(|()| wcm::sessions::load(path));
+            let sessions = match sessions {
+                Ok(sessions) => sessions,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = path.display()), e),
+            };
+
+            let total_pages = row.get_text(&config.baserow.fields.pages).and_then(|pages| pages.parse::<f64>().ok()).map(|pages| pages as u32);
+            let progress = wcm::sessions::progress(&sessions, row.id, &session.title, total_pages, &today);
+            ui::result(
+                format!("{}\n{}", t!("log.logged", title = session.title, date = session.date), wcm::sessions::format_progress(&progress)),
+                &serde_json::json!({ "session": session, "progress": progress }),
+            );
+        }
+        Commands::Quote { book, text, page } => {
+            let row = match wcm::library::find_book(&baserow_client, book).await {
+                Ok(row) => row,
+                Err(e) => exit_with_error(&t!("error.finding_book"), e),
+            };
+            let text = text.trim();
+            let entry = match page {
+                Some(page) => t!("quote.entry_page", text = text, page = page),
+                None => t!("quote.entry", text = text),
+            };
+            let field = &config.baserow.fields.quotes;
+            if let Err(e) = wcm::library::append_to_field(&baserow_client, &row, field, &entry).await {
+                exit_with_error(&t!("error.updating_book"), e);
+            }
+            let title = row.get_text("Title").unwrap_or_default();
+            ui::result(
+                t!("quote.added", title = title, field = field),
+                &serde_json::json!({ "id": row.id, "title": title, "field": field, "quote": entry }),
+            );
+        }
+        Commands::Note { book, text } => {
+            let row = match wcm::library::find_book(&baserow_client, book).await {
+                Ok(row) => row,
+                Err(e) => exit_with_error(&t!("error.finding_book"), e),
+            };
+            let entry = format!("[{}] {}", wcm::dates::now(), text.trim());
+            let field = &config.baserow.fields.notes;
+            if let Err(e) = wcm::library::append_to_field(&baserow_client, &row, field, &entry).await {
+                exit_with_error(&t!("error.updating_book"), e);
+            }
+            let title = row.get_text("Title").unwrap_or_default();
+            ui::result(
+                t!("note.added", title = title, field = field),
+                &serde_json::json!({ "id": row.id, "title": title, "field": field, "note": entry }),
+            );
+        }
+        Commands::Loan { action } => {
+            let fields = &config.baserow.fields;
+            let today = wcm::dates::today();
+            match action {
+                LoanAction::Lend { book, to, due, days } => {
+                    let row = match wcm::library::find_book(&baserow_client, book).await {
+                        Ok(row) => row,
+                        Err(e) => exit_with_error(&t!("error.finding_book"), e),
+                    };
+                    let due = due.clone().or_else(|| {
+                        days.and_then(|days| wcm::dates::parse_days(&today).map(|today| wcm::dates::format_days(today + days as i64)))
+                    });
+                    if let Err(e) = wcm::loans::lend(&baserow_client, &row, fields, to, due.as_deref(), &today).await {
+                        exit_with_error(&t!("error.updating_book"), e);
+                    }
+                    let title = row.get_text("Title").unwrap_or_default();
+                    let text = match &due {
+                        Some(due) => t!("loan.lent_due", title = title, borrower = to, due = due),
+                        None => t!("loan.lent", title = title, borrower = to),
+                    };
+                    ui::result(text, &serde_json::json!({ "id": row.id, "title": title, "borrower": to, "loaned_at": today, "due": due }));
+                }
+                LoanAction::Return { book } => {
+                    let row = match wcm::library::find_book(&baserow_client, book).await {
+                        Ok(row) => row,
+                        Err(e) => exit_with_error(&t!("error.finding_book"), e),
+                    };
+                    let title = row.get_text("Title").unwrap_or_default();
+                    if !wcm::loans::is_on_loan(&row) {
+                        ui::warn(t!("loan.not_on_loan", title = title));
+                    }
+                    if let Err(e) = wcm::loans::give_back(&baserow_client, &row, fields).await {
+                        exit_with_error(&t!("error.updating_book"), e);
+                    }
+                    ui::result(t!("loan.returned", title = title), &serde_json::json!({ "id": row.id, "title": title }));
+                }
+                LoanAction::Overdue => {
+                    let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                        Ok(rows) => rows,
+                        Err(e) => exit_with_error(&t!("error.generic"), e),
+                    };
+                    let overdue = wcm::loans::overdue(&wcm::loans::on_loan(&rows, fields), &today);
+                    ui::result(wcm::loans::format_overdue(&overdue, &today), &overdue);
+                }
+                LoanAction::ExportIcal { output } => {
+                    let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                        Ok(rows) => rows,
+                        Err(e) => exit_with_error(&t!("error.generic"), e),
+                    };
+                    let loans = wcm::loans::on_loan(&rows, fields);
+                    let count = loans.iter().filter(|loan| loan.due.is_some()).count();
+                    let text = wcm::loans::to_ical(&loans, &wcm::dates::now());
+                    match output {
+                        Some(output) => match std::fs::write(output, &text) {
+                            Ok(()) => ui::result(t!("loan.exported", count = count, file = output.display()),
+                                &serde_json::json!({ "count": count, "file": output })),
+                            Err(e) => exit_with_error(&t!("error.exporting", dir = output.display()), e),
+                        },
+                        None => ui::result(&text, &serde_json::json!({ "count": count, "text": text })),
+                    }
+                }
             }
         }
-        Commands::Test { baserow } => {
-            if *baserow {
-                println!("Testing Baserow connection...");
-                if let Err(e) = baserow_client.test_connection().await {
-                    eprintln!("Baserow connection test failed: {}", e);
-                    std::process::exit(1);
+        Commands::Next { count, busy, no_llm } => {
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let path = std::path::Path::new(&config.app.sessions_file);
+            let sessions = match wcm::sessions::load(path) {
+                Ok(sessions) => sessions,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = path.display()), e),
+            };
+
+            let count = *count as usize;
+            let mut candidates = wcm::next::rank(&rows, &config.baserow.fields, &sessions, *busy);
+            if candidates.is_empty() {
+                exit_with_error(&t!("error.generic"), WcmError::NotFound(t!("next.nothing_unread").into_owned()));
+            }
+
+            let ties = wcm::next::ties(&candidates, count);
+            if !*no_llm && !ties.is_empty() {
+                let recent = wcm::next::recent_reads(&rows, &config.baserow.fields.read_at, 5);
+                let recent = wcm::recommend::describe(&recent);
+                match wcm::llm::LlmProvider::from_config(&config) {
+                    Ok(llm) => {
+                        let llm = llm.with_http_client(http_client.clone());
+                        for tie in ties {
+                            let tied = &mut candidates[tie];
+                            let descriptions: Vec<String> = tied.iter().map(wcm::next::describe).collect();
+                            ui::detail(format!("Asking the LLM to break a tie between {} books", tied.len()));
+                            match timing::time(timing::Stage::Llm, llm.pick_next_book(&recent, &descriptions)).await {
+                                Ok(picks) => wcm::next::reorder(tied, &picks),
+                                Err(e) => {
+                                    ui::warn(t!("next.llm_failed", error = e.to_string()));
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => ui::warn(t!("next.llm_failed", error = e.to_string())),
                 }
             }
+
+            candidates.truncate(count);
+            ui::result(wcm::next::format_candidates(&candidates), &candidates);
+            timing::report();
         }
-        Commands::Label { storage_id, storage_name } => {
-            if let Some(id) = storage_id {
-                let filename = format!("storage_label_{}.png", id);
-                let output_path = std::path::Path::new(&filename);
-                if let Err(e) = label_generator.generate_label_by_id(*id, config.baserow.storage_table_id, config.baserow.database_id, config.baserow.storage_view_id, output_path).await {
-                    eprintln!("Error generating label by ID: {}", e);
-                    std::process::exit(1);
+        Commands::Open { book, storage, print } => {
+            let baserow = &config.baserow;
+            let (name, url) = match (book, storage) {
+                (Some(book), _) => match wcm::library::find_book(&baserow_client, book).await {
+                    Ok(row) => (
+                        row.get_text("Title").unwrap_or_default(),
+                        wcm::baserow::row_url(&baserow.base_url, baserow.database_id, baserow.media_table_id, baserow.media_view_id, row.id),
+                    ),
+                    Err(e) => exit_with_error(&t!("error.finding_book"), e),
+                },
+                (None, Some(storage)) => match wcm::library::find_storage(&baserow_client, storage).await {
+                    Ok(storage) => (
+                        storage.get_name().unwrap_or_else(|| format!("Storage {}", storage.id)),
+                        wcm::baserow::row_url(&baserow.base_url, baserow.database_id, baserow.storage_table_id, Some(baserow.storage_view_id), storage.id),
+                    ),
+                    Err(e) => exit_with_error(&t!("error.looking_up_storage"), e),
+                },
+                (None, None) => {
+                    ui::error(t!("error.open_usage"));
+                    std::process::exit(EXIT_USAGE);
                 }
-            } else if let Some(name) = storage_name {
-                let safe_name = name.replace(" ", "_").replace("/", "_");
-                let filename = format!("storage_label_{}.png", safe_name);
-                let output_path = std::path::Path::new(&filename);
-                if let Err(e) = label_generator.generate_label_by_name(name, config.baserow.storage_table_id, config.baserow.database_id, config.baserow.storage_view_id, output_path).await {
-                    eprintln!("Error generating label by name: {}", e);
-                    std::process::exit(1);
+            };
+            if !*print {
+                if let Err(e) = wcm::browser::open(&url) {
+                    exit_with_error(&t!("error.opening_browser"), e);
                 }
+            }
+            ui::result(
+                if *print { url.clone() } else { t!("open.opened", name = name, url = url).into_owned() },
+                &serde_json::json!({ "name": name, "url": url }),
+            );
+        }
+        Commands::Find { query, semantic, count } => {
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let matches = if *semantic {
+                let embedder = match wcm::embeddings::Embedder::from_config(&config) {
+                    Ok(embedder) => embedder.with_http_client(http_client.clone()),
+                    Err(e) => exit_with_error(&t!("error.semantic_search"), e),
+                };
+                let path = std::path::Path::new(&config.app.embeddings_file);
+                let index = match wcm::embeddings::refresh(path, &embedder, &rows).await {
+                    Ok(index) => index,
+                    Err(e) => exit_with_error(&t!("error.semantic_search"), e),
+                };
+                let query_vector = match timing::time(timing::Stage::Llm, embedder.embed(std::slice::from_ref(query))).await {
+                    Ok(mut vectors) => vectors.remove(0),
+                    Err(e) => exit_with_error(&t!("error.semantic_search"), e),
+                };
+                wcm::embeddings::semantic_matches(&index, &rows, &query_vector, *count as usize)
             } else {
-                eprintln!("Error: Please provide either --storage-id OR --storage-name");
-                std::process::exit(1);
+                wcm::embeddings::keyword_matches(&rows, query, *count as usize)
+            };
+            ui::result(wcm::embeddings::format_matches(&matches), &matches);
+        }
+        Commands::Ask { question } => {
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let max_rows = config.app.ask_max_rows;
+            let mut ranked = None;
+            if rows.len() > max_rows {
+                // Too many books for one prompt: keep the ones closest to the question
+                let ranking = async {
+                    let embedder = wcm::embeddings::Embedder::from_config(&config)?.with_http_client(http_client.clone());
+                    let index = wcm::embeddings::refresh(std::path::Path::new(&config.app.embeddings_file), &embedder, &rows).await?;
+                    let query = timing::time(timing::Stage::Llm, embedder.embed(std::slice::from_ref(question))).await?.remove(0);
+                    Ok::<_, Box<dyn std::error::Error>>(index.nearest(&query, max_rows).into_iter().map(|(id, _)| id).collect::<Vec<u64>>())
+                };
+                match ranking.await {
+                    Ok(ids) => ranked = Some(ids),
+                    Err(e) => ui::warn(t!("ask.no_index", count = max_rows, total = rows.len(), error = e.to_string())),
+                }
+            }
+            let selected = wcm::ask::select_rows(&rows, ranked.as_deref(), max_rows);
+            let catalog = selected.iter()
+                .map(|row| wcm::ask::describe_row(row, &config.baserow.fields))
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui::info(t!("ask.asking", count = selected.len()));
+
+            let llm = match wcm::llm::LlmProvider::from_config(&config) {
+                Ok(llm) => llm.with_http_client(http_client.clone()),
+                Err(e) => exit_with_error(&t!("error.ask"), e),
+            };
+            let partial = selected.len() < rows.len();
+            match timing::time(timing::Stage::Llm, llm.answer_question(question, &catalog, partial)).await {
+                Ok(answer) => {
+                    let rows = selected.iter().map(|row| row.id).collect();
+                    ui::result(&answer, &wcm::ask::Answer { answer: answer.clone(), rows });
+                }
+                Err(e) => exit_with_error(&t!("error.ask"), e),
+            }
+            timing::report();
+        }
+        Commands::Similar { book, count, no_llm } => {
+            let row = match wcm::library::find_book(&baserow_client, book).await {
+                Ok(row) => row,
+                Err(e) => exit_with_error(&t!("error.finding_book"), e),
+            };
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let title = row.get_text("Title").unwrap_or_default();
+            ui::info(t!("similar.searching", title = title));
+            let mut similar = match searcher.find_similar(&row, &rows, !*no_llm).await {
+                Ok(similar) => similar,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            if similar.is_empty() {
+                exit_with_error(&t!("error.generic"), WcmError::NotFound(t!("similar.none", title = title).into_owned()));
+            }
+            similar.truncate(*count as usize);
+            ui::result(wcm::similar::format_similar(&similar), &similar);
+            timing::report();
+        }
+        Commands::Recommend { count, min_rating, check } => {
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let sample = wcm::recommend::sample_library(&rows, &config.baserow.fields.read_at, *min_rating, &wcm::dates::today(), 40);
+            if sample.is_empty() {
+                exit_with_error(&t!("error.recommend"), WcmError::NotFound(t!("recommend.nothing_to_go_on", min_rating = min_rating).into_owned()));
+            }
+            ui::info(t!("recommend.asking", count = sample.len()));
+
+            let llm = match wcm::llm::LlmProvider::from_config(&config) {
+                Ok(llm) => llm.with_http_client(http_client.clone()),
+                Err(e) => exit_with_error(&t!("error.recommend"), e),
+            };
+            let suggestions = match timing::time(timing::Stage::Llm, llm.recommend_books(&wcm::recommend::describe(&sample), *count as usize)).await {
+                Ok(suggestions) => wcm::recommend::drop_owned(suggestions, &rows),
+                Err(e) => exit_with_error(&t!("error.recommend"), e),
+            };
+
+            let mut recommendations = Vec::new();
+            for suggestion in suggestions {
+                let mut recommendation = wcm::recommend::Recommendation { suggestion, isbn: None, published: None };
+                if *check {
+                    let found = searcher.find_by_title_author(&recommendation.suggestion.title, Some(&recommendation.suggestion.author)).await;
+                    // A failed lookup says nothing about the book, so it is kept unchecked
+                    match found.map(|results| results.books.into_iter().next()) {
+                        Ok(Some(book)) => {
+                            recommendation.isbn = book.get_isbn();
+                            recommendation.published = book.get_published_date();
+                        }
+                        Ok(None) => {
+                            ui::detail(format!("No edition found for {}, dropping it", recommendation.suggestion.title));
+                            continue;
+                        }
+                        Err(e) => ui::warn(t!("recommend.check_failed", title = &recommendation.suggestion.title, error = e)),
+                    }
+                }
+                recommendations.push(recommendation);
+            }
+            ui::result(wcm::recommend::format_recommendations(&recommendations), &recommendations);
+            timing::report();
+        }
+        Commands::Goal { action } => {
+            let path = std::path::Path::new(&config.app.goals_file);
+            match action {
+                GoalAction::Set { year, target } => {
+                    if let Err(e) = wcm::goals::set(path, *year, *target) {
+                        exit_with_error(&t!("error.reading_file", file = path.display()), e);
+                    }
+                    ui::result(t!("goal.set", year = year, target = target), &serde_json::json!({ "year": year, "target": target }));
+                }
+                GoalAction::Status { year } => {
+                    let today = wcm::dates::today();
+                    let year = year.unwrap_or_else(|| today[..4].parse().unwrap_or_default());
+                    let goals = match wcm::goals::load(path) {
+                        Ok(goals) => goals,
+                        Err(e) => exit_with_error(&t!("error.reading_file", file = path.display()), e),
+                    };
+                    let Some(&target) = goals.get(&year) else {
+                        ui::error(t!("goal.not_set", year = year));
+                        std::process::exit(EXIT_USAGE);
+                    };
+                    let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                        Ok(rows) => rows,
+                        Err(e) => exit_with_error(&t!("error.generic"), e),
+                    };
+                    let progress = wcm::goals::progress(&rows, &config.baserow.fields.read_at, year, target, &today);
+                    ui::result(wcm::goals::format_progress(&progress), &progress);
+                }
+            }
+        }
+        Commands::Stats { year, chart } => {
+            let today = wcm::dates::today();
+            let year = year.unwrap_or_else(|| today[..4].parse().unwrap_or_default());
+            // Fail before fetching the library when the chart cannot be drawn
+            let font = match chart.as_ref().map(|_| wcm::stats::load_font(config.app.chart_font.as_deref())).transpose() {
+                Ok(font) => font,
+                Err(e) => exit_with_error(&t!("error.chart"), e),
+            };
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let path = std::path::Path::new(&config.app.sessions_file);
+            let sessions = match wcm::sessions::load(path) {
+                Ok(sessions) => sessions,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = path.display()), e),
+            };
+            let mut stats = wcm::stats::aggregate(&rows, &sessions, &config.baserow.fields, year);
+            let goals_path = std::path::Path::new(&config.app.goals_file);
+            let goals = match wcm::goals::load(goals_path) {
+                Ok(goals) => goals,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = goals_path.display()), e),
+            };
+            stats.goal = goals.get(&year).map(|&target| wcm::goals::progress(&rows, &config.baserow.fields.read_at, year, target, &today));
+            let mut text = wcm::stats::format_summary(&stats);
+            if let (Some(chart), Some(font)) = (chart, &font) {
+                if let Err(e) = wcm::stats::render_chart(&stats, font, chart) {
+                    exit_with_error(&t!("error.chart"), e);
+                }
+                text.push('\n');
+                text.push_str(&t!("stats.chart_written", file = chart.display()));
+            }
+            ui::result(text, &stats);
+        }
+        Commands::Categories { action: CategoriesAction::Sync { file, dry_run } } => {
+            let canonical = match wcm::category_sync::load(file) {
+                Ok(canonical) => canonical,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = file.display()), e),
+            };
+            let table_id = config.baserow.categories_table_id;
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_rows(table_id)).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let changes = wcm::category_sync::plan(&canonical, &rows);
+            if !*dry_run {
+                if let Err(e) = wcm::category_sync::apply(&baserow_client, table_id, &changes).await {
+                    exit_with_error(&t!("error.syncing_categories"), e);
+                }
+            }
+            ui::result(wcm::category_sync::format_changes(&changes, *dry_run), &changes);
+        }
+        Commands::Enrich { book, all: _, synopsis_only, min_words, dry_run, confirm } => {
+            let options = wcm::enrich::EnrichOptions {
+                min_words: min_words.unwrap_or(config.app.min_synopsis_words),
+                synopsis_only: *synopsis_only,
+                dry_run: *dry_run,
+                confirm: *confirm,
+            };
+            let rows = match book {
+                Some(book) => match wcm::library::find_book(&baserow_client, book).await {
+                    Ok(row) => vec![row],
+                    Err(e) => exit_with_error(&t!("error.finding_book"), e),
+                },
+                None => match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                    Ok(rows) => wcm::enrich::incomplete_rows(rows, options.min_words, options.synopsis_only),
+                    Err(e) => exit_with_error(&t!("error.generic"), e),
+                },
+            };
+            ui::info(t!("enrich.starting", count = rows.len(), words = options.min_words));
+            let items = wcm::enrich::run(&searcher, &baserow_client, &rows, options, book.is_some()).await;
+            ui::result(wcm::enrich::format_summary(&items), &items);
+            timing::report();
+            if items.iter().any(|item| item.status == wcm::enrich::EnrichStatus::Failed) {
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        Commands::Isbn { action: IsbnAction::Backfill { limit, delay_ms, dry_run } } => {
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => wcm::isbn_backfill::missing_isbns(rows, *limit),
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            ui::info(t!("isbn_backfill.starting", count = rows.len()));
+            let items = match wcm::isbn_backfill::backfill(&searcher, &baserow_client, &rows, std::time::Duration::from_millis(*delay_ms), *dry_run).await {
+                Ok(items) => items,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            ui::result(wcm::isbn_backfill::format_summary(&items), &items);
+            if items.iter().any(|item| item.status == wcm::isbn_backfill::IsbnStatus::Failed) {
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        Commands::Covers { action: CoversAction::Backfill { limit, delay_ms, dry_run } } => {
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => wcm::covers::missing_covers(rows, *limit),
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            ui::info(t!("covers.starting", count = rows.len()));
+            let items = wcm::covers::backfill(&searcher, &baserow_client, &rows, std::time::Duration::from_millis(*delay_ms), *dry_run).await;
+            ui::result(wcm::covers::format_summary(&items), &items);
+            if items.iter().any(|item| item.status == wcm::covers::BackfillStatus::Failed) {
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        Commands::Covers { action: CoversAction::Hash { limit } } => {
+            let file = &config.app.cover_hashes_file;
+            if file.is_empty() {
+                exit_with_error(&t!("error.generic"), WcmError::Config(t!("covers.hashes_off").into_owned()));
+            }
+            let hashes = match wcm::cover_hashes::load(std::path::Path::new(file)) {
+                Ok(hashes) => hashes,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = file), e),
+            };
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => wcm::covers::unhashed_covers(rows, &hashes, *limit),
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            ui::info(t!("covers.hashing", count = rows.len()));
+            let summary = wcm::covers::hash_covers(&searcher, &rows).await;
+            ui::result(t!("covers.hashed", hashed = summary.hashed, failed = summary.failed), &summary);
+            if summary.failed > 0 {
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        Commands::Export { template: Some(template), output, .. } => {
+            let source = match std::fs::read_to_string(template) {
+                Ok(source) => source,
+                Err(e) => exit_with_error(&t!("error.reading_file", file = template.display()), e),
+            };
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let text = match wcm::export::render_template(&template.display().to_string(), &source, &rows) {
+                Ok(text) => text,
+                Err(e) => exit_with_error(&t!("error.template", file = template.display()), Box::<dyn std::error::Error>::from(e)),
+            };
+            match output {
+                Some(output) => match std::fs::write(output, &text) {
+                    Ok(()) => ui::result(t!("result.exported_template", count = rows.len(), file = output.display()),
+                        &serde_json::json!({ "count": rows.len(), "file": output })),
+                    Err(e) => exit_with_error(&t!("error.exporting", dir = output.display()), e),
+                },
+                None => ui::result(&text, &serde_json::json!({ "count": rows.len(), "text": text })),
+            }
+        }
+        Commands::Export { format: ExportFormat::MarkdownTree, output, template: None } => {
+            let output = output.clone().unwrap_or_else(|| std::path::PathBuf::from("catalog"));
+            let rows = match timing::time(timing::Stage::Library, baserow_client.fetch_all_media()).await {
+                Ok(rows) => rows,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            match wcm::export::write_tree(&rows, &output) {
+                Ok(summary) => ui::result(t!("result.exported",
+                    count = rows.len(), dir = output.display(),
+                    written = summary.written, unchanged = summary.unchanged, removed = summary.removed), &summary),
+                Err(e) => exit_with_error(&t!("error.exporting", dir = output.display()), e),
             }
         }
+        Commands::Daemon { action: None } => {
+            let label_generator = match label_generator.with_overwrite(true).with_logo(config.label.logo.as_ref()) {
+                Ok(label_generator) => label_generator,
+                Err(e) => exit_with_error(&t!("error.generic"), e),
+            };
+            let daemon = wcm::daemon::Daemon::new(searcher, baserow_client, label_generator, config);
+            if let Err(e) = daemon.run().await {
+                exit_with_error(&t!("daemon.error"), e);
+            }
+        }
+        Commands::Config { .. } => unreachable!("config check returns before the clients are built"),
+        Commands::Doctor { .. } => unreachable!("doctor returns before the configuration is loaded"),
+        Commands::Daemon { action: Some(DaemonAction::Status) } => unreachable!("daemon status returns before the clients are built"),
+        Commands::Mcp => {
+            let label_generator = match label_generator.with_logo(config.label.logo.as_ref()) {
+                Ok(label_generator) => label_generator,
+                Err(e) => {
+                    exit_with_error(&t!("error.generic"), e);
+                }
+            };
+            let server = McpServer::new(searcher, baserow_client, label_generator, config);
+            if let Err(e) = server.run().await {
+                exit_with_error(&t!("error.mcp"), e);
+            }
+        }
+        Commands::Bot { platform } => match platform {
+            BotPlatform::Telegram => {
+                if let Err(e) = telegram::run_bot(config.telegram.clone(), searcher).await {
+                    exit_with_error(&t!("error.telegram"), e);
+                }
+            }
+        },
+    }
+}
+
+/// A price of zero or more, as given to `--price`.
+fn parse_price(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(price) if price.is_finite() && price >= 0.0 => Ok(price),
+        _ => Err(format!("'{}' is not a price, expected an amount such as 12.50", value)),
+    }
+}
+
+/// A copy's condition, as given to `--condition`.
+fn parse_condition(value: &str) -> Result<Condition, String> {
+    Condition::parse(value).ok_or_else(|| format!("'{}' is not a condition, expected new, good or worn", value))
+}
+
+/// A `YYYY-MM-DD` date, as given to `--purchased-at`, `--due` and `--date`.
+fn parse_date(value: &str) -> Result<String, String> {
+    // Only a date written back the same way was a real one in that form
+    let is_date = wcm::dates::parse_days(value).is_some_and(|days| wcm::dates::format_days(days) == value);
+    if is_date {
+        Ok(value.to_string())
+    } else {
+        Err(format!("'{}' is not a date, expected YYYY-MM-DD", value))
     }
 }
 
+/// An issue date as given to `wcm add magazine --issue`.
+fn parse_issue_date(value: &str) -> Result<String, String> {
+    wcm::periodicals::parse_issue_date(value).ok_or_else(|| format!("'{}' is not an issue date, expected YYYY, YYYY-MM or YYYY-MM-DD", value))
+}
+
+/// An ASIN as given to `--asin`, uppercased.
+fn parse_asin(value: &str) -> Result<String, String> {
+    wcm::identifiers::normalize_asin(value).ok_or_else(|| format!("'{}' is not an ASIN, e.g. B00K0OI42W", value))
+}
+
+/// An LCCN as given to `--lccn`, normalized.
+fn parse_lccn(value: &str) -> Result<String, String> {
+    wcm::identifiers::normalize_lccn(value).ok_or_else(|| format!("'{}' is not an LCCN, e.g. 78-890351 or n78890351", value))
+}
+
+/// An OCLC number as given to `--oclc`, normalized.
+fn parse_oclc(value: &str) -> Result<String, String> {
+    wcm::identifiers::normalize_oclc(value).ok_or_else(|| format!("'{}' is not an OCLC number, e.g. 1234567 or ocm01234567", value))
+}
+
+/// Reports a failed command and exits with the code for its kind of failure.
+fn exit_with_error(context: &str, error: impl Into<WcmError>) -> ! {
+    let error = error.into();
+    // Show where the time went even when the add failed part way through
+    timing::report();
+    match error {
+        WcmError::Cancelled => ui::error(&error),
+        _ => ui::error(format!("{}: {}", context, error)),
+    }
+    std::process::exit(error.exit_code())
+}
+
+async fn select_storages(
+    label_generator: &LabelGenerator,
+    storage_ids: &[u64],
+    storage_names: &[String],
+    changed_since: Option<&str>,
+    modified_field: &str,
+) -> Result<Vec<Storage>, Box<dyn std::error::Error>> {
+    let storages = label_generator.fetch_storages(storage_ids, storage_names).await?;
+    match changed_since {
+        Some(since) => {
+            let total = storages.len();
+            let changed = LabelGenerator::filter_changed_since(storages, modified_field, since)?;
+            ui::info(format!("{} of {} storage boxes changed since {}", changed.len(), total, since));
+            Ok(changed)
+        }
+        None => Ok(storages),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn generate_label_sheet(
+    label_generator: &LabelGenerator,
+    config: &Config,
+    storages: &[Storage],
+    isbns: &[String],
+    media_ids: &[u64],
+    layout: &SheetLayout,
+    skip: u32,
+    output_path: &std::path::Path,
+) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let mut labels = Vec::new();
+    for storage in storages {
+        labels.push(label_generator.storage_label(storage, config.baserow.storage_table_id, config.baserow.database_id, config.baserow.storage_view_id).await?);
+    }
+    for isbn in isbns {
+        labels.push(label_generator.create_isbn_label(isbn)?);
+    }
+    labels.extend(label_generator.create_spine_labels(media_ids).await?);
+
+    label_generator.write_label_sheet(&labels, layout, skip, output_path)
+}
+
+async fn test_llm(config: &Config, http_client: &reqwest::Client) {
+    let provider = match LlmProvider::configured(config) {
+        Ok(provider) => provider.with_http_client(http_client.clone()),
+        Err(e) => exit_with_error(&t!("error.llm_test"), e),
+    };
+
+    ui::info(t!("test.llm_running", provider = provider.name()));
+    let report = match provider.test_connection().await {
+        Ok(report) => report,
+        Err(e) => exit_with_error(&t!("error.llm_test"), e),
+    };
+
+    let tokens = match (report.prompt_tokens, report.completion_tokens) {
+        (Some(prompt), Some(completion)) => t!("test.tokens", prompt = prompt, completion = completion).to_string(),
+        _ => t!("test.not_reported").to_string(),
+    };
+    let rows = vec![
+        (t!("test.provider").to_string(), report.provider.clone()),
+        (t!("test.model").to_string(), report.model.clone()),
+        (t!("test.latency").to_string(), format!("{} ms", report.latency_ms)),
+        (t!("test.token_usage").to_string(), tokens),
+        (t!("test.response").to_string(), report.response.clone()),
+    ];
+    ui::result(
+        format!("{}\n{}", ui::paint(Role::Success, t!("result.llm_ok")), ui::fields(&rows)),
+        &report,
+    );
+}
+
 async fn add_book_by_isbn(
     isbn: &str,
     searcher: &CombinedBookSearcher,
@@ -160,7 +1783,7 @@ async fn add_book_by_isbn(
 
 async fn add_book_by_title_author(
     title: &str, 
-    author: &str,
+    author: Option<&str>,
     searcher: &CombinedBookSearcher,
     is_ebook: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
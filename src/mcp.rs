@@ -0,0 +1,316 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use crate::baserow::BaserowClient;
+use crate::book_search::{BookSummary, CombinedBookSearcher, DraftStore, DraftSummary, SearchResults};
+use crate::config::Config;
+use crate::label::LabelGenerator;
+use crate::ui;
+
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+#[derive(Debug, Deserialize)]
+pub struct JsonRpcRequest {
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct BookArgs {
+    isbn: Option<String>,
+    title: Option<String>,
+    author: Option<String>,
+    #[serde(default)]
+    ebook: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfirmArgs {
+    draft_id: u64,
+    #[serde(default = "default_true")]
+    accept: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct LibraryArgs {
+    page: Option<u32>,
+    size: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelArgs {
+    storage_id: Option<u64>,
+    storage_name: Option<String>,
+    output_path: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub struct McpServer {
+    searcher: CombinedBookSearcher,
+    baserow_client: BaserowClient,
+    label_generator: LabelGenerator,
+    config: Config,
+    drafts: DraftStore,
+}
+
+impl McpServer {
+    pub fn new(searcher: CombinedBookSearcher, baserow_client: BaserowClient, label_generator: LabelGenerator, config: Config) -> Self {
+        Self {
+            searcher,
+            baserow_client,
+            label_generator,
+            config,
+            drafts: DraftStore::default(),
+        }
+    }
+
+    /// Serves MCP over stdio until the client closes stdin.
+    pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        // stdout carries the protocol, so the progress output the clients print
+        // has to go somewhere else
+        let mut protocol_out = redirect_stdout_to_stderr()?;
+        ui::info("wcm MCP server ready on stdio");
+
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<JsonRpcRequest>(&line) {
+                Ok(request) => self.handle_request(request).await,
+                Err(e) => Some(error_response(Value::Null, -32700, &format!("Parse error: {}", e))),
+            };
+
+            if let Some(response) = response {
+                writeln!(protocol_out, "{}", response)?;
+                protocol_out.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_request(&self, request: JsonRpcRequest) -> Option<Value> {
+        // Notifications carry no id and never get a response
+        let id = request.id?;
+
+        let result = match request.method.as_str() {
+            "initialize" => Ok(initialize_result(&request.params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => {
+                let name = request.params.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+                let arguments = request.params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                Ok(match self.call_tool(&name, arguments).await {
+                    Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+                    Err(message) => json!({ "content": [{ "type": "text", "text": message }], "isError": true }),
+                })
+            }
+            method => Err((-32601, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<String, String> {
+        match name {
+            "search_books" => {
+                let args: BookArgs = parse_args(arguments)?;
+                let results = self.find_books(&args).await?;
+                let summaries: Vec<BookSummary> = results.books.iter()
+                    .take(self.config.app.max_search_results)
+                    .map(BookSummary::from)
+                    .collect();
+                to_text(&json!({ "source": results.source, "results": summaries }))
+            }
+            "prepare_book" => {
+                let args: BookArgs = parse_args(arguments)?;
+                let results = self.find_books(&args).await?;
+                let book = results.books.into_iter().next()
+                    .ok_or("No matching book found in Google Books or Open Library")?;
+                let prepared = self.searcher.prepare_book(&book, args.ebook).await.map_err(|e| e.to_string())?;
+
+                let draft_id = self.drafts.insert(prepared.clone());
+                let summary = DraftSummary::new(draft_id, &prepared);
+                let mut text = to_text(&summary)?;
+                text.push_str("\n\nShow this summary to the user and call confirm_book with the draft_id once they approve.");
+                Ok(text)
+            }
+            "confirm_book" => {
+                let args: ConfirmArgs = parse_args(arguments)?;
+                let prepared = self.drafts.take(args.draft_id)
+                    .ok_or_else(|| format!("No pending draft with id {}", args.draft_id))?;
+                if !args.accept {
+                    return Ok(format!("Discarded draft {} ({})", args.draft_id, prepared.title));
+                }
+                let added = self.searcher.submit_prepared(&prepared).await.map_err(|e| e.to_string())?;
+                to_text(&added)
+            }
+            "list_library" => {
+                let args: LibraryArgs = parse_args(arguments)?;
+                let page = args.page.unwrap_or(1).max(1);
+                let size = args.size.unwrap_or(50).clamp(1, 200);
+                let response = self.baserow_client.fetch_media_page(page, size).await.map_err(|e| e.to_string())?;
+                to_text(&json!({ "count": response.count, "page": page, "results": response.results }))
+            }
+            "generate_label" => {
+                let args: LabelArgs = parse_args(arguments)?;
+                self.generate_label(args).await
+            }
+            _ => Err(format!("Unknown tool: {}", name)),
+        }
+    }
+
+    async fn find_books(&self, args: &BookArgs) -> Result<SearchResults, String> {
+        let results = match (&args.isbn, &args.title, &args.author) {
+            (Some(isbn), _, _) => {
+                let isbn: String = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+                self.searcher.find_by_isbn(&isbn).await
+            }
+            (None, Some(title), Some(author)) => self.searcher.find_by_title_author(title, Some(author)).await,
+            _ => return Err("Provide either isbn OR both title and author".to_string()),
+        };
+        results.map_err(|e| e.to_string())
+    }
+
+    async fn generate_label(&self, args: LabelArgs) -> Result<String, String> {
+        let baserow = &self.config.baserow;
+        let result = match (&args.storage_id, &args.storage_name) {
+            (Some(id), _) => {
+                let default_name = format!("storage_label_{}.png", id);
+                let path = PathBuf::from(args.output_path.clone().unwrap_or_else(|| default_name.clone()));
+                let result = self.label_generator.generate_label_by_id(*id, baserow.storage_table_id, baserow.database_id, baserow.storage_view_id, &path).await;
+                result.map_err(|e| e.to_string())
+            }
+            (None, Some(name)) => {
+                let default_name = format!("storage_label_{}.png", name.replace([' ', '/'], "_"));
+                let path = PathBuf::from(args.output_path.clone().unwrap_or(default_name));
+                let result = self.label_generator.generate_label_by_name(name, baserow.storage_table_id, baserow.database_id, baserow.storage_view_id, &path).await;
+                result.map_err(|e| e.to_string())
+            }
+            (None, None) => return Err("Provide either storage_id or storage_name".to_string()),
+        };
+
+        let path = result?;
+        Ok(format!("Label written to {}", path.display()))
+    }
+}
+
+fn parse_args<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, String> {
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+fn to_text<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn initialize_result(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str).unwrap_or_default();
+    let version = SUPPORTED_PROTOCOL_VERSIONS.iter()
+        .find(|v| **v == requested)
+        .unwrap_or(&SUPPORTED_PROTOCOL_VERSIONS[0]);
+
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "wcm", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Tools for managing a personal book library stored in Baserow. Always show the prepare_book summary to the user before calling confirm_book.",
+    })
+}
+
+fn tool_definitions() -> Value {
+    let book_lookup = json!({
+        "type": "object",
+        "properties": {
+            "isbn": { "type": "string", "description": "ISBN-10 or ISBN-13" },
+            "title": { "type": "string", "description": "Book title (requires author)" },
+            "author": { "type": "string", "description": "Book author (requires title)" },
+        },
+    });
+    let mut prepare_schema = book_lookup.clone();
+    prepare_schema["properties"]["ebook"] = json!({ "type": "boolean", "description": "Add as ebook instead of physical book" });
+
+    json!([
+        {
+            "name": "search_books",
+            "description": "Search Google Books (with Open Library fallback) by ISBN or by title and author.",
+            "inputSchema": book_lookup,
+        },
+        {
+            "name": "prepare_book",
+            "description": "Look up a book and prepare a library entry: picks categories from the existing Baserow categories and writes a synopsis if needed. Nothing is saved until confirm_book is called.",
+            "inputSchema": prepare_schema,
+        },
+        {
+            "name": "confirm_book",
+            "description": "Save (or discard) a draft created by prepare_book. Only call after the user approved the summary.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "draft_id": { "type": "integer" },
+                    "accept": { "type": "boolean", "description": "false discards the draft", "default": true },
+                },
+                "required": ["draft_id"],
+            },
+        },
+        {
+            "name": "list_library",
+            "description": "List rows from the library's media table.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "page": { "type": "integer", "minimum": 1 },
+                    "size": { "type": "integer", "minimum": 1, "maximum": 200 },
+                },
+            },
+        },
+        {
+            "name": "generate_label",
+            "description": "Generate a QR code PNG label for a storage box by ID or name.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "storage_id": { "type": "integer" },
+                    "storage_name": { "type": "string" },
+                    "output_path": { "type": "string", "description": "Where to write the PNG (default: current directory)" },
+                },
+            },
+        },
+    ])
+}
+
+/// Points file descriptor 1 at stderr and returns a handle to the original stdout.
+#[cfg(unix)]
+fn redirect_stdout_to_stderr() -> std::io::Result<std::fs::File> {
+    use std::os::fd::FromRawFd;
+
+    std::io::stdout().flush()?;
+    // SAFETY: plain descriptor duplication; the duplicated fd is owned by the returned File
+    unsafe {
+        let original = libc::dup(libc::STDOUT_FILENO);
+        if original < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(std::fs::File::from_raw_fd(original))
+    }
+}
+
+#[cfg(not(unix))]
+fn redirect_stdout_to_stderr() -> std::io::Result<std::fs::File> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "wcm mcp is only supported on Unix-like systems"))
+}
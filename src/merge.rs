@@ -0,0 +1,133 @@
+use crate::book_search::BookResult;
+use crate::config::{CoverSource, FieldSource, MergeConfig};
+use crate::google_books::{BookItem, ImageLinks};
+use crate::open_library::OpenLibraryBookDetails;
+
+/// The picked book as known to the other catalogues, looked up by its ISBN.
+#[derive(Debug, Default)]
+pub struct OtherRecords {
+    /// The Google Books volume, when the picked result came from Open Library
+    pub google: Option<BookItem>,
+    /// The Open Library edition, which unlike a search result has a description
+    pub edition: Option<OpenLibraryBookDetails>,
+}
+
+/// Which records `merge` needs under `policy`, as (Google Books volume, Open
+/// Library edition). Nothing is needed for a book entered by hand.
+pub fn lookups_needed(book: &BookResult, policy: &MergeConfig) -> (bool, bool) {
+    if matches!(book, BookResult::Manual(_)) {
+        return (false, false);
+    }
+    let largest = policy.cover == CoverSource::Largest;
+    let wants = |source: FieldSource, cover: CoverSource| {
+        policy.description == source || policy.subjects == source || policy.cover == cover || largest
+    };
+    let google = wants(FieldSource::GoogleBooks, CoverSource::GoogleBooks) && !matches!(book, BookResult::Google(_));
+    // An Open Library search result has its own subjects and cover, but no description
+    let edition = match book {
+        BookResult::OpenLibrary(_) => policy.description == FieldSource::OpenLibrary,
+        _ => wants(FieldSource::OpenLibrary, CoverSource::OpenLibrary),
+    };
+    (google, edition)
+}
+
+fn google_record<'a>(book: &'a BookResult, others: &'a OtherRecords) -> Option<&'a BookItem> {
+    match book {
+        BookResult::Google(item) => Some(item),
+        _ => others.google.as_ref(),
+    }
+}
+
+pub(crate) fn google_cover(item: &BookItem) -> Option<String> {
+    item.get_best_cover_image().map(|url| url.replace("http://", "https://").replace("&edge=curl", ""))
+}
+
+fn open_library_cover(book: &BookResult, others: &OtherRecords) -> Option<String> {
+    match book {
+        BookResult::OpenLibrary(ol_book) => ol_book.get_cover_url(),
+        _ => None,
+    }
+    .or_else(|| others.edition.as_ref()?.get_cover_url())
+}
+
+/// The cover URL of a result, as it would be uploaded.
+pub(crate) fn cover_url(book: &BookResult) -> Option<String> {
+    match book {
+        BookResult::Google(item) => google_cover(item),
+        BookResult::OpenLibrary(ol_book) => ol_book.cover_url.clone().or_else(|| ol_book.get_cover_url()),
+        BookResult::Manual(book) => book.cover_url.clone(),
+    }
+}
+
+/// Makes `url` the cover uploaded for `book`.
+pub(crate) fn set_cover(book: &mut BookResult, url: String) {
+    match book {
+        BookResult::Google(item) => item.volume_info.image_links = Some(ImageLinks {
+            small_thumbnail: None,
+            thumbnail: None,
+            small: None,
+            medium: None,
+            large: Some(url),
+            extra_large: None,
+        }),
+        BookResult::OpenLibrary(ol_book) => ol_book.cover_url = Some(url),
+        BookResult::Manual(book) => book.cover_url = Some(url),
+    }
+}
+
+/// The covers `CoverSource::Largest` chooses between: Google Books' first, then
+/// Open Library's.
+pub fn cover_candidates(book: &BookResult, others: &OtherRecords) -> Vec<String> {
+    google_record(book, others).and_then(google_cover).into_iter()
+        .chain(open_library_cover(book, others))
+        .collect()
+}
+
+/// The book with each field taken from the catalogue `policy` names for it.
+/// A field the preferred catalogue lacks keeps the picked result's value.
+/// `largest_cover` is the winner of `cover_candidates` when the policy asks for
+/// the largest cover.
+pub fn merge(book: &BookResult, policy: &MergeConfig, others: &OtherRecords, largest_cover: Option<String>) -> BookResult {
+    let mut merged = book.clone();
+    let google = google_record(book, others);
+
+    let description = match policy.description {
+        FieldSource::Selected => None,
+        FieldSource::GoogleBooks => google.and_then(|item| item.volume_info.description.clone()),
+        FieldSource::OpenLibrary => others.edition.as_ref().and_then(|edition| edition.get_description()),
+    };
+    if let Some(description) = description.filter(|description| !description.trim().is_empty()) {
+        match &mut merged {
+            BookResult::Google(item) => item.volume_info.description = Some(description),
+            BookResult::OpenLibrary(ol_book) => ol_book.description = Some(description),
+            BookResult::Manual(_) => {}
+        }
+    }
+
+    let subjects = match policy.subjects {
+        FieldSource::Selected => None,
+        FieldSource::GoogleBooks => google.and_then(|item| item.volume_info.categories.clone()),
+        FieldSource::OpenLibrary => match book {
+            BookResult::OpenLibrary(ol_book) => ol_book.subject.clone(),
+            _ => others.edition.as_ref().and_then(|edition| edition.subjects.clone()),
+        },
+    };
+    if let Some(subjects) = subjects.filter(|subjects| !subjects.is_empty()) {
+        match &mut merged {
+            BookResult::Google(item) => item.volume_info.categories = Some(subjects),
+            BookResult::OpenLibrary(ol_book) => ol_book.subject = Some(subjects),
+            BookResult::Manual(_) => {}
+        }
+    }
+
+    let cover = match policy.cover {
+        CoverSource::Selected => None,
+        CoverSource::GoogleBooks => google.and_then(google_cover),
+        CoverSource::OpenLibrary => open_library_cover(book, others),
+        CoverSource::Largest => largest_cover,
+    };
+    if let Some(cover) = cover.filter(|_| !matches!(book, BookResult::Manual(_))) {
+        set_cover(&mut merged, cover);
+    }
+    merged
+}
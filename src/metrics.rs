@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use axum::{
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use crate::timing::Stage;
+use crate::ui;
+
+/// Upper bounds of the duration histogram buckets, in seconds. LLM calls take
+/// tens of seconds on a small Ollama model, catalogue lookups a fraction of one.
+const BUCKETS: [f64; 11] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0];
+
+/// The Prometheus text format version `/metrics` answers in.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+struct Histogram {
+    /// Observations per bucket, not cumulative; the last counts those above every bound
+    counts: [u64; BUCKETS.len() + 1],
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        let bucket = BUCKETS.iter().position(|bound| seconds <= *bound).unwrap_or(BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum += seconds;
+    }
+}
+
+struct Metrics {
+    books_added: u64,
+    /// Failed outbound requests by host and status, `error` when no response came back
+    api_errors: BTreeMap<(String, String), u64>,
+    cache_hits: u64,
+    cache_misses: u64,
+    /// Time per outbound call, in the order the stages first ran
+    stages: Vec<(Stage, Histogram)>,
+    /// Daemon task runs by task and `ok` or `failed`
+    task_runs: BTreeMap<(String, &'static str), u64>,
+}
+
+/// Counters for the whole process, served by `/metrics` in `wcm serve` and
+/// `wcm daemon`. Other commands collect them too, but nothing reads them.
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    books_added: 0,
+    api_errors: BTreeMap::new(),
+    cache_hits: 0,
+    cache_misses: 0,
+    stages: Vec::new(),
+    task_runs: BTreeMap::new(),
+});
+
+fn update(change: impl FnOnce(&mut Metrics)) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        change(&mut metrics);
+    }
+}
+
+pub fn record_add() {
+    update(|metrics| metrics.books_added += 1);
+}
+
+/// Counts a request to `host` that failed: `status` is the HTTP status, or
+/// `None` when the request got no response at all.
+pub fn record_api_error(host: &str, status: Option<u16>) {
+    let status = status.map_or_else(|| "error".to_string(), |status| status.to_string());
+    update(|metrics| *metrics.api_errors.entry((host.to_string(), status)).or_default() += 1);
+}
+
+/// Counts a lookup through the response cache: a hit when the stored copy was
+/// still valid.
+pub fn record_cache(hit: bool) {
+    update(|metrics| if hit { metrics.cache_hits += 1 } else { metrics.cache_misses += 1 });
+}
+
+pub fn record_duration(stage: Stage, elapsed: Duration) {
+    update(|metrics| {
+        let index = match metrics.stages.iter().position(|(existing, _)| *existing == stage) {
+            Some(index) => index,
+            None => {
+                metrics.stages.push((stage, Histogram { counts: [0; BUCKETS.len() + 1], sum: 0.0 }));
+                metrics.stages.len() - 1
+            }
+        };
+        metrics.stages[index].1.observe(elapsed.as_secs_f64());
+    });
+}
+
+pub fn record_task_run(task: &str, ok: bool) {
+    update(|metrics| *metrics.task_runs.entry((task.to_string(), if ok { "ok" } else { "failed" })).or_default() += 1);
+}
+
+/// Everything collected so far in the Prometheus text format.
+pub fn render() -> String {
+    let Ok(metrics) = METRICS.lock() else {
+        return String::new();
+    };
+    let mut out = String::new();
+
+    header_lines(&mut out, "wcm_books_added_total", "counter", "Books, ebooks and magazine issues added to the library.");
+    let _ = writeln!(out, "wcm_books_added_total {}", metrics.books_added);
+
+    header_lines(&mut out, "wcm_api_errors_total", "counter", "Outbound requests that failed, by host and HTTP status (error when there was no response).");
+    for ((host, status), count) in &metrics.api_errors {
+        let _ = writeln!(out, "wcm_api_errors_total{{host=\"{}\",status=\"{}\"}} {}", escape(host), status, count);
+    }
+
+    header_lines(&mut out, "wcm_cache_requests_total", "counter", "Metadata lookups through the response cache, by whether the stored copy was still valid.");
+    let _ = writeln!(out, "wcm_cache_requests_total{{result=\"hit\"}} {}", metrics.cache_hits);
+    let _ = writeln!(out, "wcm_cache_requests_total{{result=\"miss\"}} {}", metrics.cache_misses);
+
+    header_lines(&mut out, "wcm_request_duration_seconds", "histogram", "Time taken by outbound calls, by stage (llm, metadata, web_search, ...).");
+    for (stage, histogram) in &metrics.stages {
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(&histogram.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "wcm_request_duration_seconds_bucket{{stage=\"{}\",le=\"{}\"}} {}", stage.key(), bound, cumulative);
+        }
+        cumulative += histogram.counts[BUCKETS.len()];
+        let _ = writeln!(out, "wcm_request_duration_seconds_bucket{{stage=\"{}\",le=\"+Inf\"}} {}", stage.key(), cumulative);
+        let _ = writeln!(out, "wcm_request_duration_seconds_sum{{stage=\"{}\"}} {}", stage.key(), histogram.sum);
+        let _ = writeln!(out, "wcm_request_duration_seconds_count{{stage=\"{}\"}} {}", stage.key(), cumulative);
+    }
+
+    if !metrics.task_runs.is_empty() {
+        header_lines(&mut out, "wcm_daemon_task_runs_total", "counter", "Daemon task runs, by task and result.");
+        for ((task, result), count) in &metrics.task_runs {
+            let _ = writeln!(out, "wcm_daemon_task_runs_total{{task=\"{}\",result=\"{}\"}} {}", escape(task), result, count);
+        }
+    }
+    out
+}
+
+fn header_lines(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escapes a label value as the text format requires.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub async fn metrics_page() -> Response {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], render()).into_response()
+}
+
+/// Serves `/metrics` alone on `listener`, for `wcm daemon`. With `auth_token` set
+/// the scraper must send it as a bearer token, as for `wcm serve`.
+pub async fn serve(listener: tokio::net::TcpListener, auth_token: Option<String>) -> std::io::Result<()> {
+    let router = Router::new()
+        .route("/metrics", get(metrics_page))
+        .route_layer(middleware::from_fn(move |request: Request, next: Next| {
+            let authorized = crate::server::is_authorized(&request, auth_token.as_deref());
+            async move {
+                if !authorized {
+                    return StatusCode::UNAUTHORIZED.into_response();
+                }
+                next.run(request).await
+            }
+        }));
+
+    ui::info(format!("Metrics at http://{}/metrics", listener.local_addr()?));
+    axum::serve(listener, router).await
+}
@@ -0,0 +1,200 @@
+use std::ops::Range;
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::config::MediaFieldsConfig;
+use crate::dates;
+use crate::sessions::Session;
+
+/// Read books whose categories count against a candidate for variety.
+const RECENT_READS: usize = 5;
+/// Page counts `--busy` treats as short and long.
+const SHORT_PAGES: u32 = 250;
+const LONG_PAGES: u32 = 500;
+
+/// An unread book, scored by the rules in `rank`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub id: u64,
+    pub title: String,
+    pub author: String,
+    pub categories: Vec<String>,
+    pub pages: Option<u32>,
+    pub score: i32,
+    pub reasons: Vec<String>,
+}
+
+fn is_read(row: &MediaRow) -> bool {
+    row.fields.get("Read").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+fn number(row: &MediaRow, field: &str) -> Option<u32> {
+    row.get_text(field).and_then(|n| n.parse::<f64>().ok()).map(|n| n as u32)
+}
+
+fn categories(row: &MediaRow) -> Vec<String> {
+    row.get_text("Category")
+        .map(|categories| categories.split(", ").map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// The read rows with a read date, most recent first, up to `limit`.
+pub fn recent_reads<'a>(rows: &'a [MediaRow], read_at_field: &str, limit: usize) -> Vec<&'a MediaRow> {
+    let mut read: Vec<(&MediaRow, i64)> = rows.iter()
+        .filter(|row| is_read(row))
+        .filter_map(|row| row.get_text(read_at_field).and_then(|date| dates::parse_days(&date)).map(|days| (row, days)))
+        .collect();
+    read.sort_by_key(|(_, days)| std::cmp::Reverse(*days));
+    read.into_iter().take(limit).map(|(row, _)| row).collect()
+}
+
+/// Scores every unread row, best first:
+/// - a book with logged sessions is already under way
+/// - in a series, a later volume waits for the earlier unread ones, and the
+///   volume after one already read is due
+/// - a book outside the categories of the last few reads adds variety, one in
+///   them repeats it
+/// - when `busy`, short books go up and long ones down
+///
+/// Equal scores keep the table's order.
+pub fn rank(rows: &[MediaRow], fields: &MediaFieldsConfig, sessions: &[Session], busy: bool) -> Vec<Candidate> {
+    let recent_categories: Vec<String> = recent_reads(rows, &fields.read_at, RECENT_READS)
+        .into_iter()
+        .flat_map(categories)
+        .collect();
+
+    let mut candidates: Vec<Candidate> = rows.iter()
+        .filter(|row| !is_read(row))
+        .map(|row| {
+            let mut score = 0;
+            let mut reasons = Vec::new();
+
+            if sessions.iter().any(|session| session.book_id == row.id) {
+                score += 30;
+                reasons.push(t!("next.started").into_owned());
+            }
+
+            let series = row.get_text(&fields.series);
+            if let (Some(series), Some(volume)) = (&series, number(row, &fields.volume)) {
+                let in_series = || rows.iter().filter(|other| other.id != row.id && other.get_text(&fields.series).as_ref() == Some(series));
+                let earlier_unread = in_series()
+                    .filter(|other| !is_read(other))
+                    .filter_map(|other| number(other, &fields.volume))
+                    .filter(|other_volume| *other_volume < volume)
+                    .min();
+                if let Some(earlier) = earlier_unread {
+                    score -= 50;
+                    reasons.push(t!("next.earlier_volume", series = series, volume = earlier).into_owned());
+                } else if in_series().any(|other| is_read(other) && number(other, &fields.volume) == Some(volume.saturating_sub(1))) {
+                    score += 20;
+                    reasons.push(t!("next.continues_series", series = series).into_owned());
+                }
+            }
+
+            let categories = categories(row);
+            let repeated: Vec<&String> = categories.iter().filter(|category| recent_categories.contains(category)).collect();
+            if !recent_categories.is_empty() && !categories.is_empty() {
+                if repeated.is_empty() {
+                    score += 10;
+                    reasons.push(t!("next.variety").into_owned());
+                } else {
+                    score -= 5 * repeated.len() as i32;
+                    let repeated: Vec<&str> = repeated.iter().map(|category| category.as_str()).collect();
+                    reasons.push(t!("next.same_categories", categories = repeated.join(", ")).into_owned());
+                }
+            }
+
+            let pages = number(row, &fields.pages);
+            if busy {
+                match pages {
+                    Some(pages) if pages <= SHORT_PAGES => {
+                        score += 15;
+                        reasons.push(t!("next.short", pages = pages).into_owned());
+                    }
+                    Some(pages) if pages > LONG_PAGES => {
+                        score -= 15;
+                        reasons.push(t!("next.long", pages = pages).into_owned());
+                    }
+                    _ => {}
+                }
+            }
+
+            Candidate {
+                id: row.id,
+                title: row.get_text("Title").unwrap_or_default(),
+                author: row.get_text("Author").unwrap_or_default(),
+                categories,
+                pages,
+                score,
+                reasons,
+            }
+        })
+        .collect();
+
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.score));
+    candidates
+}
+
+/// Runs of two or more candidates with the same score that reach into the first
+/// `count`, which the rules cannot put in order.
+pub fn ties(candidates: &[Candidate], count: usize) -> Vec<Range<usize>> {
+    let mut ties = Vec::new();
+    let mut start = 0;
+    while start < candidates.len().min(count) {
+        let end = start + candidates[start..].iter().take_while(|candidate| candidate.score == candidates[start].score).count();
+        if end - start > 1 {
+            ties.push(start..end);
+        }
+        start = end;
+    }
+    ties
+}
+
+/// Puts `tied` in the order the LLM picked, as indexes into it with a reason
+/// each; candidates it left out keep their place after the picked ones.
+pub fn reorder(tied: &mut [Candidate], picks: &[(usize, String)]) {
+    let mut ordered: Vec<Candidate> = Vec::with_capacity(tied.len());
+    for (index, reason) in picks {
+        if let Some(candidate) = tied.get(*index).filter(|candidate| !ordered.iter().any(|picked| picked.id == candidate.id)) {
+            let mut candidate = candidate.clone();
+            if !reason.is_empty() {
+                candidate.reasons.push(reason.clone());
+            }
+            ordered.push(candidate);
+        }
+    }
+    for candidate in tied.iter() {
+        if !ordered.iter().any(|picked| picked.id == candidate.id) {
+            ordered.push(candidate.clone());
+        }
+    }
+    tied.clone_from_slice(&ordered);
+}
+
+/// One line per candidate for the LLM prompt.
+pub fn describe(candidate: &Candidate) -> String {
+    let mut line = format!("{} by {}", candidate.title, candidate.author);
+    let mut details = Vec::new();
+    if !candidate.categories.is_empty() {
+        details.push(candidate.categories.join(", "));
+    }
+    if let Some(pages) = candidate.pages {
+        details.push(format!("{} pages", pages));
+    }
+    if !details.is_empty() {
+        line.push_str(&format!(" ({})", details.join("; ")));
+    }
+    line
+}
+
+pub fn format_candidates(candidates: &[Candidate]) -> String {
+    candidates.iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let mut lines = vec![t!("next.item", number = i + 1, title = candidate.title, author = candidate.author, id = candidate.id).into_owned()];
+            lines.extend(candidate.reasons.iter().map(|reason| format!("   - {}", reason)));
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
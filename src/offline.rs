@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use crate::baserow::{Category, Storage};
+use crate::book_search::BookResult;
+use crate::config::AppConfig;
+use crate::http_cache::stable_hash;
+
+/// What `wcm add` gets from elsewhere than the catalogues, kept next to their
+/// responses in `app.cache_dir` so `wcm add --offline` can run without a
+/// network: the Baserow categories and storage locations, the categories and synopsis the LLM
+/// wrote for each book, and the covers it downloaded.
+#[derive(Debug, Clone)]
+pub struct OfflineCache {
+    dir: PathBuf,
+}
+
+/// A stored LLM result with the key it was stored under.
+#[derive(Debug, Serialize, Deserialize)]
+struct LlmEntry<T> {
+    key: String,
+    value: T,
+}
+
+impl OfflineCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache in `app.cache_dir`, or none when it is set to an empty string.
+    pub fn from_config(app: &AppConfig) -> Option<Self> {
+        (!app.cache_dir.is_empty()).then(|| Self::new(&app.cache_dir))
+    }
+
+    fn write(&self, path: PathBuf, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, contents)
+    }
+
+    pub fn store_categories(&self, categories: &[Category]) -> std::io::Result<()> {
+        self.write(self.dir.join("categories.json"), serde_json::to_string(categories)?)
+    }
+
+    pub fn load_categories(&self) -> Option<Vec<Category>> {
+        serde_json::from_str(&std::fs::read_to_string(self.dir.join("categories.json")).ok()?).ok()
+    }
+
+    pub fn store_storages(&self, storages: &[Storage]) -> std::io::Result<()> {
+        self.write(self.dir.join("storages.json"), serde_json::to_string(storages)?)
+    }
+
+    pub fn load_storages(&self) -> Option<Vec<Storage>> {
+        serde_json::from_str(&std::fs::read_to_string(self.dir.join("storages.json")).ok()?).ok()
+    }
+
+    fn llm_path(&self, kind: &str, key: &str) -> PathBuf {
+        self.dir.join("llm").join(format!("{}-{:016x}.json", kind, stable_hash(key)))
+    }
+
+    /// Stores what the LLM answered for `kind` of request about `key`, such as
+    /// the categories for a book.
+    pub fn store_llm<T: Serialize>(&self, kind: &str, key: &str, value: &T) -> std::io::Result<()> {
+        let entry = LlmEntry { key: key.to_string(), value };
+        self.write(self.llm_path(kind, key), serde_json::to_string(&entry)?)
+    }
+
+    pub fn load_llm<T: DeserializeOwned>(&self, kind: &str, key: &str) -> Option<T> {
+        let entry: LlmEntry<T> = serde_json::from_str(&std::fs::read_to_string(self.llm_path(kind, key)).ok()?).ok()?;
+        (entry.key == key).then_some(entry.value)
+    }
+
+    /// Where the cover downloaded from `url` is kept.
+    pub fn cover_path(&self, url: &str) -> PathBuf {
+        self.dir.join("covers").join(format!("{:016x}", stable_hash(url)))
+    }
+
+    pub fn store_cover(&self, url: &str, bytes: &[u8]) -> std::io::Result<()> {
+        self.write(self.cover_path(url), bytes)
+    }
+
+    pub fn load_cover(&self, url: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.cover_path(url)).ok()
+    }
+}
+
+/// The key the LLM's answers about `book` are kept under: its ISBN, or its
+/// title and authors when it has none.
+pub fn book_key(book: &BookResult) -> String {
+    book.get_isbn().unwrap_or_else(|| title_key(&book.get_full_title(), &book.get_all_authors()))
+}
+
+/// The key for a book known only by its title and author, ignoring case and
+/// spacing.
+pub fn title_key(title: &str, author: &str) -> String {
+    let normalize = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    format!("{}|{}", normalize(title), normalize(author))
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use dialoguer::{Select, theme::ColorfulTheme};
 use crate::config::Config;
+use crate::filters::SearchFilters;
+use crate::http_cache::{self, HttpCache};
+use crate::ui::{self, Role};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OpenLibrarySearchResponse {
@@ -48,6 +51,14 @@ pub struct OpenLibraryBook {
     pub edition_key: Option<Vec<String>>,
     #[serde(rename = "first_sentence")]
     pub first_sentence: Option<Vec<String>>,
+    /// Series the work belongs to, e.g. "Discworld (5)"; only returned when asked for
+    pub series: Option<Vec<String>>,
+    /// Not in search results; set from the edition record by `merge::merge`
+    #[serde(skip)]
+    pub description: Option<String>,
+    /// Cover chosen by `merge::merge`, used instead of the one for the ISBN
+    #[serde(skip)]
+    pub cover_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -69,6 +80,8 @@ pub struct OpenLibraryBookDetails {
     pub languages: Option<Vec<OpenLibraryLanguageRef>>,
     #[serde(rename = "works")]
     pub works: Option<Vec<OpenLibraryWorkRef>>,
+    pub dewey_decimal_class: Option<Vec<String>>,
+    pub lc_classifications: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -112,6 +125,8 @@ pub struct OpenLibraryAuthor {
 pub struct OpenLibraryClient {
     client: reqwest::Client,
     base_url: String,
+    filters: SearchFilters,
+    cache: Option<HttpCache>,
 }
 
 impl OpenLibraryClient {
@@ -119,27 +134,35 @@ impl OpenLibraryClient {
         Self {
             client: reqwest::Client::new(),
             base_url,
+            filters: SearchFilters::default(),
+            cache: None,
         }
     }
 
-    pub async fn search_by_isbn(&self, isbn: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
-        let url = format!("{}/search.json?isbn={}", self.base_url, isbn);
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
 
-        println!("Making Open Library request to: {}", url);
+    /// Restricts title and series searches to a publisher and language.
+    pub fn with_filters(mut self, filters: SearchFilters) -> Self {
+        self.filters = filters;
+        self
+    }
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+    /// Keeps responses in `cache` and revalidates them instead of fetching them again.
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
-            return Err(format!("Open Library API error: {} - {}", status, error_text).into());
-        }
+    pub async fn search_by_isbn(&self, isbn: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        let url = format!("{}/search.json?isbn={}", self.base_url, isbn);
+
+        ui::detail(format!("Making Open Library request to: {}", url));
 
-        let search_response: OpenLibrarySearchResponse = response.json().await?;
-        Ok(search_response)
+        self.get_json(&url).await
     }
 
     pub async fn search_by_title_author(
@@ -147,64 +170,85 @@ impl OpenLibraryClient {
         title: &str,
         author: &str,
     ) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
-        let url = format!(
-            "{}/search.json?title={}&author={}",
-            self.base_url,
-            urlencoding::encode(title),
-            urlencoding::encode(author)
-        );
-
-        println!("Making Open Library request to: {}", url);
-
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
-            return Err(format!("Open Library API error: {} - {}", status, error_text).into());
+        self.search(&format!("title={}&author={}", urlencoding::encode(title), urlencoding::encode(author))).await
+    }
+
+    pub async fn search_by_title(&self, title: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("title={}", urlencoding::encode(title))).await
+    }
+
+    /// Searches for the words in any field, as the Open Library search box does.
+    pub async fn search_by_keywords(&self, keywords: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("q={}", urlencoding::encode(keywords))).await
+    }
+
+    /// Searches for the editions catalogued under a Library of Congress Control Number.
+    pub async fn search_by_lccn(&self, lccn: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("lccn={}", urlencoding::encode(lccn))).await
+    }
+
+    /// Searches for the editions catalogued under an OCLC (WorldCat) number.
+    pub async fn search_by_oclc(&self, oclc: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("oclc={}", urlencoding::encode(oclc))).await
+    }
+
+    /// Searches for the editions Open Library has linked to an Amazon ASIN.
+    pub async fn search_by_asin(&self, asin: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("q={}", urlencoding::encode(&format!("id_amazon:{}", asin)))).await
+    }
+
+    /// Searches for works filed under a subject, with their subjects included so
+    /// the overlap with another book can be counted.
+    pub async fn search_by_subject(&self, subject: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("subject={}&fields=*&limit=20", urlencoding::encode(subject))).await
+    }
+
+    /// Searches for works of a series, with their series data included.
+    pub async fn search_by_series(&self, series: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        self.search(&format!("q={}&fields=*,series&limit=50", urlencoding::encode(&format!("\"{}\"", series)))).await
+    }
+
+    async fn search(&self, params: &str) -> Result<OpenLibrarySearchResponse, Box<dyn std::error::Error>> {
+        let mut url = format!("{}/search.json?{}", self.base_url, params);
+        if let Some(publisher) = &self.filters.publisher {
+            url.push_str(&format!("&publisher={}", urlencoding::encode(publisher)));
         }
+        if let Some(lang) = self.filters.lang_marc() {
+            url.push_str(&format!("&language={}", urlencoding::encode(&lang)));
+        }
+
+        ui::detail(format!("Making Open Library request to: {}", url));
 
-        let search_response: OpenLibrarySearchResponse = response.json().await?;
-        Ok(search_response)
+        self.get_json(&url).await
     }
 
-    #[allow(dead_code)]
     pub async fn get_book_details(&self, key: &str) -> Result<OpenLibraryBookDetails, Box<dyn std::error::Error>> {
         let url = format!("{}{}.json", self.base_url, key);
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
-            return Err(format!("Open Library API error: {} - {}", status, error_text).into());
-        }
+        self.get_json(&url).await
+    }
 
-        let book_details: OpenLibraryBookDetails = response.json().await?;
-        Ok(book_details)
+    /// Fetches the edition record for an ISBN, which carries the Dewey and LC classifications.
+    pub async fn get_edition_by_isbn(&self, isbn: &str) -> Result<OpenLibraryBookDetails, Box<dyn std::error::Error>> {
+        self.get_book_details(&format!("/isbn/{}", isbn)).await
     }
 
     #[allow(dead_code)]
     pub async fn get_author(&self, key: &str) -> Result<OpenLibraryAuthor, Box<dyn std::error::Error>> {
         let url = format!("{}{}.json", self.base_url, key);
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        self.get_json(&url).await
+    }
+
+    /// GETs `url` through the cache and parses the JSON body.
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, Box<dyn std::error::Error>> {
+        let response = http_cache::get(&self.client, self.cache.as_ref(), url).await?;
 
-        if !response.status().is_success() {
-            return Err(format!("Open Library API error: {}", response.status()).into());
+        if !response.status.is_success() {
+            return Err(format!("Open Library API error: {} - {}", response.status, response.body).into());
         }
 
-        let author: OpenLibraryAuthor = response.json().await?;
-        Ok(author)
+        Ok(serde_json::from_str(&response.body)?)
     }
 }
 
@@ -251,7 +295,6 @@ impl OpenLibraryBook {
 }
 
 impl OpenLibraryBookDetails {
-    #[allow(dead_code)]
     pub fn get_description(&self) -> Option<String> {
         match &self.description {
             Some(OpenLibraryDescription::String(desc)) => Some(desc.clone()),
@@ -260,7 +303,6 @@ impl OpenLibraryBookDetails {
         }
     }
 
-    #[allow(dead_code)]
     pub fn get_cover_url(&self) -> Option<String> {
         self.covers.as_ref()?.first()
             .map(|id| format!("https://covers.openlibrary.org/b/id/{}-L.jpg", id))
@@ -286,49 +328,47 @@ impl OpenLibraryBookDetails {
 }
 
 pub async fn display_open_library_book_info(book: &OpenLibraryBook, _config: &Config) {
-    println!("\n=== Book Information (Open Library) ===");
-    println!("Title: {}", book.get_full_title());
-    println!("Author(s): {}", book.get_all_authors());
+    let mut rows = Vec::new();
+    rows.push(("Title".to_string(), book.get_full_title()));
+    rows.push(("Author(s)".to_string(), book.get_all_authors()));
     
     if let Some(publisher) = book.get_primary_publisher() {
-        println!("Publisher: {}", publisher);
+        rows.push(("Publisher".to_string(), publisher.to_string()));
     }
     
     if let Some(year) = book.get_latest_publish_year() {
-        println!("Published: {}", year);
+        rows.push(("Published".to_string(), year.to_string()));
     } else if let Some(date) = book.get_latest_publish_date() {
-        println!("Published: {}", date);
+        rows.push(("Published".to_string(), date.to_string()));
     }
     
     if let Some(pages) = book.number_of_pages_median {
-        println!("Pages: {}", pages);
+        rows.push(("Pages".to_string(), pages.to_string()));
     }
     
     if let Some(isbn) = book.get_best_isbn() {
-        println!("ISBN: {}", isbn);
+        rows.push(("ISBN".to_string(), isbn));
     }
     
     if let Some(cover_url) = book.get_cover_url() {
-        println!("Cover Image: {}", cover_url);
+        rows.push(("Cover Image".to_string(), cover_url));
     }
     
     if let Some(subjects) = &book.subject {
         let subjects_str = subjects.iter().take(5).cloned().collect::<Vec<String>>().join(", ");
-        println!("Subjects: {}", subjects_str);
+        rows.push(("Subjects".to_string(), subjects_str));
     }
     
     if let Some(first_sentence) = &book.first_sentence {
         if let Some(sentence) = first_sentence.first() {
-            let desc = if sentence.len() > 1000 {
-                format!("{}...", &sentence[..1000])
-            } else {
-                sentence.clone()
-            };
-            println!("First Sentence: {}", desc);
+            rows.push(("First Sentence".to_string(), crate::text::truncate(sentence, 1000)));
         }
     }
     
-    println!("========================================\n");
+    ui::info("");
+    ui::info(ui::paint(Role::Heading, "Book Information (Open Library)"));
+    ui::info(ui::fields(&rows));
+    ui::info("");
 }
 
 #[allow(dead_code)]
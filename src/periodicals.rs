@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// The issue of a magazine or other periodical added with `wcm add magazine`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issue {
+    /// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, as precise as the cover dates it
+    pub date: Option<String>,
+    /// Issue number as printed, e.g. `12` or `Vol. 3 No. 4`
+    pub number: Option<String>,
+}
+
+impl Issue {
+    /// How the issue is named in its row's title: the number, then the date.
+    pub fn label(&self) -> String {
+        match (&self.number, &self.date) {
+            (Some(number), Some(date)) => format!("{} ({})", number_label(number), date),
+            (Some(number), None) => number_label(number),
+            (None, Some(date)) => date.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// The issue date as a full `YYYY-MM-DD` for a Baserow date field, taking the
+    /// first of the month or year when the cover gives no day.
+    pub fn date_value(&self) -> Option<String> {
+        let date = self.date.as_ref()?;
+        Some(match date.len() {
+            4 => format!("{}-01-01", date),
+            7 => format!("{}-01", date),
+            _ => date.clone(),
+        })
+    }
+}
+
+/// Bare numbers get a `No.` in front; anything else is kept as printed.
+fn number_label(number: &str) -> String {
+    if number.chars().all(|c| c.is_ascii_digit()) {
+        format!("No. {}", number)
+    } else {
+        number.to_string()
+    }
+}
+
+/// The title a magazine issue is stored under, e.g. `Wired, 2024-07`, so every
+/// issue gets a row of its own and the issues of one magazine sort together.
+pub fn issue_title(title: &str, issue: &Issue) -> String {
+    let label = issue.label();
+    if label.is_empty() {
+        title.trim().to_string()
+    } else {
+        format!("{}, {}", title.trim(), label)
+    }
+}
+
+/// Checks an issue date given as `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+pub fn parse_issue_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    let parts: Vec<&str> = value.split('-').collect();
+    let digits = |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    let in_range = |part: &str, max: u32| (1..=max).contains(&part.parse::<u32>().unwrap_or(0));
+    let valid = match parts.as_slice() {
+        [year] => digits(year, 4),
+        [year, month] => digits(year, 4) && digits(month, 2) && in_range(month, 12),
+        [year, month, day] => digits(year, 4) && digits(month, 2) && in_range(month, 12) && digits(day, 2) && in_range(day, 31),
+        _ => false,
+    };
+    valid.then(|| value.to_string())
+}
@@ -0,0 +1,128 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use image::{imageops, GrayImage};
+use crate::config::{BrotherQlConfig, PrinterConfig, PrinterDriver};
+use crate::ui;
+
+/// The QL print head has 720 pins; every raster line carries one bit per pin.
+const BROTHER_QL_HEAD_PINS: u32 = 720;
+
+/// Printable dots and the unused pins on the right of the head for each continuous
+/// tape width, from Brother's QL raster command reference (300 DPI).
+const BROTHER_QL_TAPES: &[(u32, u32, u32)] = &[
+    // (tape mm, printable dots, right margin dots)
+    (12, 106, 29),
+    (29, 306, 6),
+    (38, 413, 12),
+    (50, 554, 12),
+    (54, 590, 0),
+    (62, 696, 12),
+];
+
+/// Sends a label file to the configured printer.
+pub fn print_label(path: &Path, config: &PrinterConfig) -> Result<(), Box<dyn std::error::Error>> {
+    match config.driver {
+        PrinterDriver::Cups => print_with_cups(path, config),
+        PrinterDriver::BrotherQl => {
+            if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")) {
+                return Err("Brother QL printers take single labels, not PDF sheets; print without --pdf".into());
+            }
+            let label = image::open(path)?.to_luma8();
+            let data = brother_ql_raster(&label, &config.brother_ql)?;
+            send_to_device(&config.brother_ql.device, &data)
+        }
+    }
+}
+
+/// Prints through `lp`, which talks to CUPS on Linux and macOS (and to IPP printers
+/// CUPS knows about).
+fn print_with_cups(path: &Path, config: &PrinterConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let mut command = Command::new("lp");
+    if let Some(name) = &config.name {
+        command.arg("-d").arg(name);
+    }
+    for option in &config.options {
+        command.arg("-o").arg(option);
+    }
+    command.arg(path);
+
+    let output = command.output()
+        .map_err(|e| format!("Could not run lp (is CUPS installed?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!("lp failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+
+    let job = String::from_utf8_lossy(&output.stdout);
+    ui::info(format!("Sent {} to the printer. {}", path.display(), job.trim()));
+    Ok(())
+}
+
+/// Converts a label to Brother QL raster commands for continuous tape, uncompressed.
+/// Labels wider than the tape are turned to run along it, then scaled down if needed.
+pub fn brother_ql_raster(label: &GrayImage, config: &BrotherQlConfig) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let &(tape, printable, right_margin) = BROTHER_QL_TAPES.iter()
+        .find(|(tape, _, _)| *tape == config.tape)
+        .ok_or_else(|| format!("Unsupported Brother QL tape width: {} mm (supported: 12, 29, 38, 50, 54, 62)", config.tape))?;
+
+    let mut label = if label.width() > printable && label.height() < label.width() {
+        imageops::rotate90(label)
+    } else {
+        label.clone()
+    };
+    if label.width() > printable {
+        let height = (label.height() as u64 * printable as u64 / label.width() as u64).max(1) as u32;
+        label = imageops::resize(&label, printable, height, imageops::FilterType::Triangle);
+    }
+
+    let line_bytes = (BROTHER_QL_HEAD_PINS / 8) as usize;
+    let offset = right_margin + (printable - label.width()) / 2;
+    let line_count = label.height();
+
+    let mut data = vec![0u8; 200]; // invalidate: clears any half-received job
+    data.extend_from_slice(&[0x1B, 0x40]); // initialize
+    data.extend_from_slice(&[0x1B, 0x69, 0x61, 0x01]); // switch to raster mode
+    // print information: media type, width and raster line count are valid
+    data.extend_from_slice(&[0x1B, 0x69, 0x7A, 0x86, 0x0A, tape as u8, 0x00]);
+    data.extend_from_slice(&line_count.to_le_bytes());
+    data.extend_from_slice(&[0x00, 0x00]);
+    data.extend_from_slice(&[0x1B, 0x69, 0x4D, 0x40]); // auto cut
+    data.extend_from_slice(&[0x1B, 0x69, 0x41, 0x01]); // cut after every label
+    data.extend_from_slice(&[0x1B, 0x69, 0x4B, 0x08]); // cut at end
+    data.extend_from_slice(&[0x1B, 0x69, 0x64, 0x23, 0x00]); // 35 dot feed margin
+    data.extend_from_slice(&[0x4D, 0x00]); // no compression
+
+    for y in 0..line_count {
+        let mut line = vec![0u8; line_bytes];
+        for x in 0..label.width() {
+            if label.get_pixel(x, y)[0] < 128 {
+                // The head prints right to left, so the image is mirrored onto the pins
+                let pin = offset + (label.width() - 1 - x);
+                line[(pin / 8) as usize] |= 0x80 >> (pin % 8);
+            }
+        }
+        data.extend_from_slice(&[0x67, 0x00, line_bytes as u8]);
+        data.extend_from_slice(&line);
+    }
+
+    data.push(0x1A); // print with feed
+    Ok(data)
+}
+
+fn send_to_device(device: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    match device.strip_prefix("tcp://") {
+        Some(address) => {
+            let mut stream = std::net::TcpStream::connect(address)
+                .map_err(|e| format!("Could not connect to Brother QL printer at {}: {}", address, e))?;
+            stream.write_all(data)?;
+        }
+        None => {
+            let mut file = std::fs::OpenOptions::new().write(true).open(device)
+                .map_err(|e| format!("Could not open Brother QL printer at {}: {}", device, e))?;
+            file.write_all(data)?;
+        }
+    }
+
+    ui::info(format!("Sent label to Brother QL printer at {}", device));
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use crate::duplicates::fold;
+
+/// How alike two publisher keys must be to count as one publisher, catching
+/// typos such as "Bloomsbery" that `publisher_key` leaves apart.
+pub const MIN_SIMILARITY: f64 = 0.9;
+
+/// Words that vary between the ways one publisher is written.
+const NOISE: &[&str] = &[
+    "the", "and", "publishing", "publishers", "publisher", "publications", "press", "books",
+    "inc", "incorporated", "ltd", "limited", "llc", "plc", "co", "company", "corp", "corporation", "group",
+];
+
+/// What the spellings of one publisher have in common: "Penguin Books Ltd",
+/// "Penguin" and "The Penguin Press" all give "penguin", and "HarperCollins
+/// Publishers" and "Harper Collins" give "harpercollins". Imprints with a name of
+/// their own, such as "Penguin Classics", stay apart.
+pub fn publisher_key(name: &str) -> String {
+    let folded = fold(name);
+    let words: Vec<&str> = folded.split(' ').filter(|word| !NOISE.contains(word)).collect();
+    if words.is_empty() {
+        // A publisher called just "The Press" keeps its words
+        return folded.replace(' ', "");
+    }
+    words.concat()
+}
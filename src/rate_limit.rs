@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use rust_i18n::t;
+use crate::ui;
+
+/// How many times a request answered with 429 Too Many Requests is sent again
+/// before the 429 is returned to the caller. Set from `app.rate_limit_retries`.
+static MAX_RETRIES: AtomicU32 = AtomicU32::new(3);
+
+/// A server asking to wait longer than this is treated as a quota that will not
+/// reset during the run, and its 429 is returned straight away.
+const MAX_WAIT: Duration = Duration::from_secs(300);
+
+pub fn set_max_retries(retries: u32) {
+    MAX_RETRIES.store(retries, Ordering::Relaxed);
+}
+
+/// Sends `request`, and while the server answers 429 waits as long as its
+/// `Retry-After` header asks (or 2, 4, 8... seconds when it does not say) and
+/// sends it again. Requests whose body cannot be copied, such as file uploads,
+/// are sent once.
+pub async fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let max_retries = MAX_RETRIES.load(Ordering::Relaxed);
+    let mut request = request;
+    let mut attempt = 0;
+    loop {
+        let next = request.try_clone();
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                crate::metrics::record_api_error(e.url().and_then(|url| url.host_str()).unwrap_or_default(), None);
+                return Err(e);
+            }
+        };
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= max_retries {
+            record_if_failed(&response);
+            return Ok(response);
+        }
+        let Some(next) = next else {
+            record_if_failed(&response);
+            return Ok(response);
+        };
+
+        attempt += 1;
+        let wait = retry_after(&response).unwrap_or(Duration::from_secs(2u64.pow(attempt)));
+        if wait > MAX_WAIT {
+            record_if_failed(&response);
+            return Ok(response);
+        }
+
+        let host = response.url().host_str().unwrap_or_default().to_string();
+        ui::countdown(t!("rate_limit.waiting", host = host, attempt = attempt, max = max_retries), wait).await;
+        request = next;
+    }
+}
+
+/// Counts server errors, refused credentials and exhausted rate limits for
+/// `/metrics`. Other client errors, such as a 404 for an unknown ISBN, are
+/// ordinary answers.
+fn record_if_failed(response: &Response) {
+    let status = response.status();
+    if status.is_server_error() || matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        crate::metrics::record_api_error(response.url().host_str().unwrap_or_default(), Some(status.as_u16()));
+    }
+}
+
+/// `send` as a method, so call sites read like plain reqwest.
+#[async_trait]
+pub trait SendWithRetry {
+    async fn send_with_retry(self) -> reqwest::Result<Response>;
+}
+
+#[async_trait]
+impl SendWithRetry for RequestBuilder {
+    async fn send_with_retry(self) -> reqwest::Result<Response> {
+        send(self).await
+    }
+}
+
+/// Reads `Retry-After`, given either in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
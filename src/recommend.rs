@@ -0,0 +1,92 @@
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::dates;
+use crate::duplicates::normalize_title;
+use crate::llm::BookSuggestion;
+
+/// Days a book counts as recently read.
+const RECENT_DAYS: i64 = 365;
+
+/// A suggestion, with the edition Google Books or Open Library found for it when
+/// it was checked.
+#[derive(Debug, Serialize)]
+pub struct Recommendation {
+    #[serde(flatten)]
+    pub suggestion: BookSuggestion,
+    pub isbn: Option<String>,
+    pub published: Option<String>,
+}
+
+/// Picks the rows that say most about the reader's taste, up to `limit`: the
+/// ones rated at least `min_rating`, best first, then the ones read in the last
+/// year, most recent first.
+pub fn sample_library<'a>(rows: &'a [MediaRow], read_at_field: &str, min_rating: u32, today: &str, limit: usize) -> Vec<&'a MediaRow> {
+    let rating = |row: &MediaRow| row.get_text("Rating").and_then(|rating| rating.parse::<f64>().ok()).unwrap_or(0.0) as u32;
+    let read_days = |row: &MediaRow| row.get_text(read_at_field).and_then(|date| dates::parse_days(&date));
+
+    let mut rated: Vec<&MediaRow> = rows.iter().filter(|row| min_rating > 0 && rating(row) >= min_rating).collect();
+    rated.sort_by_key(|row| std::cmp::Reverse(rating(row)));
+
+    let today = dates::parse_days(today).unwrap_or_default();
+    let mut recent: Vec<&MediaRow> = rows.iter()
+        .filter(|row| read_days(row).is_some_and(|days| today - days <= RECENT_DAYS))
+        .collect();
+    recent.sort_by_key(|row| std::cmp::Reverse(read_days(row)));
+
+    let mut sample: Vec<&MediaRow> = Vec::new();
+    for row in rated.into_iter().chain(recent) {
+        if sample.len() == limit {
+            break;
+        }
+        if !sample.iter().any(|picked| picked.id == row.id) {
+            sample.push(row);
+        }
+    }
+    sample
+}
+
+/// One line per book for the prompt, with its rating and categories.
+pub fn describe(rows: &[&MediaRow]) -> String {
+    rows.iter()
+        .map(|row| {
+            let mut line = format!("- {} by {}", row.get_text("Title").unwrap_or_default(), row.get_text("Author").unwrap_or_default());
+            let rating = row.get_text("Rating").filter(|rating| rating != "0");
+            let categories = row.get_text("Category").filter(|categories| !categories.is_empty());
+            match (rating, categories) {
+                (Some(rating), Some(categories)) => line.push_str(&format!(" (rated {}/10; {})", rating, categories)),
+                (Some(rating), None) => line.push_str(&format!(" (rated {}/10)", rating)),
+                (None, Some(categories)) => line.push_str(&format!(" ({})", categories)),
+                (None, None) => {}
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drops the suggestions whose title is already in the library.
+pub fn drop_owned(suggestions: Vec<BookSuggestion>, rows: &[MediaRow]) -> Vec<BookSuggestion> {
+    let owned: Vec<String> = rows.iter().filter_map(|row| row.get_text("Title")).map(|title| normalize_title(&title)).collect();
+    suggestions.into_iter()
+        .filter(|suggestion| !owned.contains(&normalize_title(&suggestion.title)))
+        .collect()
+}
+
+pub fn format_recommendations(recommendations: &[Recommendation]) -> String {
+    recommendations.iter()
+        .enumerate()
+        .map(|(i, recommendation)| {
+            let suggestion = &recommendation.suggestion;
+            let mut lines = vec![t!("recommend.item", number = i + 1, title = suggestion.title, author = suggestion.author).into_owned()];
+            if !suggestion.reason.is_empty() {
+                lines.push(format!("   {}", suggestion.reason));
+            }
+            if let Some(isbn) = &recommendation.isbn {
+                lines.push(format!("   {}", t!("recommend.add_hint", isbn = isbn)));
+            }
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,88 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::baserow::{BaserowClient, MediaEntry};
+use crate::config::HooksConfig;
+
+/// Writes an entry Baserow refused (or never received) to the retry directory, so it
+/// can be resubmitted with `wcm retry` without searching and confirming again. The
+/// cover has already been uploaded; the entry only references it by name.
+pub fn save_failed_entry(dir: &Path, entry: &MediaEntry) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let slug: String = entry.title.chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .take(40)
+        .collect();
+    let contents = serde_json::to_string_pretty(entry)?;
+
+    // Imports save several entries at once, often for the same title
+    for attempt in 1.. {
+        let name = match attempt {
+            1 => format!("{}_{}.json", timestamp, slug),
+            n => format!("{}_{}_{}.json", timestamp, slug, n),
+        };
+        let path = std::path::absolute(dir.join(name))?;
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    unreachable!()
+}
+
+/// Queues an entry added with `wcm add --offline`, with the cover to upload
+/// for it, if any, copied beside it as `<entry>.cover`.
+pub fn save_offline_entry(dir: &Path, entry: &MediaEntry, cover: Option<&[u8]>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = save_failed_entry(dir, entry)?;
+    if let Some(cover) = cover {
+        std::fs::write(cover_path(&path), cover)?;
+    }
+    Ok(path)
+}
+
+/// The cover queued with the entry saved at `path`.
+pub fn cover_path(path: &Path) -> PathBuf {
+    path.with_extension("cover")
+}
+
+/// Lists saved entries, oldest first.
+pub fn pending_entries(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Resubmits a saved entry and removes the file once Baserow has accepted it,
+/// uploading first the cover queued with it by an offline add. The entry
+/// already passed `hooks.pre_add` when it was first tried, so only
+/// `hooks.post_add` runs.
+pub async fn retry_entry(client: &BaserowClient, path: &Path, hooks: &HooksConfig) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut entry: MediaEntry = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| format!("{} is not a saved entry: {}", path.display(), e))?;
+
+    let cover = cover_path(path);
+    if cover.exists() {
+        let uploaded = client.upload_file_direct(std::fs::read(&cover)?, "cover.jpg").await?;
+        entry.cover = vec![uploaded.into()];
+    }
+    let created = client.create_media_entry(entry).await?;
+    std::fs::remove_file(path)?;
+    if cover.exists() {
+        std::fs::remove_file(&cover)?;
+    }
+    crate::hooks::after_add(hooks, &created).await;
+    Ok(created.id)
+}
@@ -0,0 +1,126 @@
+use crate::dates;
+
+/// How far ahead `Schedule::next_after` looks before deciding a schedule never
+/// fires, e.g. for February 30th.
+const SEARCH_DAYS: u64 = 5 * 366;
+
+/// A cron schedule: minute, hour, day of month, month and day of week, in UTC.
+/// Each field takes `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and
+/// lists of these (`1,15`); Sunday is 0 or 7. `@hourly`, `@daily`, `@weekly` and
+/// `@monthly` stand for the usual expressions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    /// Whether the day of month and day of week were restricted, as cron then
+    /// fires on a day matching either
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            expression => expression,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("'{}' is not a cron schedule, expected five fields such as \"0 3 * * *\"", expression));
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // 7 is another name for Sunday
+        weekdays[0] |= weekdays[7];
+        weekdays.truncate(7);
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            days_restricted: day != "*",
+            weekdays_restricted: weekday != "*",
+        })
+    }
+
+    fn matches_day(&self, days_since_epoch: u64) -> bool {
+        let (_, month, day) = dates::civil_from_days(days_since_epoch as i64);
+        // 1970-01-01 was a Thursday
+        let weekday = ((days_since_epoch + 4) % 7) as usize;
+        let day_matches = self.days[day as usize];
+        let weekday_matches = self.weekdays[weekday];
+        self.months[month as usize] && match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        }
+    }
+
+    /// The first time the schedule fires after `minute`, both in minutes since
+    /// 1970-01-01 00:00 UTC, or `None` if it never does.
+    pub fn next_after(&self, minute: u64) -> Option<u64> {
+        let mut candidate = minute + 1;
+        let limit = minute + SEARCH_DAYS * 1440;
+        while candidate <= limit {
+            let day = candidate / 1440;
+            if !self.matches_day(day) {
+                candidate = (day + 1) * 1440;
+                continue;
+            }
+            let hour = candidate % 1440 / 60;
+            if !self.hours[hour as usize] {
+                candidate = candidate - candidate % 60 + 60;
+                continue;
+            }
+            if self.minutes[(candidate % 60) as usize] {
+                return Some(candidate);
+            }
+            candidate += 1;
+        }
+        None
+    }
+}
+
+/// Which values from 0 to `max` one field allows; values below `min` are never set.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0).ok_or_else(|| invalid(field))?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (number(start, field)?, number(end, field)?),
+                // `5/10` means from 5 to the end in steps of 10
+                None if step > 1 => (number(range, field)?, max),
+                None => {
+                    let value = number(range, field)?;
+                    (value, value)
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("'{}' is out of range, expected {} to {}", field, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+fn number(text: &str, field: &str) -> Result<u32, String> {
+    text.parse().map_err(|_| invalid(field))
+}
+
+fn invalid(field: &str) -> String {
+    format!("'{}' is not a valid cron field", field)
+}
@@ -0,0 +1,86 @@
+use crate::book_search::BookResult;
+
+/// A book found for a series, with its place in the series when the APIs say.
+#[derive(Debug, Clone)]
+pub struct SeriesVolume {
+    pub number: Option<u32>,
+    pub book: BookResult,
+}
+
+/// Keeps the books that belong to `series`, one per volume, ordered by volume
+/// number with unnumbered books last. Books earlier in `books` win over later
+/// editions of the same volume, so Google Books results should come first.
+pub fn collect_volumes(series: &str, books: Vec<BookResult>) -> Vec<SeriesVolume> {
+    let series = series.trim().to_lowercase();
+    let mut volumes: Vec<SeriesVolume> = Vec::new();
+
+    for book in books {
+        if !belongs_to(&series, &book) {
+            continue;
+        }
+
+        let number = volume_number(&series, &book);
+        let title = book.get_full_title().to_lowercase();
+        let seen = volumes.iter().any(|volume| match (number, volume.number) {
+            (Some(number), Some(existing)) => number == existing,
+            _ => volume.book.get_full_title().to_lowercase() == title,
+        });
+        if !seen {
+            volumes.push(SeriesVolume { number, book });
+        }
+    }
+
+    volumes.sort_by(|a, b| match (a.number, b.number) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.book.get_full_title().cmp(&b.book.get_full_title()),
+    });
+    volumes
+}
+
+/// A book belongs to the series when the API lists it in a series of that name,
+/// or its title or subtitle names the series (e.g. "Mort: A Discworld Novel").
+fn belongs_to(series: &str, book: &BookResult) -> bool {
+    if book.get_full_title().to_lowercase().contains(series) {
+        return true;
+    }
+    match book {
+        // Google's series data has no name, but the query already asked for it
+        BookResult::Google(book) => book.volume_info.series_info.is_some(),
+        BookResult::OpenLibrary(book) => book.series.iter().flatten()
+            .any(|name| name.to_lowercase().contains(series)),
+        BookResult::Manual(_) => false,
+    }
+}
+
+fn volume_number(series: &str, book: &BookResult) -> Option<u32> {
+    match book {
+        BookResult::Google(google) => google.volume_info.series_info.as_ref()
+            .and_then(|info| info.book_display_number.as_deref())
+            .and_then(first_number),
+        BookResult::OpenLibrary(open_library) => open_library.series.iter().flatten()
+            .filter_map(|name| {
+                let name = name.to_lowercase();
+                let start = name.find(series)? + series.len();
+                first_number(&name[start..])
+            })
+            .next(),
+        BookResult::Manual(_) => None,
+    }
+    .or_else(|| {
+        // "Discworld 5: ..." or "Mort (Discworld Novel 4)"
+        let title = book.get_full_title().to_lowercase();
+        let start = title.find(series)? + series.len();
+        first_number(&title[start..])
+    })
+}
+
+/// The first run of digits in `text`, e.g. 5 in " -- 5" or "#5".
+fn first_number(text: &str) -> Option<u32> {
+    let digits: String = text.chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
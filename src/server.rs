@@ -0,0 +1,269 @@
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::baserow::{BaserowClient, MediaRow};
+use crate::book_search::{AddedBook, BookSummary, CombinedBookSearcher, DraftStore, DraftSummary, SearchResults};
+use crate::config::Config;
+use crate::ui;
+
+#[derive(Clone)]
+pub struct AppState {
+    searcher: Arc<CombinedBookSearcher>,
+    baserow_client: BaserowClient,
+    config: Arc<Config>,
+    drafts: Arc<DraftStore>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddRequest {
+    pub isbn: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    #[serde(default)]
+    pub ebook: bool,
+    /// Which search result to use, as listed by `/search` (default: the first)
+    #[serde(default)]
+    pub index: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmRequest {
+    pub draft_id: u64,
+    #[serde(default = "default_accept")]
+    pub accept: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodeResponse {
+    pub isbn: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub isbn: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LibraryQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub source: String,
+    pub results: Vec<BookSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LibraryResponse {
+    pub count: Option<u32>,
+    pub page: u32,
+    pub results: Vec<MediaRow>,
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+
+    fn internal(error: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::BAD_GATEWAY, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+impl AppState {
+    pub fn new(searcher: CombinedBookSearcher, baserow_client: BaserowClient, config: Config) -> Self {
+        Self {
+            searcher: Arc::new(searcher),
+            baserow_client,
+            config: Arc::new(config),
+            drafts: Arc::new(DraftStore::default()),
+        }
+    }
+}
+
+fn default_accept() -> bool {
+    true
+}
+
+const INDEX_HTML: &str = include_str!("web/index.html");
+
+// Phone photos are large even after the page downscales them
+const DECODE_BODY_LIMIT: usize = 10 * 1024 * 1024;
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/add", post(add_book))
+        .route("/search", get(search_books))
+        .route("/library", get(list_library))
+        .route("/prepare", post(prepare_book))
+        .route("/confirm", post(confirm_book))
+        .route("/decode", post(decode_barcode).layer(DefaultBodyLimit::max(DECODE_BODY_LIMIT)))
+        .route("/metrics", get(crate::metrics::metrics_page))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        // The page itself holds no data; it asks for the token before calling the API
+        .route("/", get(index_page))
+        .with_state(state)
+}
+
+pub async fn serve(state: AppState, bind: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if state.config.server.auth_token.is_none() {
+        ui::warn(format!("Warning: no server.auth_token configured, the API is open to anyone who can reach {}", bind));
+    }
+
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    ui::info(format!("wcm server listening on http://{} (web UI at /)", listener.local_addr()?));
+
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+/// Whether `request` carries the bearer token, or no token is required.
+pub(crate) fn is_authorized(request: &Request, auth_token: Option<&str>) -> bool {
+    let Some(expected) = auth_token else {
+        return true;
+    };
+    let provided = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    provided.is_some_and(|provided| constant_time_eq(provided.as_bytes(), expected.as_bytes()))
+}
+
+/// Compares every byte whatever the first difference, so the time taken does not
+/// tell a caller how much of the token it guessed right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn require_token(State(state): State<AppState>, request: Request, next: Next) -> Result<Response, ApiError> {
+    if !is_authorized(&request, state.config.server.auth_token.as_deref()) {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token"));
+    }
+
+    Ok(next.run(request).await)
+}
+
+async fn find_books(searcher: &CombinedBookSearcher, isbn: Option<&str>, title: Option<&str>, author: Option<&str>) -> Result<SearchResults, ApiError> {
+    let results = match (isbn, title, author) {
+        (Some(isbn), _, _) => {
+            // Barcode scanners and shortcut apps often include separators
+            let isbn: String = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+            searcher.find_by_isbn(&isbn).await
+        }
+        (None, Some(title), Some(author)) => searcher.find_by_title_author(title, Some(author)).await,
+        (None, Some(title), None) => searcher.find_by_title_author(title, None).await,
+        _ => return Err(ApiError::new(StatusCode::BAD_REQUEST, "Provide either isbn OR a title, with an optional author")),
+    };
+
+    results.map_err(ApiError::internal)
+}
+
+async fn select_book(state: &AppState, request: &AddRequest) -> Result<crate::book_search::BookResult, ApiError> {
+    let results = find_books(&state.searcher, request.isbn.as_deref(), request.title.as_deref(), request.author.as_deref()).await?;
+
+    // There is nobody at the terminal to pick from a list, so default to the best match
+    results.books.into_iter().nth(request.index)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "No matching book found in Google Books or Open Library"))
+}
+
+async fn index_page() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn add_book(State(state): State<AppState>, Json(request): Json<AddRequest>) -> Result<(StatusCode, Json<AddedBook>), ApiError> {
+    let book = select_book(&state, &request).await?;
+
+    let added = state.searcher.add_book_unattended(&book, request.ebook).await
+        .map_err(ApiError::internal)?;
+
+    Ok((StatusCode::CREATED, Json(added)))
+}
+
+async fn search_books(State(state): State<AppState>, Query(query): Query<SearchQuery>) -> Result<Json<SearchResponse>, ApiError> {
+    let results = find_books(&state.searcher, query.isbn.as_deref(), query.title.as_deref(), query.author.as_deref()).await?;
+
+    let hits = results.books.iter()
+        .take(state.config.app.max_search_results)
+        .map(BookSummary::from)
+        .collect();
+
+    Ok(Json(SearchResponse {
+        source: results.source,
+        results: hits,
+    }))
+}
+
+async fn list_library(State(state): State<AppState>, Query(query): Query<LibraryQuery>) -> Result<Json<LibraryResponse>, ApiError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let size = query.size.unwrap_or(50).clamp(1, 200);
+
+    let response = state.baserow_client.fetch_media_page(page, size).await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(LibraryResponse {
+        count: response.count,
+        page,
+        results: response.results,
+    }))
+}
+
+async fn prepare_book(State(state): State<AppState>, Json(request): Json<AddRequest>) -> Result<Json<DraftSummary>, ApiError> {
+    let book = select_book(&state, &request).await?;
+
+    let prepared = state.searcher.prepare_book(&book, request.ebook).await
+        .map_err(ApiError::internal)?;
+
+    let draft_id = state.drafts.insert(prepared.clone());
+    Ok(Json(DraftSummary::new(draft_id, &prepared)))
+}
+
+async fn confirm_book(State(state): State<AppState>, Json(request): Json<ConfirmRequest>) -> Result<Response, ApiError> {
+    let prepared = state.drafts.take(request.draft_id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("No pending draft with id {}", request.draft_id)))?;
+
+    if !request.accept {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+
+    let added = state.searcher.submit_prepared(&prepared).await
+        .map_err(ApiError::internal)?;
+
+    Ok((StatusCode::CREATED, Json(added)).into_response())
+}
+
+async fn decode_barcode(body: Bytes) -> Result<Json<DecodeResponse>, ApiError> {
+    // Decoding scans the whole image, keep it off the async workers
+    let isbn = tokio::task::spawn_blocking(move || {
+        image::load_from_memory(&body).ok().and_then(|img| crate::barcode::decode_ean13(&img))
+    })
+    .await
+    .map_err(ApiError::internal)?;
+
+    isbn.map(|isbn| Json(DecodeResponse { isbn }))
+        .ok_or_else(|| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "No EAN-13 barcode found in the image"))
+}
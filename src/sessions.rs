@@ -0,0 +1,95 @@
+use std::io::Write;
+use std::path::Path;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use crate::dates;
+
+/// One sitting with a book, as logged by `wcm log`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Media row ID
+    pub book_id: u64,
+    pub title: String,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub pages: Option<u32>,
+    pub minutes: Option<u32>,
+}
+
+/// Adds a session to the end of the log, one JSON object per line.
+pub fn append(path: &Path, session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(session)?)?;
+    Ok(())
+}
+
+/// Reads every logged session, oldest first. A missing log has none.
+pub fn load(path: &Path) -> Result<Vec<Session>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    std::fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| serde_json::from_str(line)
+            .map_err(|e| format!("{} line {}: {}", path.display(), number + 1, e).into()))
+        .collect()
+}
+
+/// Totals of the sessions logged for one book.
+#[derive(Debug, Serialize)]
+pub struct BookProgress {
+    pub book_id: u64,
+    pub title: String,
+    pub sessions: usize,
+    pub pages: u32,
+    pub minutes: u32,
+    /// The book's page count, when the media row has one
+    pub total_pages: Option<u32>,
+    /// When the book will be finished at the pace since its first session
+    pub estimated_finish: Option<String>,
+}
+
+/// Adds up the sessions for `book_id`. With the book's page count, the pace since
+/// the first session (pages per calendar day, counting days without reading) gives
+/// an estimated finish date after `today`.
+pub fn progress(sessions: &[Session], book_id: u64, title: &str, total_pages: Option<u32>, today: &str) -> BookProgress {
+    let logged: Vec<&Session> = sessions.iter().filter(|session| session.book_id == book_id).collect();
+    let pages = logged.iter().filter_map(|session| session.pages).sum();
+    let minutes = logged.iter().filter_map(|session| session.minutes).sum();
+
+    let estimated_finish = (|| {
+        let remaining = total_pages?.checked_sub(pages).filter(|remaining| *remaining > 0)?;
+        let today = dates::parse_days(today)?;
+        let first = logged.iter().filter_map(|session| dates::parse_days(&session.date)).min()?;
+        let days = (today - first + 1).max(1) as f64;
+        let per_day = pages as f64 / days;
+        (per_day > 0.0).then(|| dates::format_days(today + (remaining as f64 / per_day).ceil() as i64))
+    })();
+
+    BookProgress {
+        book_id,
+        title: title.to_string(),
+        sessions: logged.len(),
+        pages,
+        minutes,
+        total_pages,
+        estimated_finish,
+    }
+}
+
+pub fn format_progress(progress: &BookProgress) -> String {
+    let mut lines = vec![t!("log.totals", sessions = progress.sessions, pages = progress.pages, minutes = progress.minutes).into_owned()];
+    if let Some(total) = progress.total_pages.filter(|total| *total > 0) {
+        let percent = (progress.pages as f64 / total as f64 * 100.0).min(100.0);
+        lines.push(t!("log.of_total", pages = progress.pages.min(total), total = total, percent = format!("{:.0}", percent)).into_owned());
+    }
+    if let Some(finish) = &progress.estimated_finish {
+        lines.push(t!("log.estimated_finish", date = finish).into_owned());
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,137 @@
+use rust_i18n::t;
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::book_search::BookResult;
+use crate::duplicates::{fold, normalize_title};
+use crate::llm::BookSuggestion;
+
+/// Where a similar book was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SimilarSource {
+    #[serde(rename = "google_books")]
+    GoogleBooks,
+    #[serde(rename = "open_library")]
+    OpenLibrary,
+    #[serde(rename = "llm")]
+    Llm,
+}
+
+impl std::fmt::Display for SimilarSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SimilarSource::GoogleBooks => write!(f, "Google Books"),
+            SimilarSource::OpenLibrary => write!(f, "Open Library"),
+            SimilarSource::Llm => write!(f, "LLM"),
+        }
+    }
+}
+
+/// A book comparable to the one `wcm similar` was given, with what points to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarBook {
+    pub title: String,
+    pub author: String,
+    pub isbn: Option<String>,
+    pub sources: Vec<SimilarSource>,
+    /// Subjects it shares with the book, as the catalogues file them
+    pub shared_subjects: Vec<String>,
+    /// Why the LLM suggested it
+    pub reason: Option<String>,
+}
+
+/// The subjects a catalogue files a book under: Google Books categories or Open
+/// Library subjects.
+pub fn subjects(book: &BookResult) -> Vec<String> {
+    match book {
+        BookResult::Google(book) => book.volume_info.categories.clone().unwrap_or_default(),
+        BookResult::OpenLibrary(book) => book.subject.clone().unwrap_or_default(),
+        BookResult::Manual(_) => Vec::new(),
+    }
+}
+
+/// Merges the books found by the catalogues and the LLM's suggestions into one
+/// list, one entry per title, leaving out the book itself (`title`) and anything
+/// already in `owned`. Books found by more sources come first, then those sharing
+/// more of `subjects`; otherwise the order found is kept.
+pub fn collect_similar(
+    title: &str,
+    subjects: &[String],
+    books: Vec<(BookResult, SimilarSource)>,
+    suggestions: Vec<BookSuggestion>,
+    owned: &[MediaRow],
+) -> Vec<SimilarBook> {
+    let mut skipped: Vec<String> = owned.iter().filter_map(|row| row.get_text("Title")).map(|title| normalize_title(&title)).collect();
+    skipped.push(normalize_title(title));
+    let subjects: Vec<String> = subjects.iter().map(|subject| fold(subject)).collect();
+
+    let found = books.into_iter().map(|(book, source)| {
+        let shared: Vec<String> = self::subjects(&book).into_iter()
+            .filter(|subject| subjects.contains(&fold(subject)))
+            .collect();
+        SimilarBook {
+            title: book.get_full_title(),
+            author: book.get_all_authors(),
+            isbn: book.get_isbn(),
+            sources: vec![source],
+            shared_subjects: shared,
+            reason: None,
+        }
+    });
+    let suggested = suggestions.into_iter().map(|suggestion| SimilarBook {
+        title: suggestion.title,
+        author: suggestion.author,
+        isbn: None,
+        sources: vec![SimilarSource::Llm],
+        shared_subjects: Vec::new(),
+        reason: Some(suggestion.reason).filter(|reason| !reason.is_empty()),
+    });
+
+    let mut similar: Vec<SimilarBook> = Vec::new();
+    for book in found.chain(suggested) {
+        let key = normalize_title(&book.title);
+        if key.is_empty() || skipped.contains(&key) {
+            continue;
+        }
+        match similar.iter_mut().find(|existing| normalize_title(&existing.title) == key) {
+            Some(existing) => {
+                for source in book.sources {
+                    if !existing.sources.contains(&source) {
+                        existing.sources.push(source);
+                    }
+                }
+                for subject in book.shared_subjects {
+                    if !existing.shared_subjects.iter().any(|shared| fold(shared) == fold(&subject)) {
+                        existing.shared_subjects.push(subject);
+                    }
+                }
+                existing.isbn = existing.isbn.take().or(book.isbn);
+                existing.reason = existing.reason.take().or(book.reason);
+            }
+            None => similar.push(book),
+        }
+    }
+
+    similar.sort_by_key(|book| std::cmp::Reverse((book.sources.len(), book.shared_subjects.len())));
+    similar
+}
+
+pub fn format_similar(books: &[SimilarBook]) -> String {
+    books.iter()
+        .enumerate()
+        .map(|(i, book)| {
+            let sources: Vec<String> = book.sources.iter().map(|source| source.to_string()).collect();
+            let mut lines = vec![t!("similar.item", number = i + 1, title = book.title, author = book.author, sources = sources.join(", ")).into_owned()];
+            if !book.shared_subjects.is_empty() {
+                lines.push(format!("   {}", t!("similar.shared", subjects = book.shared_subjects.join("; "))));
+            }
+            if let Some(reason) = &book.reason {
+                lines.push(format!("   {}", reason));
+            }
+            if let Some(isbn) = &book.isbn {
+                lines.push(format!("   {}", t!("recommend.add_hint", isbn = isbn)));
+            }
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
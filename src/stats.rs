@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use image::{Rgb, RgbImage};
+use plotters::coord::ranged1d::SegmentValue;
+use plotters::prelude::*;
+use plotters_backend::{text_anchor, BackendColor, BackendCoord, BackendTextStyle, DrawingErrorKind};
+use rust_i18n::t;
+use rusttype::{point, Font, Scale};
+use serde::Serialize;
+use crate::baserow::MediaRow;
+use crate::config::MediaFieldsConfig;
+use crate::goals::GoalProgress;
+use crate::sessions::Session;
+use crate::text::truncate;
+
+/// Fonts tried for the chart text when `app.chart_font` is unset, on Linux,
+/// macOS and Windows.
+const FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// Categories shown on the chart; the rest are only in the summary's JSON.
+const CHART_CATEGORIES: usize = 10;
+
+const CHART_WIDTH: u32 = 1200;
+const CHART_HEIGHT: u32 = 1500;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryCount {
+    pub name: String,
+    pub count: u32,
+}
+
+/// A year of the library in numbers, for `wcm stats`.
+#[derive(Debug, Serialize)]
+pub struct YearStats {
+    pub year: u32,
+    /// Books added in each month, January first
+    pub added: [u32; 12],
+    /// Rows without a created date, which count towards no month
+    pub undated: u32,
+    /// Rows marked read with a read date in the year
+    pub read: u32,
+    /// Pages logged with `wcm log` in each month, plus the page count of the
+    /// books finished that month without any logged session
+    pub pages: [u32; 12],
+    /// How many of the books added in the year are in each category, most first
+    pub categories: Vec<CategoryCount>,
+    /// Progress towards the year's reading goal, when one is set with `wcm goal set`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal: Option<GoalProgress>,
+}
+
+impl YearStats {
+    pub fn total_added(&self) -> u32 {
+        self.added.iter().sum()
+    }
+
+    pub fn total_pages(&self) -> u32 {
+        self.pages.iter().sum()
+    }
+}
+
+/// The month, 1 to 12, of a `YYYY-MM-DD` date or timestamp in `year`.
+fn month_in(date: &str, year: u32) -> Option<usize> {
+    let prefix = format!("{}-", year);
+    let month: usize = date.strip_prefix(&prefix)?.get(..2)?.parse().ok()?;
+    (1..=12).contains(&month).then_some(month)
+}
+
+fn categories(row: &MediaRow) -> Vec<String> {
+    row.fields.get("Category")
+        .and_then(|categories| categories.as_array())
+        .map(|categories| categories.iter()
+            .filter_map(|category| category.get("value").and_then(|value| value.as_str()))
+            .map(str::to_string)
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Counts the books added and read in `year`, the pages read each month and
+/// the categories of the year's books.
+pub fn aggregate(rows: &[MediaRow], sessions: &[Session], fields: &MediaFieldsConfig, year: u32) -> YearStats {
+    let mut stats = YearStats { year, added: [0; 12], undated: 0, read: 0, pages: [0; 12], categories: Vec::new(), goal: None };
+    let mut category_counts: HashMap<String, u32> = HashMap::new();
+    let logged: HashSet<u64> = sessions.iter().filter(|session| session.pages.is_some()).map(|session| session.book_id).collect();
+
+    for row in rows {
+        match row.get_text(&fields.created_at) {
+            Some(created) => if let Some(month) = month_in(&created, year) {
+                stats.added[month - 1] += 1;
+                for category in categories(row) {
+                    *category_counts.entry(category).or_default() += 1;
+                }
+            },
+            None => stats.undated += 1,
+        }
+
+        let read = row.fields.get("Read").and_then(|read| read.as_bool()).unwrap_or(false);
+        if let Some(month) = row.get_text(&fields.read_at).filter(|_| read).and_then(|date| month_in(&date, year)) {
+            stats.read += 1;
+            if !logged.contains(&row.id) {
+                let pages = row.get_text(&fields.pages).and_then(|pages| pages.parse::<f64>().ok()).unwrap_or_default();
+                stats.pages[month - 1] += pages as u32;
+            }
+        }
+    }
+
+    for session in sessions {
+        if let (Some(month), Some(pages)) = (month_in(&session.date, year), session.pages) {
+            stats.pages[month - 1] += pages;
+        }
+    }
+
+    stats.categories = category_counts.into_iter().map(|(name, count)| CategoryCount { name, count }).collect();
+    stats.categories.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    stats
+}
+
+/// The year's totals, busiest month and top categories, and the goal's
+/// progress bar as `wcm goal status` prints it.
+pub fn format_summary(stats: &YearStats) -> String {
+    let mut lines = vec![
+        t!("stats.heading", year = stats.year).into_owned(),
+        t!("stats.added", count = stats.total_added()).into_owned(),
+    ];
+    if let Some((month, count)) = stats.added.iter().enumerate().filter(|(_, count)| **count > 0).max_by_key(|(month, count)| (**count, std::cmp::Reverse(*month))) {
+        lines.push(t!("stats.busiest_month", month = format!("{}-{:02}", stats.year, month + 1), count = count).into_owned());
+    }
+    lines.push(t!("stats.read", count = stats.read, pages = stats.total_pages()).into_owned());
+    if !stats.categories.is_empty() {
+        let top: Vec<String> = stats.categories.iter().take(5).map(|category| format!("{} ({})", category.name, category.count)).collect();
+        lines.push(t!("stats.top_categories", categories = top.join(", ")).into_owned());
+    }
+    if stats.undated > 0 {
+        lines.push(t!("stats.undated", count = stats.undated).into_owned());
+    }
+    if let Some(goal) = &stats.goal {
+        lines.push(String::new());
+        lines.push(crate::goals::format_progress(goal));
+    }
+    lines.join("\n")
+}
+
+/// The font in `configured`, or the first of `FONT_CANDIDATES` that exists.
+pub fn load_font(configured: Option<&str>) -> Result<Font<'static>, Box<dyn std::error::Error>> {
+    let candidates: Vec<&str> = match configured {
+        Some(path) => vec![path],
+        None => FONT_CANDIDATES.to_vec(),
+    };
+    for path in candidates {
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        return Font::try_from_vec(bytes).ok_or_else(|| format!("{} is not a TrueType font", path).into());
+    }
+    Err(t!("stats.no_font").into_owned().into())
+}
+
+/// A drawing backend on an RGB image that draws text with rusttype, since the
+/// chart library's own font support needs the system font libraries.
+struct ImageBackend<'a> {
+    image: &'a mut RgbImage,
+    font: &'a Font<'static>,
+}
+
+impl ImageBackend<'_> {
+    fn blend(&mut self, (x, y): BackendCoord, color: BackendColor, coverage: f64) {
+        if x < 0 || y < 0 || x as u32 >= self.image.width() || y as u32 >= self.image.height() {
+            return;
+        }
+        let alpha = (color.alpha * coverage).clamp(0.0, 1.0);
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        let (r, g, b) = color.rgb;
+        for (channel, value) in pixel.0.iter_mut().zip([r, g, b]) {
+            *channel = (*channel as f64 * (1.0 - alpha) + value as f64 * alpha).round() as u8;
+        }
+    }
+
+    /// Width and height of `text` at `size` pixels.
+    fn measure(&self, text: &str, size: f64) -> (u32, u32) {
+        let scale = Scale::uniform(size as f32);
+        let metrics = self.font.v_metrics(scale);
+        let width = self.font.layout(text, scale, point(0.0, 0.0))
+            .last()
+            .map(|glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+            .unwrap_or_default();
+        (width.ceil() as u32, (metrics.ascent - metrics.descent).ceil() as u32)
+    }
+}
+
+impl DrawingBackend for ImageBackend<'_> {
+    type ErrorType = std::io::Error;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        Ok(())
+    }
+
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.blend(point, color, 1.0);
+        Ok(())
+    }
+
+    fn draw_text<S: BackendTextStyle>(&mut self, text: &str, style: &S, (x, y): BackendCoord) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let (width, height) = self.measure(text, style.size());
+        let (width, height) = (width as i32, height as i32);
+        let anchor = style.anchor();
+        let dx = match anchor.h_pos {
+            text_anchor::HPos::Left => 0,
+            text_anchor::HPos::Center => -width / 2,
+            text_anchor::HPos::Right => -width,
+        };
+        let dy = match anchor.v_pos {
+            text_anchor::VPos::Top => 0,
+            text_anchor::VPos::Center => -height / 2,
+            text_anchor::VPos::Bottom => -height,
+        };
+
+        let scale = Scale::uniform(style.size() as f32);
+        let ascent = self.font.v_metrics(scale).ascent;
+        let color = style.color();
+        let glyphs: Vec<_> = self.font.layout(text, scale, point((x + dx) as f32, (y + dy) as f32 + ascent)).collect();
+        for glyph in glyphs {
+            if let Some(bounds) = glyph.pixel_bounding_box() {
+                glyph.draw(|gx, gy, coverage| {
+                    self.blend((bounds.min.x + gx as i32, bounds.min.y + gy as i32), color, coverage as f64);
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn estimate_text_size<S: BackendTextStyle>(&self, text: &str, style: &S) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        Ok(self.measure(text, style.size()))
+    }
+}
+
+/// Draws the year as a PNG at `path`: bars of the books added per month, a
+/// line of the pages read per month and bars of the top categories.
+pub fn render_chart(stats: &YearStats, font: &Font<'static>, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut image = RgbImage::from_pixel(CHART_WIDTH, CHART_HEIGHT, Rgb([255, 255, 255]));
+    {
+        let root = ImageBackend { image: &mut image, font }.into_drawing_area();
+        let root = root.titled(&t!("stats.chart_title", year = stats.year), ("sans-serif", 36))?;
+        let panels = root.split_evenly((3, 1));
+        let label = ("sans-serif", 16);
+        let caption = ("sans-serif", 24);
+        let month_label = |value: &SegmentValue<u32>| match value {
+            SegmentValue::CenterOf(month) | SegmentValue::Exact(month) => month.to_string(),
+            SegmentValue::Last => String::new(),
+        };
+
+        let max_added = stats.added.iter().copied().max().unwrap_or_default().max(1);
+        let mut added = ChartBuilder::on(&panels[0])
+            .caption(t!("stats.chart_added"), caption)
+            .margin(20)
+            .x_label_area_size(56)
+            .y_label_area_size(56)
+            .build_cartesian_2d((1u32..12u32).into_segmented(), 0u32..max_added + max_added / 10 + 1)?;
+        added.configure_mesh()
+            .disable_x_mesh()
+            .x_labels(12)
+            .x_label_formatter(&month_label)
+            .x_desc(t!("stats.chart_month"))
+            .label_style(label)
+            .axis_desc_style(label)
+            .draw()?;
+        added.draw_series(stats.added.iter().enumerate().map(|(month, count)| {
+            let month = month as u32 + 1;
+            let mut bar = Rectangle::new([(SegmentValue::Exact(month), 0), (SegmentValue::Exact(month + 1), *count)], BLUE.mix(0.7).filled());
+            bar.set_margin(0, 0, 12, 12);
+            bar
+        }))?;
+
+        let max_pages = stats.pages.iter().copied().max().unwrap_or_default().max(1);
+        let mut pages = ChartBuilder::on(&panels[1])
+            .caption(t!("stats.chart_pages"), caption)
+            .margin(20)
+            .x_label_area_size(56)
+            .y_label_area_size(56)
+            .build_cartesian_2d(1u32..12u32, 0u32..max_pages + max_pages / 10 + 1)?;
+        pages.configure_mesh()
+            .disable_x_mesh()
+            .x_labels(12)
+            .x_desc(t!("stats.chart_month"))
+            .label_style(label)
+            .axis_desc_style(label)
+            .draw()?;
+        let points: Vec<(u32, u32)> = stats.pages.iter().enumerate().map(|(month, pages)| (month as u32 + 1, *pages)).collect();
+        pages.draw_series(LineSeries::new(points.clone(), RED.stroke_width(3)))?;
+        pages.draw_series(points.into_iter().map(|point| Circle::new(point, 5, RED.filled())))?;
+
+        let shown: Vec<&CategoryCount> = stats.categories.iter().take(CHART_CATEGORIES).collect();
+        let max_count = shown.first().map(|category| category.count).unwrap_or_default().max(1);
+        let rows = shown.len().max(1) as u32;
+        let mut categories = ChartBuilder::on(&panels[2])
+            .caption(t!("stats.chart_categories"), caption)
+            .margin(20)
+            .x_label_area_size(36)
+            .y_label_area_size(260)
+            .build_cartesian_2d(0u32..max_count + max_count / 10 + 1, (0u32..rows - 1).into_segmented())?;
+        categories.configure_mesh()
+            .disable_y_mesh()
+            .y_labels(shown.len().max(1))
+            // The first category is drawn at the top
+            .y_label_formatter(&|value: &SegmentValue<u32>| match value {
+                SegmentValue::CenterOf(row) | SegmentValue::Exact(row) => rows.checked_sub(row + 1)
+                    .and_then(|index| shown.get(index as usize))
+                    .map(|category| truncate(&category.name, 28))
+                    .unwrap_or_default(),
+                SegmentValue::Last => String::new(),
+            })
+            .label_style(label)
+            .draw()?;
+        categories.draw_series(shown.iter().enumerate().map(|(index, category)| {
+            let row = rows - 1 - index as u32;
+            let mut bar = Rectangle::new([(0, SegmentValue::Exact(row)), (category.count, SegmentValue::Exact(row + 1))], GREEN.mix(0.7).filled());
+            bar.set_margin(6, 6, 0, 0);
+            bar
+        }))?;
+
+        root.present()?;
+    }
+    image.save(path)?;
+    Ok(())
+}
@@ -0,0 +1,418 @@
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::book_search::{CombinedBookSearcher, DraftStore, PreparedBook};
+use crate::config::TelegramConfig;
+use crate::ui;
+use crate::rate_limit::SendWithRetry;
+
+const API_BASE: &str = "https://api.telegram.org";
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct TelegramResponse<T> {
+    pub ok: bool,
+    pub result: Option<T>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+    pub callback_query: Option<CallbackQuery>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    pub message_id: i64,
+    pub chat: Chat,
+    pub text: Option<String>,
+    pub caption: Option<String>,
+    pub photo: Option<Vec<PhotoSize>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PhotoSize {
+    pub file_id: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub id: String,
+    pub data: Option<String>,
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TelegramFile {
+    pub file_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InlineKeyboardButton {
+    pub text: String,
+    pub callback_data: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InlineKeyboardMarkup {
+    pub inline_keyboard: Vec<Vec<InlineKeyboardButton>>,
+}
+
+/// What the user asked the bot to look up.
+#[derive(Debug, PartialEq)]
+pub enum BookQuery {
+    Isbn(String),
+    TitleAuthor(String, String),
+    Title(String),
+}
+
+#[derive(Debug)]
+pub enum TelegramError {
+    RequestFailed(reqwest::Error),
+    ApiError(String),
+}
+
+impl std::fmt::Display for TelegramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TelegramError::RequestFailed(e) => write!(f, "Telegram request failed: {}", e),
+            TelegramError::ApiError(msg) => write!(f, "Telegram API error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TelegramError {}
+
+impl From<reqwest::Error> for TelegramError {
+    fn from(error: reqwest::Error) -> Self {
+        TelegramError::RequestFailed(error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TelegramClient {
+    client: reqwest::Client,
+    base_url: String,
+    token: String,
+}
+
+impl TelegramClient {
+    pub fn new(token: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: API_BASE.to_string(),
+            token,
+        }
+    }
+
+    /// Sends requests to `base_url` instead of the Bot API, e.g. a local Bot API server.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    async fn call<T>(&self, method: &str, body: serde_json::Value) -> Result<T, TelegramError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = format!("{}/bot{}/{}", self.base_url, self.token, method);
+        let response: TelegramResponse<T> = self.client
+            .post(&url)
+            .json(&body)
+            .send_with_retry()
+            .await?
+            .json()
+            .await?;
+
+        match (response.ok, response.result) {
+            (true, Some(result)) => Ok(result),
+            _ => Err(TelegramError::ApiError(
+                response.description.unwrap_or_else(|| format!("{} failed", method))
+            )),
+        }
+    }
+
+    pub async fn get_updates(&self, offset: i64) -> Result<Vec<Update>, TelegramError> {
+        self.call("getUpdates", serde_json::json!({
+            "offset": offset,
+            "timeout": POLL_TIMEOUT_SECS,
+            "allowed_updates": ["message", "callback_query"],
+        })).await
+    }
+
+    pub async fn send_message(&self, chat_id: i64, text: &str, keyboard: Option<InlineKeyboardMarkup>) -> Result<(), TelegramError> {
+        let mut body = serde_json::json!({ "chat_id": chat_id, "text": text });
+        if let Some(keyboard) = keyboard {
+            body["reply_markup"] = serde_json::to_value(keyboard).unwrap_or_default();
+        }
+        self.call::<serde_json::Value>("sendMessage", body).await?;
+        Ok(())
+    }
+
+    pub async fn edit_message_text(&self, chat_id: i64, message_id: i64, text: &str) -> Result<(), TelegramError> {
+        self.call::<serde_json::Value>("editMessageText", serde_json::json!({
+            "chat_id": chat_id,
+            "message_id": message_id,
+            "text": text,
+        })).await?;
+        Ok(())
+    }
+
+    pub async fn answer_callback_query(&self, callback_query_id: &str) -> Result<(), TelegramError> {
+        self.call::<serde_json::Value>("answerCallbackQuery", serde_json::json!({
+            "callback_query_id": callback_query_id,
+        })).await?;
+        Ok(())
+    }
+
+    pub async fn download_file(&self, file_id: &str) -> Result<Vec<u8>, TelegramError> {
+        let file: TelegramFile = self.call("getFile", serde_json::json!({ "file_id": file_id })).await?;
+        let file_path = file.file_path
+            .ok_or_else(|| TelegramError::ApiError("File is not available for download".to_string()))?;
+
+        let url = format!("{}/file/bot{}/{}", self.base_url, self.token, file_path);
+        let bytes = self.client.get(&url).send_with_retry().await?.bytes().await?;
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Parses a chat message into a lookup. Accepts a bare ISBN, a
+/// "Title by Author" line, or a title on its own.
+pub fn parse_book_query(text: &str) -> Option<BookQuery> {
+    let text = text.trim();
+    let compact: String = text.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+    let looks_like_isbn = (compact.len() == 10 || compact.len() == 13)
+        && compact.chars().enumerate().all(|(i, c)| c.is_ascii_digit() || (i == 9 && (c == 'X' || c == 'x')));
+    if looks_like_isbn {
+        return Some(BookQuery::Isbn(compact));
+    }
+
+    // An ASCII-only lowercasing keeps the byte offsets of `text`
+    let lower = text.to_ascii_lowercase();
+    let Some(split_at) = lower.rfind(" by ") else {
+        return (!text.is_empty()).then(|| BookQuery::Title(text.to_string()));
+    };
+    let title = text[..split_at].trim();
+    let author = text[split_at + 4..].trim();
+    if title.is_empty() || author.is_empty() {
+        return None;
+    }
+    Some(BookQuery::TitleAuthor(title.to_string(), author.to_string()))
+}
+
+/// Whether a message starts with the word "ebook", in any case, and the rest
+/// of it. "ebooks of ..." is not the prefix.
+pub fn strip_ebook_prefix(text: &str) -> (bool, &str) {
+    let word_end = text.find(char::is_whitespace).unwrap_or(text.len());
+    if text[..word_end].eq_ignore_ascii_case("ebook") {
+        (true, text[word_end..].trim())
+    } else {
+        (false, text)
+    }
+}
+
+pub fn format_summary(prepared: &PreparedBook) -> String {
+    let mut summary = String::new();
+    if !prepared.possible_duplicates.is_empty() {
+        summary.push_str(&format!("{}\n", t!("duplicates.heading")));
+        for duplicate in &prepared.possible_duplicates {
+            summary.push_str(&format!("{}\n", t!("duplicates.entry",
+                id = duplicate.id, title = &duplicate.title, author = &duplicate.author, reason = &duplicate.reason)));
+        }
+        summary.push('\n');
+    }
+    summary.push_str(&format!("{}\n\n", t!("summary.heading")));
+    summary.push_str(&format!("{}: {}\n", t!("summary.title"), prepared.title));
+    summary.push_str(&format!("{}: {}\n", t!("summary.author"), prepared.author));
+    if let Some(isbn) = &prepared.isbn {
+        summary.push_str(&format!("{}: {}\n", t!("summary.isbn"), isbn));
+    }
+    let media_type = if prepared.is_ebook { t!("media_type.ebook") } else { t!("media_type.physical") };
+    summary.push_str(&format!("{}: {}\n", t!("summary.type"), media_type));
+    summary.push_str(&format!("{}: {}\n", t!("summary.categories"), prepared.categories.join(", ")));
+
+    summary.push_str(&format!("{}: {}\n\n{}", t!("summary.synopsis"), crate::text::truncate(&prepared.synopsis, 300), t!("confirm.prompt")));
+    summary
+}
+
+struct BotState {
+    client: TelegramClient,
+    searcher: Arc<CombinedBookSearcher>,
+    config: TelegramConfig,
+    drafts: DraftStore,
+}
+
+pub async fn run_bot(config: TelegramConfig, searcher: CombinedBookSearcher) -> Result<(), Box<dyn std::error::Error>> {
+    if config.bot_token.is_empty() || config.bot_token.contains("your_") {
+        return Err(t!("telegram.token_missing").into());
+    }
+    if config.allowed_chat_ids.is_empty() {
+        ui::warn(t!("telegram.anyone_allowed"));
+    }
+
+    let state = Arc::new(BotState {
+        client: TelegramClient::new(config.bot_token.clone()).with_http_client(searcher.http_client().clone()),
+        searcher: Arc::new(searcher),
+        config,
+        drafts: DraftStore::default(),
+    });
+
+    ui::info(t!("telegram.running"));
+
+    let mut offset = 0;
+    loop {
+        let updates = match state.client.get_updates(offset).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                ui::error(t!("telegram.updates_failed", error = e));
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        for update in updates {
+            offset = update.update_id + 1;
+            let state = state.clone();
+            // Lookups and LLM calls are slow, so keep polling while they run
+            tokio::spawn(async move {
+                if let Err(e) = handle_update(&state, update).await {
+                    ui::error(t!("telegram.update_failed", error = e));
+                }
+            });
+        }
+    }
+}
+
+fn is_allowed(state: &BotState, chat_id: i64) -> bool {
+    state.config.allowed_chat_ids.is_empty() || state.config.allowed_chat_ids.contains(&chat_id)
+}
+
+async fn handle_update(state: &BotState, update: Update) -> Result<(), TelegramError> {
+    if let Some(callback) = update.callback_query {
+        return handle_callback(state, callback).await;
+    }
+
+    let Some(message) = update.message else {
+        return Ok(());
+    };
+    let chat_id = message.chat.id;
+
+    if !is_allowed(state, chat_id) {
+        return state.client.send_message(chat_id, &t!("telegram.not_allowed", chat_id = chat_id), None).await;
+    }
+
+    let text = message.text.as_deref().or(message.caption.as_deref()).unwrap_or("").trim();
+    if text == "/start" || text == "/help" {
+        return state.client.send_message(chat_id, &t!("telegram.help"), None).await;
+    }
+
+    let (is_ebook, text) = strip_ebook_prefix(text);
+
+    let query = if let Some(photos) = &message.photo {
+        match decode_photo(state, photos).await? {
+            Some(isbn) => BookQuery::Isbn(isbn),
+            None => {
+                return state.client.send_message(chat_id, &t!("telegram.no_barcode"), None).await;
+            }
+        }
+    } else {
+        match parse_book_query(text) {
+            Some(query) => query,
+            None => {
+                return state.client.send_message(chat_id, &t!("telegram.unrecognized"), None).await;
+            }
+        }
+    };
+
+    state.client.send_message(chat_id, &t!("telegram.looking_up"), None).await?;
+
+    let prepared = match prepare(state, &query, is_ebook).await {
+        Ok(Some(prepared)) => prepared,
+        Ok(None) => return state.client.send_message(chat_id, &t!("telegram.not_found"), None).await,
+        Err(message) => return state.client.send_message(chat_id, &t!("telegram.prepare_failed", error = message), None).await,
+    };
+
+    let summary = format_summary(&prepared);
+    let pending_id = state.drafts.insert_for(chat_id, prepared);
+
+    let keyboard = InlineKeyboardMarkup {
+        inline_keyboard: vec![vec![
+            InlineKeyboardButton { text: t!("telegram.add").into_owned(), callback_data: format!("accept:{}", pending_id) },
+            InlineKeyboardButton { text: t!("confirm.cancel").into_owned(), callback_data: format!("cancel:{}", pending_id) },
+        ]],
+    };
+    state.client.send_message(chat_id, &summary, Some(keyboard)).await
+}
+
+async fn decode_photo(state: &BotState, photos: &[PhotoSize]) -> Result<Option<String>, TelegramError> {
+    let Some(largest) = photos.iter().max_by_key(|p| p.width * p.height) else {
+        return Ok(None);
+    };
+    let bytes = state.client.download_file(&largest.file_id).await?;
+    Ok(image::load_from_memory(&bytes).ok().and_then(|img| crate::barcode::decode_ean13(&img)))
+}
+
+/// Errors are flattened to strings here so the future stays `Send` for `tokio::spawn`.
+async fn prepare(state: &BotState, query: &BookQuery, is_ebook: bool) -> Result<Option<PreparedBook>, String> {
+    let results = match query {
+        BookQuery::Isbn(isbn) => state.searcher.find_by_isbn(isbn).await,
+        BookQuery::TitleAuthor(title, author) => state.searcher.find_by_title_author(title, Some(author)).await,
+        BookQuery::Title(title) => state.searcher.find_by_title_author(title, None).await,
+    }.map_err(|e| e.to_string())?;
+
+    let Some(book) = results.books.into_iter().next() else {
+        return Ok(None);
+    };
+
+    state.searcher.prepare_book(&book, is_ebook).await
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+async fn handle_callback(state: &BotState, callback: CallbackQuery) -> Result<(), TelegramError> {
+    state.client.answer_callback_query(&callback.id).await?;
+
+    let Some(message) = callback.message else {
+        return Ok(());
+    };
+    let chat_id = message.chat.id;
+    if !is_allowed(state, chat_id) {
+        return Ok(());
+    }
+
+    let data = callback.data.unwrap_or_default();
+    let (action, id) = data.split_once(':').unwrap_or((data.as_str(), ""));
+    // A draft is only taken from the chat it was sent to
+    let pending = id.parse().ok().and_then(|id| state.drafts.take_for(chat_id, id));
+
+    let Some(prepared) = pending else {
+        return state.client.edit_message_text(chat_id, message.message_id, &t!("telegram.expired")).await;
+    };
+
+    if action != "accept" {
+        return state.client.edit_message_text(chat_id, message.message_id, &t!("telegram.cancelled", title = &prepared.title)).await;
+    }
+
+    state.client.edit_message_text(chat_id, message.message_id, &t!("telegram.adding", title = &prepared.title)).await?;
+
+    let reply = match state.searcher.submit_prepared(&prepared).await.map_err(|e| e.to_string()) {
+        Ok(added) => t!("telegram.added", title = added.title, id = added.id).into_owned(),
+        Err(e) => t!("telegram.add_failed", error = e).into_owned(),
+    };
+    state.client.send_message(chat_id, &reply, None).await
+}
@@ -0,0 +1,115 @@
+/// Plain text from a description that may be HTML, as Google Books sends many:
+/// tags are removed, line breaks and paragraphs become new lines, entities are
+/// decoded, and runs of blank lines are collapsed. A `<` that does not start a
+/// tag is kept as it is.
+pub fn strip_html(text: &str) -> String {
+    if !text.contains(['<', '&']) {
+        return text.to_string();
+    }
+
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        plain.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let is_tag = after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        let Some(end) = after.find('>').filter(|_| is_tag) else {
+            plain.push('<');
+            rest = after;
+            continue;
+        };
+        let name: String = after[..end].trim_start_matches('/')
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "br" | "li" | "tr" => plain.push('\n'),
+            "p" | "div" | "blockquote" | "ul" | "ol" | "table" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => plain.push_str("\n\n"),
+            _ => {}
+        }
+        rest = &after[end + 1..];
+    }
+    plain.push_str(rest);
+
+    let decoded = decode_entities(&plain);
+    let mut lines: Vec<String> = Vec::new();
+    for line in decoded.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        // One blank line at most between paragraphs
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Replaces the named entities descriptions use and numeric ones; anything
+/// else that looks like an entity is left as it is.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let entity = after.find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((entity_char(&after[..end])?, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &after[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = after;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn entity_char(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        // A numeric non-breaking space is as common as the named one
+        return char::from_u32(code).map(|c| if c == '\u{a0}' { ' ' } else { c });
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '\u{2013}',
+        "mdash" => '\u{2014}',
+        "hellip" => '\u{2026}',
+        "lsquo" => '\u{2018}',
+        "rsquo" => '\u{2019}',
+        "ldquo" => '\u{201c}',
+        "rdquo" => '\u{201d}',
+        "laquo" => '\u{ab}',
+        "raquo" => '\u{bb}',
+        "copy" => '\u{a9}',
+        _ => return None,
+    })
+}
+
+/// `text` cut to its first `max` characters, with an ellipsis when it was
+/// longer. Counts characters rather than bytes, so Thai and other multi-byte
+/// text is never cut inside a character.
+pub fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
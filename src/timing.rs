@@ -0,0 +1,93 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::ui;
+
+/// The kinds of outbound call an add makes, each timed separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Metadata,
+    WebSearch,
+    Llm,
+    Classification,
+    Library,
+    Upload,
+    Create,
+    /// Changes to rows that already exist
+    Update,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Metadata => "metadata",
+            Stage::WebSearch => "web search",
+            Stage::Llm => "LLM",
+            Stage::Classification => "classification",
+            Stage::Library => "library lookups",
+            Stage::Upload => "upload",
+            Stage::Create => "create",
+            Stage::Update => "update",
+        }
+    }
+
+    /// The stage's label in `/metrics`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Stage::Metadata => "metadata",
+            Stage::WebSearch => "web_search",
+            Stage::Llm => "llm",
+            Stage::Classification => "classification",
+            Stage::Library => "library",
+            Stage::Upload => "upload",
+            Stage::Create => "create",
+            Stage::Update => "update",
+        }
+    }
+}
+
+/// Total time per stage, in the order the stages first ran. Only filled in
+/// verbose mode, and holds at most one entry per stage.
+static TIMINGS: Mutex<Vec<(Stage, Duration)>> = Mutex::new(Vec::new());
+
+/// Runs `future`, records how long it took for `/metrics` and, in verbose mode,
+/// prints the time and adds it to its stage for the summary printed by `report`.
+pub async fn time<F: Future>(stage: Stage, future: F) -> F::Output {
+    let started = Instant::now();
+    let output = future.await;
+    let elapsed = started.elapsed();
+    crate::metrics::record_duration(stage, elapsed);
+    if !ui::is_verbose() {
+        return output;
+    }
+
+    ui::detail(format!("{} took {}", stage.name(), format_duration(elapsed)));
+    if let Ok(mut timings) = TIMINGS.lock() {
+        match timings.iter_mut().find(|(existing, _)| *existing == stage) {
+            Some((_, total)) => *total += elapsed,
+            None => timings.push((stage, elapsed)),
+        }
+    }
+    output
+}
+
+/// Prints the per-stage totals collected so far in verbose mode, then clears them.
+pub fn report() {
+    let timings = match TIMINGS.lock() {
+        Ok(mut timings) => std::mem::take(&mut *timings),
+        Err(_) => return,
+    };
+    if timings.is_empty() {
+        return;
+    }
+
+    let summary = timings.iter()
+        .map(|(stage, total)| format!("{}: {}", stage.name(), format_duration(*total)))
+        .collect::<Vec<String>>()
+        .join(", ");
+    ui::detail(format!("Timings: {}", summary));
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
@@ -0,0 +1,28 @@
+/// Whether `text` has any letters in Thai script.
+pub fn is_thai(text: &str) -> bool {
+    text.chars().any(|c| ('\u{0E00}'..='\u{0E7F}').contains(&c))
+}
+
+/// A romanization that needs no LLM. Thai is transcribed letter by letter, so
+/// leading vowels come out before their consonant and words run together, but
+/// it is stable and plain ASCII, which is enough to sort and search by.
+pub fn simple(text: &str) -> String {
+    deunicode::deunicode(text)
+        .replace('`', "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The romanized title in an LLM response: its first non-empty line, without
+/// a label, quotes or emphasis. `None` when the response is empty or still in
+/// Thai script.
+pub fn parse_response(response: &str) -> Option<String> {
+    let line = response.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = match line.split_once(':') {
+        Some((label, rest)) if label.to_lowercase().contains("romaniz") || label.to_lowercase().contains("translit") => rest,
+        _ => line,
+    };
+    let line = line.trim().trim_matches(|c| matches!(c, '*' | '"' | '\'' | '`')).trim();
+    Some(line.to_string()).filter(|line| !line.is_empty() && !is_thai(line))
+}
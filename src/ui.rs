@@ -0,0 +1,178 @@
+use console::Style;
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::RwLock;
+use crate::config::ThemeConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only warnings, errors, prompts and command results
+    Quiet,
+    /// Progress messages as well
+    Normal,
+    /// Request URLs and lookup details as well
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+static JSON: AtomicBool = AtomicBool::new(false);
+static THEME: RwLock<Option<ThemeConfig>> = RwLock::new(None);
+
+/// What a piece of text is, which decides its style in the theme.
+#[derive(Debug, Clone, Copy)]
+pub enum Role {
+    Heading,
+    Label,
+    Success,
+    Warning,
+    Error,
+}
+
+/// Sets the output level for the rest of the run. All user-facing output goes
+/// through this module so that `--quiet`, `--verbose` and `--json` apply
+/// everywhere. In JSON mode stdout carries only the JSON results and all other
+/// output moves to stderr.
+pub fn init(verbosity: Verbosity, json: bool) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    JSON.store(json, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+pub fn is_verbose() -> bool {
+    verbosity() == Verbosity::Verbose
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Turns colors off for the rest of the run. `--no-color` and a non-empty
+/// `NO_COLOR` both end up here; otherwise colors are used when the output is a
+/// terminal.
+pub fn disable_colors() {
+    console::set_colors_enabled(false);
+    console::set_colors_enabled_stderr(false);
+}
+
+/// Replaces the default styles with the ones from the `theme` config section.
+pub fn set_theme(theme: &ThemeConfig) {
+    if let Ok(mut current) = THEME.write() {
+        *current = Some(theme.clone());
+    }
+}
+
+fn style(role: Role, for_stderr: bool) -> Style {
+    let theme = THEME.read().ok().and_then(|theme| theme.clone()).unwrap_or_default();
+    let spec = match role {
+        Role::Heading => &theme.heading,
+        Role::Label => &theme.label,
+        Role::Success => &theme.success,
+        Role::Warning => &theme.warning,
+        Role::Error => &theme.error,
+    };
+    let colors = if for_stderr { console::colors_enabled_stderr() } else { console::colors_enabled() };
+    Style::from_dotted_str(spec).force_styling(colors)
+}
+
+/// Styles text for the stream `show`, `info` and `detail` write to.
+pub fn paint(role: Role, text: impl Display) -> String {
+    style(role, is_json()).apply_to(text).to_string()
+}
+
+/// Formats `label: value` rows with the labels styled and the values lined up.
+pub fn fields(rows: &[(String, String)]) -> String {
+    let width = rows.iter().map(|(label, _)| console::measure_text_width(label)).max().unwrap_or(0);
+    rows.iter()
+        .map(|(label, value)| {
+            let padding = " ".repeat(width - console::measure_text_width(label));
+            format!("{}{} {}", paint(Role::Label, format!("{}:", label)), padding, value)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn write_line(message: impl Display) {
+    if is_json() {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Details that only help when debugging a run, such as request URLs.
+pub fn detail(message: impl Display) {
+    if is_verbose() {
+        write_line(message);
+    }
+}
+
+/// Progress messages, hidden by `--quiet`.
+pub fn info(message: impl Display) {
+    if verbosity() >= Verbosity::Normal {
+        write_line(message);
+    }
+}
+
+/// Output the user has to read to answer a prompt, such as the confirmation
+/// summary. Shown at every level.
+pub fn show(message: impl Display) {
+    write_line(message);
+}
+
+/// A problem that does not stop the command.
+pub fn warn(message: impl Display) {
+    eprintln!("{}", style(Role::Warning, true).apply_to(message));
+}
+
+/// A failure, printed to stderr at every level.
+pub fn error(message: impl Display) {
+    eprintln!("{}", style(Role::Error, true).apply_to(message));
+}
+
+/// The outcome of a command: the message normally, or `value` as one line of
+/// JSON on stdout in JSON mode. Shown even with `--quiet`.
+pub fn result(message: impl Display, value: &impl Serialize) {
+    if is_json() {
+        match serde_json::to_string(value) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Could not serialize the result: {}", e),
+        }
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Waits out `duration` after `message`, counting the seconds down in place when
+/// stderr is a terminal. Shown at every level but `--quiet`, which only waits.
+pub async fn countdown(message: impl Display, duration: std::time::Duration) {
+    let term = console::Term::stderr();
+    if verbosity() < Verbosity::Normal {
+        tokio::time::sleep(duration).await;
+        return;
+    }
+    if !term.is_term() {
+        warn(format!("{} ({}s)", message, duration.as_secs()));
+        tokio::time::sleep(duration).await;
+        return;
+    }
+
+    let deadline = tokio::time::Instant::now() + duration;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        let _ = term.clear_line();
+        let _ = term.write_str(&style(Role::Warning, true).apply_to(format!("{} {}s", message, remaining.as_secs_f64().ceil())).to_string());
+        tokio::time::sleep(remaining.min(std::time::Duration::from_secs(1))).await;
+    }
+    let _ = term.clear_line();
+}
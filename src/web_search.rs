@@ -1,9 +1,12 @@
-use reqwest;
 use serde::{Deserialize, Serialize};
+use crate::timing::{self, Stage};
+use crate::ui;
+use crate::rate_limit::SendWithRetry;
 
 #[derive(Debug, Clone)]
 pub struct WebSearchClient {
     client: reqwest::Client,
+    base_url: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -60,18 +63,34 @@ impl From<reqwest::Error> for SearchError {
     }
 }
 
+impl Default for WebSearchClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WebSearchClient {
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36")
-            .build()
-            .unwrap_or_default();
-        
-        Self { client }
+        Self {
+            client: reqwest::Client::new(),
+            base_url: "https://api.duckduckgo.com".to_string(),
+        }
+    }
+
+    /// Sends the instant answer queries to `base_url` instead of DuckDuckGo.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Uses `client` for every request, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
     }
 
     pub async fn search_book_info(&self, title: &str, author: &str) -> Result<Vec<SearchResult>, SearchError> {
-        println!("Searching web for additional book information...");
+        ui::detail("Searching web for additional book information...");
         
         // Try DuckDuckGo instant answer API first
         if let Ok(results) = self.search_duckduckgo(title, author).await {
@@ -84,16 +103,23 @@ impl WebSearchClient {
         self.search_basic(title, author).await
     }
 
+    /// Runs a fixed query against the search provider and returns how many
+    /// results came back, without the placeholder fallback.
+    pub async fn test_connection(&self) -> Result<usize, SearchError> {
+        self.search_duckduckgo("The Odyssey", "Homer").await.map(|results| results.len())
+    }
+
     async fn search_duckduckgo(&self, title: &str, author: &str) -> Result<Vec<SearchResult>, SearchError> {
         let query = format!("{} by {} book synopsis review", title, author);
         let url = format!(
-            "https://api.duckduckgo.com/?q={}&format=json&no_redirect=1&no_html=1&skip_disambig=1",
+            "{}/?q={}&format=json&no_redirect=1&no_html=1&skip_disambig=1",
+            self.base_url,
             urlencoding::encode(&query)
         );
 
         let response = self.client
             .get(&url)
-            .send()
+            .send_with_retry()
             .await?;
 
         if !response.status().is_success() {
@@ -135,7 +161,7 @@ impl WebSearchClient {
         // - Bing Search API (requires API key) 
         // - Custom scraping (be careful about rate limits)
         
-        println!("DuckDuckGo search didn't return results, trying basic search...");
+        ui::detail("DuckDuckGo search didn't return results, trying basic search...");
         
         // For now, return a minimal result to indicate we tried
         let basic_result = SearchResult {
@@ -173,10 +199,11 @@ pub async fn enhance_book_info_with_search(
     title: &str,
     author: &str,
     existing_description: &str,
+    client: &reqwest::Client,
 ) -> String {
-    let search_client = WebSearchClient::new();
+    let search_client = WebSearchClient::new().with_http_client(client.clone());
     
-    match search_client.search_book_info(title, author).await {
+    match timing::time(Stage::WebSearch, search_client.search_book_info(title, author)).await {
         Ok(results) => {
             let mut enhanced_info = String::new();
             enhanced_info.push_str("=== Original Book Information ===\n");
@@ -188,7 +215,7 @@ pub async fn enhance_book_info_with_search(
             enhanced_info
         }
         Err(e) => {
-            println!("Web search failed: {}", e);
+            ui::warn(format!("Web search failed: {}", e));
             format!(
                 "=== Book Information (Web Search Failed) ===\nTitle: {}\nAuthor: {}\nDescription: {}\n\nNote: Unable to fetch additional information from web search.",
                 title, author, existing_description
@@ -0,0 +1,35 @@
+mod common;
+
+use common::media_row;
+use serde_json::json;
+use wcm::ask::{describe_row, select_rows};
+use wcm::baserow::MediaRow;
+use wcm::config::MediaFieldsConfig;
+
+#[test]
+fn rows_are_described_with_the_fields_questions_filter_on() {
+    let book = media_row(12, json!({
+        "Title": "SPQR",
+        "Author": "Mary Beard",
+        "Category": [{ "id": 1, "value": "History" }],
+        "Status": { "id": 3028, "value": "In Place" },
+        "Pages": 608,
+        "Rating": 0,
+        "Read": false,
+        "Synopsis": "A history of ancient Rome. From its founding to 212 AD.",
+    }));
+    assert_eq!(
+        describe_row(&book, &MediaFieldsConfig::default()),
+        "#12 | SPQR | by Mary Beard | categories: History | status: In Place | pages: 608 | unread | synopsis: A history of ancient Rome."
+    );
+}
+
+#[test]
+fn a_large_library_is_cut_to_the_closest_books() {
+    let rows: Vec<MediaRow> = (1..=5).map(|id| media_row(id, json!({ "Title": format!("Book {}", id) }))).collect();
+    let ids = |selected: Vec<&MediaRow>| selected.iter().map(|row| row.id).collect::<Vec<_>>();
+
+    assert_eq!(ids(select_rows(&rows, Some(&[4, 2]), 5)), [1, 2, 3, 4, 5], "a small library is shown whole");
+    assert_eq!(ids(select_rows(&rows, Some(&[4, 2, 5]), 2)), [4, 2]);
+    assert_eq!(ids(select_rows(&rows, None, 2)), [1, 2]);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{baserow_config, fixture};
+use wcm::authors::{canonical_name, format_authors, name_key, split_author_field, split_authors, surname_key};
+use wcm::baserow::BaserowClient;
+use wcm::book_search::{BookResult, CombinedBookSearcher, ManualBook, PreparedBook};
+use wcm::config::{AuthorFormatConfig, Config, NameOrder};
+use wcm::google_books::GoogleBooksClient;
+use wcm::open_library::OpenLibraryClient;
+use wiremock::matchers::{body_json, body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn spellings_of_one_name_share_a_key() {
+    assert_eq!(canonical_name("Tolkien, J.R.R."), "J. R. R. Tolkien");
+    assert_eq!(canonical_name("J.R.R.  Tolkien"), "J. R. R. Tolkien");
+    assert_eq!(canonical_name("Le Guin, Ursula K."), "Ursula K. Le Guin");
+    assert_eq!(canonical_name("Martin Luther King, Jr."), "Martin Luther King, Jr.");
+    assert_eq!(canonical_name("King, Martin Luther, Jr."), "Martin Luther King, Jr.");
+    assert_eq!(name_key("Tolkien, J. R. R."), name_key("J.R.R. Tolkien"));
+}
+
+#[test]
+fn authors_split_without_breaking_inverted_names() {
+    assert_eq!(split_authors("Homer, Robert Fagles"), vec!["Homer", "Robert Fagles"]);
+    assert_eq!(split_authors("Tolkien, J. R. R."), vec!["Tolkien, J. R. R."]);
+    assert_eq!(split_authors("Terry Pratchett & Neil Gaiman"), vec!["Terry Pratchett", "Neil Gaiman"]);
+    assert_eq!(split_authors("King, Jr.; Coretta Scott King"), vec!["King, Jr.", "Coretta Scott King"]);
+    let last_first = AuthorFormatConfig { order: NameOrder::LastFirst, ..Default::default() };
+    assert_eq!(split_author_field("Le Guin, Ursula K.; King, Martin Luther, Jr.", &last_first), vec!["Le Guin, Ursula K.", "King, Martin Luther, Jr."]);
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn the_default_format_keeps_names_as_given() {
+    let authors = names(&["Tolkien, J.R.R.", "Christopher Tolkien"]);
+    assert_eq!(format_authors(&authors, &AuthorFormatConfig::default()), "Tolkien, J.R.R., Christopher Tolkien");
+}
+
+#[test]
+fn names_are_written_in_the_chosen_order() {
+    let authors = names(&["Le Guin, Ursula K.", "Martin Luther King, Jr.", "Homer"]);
+    let first_last = AuthorFormatConfig { order: NameOrder::FirstLast, ..Default::default() };
+    assert_eq!(format_authors(&authors, &first_last), "Ursula K. Le Guin, Martin Luther King, Jr., Homer");
+    let last_first = AuthorFormatConfig { order: NameOrder::LastFirst, ..Default::default() };
+    assert_eq!(format_authors(&authors, &last_first), "Le Guin, Ursula K.; King, Martin Luther, Jr.; Homer");
+}
+
+#[test]
+fn accents_and_repeated_names_can_be_dropped() {
+    let format = AuthorFormatConfig { strip_diacritics: true, dedupe: true, ..Default::default() };
+    let authors = names(&["Gabriel García Márquez", "García Márquez, Gabriel", "ชาติ กอบจิตติ"]);
+    assert_eq!(format_authors(&authors, &format), "Gabriel Garcia Marquez, ชาติ กอบจิตติ");
+}
+
+#[test]
+fn surnames_match_in_either_order() {
+    assert_eq!(surname_key("Le Guin, Ursula K."), surname_key("Ursula K. Le Guin"));
+    assert_eq!(surname_key("King, Martin Luther, Jr.").as_deref(), Some("king"));
+}
+
+#[tokio::test]
+async fn inverted_names_are_linked_whole() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/9/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "count": 1, "next": null, "previous": null,
+            "results": [{ "id": 4, "Name": "Ursula K. Le Guin" }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/9/"))
+        .and(body_json(serde_json::json!({ "Name": "Martin Luther King, Jr." })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": 5, "Name": "Martin Luther King, Jr." })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/2/"))
+        .and(body_partial_json(serde_json::json!({
+            "Author": "Le Guin, Ursula K.; King, Martin Luther, Jr.",
+            "Authors": [4, 5],
+        })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut config = Config { baserow: baserow_config(server.uri()), ..Default::default() };
+    config.baserow.authors_table_id = Some(9);
+    config.author_format.order = NameOrder::LastFirst;
+    let book = BookResult::Manual(ManualBook {
+        title: "Dreams".to_string(),
+        author: "Ursula K. Le Guin; Martin Luther King, Jr.".to_string(),
+        isbn: None,
+        cover_url: None,
+    });
+    let searcher = CombinedBookSearcher::new(
+        GoogleBooksClient::new(String::new(), server.uri()),
+        OpenLibraryClient::new(server.uri()),
+        BaserowClient::new(config.baserow.clone()),
+        config.clone(),
+    );
+    let fiction = serde_json::from_value(serde_json::json!({ "id": 3, "Name": "Fiction" })).unwrap();
+    let mut prepared = PreparedBook::new(&book, vec!["Fiction".to_string()], String::new(), false, vec![fiction], &config.app.defaults);
+    prepared.author = format_authors(&book.get_authors(), &config.author_format);
+
+    let added = searcher.submit_prepared(&prepared).await.unwrap();
+
+    assert_eq!(added.id, 321);
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use common::{baserow_config, fixture, fixture_with_status};
+use wcm::baserow::{BaserowClient, BaserowError, Category, MediaEntry};
+use wiremock::matchers::{body_json, body_partial_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn entry() -> MediaEntry {
+    MediaEntry {
+        title: "The Odyssey".to_string(),
+        author: "Homer".to_string(),
+        isbn: Some("9780140449136".to_string()),
+        synopsis: "Odysseus sails home.".to_string(),
+        category: vec![3],
+        read: false,
+        rating: 0,
+        media_type: Some(1),
+        location: Vec::new(),
+        cover: Vec::new(),
+        status: 3028,
+        extra: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn fetch_all_rows_follows_pagination() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/3/"))
+        .and(query_param("page", "1"))
+        .and(header("Authorization", "Token test-token"))
+        .respond_with(fixture("baserow_categories_page1.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/3/"))
+        .and(query_param("page", "2"))
+        .respond_with(fixture("baserow_categories_page2.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let categories: Vec<Category> = client.fetch_all_rows(3).await.unwrap();
+
+    let names: Vec<String> = categories.iter().filter_map(|c| c.get_name()).collect();
+    assert_eq!(names, ["Fiction", "History", "Poetry"]);
+}
+
+#[tokio::test]
+async fn create_media_entry_posts_fields() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/2/"))
+        .and(query_param("user_field_names", "true"))
+        .and(header("Authorization", "Token test-token"))
+        .and(body_partial_json(serde_json::json!({
+            "Title": "The Odyssey",
+            "ISBN": "9780140449136",
+            "Category": [3],
+            "Status": 3028,
+        })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let created = client.create_media_entry(entry()).await.unwrap();
+
+    assert_eq!(created.id, 321);
+}
+
+#[tokio::test]
+async fn rejected_create_keeps_response_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("ERROR_REQUEST_BODY_VALIDATION"))
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let error = client.create_media_entry(entry()).await.unwrap_err();
+
+    assert!(matches!(&error, BaserowError::InvalidResponse(message) if message.contains("ERROR_REQUEST_BODY_VALIDATION")));
+}
+
+#[tokio::test]
+async fn test_connection_reports_invalid_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/3/"))
+        .respond_with(fixture_with_status(401, "baserow_unauthorized.json"))
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+
+    assert!(matches!(client.test_connection().await, Err(BaserowError::AuthenticationFailed)));
+}
+
+#[tokio::test]
+async fn upload_file_returns_stored_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/user-files/upload-file/"))
+        .and(header("Authorization", "Token test-token"))
+        .respond_with(fixture("baserow_upload.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let upload = client.upload_file(vec![0xFF, 0xD8, 0xFF], "cover.jpg").await.unwrap();
+
+    assert_eq!(upload.name, "VXotniBOVm8tbstZkKsMKbj2Qg7KmPvn_cover.jpg");
+}
+
+#[tokio::test]
+async fn write_check_deletes_the_row_it_creates() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/2/"))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/api/database/rows/table/2/321/"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+
+    client.check_write_access().await.unwrap();
+}
+
+#[tokio::test]
+async fn fetch_fields_lists_table_fields() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/fields/table/2/"))
+        .and(header("Authorization", "Token test-token"))
+        .respond_with(fixture("baserow_fields.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let fields = client.fetch_fields(2).await.unwrap();
+
+    let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+    assert_eq!(names, ["Title", "Author", "Category"]);
+    assert_eq!(fields[2].field_type, "link_row");
+}
+
+#[tokio::test]
+async fn update_media_row_patches_only_given_fields() {
+    let server = MockServer::start().await;
+    Mock::given(method("PATCH"))
+        .and(path("/api/database/rows/table/2/321/"))
+        .and(query_param("user_field_names", "true"))
+        .and(body_json(serde_json::json!({ "Quotes": "\"Sing to me of the man, Muse\" (p. 77)" })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let row = client.update_media_row(321, &serde_json::json!({ "Quotes": "\"Sing to me of the man, Muse\" (p. 77)" })).await.unwrap();
+
+    assert_eq!(row.id, 321);
+}
+
+#[test]
+fn row_url_opens_the_row_in_a_view_when_given() {
+    assert_eq!(wcm::baserow::row_url("https://baserow.example/", 1, 709, Some(3153), 42), "https://baserow.example/database/1/table/709/3153/row/42");
+    assert_eq!(wcm::baserow::row_url("https://baserow.example", 1, 2, None, 7), "https://baserow.example/database/1/table/2/row/7");
+}
+
+#[tokio::test]
+async fn linked_rows_are_matched_by_key_and_created_when_missing() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/9/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "count": 1, "next": null, "previous": null,
+            "results": [{ "id": 4, "Name": "J. R. R. Tolkien" }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/9/"))
+        .and(body_json(serde_json::json!({ "Name": "Christopher Tolkien" })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let names: Vec<String> = wcm::authors::split_authors("Tolkien, J.R.R.; Christopher Tolkien")
+        .iter()
+        .map(|name| wcm::authors::canonical_name(name))
+        .collect();
+    let ids = wcm::linked::resolve(&client, 9, &names, wcm::authors::name_key, 1.0).await.unwrap();
+
+    assert_eq!(ids, vec![4, 321]);
+}
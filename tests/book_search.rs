@@ -0,0 +1,74 @@
+use wcm::book_search::{BookResult, Condition, DraftStore, ManualBook, PreparedBook, SavedDraft, TitleQuery};
+use wcm::config::EntryDefaults;
+
+#[test]
+fn widened_queries_relax_step_by_step() {
+    let queries: Vec<TitleQuery> = TitleQuery::widened("Crime and Punishment: A Novel", Some("Fyodor Dostoyevsky"))
+        .into_iter()
+        .map(|(query, _)| query)
+        .collect();
+
+    assert_eq!(queries, vec![
+        TitleQuery::TitleAuthor("Crime and Punishment: A Novel".to_string(), "Fyodor Dostoyevsky".to_string()),
+        TitleQuery::TitleAuthor("Crime and Punishment".to_string(), "Fyodor Dostoyevsky".to_string()),
+        TitleQuery::Keywords("Crime and Punishment Fyodor Dostoyevsky".to_string()),
+        TitleQuery::Title("Crime and Punishment".to_string()),
+    ]);
+}
+
+#[test]
+fn widened_queries_transliterate_the_author() {
+    let queries = TitleQuery::widened("Преступление и наказание", Some("Фёдор Достоевский"));
+
+    assert_eq!(queries[0].1, None);
+    assert!(queries.iter().any(|(query, relaxation)| relaxation.is_some()
+        && *query == TitleQuery::TitleAuthor("Преступление и наказание".to_string(), "Fiodor Dostoevskii".to_string())));
+}
+
+#[test]
+fn copy_details_survive_a_saved_draft() {
+    let draft = serde_json::json!({
+        "title": "The Hobbit", "author": "J.R.R. Tolkien", "isbn": null, "categories": ["Fantasy"],
+        "synopsis": "", "is_ebook": false, "status": 1,
+        "copy": { "condition": "worn", "edition": "First edition", "signed": true, "provenance": "Secondhand shop" },
+    });
+    let draft: SavedDraft = serde_json::from_value(draft).unwrap();
+    assert_eq!(draft.copy.condition, Some(Condition::Worn));
+    assert!(draft.copy.signed);
+
+    // Drafts saved before copy details existed have none
+    let mut old = serde_json::to_value(&draft).unwrap();
+    old.as_object_mut().unwrap().remove("copy");
+    let old: SavedDraft = serde_json::from_value(old).unwrap();
+    assert!(old.copy.is_empty());
+    assert_eq!(Condition::parse(" Good "), Some(Condition::Good));
+    assert_eq!(Condition::parse("mint"), None);
+}
+
+#[test]
+fn a_chat_cannot_take_another_chats_draft() {
+    let book = BookResult::Manual(ManualBook { title: "Dune".to_string(), ..ManualBook::default() });
+    let prepared = || PreparedBook::new(&book, Vec::new(), String::new(), false, Vec::new(), &EntryDefaults::default());
+    let drafts = DraftStore::default();
+    let owned = drafts.insert_for(42, prepared());
+    let shared = drafts.insert(prepared());
+
+    assert!(drafts.take_for(7, owned).is_none());
+    assert!(drafts.take(owned).is_none());
+    assert!(drafts.take_for(42, shared).is_none());
+    assert_eq!(drafts.take_for(42, owned).unwrap().title, "Dune");
+    assert!(drafts.take(shared).is_some());
+}
+
+#[test]
+fn the_oldest_draft_makes_room_for_a_new_one() {
+    let book = BookResult::Manual(ManualBook::default());
+    let drafts = DraftStore::default();
+    let ids: Vec<u64> = (0..=DraftStore::MAX_DRAFTS)
+        .map(|_| drafts.insert(PreparedBook::new(&book, Vec::new(), String::new(), false, Vec::new(), &EntryDefaults::default())))
+        .collect();
+
+    assert!(drafts.take(ids[0]).is_none());
+    assert!(drafts.take(ids[1]).is_some());
+    assert!(drafts.take(ids[DraftStore::MAX_DRAFTS]).is_some());
+}
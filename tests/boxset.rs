@@ -0,0 +1,43 @@
+use wcm::book_search::{BookResult, ManualBook, PreparedBook};
+use wcm::boxset::volume_entries;
+use wcm::config::EntryDefaults;
+
+fn box_set() -> PreparedBook {
+    let book = BookResult::Manual(ManualBook {
+        title: "The Chronicles of Narnia".to_string(),
+        author: "C. S. Lewis".to_string(),
+        isbn: Some("9780064471190".to_string()),
+        cover_url: Some("https://example.com/box.jpg".to_string()),
+    });
+    let mut prepared = PreparedBook::new(&book, vec!["Fantasy".to_string()], "Seven books.".to_string(), false, Vec::new(), &EntryDefaults::default());
+    prepared.location = vec![12];
+    prepared.publisher = Some("HarperCollins".to_string());
+    prepared
+}
+
+#[test]
+fn volumes_are_numbered_and_linked_to_the_box_set() {
+    let titles = ["The Magician's Nephew", "  ", "The Lion, the Witch and the Wardrobe "].map(String::from);
+    let volumes = volume_entries(&box_set(), 42, &titles, &EntryDefaults::default());
+
+    assert_eq!(volumes.len(), 2);
+    assert_eq!(volumes[1].title, "The Lion, the Witch and the Wardrobe");
+    assert_eq!(volumes[1].volume_number, Some(2));
+    for volume in &volumes {
+        assert_eq!(volume.box_set, Some(42));
+        assert_eq!(volume.series.as_deref(), Some("The Chronicles of Narnia"));
+        assert_eq!(volume.author, "C. S. Lewis");
+        assert_eq!(volume.categories, ["Fantasy"]);
+        assert_eq!(volume.location, [12]);
+        assert_eq!(volume.isbn, None);
+        assert!(volume.synopsis.is_empty());
+    }
+}
+
+#[test]
+fn volumes_join_the_box_sets_series() {
+    let mut parent = box_set();
+    parent.series = Some("Narnia".to_string());
+    let volumes = volume_entries(&parent, 42, &["Prince Caspian".to_string()], &EntryDefaults::default());
+    assert_eq!(volumes[0].series.as_deref(), Some("Narnia"));
+}
@@ -0,0 +1,44 @@
+use serde_json::json;
+use wcm::baserow::Category;
+use wcm::category_sync::{plan, CanonicalCategory, Change};
+
+fn row(id: u64, name: &str, description: &str) -> Category {
+    serde_json::from_value(json!({ "id": id, "Name": name, "Description": description })).unwrap()
+}
+
+#[test]
+fn plan_creates_renames_describes_and_flags() {
+    let canonical: Vec<CanonicalCategory> = serde_yaml::from_str(r#"
+- name: Fiction
+  description: Novels and short stories
+- name: Thai History
+  aliases: [History]
+- name: poetry
+- name: Cookery
+"#).unwrap();
+    let rows = vec![
+        row(1, "Fiction", "Novels"),
+        row(2, "History", ""),
+        row(3, "Poetry", ""),
+        row(4, "Craft", ""),
+    ];
+
+    assert_eq!(plan(&canonical, &rows), vec![
+        Change::Describe { id: 1, name: "Fiction".to_string(), description: "Novels and short stories".to_string() },
+        Change::Rename { id: 2, from: "History".to_string(), to: "Thai History".to_string() },
+        Change::Rename { id: 3, from: "Poetry".to_string(), to: "poetry".to_string() },
+        Change::Create { name: "Cookery".to_string(), description: String::new() },
+        Change::Unlisted { id: 4, name: "Craft".to_string() },
+    ]);
+}
+
+#[test]
+fn unlisted_rows_alone_leave_the_table_in_sync() {
+    let changes = [Change::Unlisted { id: 9, name: "Misc".to_string() }];
+    let text = wcm::category_sync::format_changes(&changes, false);
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2].contains("Misc (row 9)"));
+    assert!(!text.contains("Changes made"));
+}
@@ -0,0 +1,42 @@
+use wcm::config::BaserowConfig;
+use wcm::baserow::MediaRow;
+use wiremock::ResponseTemplate;
+
+/// A 200 JSON response with the body of `tests/fixtures/<name>`.
+#[allow(dead_code)]
+pub fn fixture(name: &str) -> ResponseTemplate {
+    fixture_with_status(200, name)
+}
+
+/// A JSON response with the given status and the body of `tests/fixtures/<name>`.
+pub fn fixture_with_status(status: u16, name: &str) -> ResponseTemplate {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let body = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path, e));
+    ResponseTemplate::new(status).set_body_raw(body, "application/json")
+}
+
+/// A media table row with the ID and the fields as Baserow returns them.
+#[allow(dead_code)]
+pub fn media_row(id: u64, mut fields: serde_json::Value) -> MediaRow {
+    fields["id"] = serde_json::json!(id);
+    serde_json::from_value(fields).unwrap()
+}
+
+/// A Baserow config pointing at the mock server, with the tables from the README.
+#[allow(dead_code)]
+pub fn baserow_config(base_url: String) -> BaserowConfig {
+    BaserowConfig {
+        api_token: "test-token".to_string(),
+        base_url,
+        database_id: 1,
+        media_table_id: 2,
+        categories_table_id: 3,
+        storage_table_id: 4,
+        storage_view_id: 5,
+        media_view_id: None,
+        authors_table_id: None,
+        publishers_table_id: None,
+        accept_invalid_certs: false,
+        fields: Default::default(),
+    }
+}
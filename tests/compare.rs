@@ -0,0 +1,97 @@
+use serde_json::Value;
+use wcm::book_search::{BookResult, PreparedBook};
+use wcm::compare::{compare, pair, Source, SourceRecords, SourcedField};
+use wcm::config::EntryDefaults;
+use wcm::google_books::BookItem;
+use wcm::open_library::{OpenLibraryBook, OpenLibraryBookDetails};
+
+fn google() -> BookItem {
+    let response: Value = serde_json::from_str(include_str!("fixtures/google_books_isbn.json")).unwrap();
+    serde_json::from_value(response["items"][0].clone()).unwrap()
+}
+
+fn open_library() -> OpenLibraryBook {
+    let response: Value = serde_json::from_str(include_str!("fixtures/open_library_search.json")).unwrap();
+    serde_json::from_value(response["docs"][0].clone()).unwrap()
+}
+
+fn edition() -> OpenLibraryBookDetails {
+    serde_json::from_str(include_str!("fixtures/open_library_edition.json")).unwrap()
+}
+
+#[test]
+fn results_sharing_an_isbn_are_paired_first() {
+    let mut other = open_library();
+    other.isbn = Some(vec!["9780000000002".to_string()]);
+    let ol_results = [BookResult::OpenLibrary(other), BookResult::OpenLibrary(open_library())];
+    let google_results = [BookResult::Google(google())];
+
+    let (item, ol_book) = pair(&google_results, &ol_results).unwrap();
+    assert_eq!(item.id, google().id);
+    assert_eq!(ol_book.isbn, open_library().isbn);
+}
+
+#[test]
+fn results_without_a_shared_isbn_are_paired_by_title() {
+    let mut ol_book = open_library();
+    ol_book.isbn = None;
+    assert!(pair(&[BookResult::Google(google())], &[BookResult::OpenLibrary(ol_book.clone())]).is_some());
+
+    ol_book.title = "The Iliad".to_string();
+    assert!(pair(&[BookResult::Google(google())], &[BookResult::OpenLibrary(ol_book)]).is_none());
+}
+
+#[test]
+fn the_edition_fills_in_what_the_search_result_lacks() {
+    let fields = compare(&google(), &open_library(), Some(&edition()), [Some((128, 198)), None]);
+    let field = |name: &str| fields.iter().find(|field| field.name == name).unwrap().clone();
+
+    assert_eq!(field("Published").open_library.as_deref(), Some("November 28, 2006"));
+    assert_eq!(field("Pages").open_library.as_deref(), Some("541"));
+    assert_eq!(field("ISBN").google, field("ISBN").open_library);
+    assert_eq!(field("Description").open_library, None);
+    assert!(field("Description").google.unwrap().ends_with("words"));
+    assert_eq!(field("Cover").google.as_deref(), Some("128x198"));
+    assert_eq!(field("Cover").open_library, None);
+}
+
+fn records() -> SourceRecords {
+    let mut edition = edition();
+    edition.description = serde_json::from_value(serde_json::json!("Fagles' verse translation.")).unwrap();
+    SourceRecords { google: google(), open_library: open_library(), edition: Some(edition) }
+}
+
+#[test]
+fn toggling_switches_to_the_other_catalogue() {
+    let records = records();
+    let google_pages = records.value(SourcedField::Pages, Source::GoogleBooks).unwrap();
+    assert_eq!(records.source_of(SourcedField::Pages, Some(&google_pages)), Some(Source::GoogleBooks));
+    assert_eq!(records.toggled(SourcedField::Pages, Some(&google_pages)), Some((Source::OpenLibrary, "541".to_string())));
+    assert_eq!(records.toggled(SourcedField::Pages, Some("541")), Some((Source::GoogleBooks, google_pages)));
+
+    // A value from neither catalogue switches to Google Books first
+    assert_eq!(records.source_of(SourcedField::Description, Some("A generated synopsis.")), None);
+    assert_eq!(records.toggled(SourcedField::Description, Some("A generated synopsis.")).unwrap().0, Source::GoogleBooks);
+
+    // Both catalogues have the same ISBN, so there is nothing to switch to
+    let isbn = records.value(SourcedField::Isbn, Source::GoogleBooks);
+    assert_eq!(records.toggled(SourcedField::Isbn, isbn.as_deref()), None);
+}
+
+#[test]
+fn sourced_values_are_written_to_the_entry() {
+    let records = records();
+    let book = BookResult::Google(google());
+    let mut prepared = PreparedBook::new(&book, vec!["Poetry".to_string()], "A generated synopsis.".to_string(), false, Vec::new(), &EntryDefaults::default());
+    assert_eq!(records.source_of(SourcedField::Cover, prepared.sourced_value(SourcedField::Cover).as_deref()), Some(Source::GoogleBooks));
+
+    // The generated synopsis came from neither, so it takes two switches to reach Open Library
+    for field in [SourcedField::Pages, SourcedField::Description, SourcedField::Description, SourcedField::Cover] {
+        let (_, value) = records.toggled(field, prepared.sourced_value(field).as_deref()).unwrap();
+        prepared.set_sourced_value(field, value);
+    }
+    assert_eq!(prepared.pages, Some(541));
+    assert_eq!(prepared.synopsis, "Fagles' verse translation.");
+    assert_eq!(prepared.sourced_value(SourcedField::Cover), Some("https://covers.openlibrary.org/b/id/12660447-L.jpg".to_string()));
+    assert_eq!(prepared.title, google().get_full_title());
+}
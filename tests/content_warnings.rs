@@ -0,0 +1,21 @@
+use wcm::content_warnings::{field_value, parse_response};
+
+fn kinds() -> Vec<String> {
+    ["violence", "sexual content", "strong language"].into_iter().map(String::from).collect()
+}
+
+#[test]
+fn warnings_are_read_from_kind_and_detail_lines() {
+    let response = "- Violence | battle scenes, some gore\n**Strong language** | occasional\nViolence | again\nCannibalism | the Cyclops\n";
+    assert_eq!(parse_response(response, &kinds()), Some(vec![
+        "Violence: battle scenes, some gore".to_string(),
+        "Strong language: occasional".to_string(),
+    ]));
+}
+
+#[test]
+fn none_means_checked_and_clean() {
+    assert_eq!(parse_response("NONE.", &kinds()), Some(Vec::new()));
+    assert_eq!(field_value(&[]), "None");
+    assert_eq!(parse_response("I am not sure about this book.", &kinds()), None);
+}
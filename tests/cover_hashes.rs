@@ -0,0 +1,54 @@
+use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage};
+use wcm::cover_hashes::{dhash, distance, hash_bytes, load, record, same_cover};
+
+/// A cover-like picture: stripes across a gradient, `design` setting how many.
+fn cover(width: u32, height: u32, design: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        let stripe = (x * (3 + design) / width + y * 4 / height).is_multiple_of(2);
+        let shade = (x * 160 / width) as u8;
+        if stripe { Rgb([40 + shade, 30, 90]) } else { Rgb([230 - shade, 220, 200]) }
+    }))
+}
+
+#[test]
+fn the_same_cover_resized_and_recompressed_matches() {
+    let original = cover(400, 600, 3);
+    let mut jpeg = Vec::new();
+    original.resize_exact(128, 192, image::imageops::FilterType::Triangle)
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), ImageOutputFormat::Jpeg(60))
+        .unwrap();
+
+    assert!(distance(dhash(&original), hash_bytes(&jpeg).unwrap()) <= wcm::cover_hashes::SAME_COVER_DISTANCE);
+    assert!(distance(dhash(&original), dhash(&cover(400, 600, 7))) > wcm::cover_hashes::SAME_COVER_DISTANCE);
+}
+
+#[test]
+fn stored_hashes_flag_the_rows_with_the_same_cover() {
+    let path = std::env::temp_dir().join(format!("wcm-cover-hashes-test-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert!(load(&path).unwrap().is_empty());
+
+    let hash = dhash(&cover(400, 600, 3));
+    record(&path, 12, hash).unwrap();
+    record(&path, 34, dhash(&cover(400, 600, 7))).unwrap();
+    record(&path, 56, hash ^ 0b11).unwrap();
+
+    assert_eq!(same_cover(&load(&path).unwrap(), hash), vec![12, 56]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn hashes_recorded_at_once_are_all_kept() {
+    let path = std::env::temp_dir().join(format!("wcm-cover-hashes-parallel-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    std::thread::scope(|scope| {
+        for row_id in 1..=8 {
+            let path = &path;
+            scope.spawn(move || record(path, row_id, row_id).unwrap());
+        }
+    });
+    let hashes = load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(hashes.keys().copied().collect::<Vec<u64>>(), (1..=8).collect::<Vec<u64>>());
+}
@@ -0,0 +1,20 @@
+use serde_json::{json, Value};
+use wcm::baserow::MediaRow;
+use wcm::covers::missing_covers;
+
+fn row(id: u64, cover: Value) -> MediaRow {
+    serde_json::from_value(json!({ "id": id, "Title": format!("Book {}", id), "Cover": cover })).unwrap()
+}
+
+#[test]
+fn rows_with_an_empty_cover_field_are_backfilled() {
+    let rows = vec![
+        row(1, json!([{ "name": "cover.jpg", "url": "http://localhost/cover.jpg" }])),
+        row(2, json!([])),
+        row(3, Value::Null),
+        row(4, json!([])),
+    ];
+    let ids = |rows: Vec<MediaRow>| rows.iter().map(|row| row.id).collect::<Vec<_>>();
+    assert_eq!(ids(missing_covers(rows.clone(), None)), vec![2, 3, 4]);
+    assert_eq!(ids(missing_covers(rows, Some(2))), vec![2, 3]);
+}
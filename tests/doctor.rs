@@ -0,0 +1,50 @@
+use wcm::config::Config;
+use wcm::config_check::{Finding, NetworkProblem, Severity};
+use wcm::doctor::{check_writable, data_paths, prioritized};
+
+#[test]
+fn network_errors_are_classified_by_their_causes() {
+    let dns = "error sending request for url (https://baserow.example/): error trying to connect: dns error: failed to lookup address information: Name or service not known";
+    assert_eq!(NetworkProblem::classify(dns), NetworkProblem::Dns);
+    let tls = "error sending request: error trying to connect: error:0A000086:SSL routines:tls_post_process_server_certificate:certificate verify failed";
+    assert_eq!(NetworkProblem::classify(tls), NetworkProblem::Tls);
+    assert_eq!(NetworkProblem::classify("tcp connect error: Connection refused (os error 111)"), NetworkProblem::Refused);
+    assert_eq!(NetworkProblem::classify("operation timed out"), NetworkProblem::Timeout);
+    assert_eq!(NetworkProblem::classify("connection closed before message completed"), NetworkProblem::Other);
+}
+
+#[test]
+fn a_missing_directory_is_checked_through_its_nearest_parent() {
+    let dir = std::env::temp_dir().join(format!("wcm-doctor-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let finding = check_writable("app.retry_dir", &dir.join("not/yet/created"));
+    assert_eq!(finding.severity, Severity::Ok);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "the probe file is removed");
+
+    let file = dir.join("file");
+    std::fs::write(&file, "").unwrap();
+    assert_eq!(check_writable("app.cache_dir", &file.join("cache")).severity, Severity::Error);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn settings_that_turn_a_feature_off_are_not_checked() {
+    let mut config = Config::default();
+    config.app.cache_dir.clear();
+    let settings: Vec<&str> = data_paths(&config).into_iter().map(|(setting, _, _)| setting).collect();
+    assert!(settings.contains(&"app.retry_dir"));
+    assert!(!settings.contains(&"app.cache_dir"));
+}
+
+#[test]
+fn errors_are_listed_before_warnings() {
+    let findings = [
+        Finding::warning("terminal", "not a terminal", "run it in one"),
+        Finding::ok("wcm", "version 0.1.0"),
+        Finding::error("app.retry_dir", "cannot write", "make it writable"),
+        Finding::warning("config.yaml", "missing", "create it"),
+    ];
+    let order: Vec<&str> = prioritized(&findings).iter().map(|finding| finding.setting.as_str()).collect();
+    assert_eq!(order, ["app.retry_dir", "terminal", "config.yaml"]);
+}
@@ -0,0 +1,42 @@
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::book_search::{BookResult, ManualBook};
+use wcm::duplicates::find_possible_duplicates;
+
+#[test]
+fn an_isbn_10_matches_its_isbn_13() {
+    let rows: Vec<MediaRow> = vec![
+        serde_json::from_value(json!({ "id": 7, "Title": "Jurassic Park", "ISBN": "978-0-345-39180-3" })).unwrap(),
+        serde_json::from_value(json!({ "id": 8, "Title": "The Lost World", "ISBN": "9780345400284" })).unwrap(),
+    ];
+    let book = BookResult::Manual(ManualBook {
+        title: "Some Other Title".to_string(),
+        author: String::new(),
+        isbn: Some("0345391802".to_string()),
+        cover_url: None,
+    });
+
+    let duplicates = find_possible_duplicates(&book, &rows);
+
+    assert_eq!(duplicates.iter().map(|duplicate| duplicate.id).collect::<Vec<u64>>(), [7]);
+}
+
+#[test]
+fn thai_vowel_and_tone_marks_are_kept() {
+    let row = |id: u64, title: &str, author: &str| -> MediaRow {
+        serde_json::from_value(json!({ "id": id, "Title": title, "Author": author })).unwrap()
+    };
+    let rows = vec![row(1, "บ้าน", "ทมยันตี"), row(2, "Les Misérables", "Victor Hugo")];
+    let book = |title: &str, author: &str| BookResult::Manual(ManualBook {
+        title: title.to_string(),
+        author: author.to_string(),
+        isbn: None,
+        cover_url: None,
+    });
+
+    assert!(find_possible_duplicates(&book("บาน", "ทมยันตี"), &rows).is_empty());
+    assert_eq!(find_possible_duplicates(&book("บ้าน", "ทมยันตี"), &rows).len(), 1);
+    // Accents on Latin letters are still ignored
+    let duplicates = find_possible_duplicates(&book("Les Miserables", "Victor Hugo"), &rows);
+    assert_eq!(duplicates.iter().map(|duplicate| duplicate.id).collect::<Vec<u64>>(), [2]);
+}
@@ -0,0 +1,81 @@
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::config::Config;
+use wcm::embeddings::{book_text, cosine, digest, keyword_matches, semantic_matches, Embedder, Index, IndexedBook};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn row(id: u64, title: &str, synopsis: &str) -> MediaRow {
+    serde_json::from_value(json!({ "id": id, "Title": title, "Author": "Ursula K. Le Guin", "Synopsis": synopsis })).unwrap()
+}
+
+#[test]
+fn only_new_and_changed_books_are_embedded_again() {
+    let rows = [row(1, "The Dispossessed", "An anarchist physicist."), row(2, "The Lathe of Heaven", "Dreams that change the world.")];
+    let mut index = Index::default();
+    index.prune("nomic-embed-text", &rows);
+    index.books.insert(1, IndexedBook { digest: digest(&book_text(&rows[0])), vector: vec![1.0, 0.0] });
+    index.books.insert(2, IndexedBook { digest: "stale".to_string(), vector: vec![0.0, 1.0] });
+    index.books.insert(3, IndexedBook { digest: "deleted".to_string(), vector: vec![0.0, 1.0] });
+
+    let stale: Vec<u64> = index.stale(&rows).iter().map(|(row, _)| row.id).collect();
+    assert_eq!(stale, [2]);
+
+    index.prune("nomic-embed-text", &rows);
+    assert!(!index.books.contains_key(&3));
+    index.prune("text-embedding-3-small", &rows);
+    assert!(index.books.is_empty(), "vectors of another model are dropped");
+}
+
+#[test]
+fn the_closest_books_come_first() {
+    assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+    assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+    assert_eq!(cosine(&[], &[1.0]), 0.0);
+
+    let rows = [row(1, "The Dispossessed", ""), row(2, "The Lathe of Heaven", ""), row(3, "Tehanu", "")];
+    let mut index = Index::default();
+    for (id, vector) in [(1, vec![1.0, 0.0]), (2, vec![0.6, 0.8]), (3, vec![0.0, 1.0])] {
+        index.books.insert(id, IndexedBook { digest: String::new(), vector });
+    }
+    let found = semantic_matches(&index, &rows, &[0.0, 1.0], 2);
+    assert_eq!(found.iter().map(|book| book.id).collect::<Vec<_>>(), [3, 2]);
+    assert!(found[0].score.unwrap() > found[1].score.unwrap());
+}
+
+#[test]
+fn keywords_match_word_starts_ignoring_accents() {
+    let rows = [row(1, "The Dispossessed", "An anarchist physicist on Anarres."), row(2, "Les Misérables", "")];
+    let ids = |query: &str| keyword_matches(&rows, query, 10).iter().map(|book| book.id).collect::<Vec<_>>();
+    assert_eq!(ids("anarch physic"), [1]);
+    assert_eq!(ids("miserables"), [2]);
+    assert_eq!(ids("le guin"), [1, 2]);
+    assert!(ids("  ").is_empty());
+}
+
+#[tokio::test]
+async fn ollama_and_openai_embeddings_come_back_in_input_order() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST")).and(path("/api/embed")).and(body_partial_json(json!({ "model": "nomic-embed-text" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "embeddings": [[0.1, 0.2], [0.3, 0.4]] })))
+        .mount(&server).await;
+    Mock::given(method("POST")).and(path("/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": [
+            { "index": 1, "embedding": [0.3, 0.4] },
+            { "index": 0, "embedding": [0.1, 0.2] },
+        ] })))
+        .mount(&server).await;
+    let texts = ["first".to_string(), "second".to_string()];
+
+    let mut config = Config::default();
+    config.llm.ollama.base_url = server.uri();
+    let vectors = Embedder::from_config(&config).unwrap().embed(&texts).await.unwrap();
+    assert_eq!(vectors, [vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+    config.llm.embedding_provider = Some("openai".to_string());
+    config.llm.openai.api_key = "test-key".to_string();
+    config.llm.openai.base_url = server.uri();
+    let embedder = Embedder::from_config(&config).unwrap();
+    assert_eq!(embedder.model(), "text-embedding-3-small");
+    assert_eq!(embedder.embed(&texts).await.unwrap(), [vec![0.1, 0.2], vec![0.3, 0.4]]);
+}
@@ -0,0 +1,22 @@
+use serde_json::{json, Value};
+use wcm::baserow::MediaRow;
+use wcm::enrich::incomplete_rows;
+
+fn row(id: u64, synopsis: &str, cover: Value) -> MediaRow {
+    serde_json::from_value(json!({ "id": id, "Title": format!("Book {}", id), "Synopsis": synopsis, "Cover": cover })).unwrap()
+}
+
+#[test]
+fn short_synopses_and_missing_covers_are_enriched() {
+    let cover = json!([{ "name": "cover.jpg" }]);
+    let rows = vec![
+        row(1, "A long enough synopsis of five words", cover.clone()),
+        row(2, "Too short", cover.clone()),
+        row(3, "", cover),
+        row(4, "A long enough synopsis of five words", json!([])),
+    ];
+    let ids = |rows: Vec<MediaRow>| rows.iter().map(|row| row.id).collect::<Vec<_>>();
+    assert_eq!(ids(incomplete_rows(rows.clone(), 5, false)), vec![2, 3, 4]);
+    assert_eq!(ids(incomplete_rows(rows.clone(), 5, true)), vec![2, 3]);
+    assert_eq!(ids(incomplete_rows(rows, 10, true)), vec![1, 2, 3, 4]);
+}
@@ -0,0 +1,65 @@
+use wcm::entry_editor::{parse, to_yaml, with_errors, EditableEntry};
+
+fn categories() -> Vec<String> {
+    vec!["Fantasy".to_string(), "History".to_string()]
+}
+
+fn entry() -> EditableEntry {
+    EditableEntry {
+        title: "Small Gods".to_string(),
+        author: "Terry Pratchett".to_string(),
+        isbn: Some("9780552152976".to_string()),
+        publisher: None,
+        pages: Some(400),
+        categories: vec!["Fantasy".to_string()],
+        synopsis: "Brutha is a novice.".to_string(),
+        ebook: false,
+        status: "In Place".to_string(),
+        read: true,
+        rating: 8,
+        series: Some("Discworld".to_string()),
+        volume: Some(13),
+        call_number: None,
+    }
+}
+
+#[test]
+fn an_unchanged_entry_reads_back_as_it_was_written() {
+    let text = to_yaml(&entry(), &categories());
+    assert!(text.starts_with('#'), "the file opens with the choices as comments");
+    assert_eq!(parse(&text, &categories()), Ok(Some(entry())));
+}
+
+#[test]
+fn categories_and_statuses_are_matched_ignoring_case() {
+    let text = to_yaml(&entry(), &categories())
+        .replace("- Fantasy", "- fantasy\n- HISTORY")
+        .replace("status: In Place", "status: on loan");
+    let parsed = parse(&text, &categories()).unwrap().unwrap();
+    assert_eq!(parsed.categories, ["Fantasy", "History"]);
+    assert_eq!(parsed.status, "on loan");
+}
+
+#[test]
+fn every_problem_is_reported_at_once() {
+    let text = to_yaml(&entry(), &categories())
+        .replace("9780552152976", "9780552152977")
+        .replace("- Fantasy", "- Cookery")
+        .replace("rating: 8", "rating: 11")
+        .replace("status: In Place", "status: Lost");
+    let errors = parse(&text, &categories()).unwrap_err();
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert!(errors[0].contains("9780552152977"));
+    assert!(errors[1].contains("Cookery"));
+
+    let reopened = with_errors(&with_errors(&text, &errors), &errors[..1]);
+    assert_eq!(reopened.lines().filter(|line| line.starts_with("# ERROR: ")).count(), 1, "old errors are replaced");
+    assert!(parse(&reopened, &categories()).is_err());
+}
+
+#[test]
+fn an_emptied_file_cancels_and_broken_yaml_is_an_error() {
+    assert_eq!(parse("# only comments\n\n", &categories()), Ok(None));
+    assert!(parse("title: [unclosed", &categories()).is_err());
+    assert!(parse("title: Mort\nstatus: In Place\ncolour: red\n", &categories()).is_err(), "unknown keys are typos");
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::media_row;
+use serde_json::{json, Value};
+use wcm::export::{field_key, file_name, render, render_template, write_tree};
+
+#[test]
+fn file_names_are_ascii_slugs_ending_in_the_row_id() {
+    assert_eq!(file_name(&media_row(7, json!({ "Title": "Small Gods: A Discworld Novel" }))), "small-gods-a-discworld-novel-7.md");
+    assert_eq!(file_name(&media_row(8, json!({ "Title": "Les Misérables" }))), "les-miserables-8.md");
+    assert_eq!(file_name(&media_row(9, json!({ "Title": "" }))), "untitled-9.md");
+    let long = file_name(&media_row(10, json!({ "Title": "A Very Long Title ".repeat(10) })));
+    assert!(long.len() <= 60 + "-10.md".len(), "{}", long);
+    assert!(long.starts_with("a-very-long-title-a-very-long-title-") && long.ends_with("-10.md"), "{}", long);
+}
+
+#[test]
+fn rows_render_the_same_whatever_the_field_order() {
+    let fields = json!({
+        "Synopsis": "  A tortoise and a prophet.  ",
+        "Rating": 4,
+        "Author": "Terry Pratchett",
+        "Category": [{ "id": 1, "value": "Fantasy" }, { "id": 2, "value": "Satire" }],
+        "Read": true,
+        "Status": { "id": 3, "value": "Owned" },
+        "Cover": [{ "url": "https://example.org/c.jpg", "visible_name": "cover.jpg" }],
+        "Title": "Small Gods",
+        "ISBN": "9780552152976",
+        "Notes": "",
+        "Series": null,
+        "order": "1.00000000000000000000",
+    });
+    let text = render(&media_row(7, fields.clone()));
+    assert_eq!(text, "---\nid: 7\nTitle: Small Gods\nAuthor: Terry Pratchett\nISBN: '9780552152976'\nCategory: Fantasy, Satire\nCover: cover.jpg\nRating: 4\nRead: true\nStatus: Owned\n---\n\nA tortoise and a prophet.\n");
+
+    let reversed: serde_json::Map<String, Value> = fields.as_object().unwrap().clone().into_iter().rev().collect();
+    assert_eq!(render(&media_row(7, reversed.into())), text);
+}
+
+#[test]
+fn the_tree_only_changes_for_changed_rows() {
+    let dir = std::env::temp_dir().join(format!("wcm-export-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("README.md"), "My catalogue\n").unwrap();
+
+    let first = [media_row(1, json!({ "Title": "Dune" })), media_row(2, json!({ "Title": "Emma" }))];
+    let summary = write_tree(&first, &dir).unwrap();
+    assert_eq!((summary.written, summary.unchanged, summary.removed), (2, 0, 0));
+
+    let second = [media_row(1, json!({ "Title": "Dune", "Rating": 5 }))];
+    let summary = write_tree(&second, &dir).unwrap();
+    assert_eq!((summary.written, summary.unchanged, summary.removed), (1, 0, 1));
+    assert!(!dir.join("emma-2.md").exists());
+    assert!(dir.join("README.md").exists());
+
+    let summary = write_tree(&second, &dir).unwrap();
+    assert_eq!((summary.written, summary.unchanged, summary.removed), (0, 1, 0));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn templates_see_every_field_by_name_and_by_key() {
+    assert_eq!(field_key("Media Type"), "media_type");
+    assert_eq!(field_key("Title (Romanized)"), "title_romanized");
+
+    let rows = [
+        media_row(7, json!({ "Title": "Small Gods", "Author": "Terry Pratchett", "Media Type": { "id": 1, "value": "Book" }, "Series": null })),
+        media_row(8, json!({ "Title": "Mort", "Author": "Terry Pratchett", "Media Type": { "id": 1, "value": "Book" } })),
+    ];
+    let template = "{{ count }} books\n{% for book in books|sort(attribute='title') %}- {{ book.title }} ({{ book[\"Media Type\"] }}, #{{ book.id }}){% if book.series %} in {{ book.series }}{% endif %}\n{% endfor %}";
+    assert_eq!(render_template("shelf", template, &rows).unwrap(), "2 books\n- Mort (Book, #8)\n- Small Gods (Book, #7)\n");
+
+    assert!(render_template("broken", "{% for book in books %}", &rows).is_err());
+}
@@ -0,0 +1,28 @@
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::goals::progress;
+
+fn row(id: u64, read: bool, date_read: Option<&str>) -> MediaRow {
+    serde_json::from_value(json!({ "id": id, "Read": read, "Date Read": date_read })).unwrap()
+}
+
+#[test]
+fn progress_counts_books_read_in_the_year() {
+    let rows = vec![
+        row(1, true, Some("2025-01-15")),
+        row(2, true, Some("2025-07-02")),
+        row(3, true, Some("2024-12-31")),
+        row(4, true, None),
+        row(5, false, Some("2025-03-01")),
+    ];
+
+    let progress = progress(&rows, "Date Read", 2025, 52, "2025-07-02");
+    assert_eq!(progress.read, 2);
+    assert_eq!(progress.undated, 1);
+    // Day 183 of 365
+    assert_eq!(progress.expected, Some(26));
+
+    let past = wcm::goals::progress(&rows, "Date Read", 2024, 12, "2025-07-02");
+    assert_eq!(past.read, 1);
+    assert_eq!(past.expected, None);
+}
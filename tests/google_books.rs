@@ -0,0 +1,193 @@
+mod common;
+
+use common::fixture;
+use wcm::filters::SearchFilters;
+use wcm::google_books::GoogleBooksClient;
+use wcm::http_cache::HttpCache;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn search_by_isbn_parses_volume() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .and(query_param("q", "isbn:9780140449136"))
+        .respond_with(fixture("google_books_isbn.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = GoogleBooksClient::new(String::new(), server.uri());
+    let response = client.search_by_isbn("9780140449136").await.unwrap();
+
+    let book = &response.items.unwrap()[0];
+    assert_eq!(book.get_full_title(), "The Odyssey: Translated by Robert Fagles");
+    assert_eq!(book.get_all_authors(), "Homer, Robert Fagles");
+    assert_eq!(book.get_isbn_13().as_deref(), Some("9780140449136"));
+    assert_eq!(book.get_isbn_10().as_deref(), Some("0140449132"));
+    assert!(book.get_best_cover_image().unwrap().contains("zoom=1"));
+}
+
+#[tokio::test]
+async fn search_by_isbn_sends_api_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .and(query_param("key", "test-key"))
+        .respond_with(fixture("google_books_empty.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = GoogleBooksClient::new("test-key".to_string(), server.uri());
+    let response = client.search_by_isbn("9780000000000").await.unwrap();
+
+    assert_eq!(response.total_items, 0);
+    assert!(response.items.is_none());
+}
+
+#[tokio::test]
+async fn search_by_title_author_builds_query() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .and(query_param("q", "intitle:\"The Odyssey\" inauthor:\"Homer\""))
+        .respond_with(fixture("google_books_isbn.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = GoogleBooksClient::new(String::new(), server.uri());
+    let response = client.search_by_title_author("The Odyssey", "Homer").await.unwrap();
+
+    assert_eq!(response.total_items, 1);
+}
+
+#[tokio::test]
+async fn relaxed_searches_build_queries() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .and(query_param("q", "intitle:\"The Odyssey\""))
+        .respond_with(fixture("google_books_isbn.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .and(query_param("q", "The Odyssey Homer"))
+        .respond_with(fixture("google_books_empty.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = GoogleBooksClient::new(String::new(), server.uri());
+
+    assert_eq!(client.search_by_title("The Odyssey").await.unwrap().total_items, 1);
+    assert_eq!(client.search_by_keywords("The Odyssey Homer").await.unwrap().total_items, 0);
+}
+
+#[tokio::test]
+async fn filters_restrict_publisher_and_language() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .and(query_param("q", "intitle:\"The Odyssey\" inpublisher:\"Penguin\""))
+        .and(query_param("langRestrict", "en"))
+        .respond_with(fixture("google_books_isbn.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let filters = SearchFilters { year: None, publisher: Some("Penguin".to_string()), lang: Some("eng".to_string()) };
+    let client = GoogleBooksClient::new(String::new(), server.uri()).with_filters(filters);
+    let response = client.search_by_title("The Odyssey").await.unwrap();
+
+    assert_eq!(response.total_items, 1);
+}
+
+#[tokio::test]
+async fn rate_limited_request_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(fixture("google_books_isbn.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = GoogleBooksClient::new(String::new(), server.uri());
+    let response = client.search_by_isbn("9780140449136").await.unwrap();
+
+    assert_eq!(response.total_items, 1);
+}
+
+#[tokio::test]
+async fn cached_response_is_revalidated_with_etag() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(fixture("google_books_isbn.json").insert_header("ETag", "\"v1\""))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = std::env::temp_dir().join(format!("wcm-cache-test-{}", std::process::id()));
+    let client = GoogleBooksClient::new(String::new(), server.uri()).with_cache(HttpCache::new(&dir));
+    let first = client.search_by_isbn("9780140449136").await.unwrap();
+    let second = client.search_by_isbn("9780140449136").await.unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(first.total_items, 1);
+    assert_eq!(second.items.unwrap()[0].get_full_title(), "The Odyssey: Translated by Robert Fagles");
+}
+
+#[tokio::test]
+async fn offline_lookups_only_use_the_cache() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(fixture("google_books_isbn.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = std::env::temp_dir().join(format!("wcm-offline-cache-test-{}", std::process::id()));
+    let online = GoogleBooksClient::new(String::new(), server.uri()).with_cache(HttpCache::new(&dir));
+    online.search_by_isbn("9780140449136").await.unwrap();
+    let offline = GoogleBooksClient::new(String::new(), server.uri()).with_cache(HttpCache::new(&dir).with_offline(true));
+    let cached = offline.search_by_isbn("9780140449136").await;
+    let missing = offline.search_by_isbn("9780441013593").await;
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(cached.unwrap().total_items, 1);
+    assert!(missing.unwrap_err().to_string().contains("9780441013593"));
+}
+
+#[tokio::test]
+async fn error_status_is_an_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        // Longer than wcm is willing to wait, so the 429 is returned at once
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600").set_body_string("Rate limit exceeded"))
+        .mount(&server)
+        .await;
+
+    let client = GoogleBooksClient::new(String::new(), server.uri());
+    let error = client.search_by_isbn("9780140449136").await.unwrap_err();
+
+    assert!(error.to_string().contains("429"));
+    assert!(error.to_string().contains("Rate limit exceeded"));
+}
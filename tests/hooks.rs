@@ -0,0 +1,34 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+use serde_json::json;
+use wcm::hooks::{run, HookError};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test]
+async fn the_hook_reads_the_entry_from_stdin() {
+    let entry = json!({ "Title": "Small Gods", "Author": "Terry Pratchett" });
+    let output = run("post_add", "cat", &entry, &[], TIMEOUT).await.unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&output).unwrap(), entry);
+
+    let output = run("post_add", "echo \"$WCM_HOOK $WCM_ROW_ID\"", &entry, &[("WCM_ROW_ID", "42".to_string())], TIMEOUT).await.unwrap();
+    assert_eq!(output, "post_add 42");
+}
+
+#[tokio::test]
+async fn a_non_zero_exit_is_an_error() {
+    match run("pre_add", "echo duplicate; exit 3", &json!({}), &[], TIMEOUT).await {
+        Err(HookError::Failed { code, output }) => {
+            assert_eq!(code, Some(3));
+            assert_eq!(output, "duplicate");
+        }
+        other => panic!("expected a failure, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn a_hook_that_runs_too_long_is_stopped() {
+    let result = run("post_add", "sleep 5", &json!({}), &[], Duration::from_millis(200)).await;
+    assert!(matches!(result, Err(HookError::TimedOut(_))));
+}
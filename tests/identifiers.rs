@@ -0,0 +1,43 @@
+use wcm::identifiers::{asin_as_isbn, is_valid_isbn, normalize_asin, normalize_lccn, normalize_oclc};
+
+#[test]
+fn lccns_are_normalized_like_the_library_of_congress_does() {
+    assert_eq!(normalize_lccn("n 78-890351").as_deref(), Some("n78890351"));
+    assert_eq!(normalize_lccn("78-5").as_deref(), Some("78000005"));
+    assert_eq!(normalize_lccn("2001-1114").as_deref(), Some("2001001114"));
+    assert_eq!(normalize_lccn("85000002 /AC").as_deref(), Some("85000002"));
+    assert_eq!(normalize_lccn("not a number"), None);
+}
+
+#[test]
+fn oclc_numbers_lose_their_prefixes() {
+    assert_eq!(normalize_oclc("ocm01234567").as_deref(), Some("1234567"));
+    assert_eq!(normalize_oclc("(OCoLC)987654").as_deref(), Some("987654"));
+    assert_eq!(normalize_oclc("on1000001").as_deref(), Some("1000001"));
+    assert_eq!(normalize_oclc("ocm"), None);
+}
+
+#[test]
+fn asins_are_uppercased_and_checked() {
+    assert_eq!(normalize_asin(" b00k0oi42w ").as_deref(), Some("B00K0OI42W"));
+    assert_eq!(normalize_asin("B00K0OI42"), None);
+    assert_eq!(normalize_asin("B00K-OI42W"), None);
+}
+
+#[test]
+fn only_asins_with_an_isbn_check_digit_are_isbns() {
+    assert_eq!(asin_as_isbn("0306406152").as_deref(), Some("0306406152"));
+    assert_eq!(asin_as_isbn("080442957X").as_deref(), Some("080442957X"));
+    assert_eq!(asin_as_isbn("0306406153"), None);
+    assert_eq!(asin_as_isbn("B00K0OI42W"), None);
+}
+
+#[test]
+fn isbns_are_checked_by_their_check_digit() {
+    assert!(is_valid_isbn("978-0-552-15297-6"));
+    assert!(is_valid_isbn("0 345 39180 2"));
+    assert!(is_valid_isbn("080442957X"));
+    assert!(!is_valid_isbn("9780552152977"));
+    assert!(!is_valid_isbn("0345391803"));
+    assert!(!is_valid_isbn("97805521529"));
+}
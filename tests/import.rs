@@ -0,0 +1,92 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+use common::{baserow_config, fixture};
+use wcm::baserow::BaserowClient;
+use wcm::book_search::CombinedBookSearcher;
+use wcm::config::Config;
+use wcm::google_books::GoogleBooksClient;
+use wcm::import::{Checkpoint, ImportEntry, ImportItem, ImportStatus};
+use wcm::open_library::OpenLibraryClient;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Imports `entries` against a library that has none of them yet, where creating
+/// a row takes `create_delay`.
+async fn import(name: &str, entries: Vec<ImportEntry>, jobs: usize, create_delay: Duration) -> Vec<ImportItem> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .respond_with(fixture("google_books_isbn.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/3/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "count": 1, "next": null, "previous": null,
+            "results": [{ "id": 1, "Name": "Classics" }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/2/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "count": 0, "next": null, "previous": null, "results": [],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/2/"))
+        .respond_with(fixture("baserow_created_row.json").set_delay(create_delay))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut config = Config { baserow: baserow_config(server.uri()), ..Default::default() };
+    config.app.cache_dir = String::new();
+    let searcher = CombinedBookSearcher::new(
+        GoogleBooksClient::new(String::new(), server.uri()),
+        OpenLibraryClient::new(server.uri()),
+        BaserowClient::new(config.baserow.clone()),
+        config,
+    )
+    .with_categories(vec!["Classics".to_string()])
+    .with_synopsis(Some("Odysseus sails home.".to_string()))
+    .with_covers(false);
+
+    let input = std::env::temp_dir().join(format!("wcm-import-test-{}-{}.txt", name, std::process::id()));
+    let mut checkpoint = Checkpoint::open(Checkpoint::path_for(&input), false).unwrap();
+    let items = wcm::import::run(Arc::new(searcher), entries, false, jobs, Some(Arc::new(Vec::new())), &mut checkpoint).await;
+    checkpoint.remove().unwrap();
+    items
+}
+
+#[tokio::test]
+async fn an_isbn_listed_twice_is_added_once() {
+    // The ISBN-10 form of the same book is caught too
+    let entries = vec![
+        ImportEntry::Isbn("9780140449136".to_string()),
+        ImportEntry::Isbn("9780140449136".to_string()),
+        ImportEntry::Isbn("0140449132".to_string()),
+    ];
+    let items = import("sequential", entries, 1, Duration::ZERO).await;
+
+    let statuses: Vec<ImportStatus> = items.iter().map(|item| item.status).collect();
+    assert_eq!(statuses, [ImportStatus::Added, ImportStatus::Duplicate, ImportStatus::Duplicate]);
+    assert_eq!(items[1].duplicate_of, [321]);
+}
+
+#[tokio::test]
+async fn a_parallel_job_waits_for_the_isbn_being_added() {
+    let entries = vec![
+        ImportEntry::Isbn("9780140449136".to_string()),
+        ImportEntry::Isbn("0140449132".to_string()),
+    ];
+    let items = import("parallel", entries, 2, Duration::from_millis(300)).await;
+
+    let added = items.iter().filter(|item| item.status == ImportStatus::Added).count();
+    let duplicate = items.iter().find(|item| item.status == ImportStatus::Duplicate).unwrap();
+    assert_eq!(added, 1);
+    assert_eq!(duplicate.duplicate_of, [321]);
+}
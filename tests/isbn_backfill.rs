@@ -0,0 +1,39 @@
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::book_search::{BookResult, ManualBook};
+use wcm::isbn_backfill::{missing_isbns, proposals};
+
+fn book(title: &str, author: &str, isbn: Option<&str>) -> BookResult {
+    BookResult::Manual(ManualBook {
+        title: title.to_string(),
+        author: author.to_string(),
+        isbn: isbn.map(str::to_string),
+        cover_url: None,
+    })
+}
+
+#[test]
+fn only_editions_of_the_same_book_are_proposed() {
+    let row: MediaRow = serde_json::from_value(json!({ "id": 1, "Title": "The Odyssey", "Author": "Homer" })).unwrap();
+    let books = vec![
+        book("The Odyssey: Translated by Robert Fagles", "Homer, Robert Fagles", Some("9780140449136")),
+        book("Odyssey", "Homer", Some("9780140449136")),
+        book("The Odyssey", "Homer", Some("9780374525743")),
+        book("The Iliad", "Homer", Some("9780140275360")),
+        book("The Odyssey", "Gillian Cross", Some("9780763647926")),
+        book("The Odyssey", "Homer", None),
+    ];
+    let isbns: Vec<String> = proposals(&row, &books).into_iter().map(|proposal| proposal.isbn).collect();
+    assert_eq!(isbns, vec!["9780140449136", "9780374525743"]);
+}
+
+#[test]
+fn rows_with_an_isbn_are_left_alone() {
+    let rows: Vec<MediaRow> = serde_json::from_value(json!([
+        { "id": 1, "Title": "A", "ISBN": "9780140449136" },
+        { "id": 2, "Title": "B", "ISBN": "" },
+        { "id": 3, "Title": "C", "ISBN": null },
+    ])).unwrap();
+    let ids: Vec<u64> = missing_isbns(rows, None).iter().map(|row| row.id).collect();
+    assert_eq!(ids, vec![2, 3]);
+}
@@ -0,0 +1,43 @@
+use serde_json::Value;
+use wcm::book_search::BookResult;
+use wcm::google_books::BookItem;
+use wcm::language::{book_language, detect, from_code, is_preferred, text_language};
+
+fn google() -> BookItem {
+    let response: Value = serde_json::from_str(include_str!("fixtures/google_books_isbn.json")).unwrap();
+    serde_json::from_value(response["items"][0].clone()).unwrap()
+}
+
+#[test]
+fn thai_script_is_always_detected() {
+    let language = detect("ความสุขของกะทิ").unwrap();
+    assert_eq!(language.code, "th");
+    assert_eq!(language.name, "Thai");
+}
+
+#[test]
+fn a_short_title_falls_back_to_the_description() {
+    assert_eq!(detect("Dune"), None);
+    let description = "La historia de una familia a lo largo de siete generaciones en el pueblo ficticio de Macondo, \
+        con sus guerras, sus amores y su soledad.";
+    assert_eq!(text_language("Cien años de soledad", Some(description)).unwrap().code, "es");
+}
+
+#[test]
+fn the_catalogue_language_is_used_when_the_text_says_nothing() {
+    let mut item = google();
+    item.volume_info.title = "Dune".to_string();
+    item.volume_info.subtitle = None;
+    item.volume_info.description = None;
+    item.volume_info.language = Some("fr".to_string());
+    assert_eq!(book_language(&BookResult::Google(item)).unwrap().name, "French");
+}
+
+#[test]
+fn marc_codes_match_the_detected_language() {
+    let thai = from_code("tha").unwrap();
+    assert_eq!(thai.code, "th");
+    assert!(is_preferred(&thai, "th"));
+    assert!(is_preferred(&thai, "THA"));
+    assert!(!is_preferred(&thai, "en"));
+}
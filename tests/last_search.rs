@@ -0,0 +1,47 @@
+use wcm::book_search::{BookResult, ManualBook};
+use wcm::last_search::{format_results, LastSearch, SavedResult};
+
+fn book(title: &str, isbn: Option<&str>) -> BookResult {
+    BookResult::Manual(ManualBook { title: title.to_string(), author: "Terry Pratchett".to_string(), isbn: isbn.map(str::to_string), cover_url: None })
+}
+
+fn search() -> LastSearch {
+    LastSearch {
+        query: "Discworld".to_string(),
+        results: vec![
+            SavedResult { book: book("The Colour of Magic", Some("9780552166591")), series: Some("Discworld".to_string()), volume: Some(1) },
+            SavedResult::new(book("Mort", None)),
+        ],
+    }
+}
+
+#[test]
+fn the_last_search_survives_a_restart() {
+    let path = std::env::temp_dir().join(format!("wcm-last-search-test-{}", std::process::id())).join("last_search.json");
+    search().save(&path).unwrap();
+    let loaded = LastSearch::load(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(loaded.query, "Discworld");
+    let first = loaded.pick(1).unwrap();
+    assert_eq!(first.book.get_full_title(), "The Colour of Magic");
+    assert_eq!((first.series.as_deref(), first.volume), (Some("Discworld"), Some(1)));
+    assert_eq!(loaded.pick(2).unwrap().book.get_full_title(), "Mort");
+}
+
+#[test]
+fn results_are_picked_by_their_printed_number() {
+    let search = search();
+    assert!(search.pick(0).is_err());
+    assert!(search.pick(3).is_err());
+
+    let listed = format_results(&search);
+    assert!(listed.contains("  1. The Colour of Magic"));
+    assert!(listed.contains("[9780552166591]"));
+    assert!(listed.contains("  2. Mort"));
+}
+
+#[test]
+fn without_a_saved_search_there_is_nothing_to_pick() {
+    assert!(LastSearch::load(std::path::Path::new("/nonexistent/last_search.json")).is_err());
+}
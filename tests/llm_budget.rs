@@ -0,0 +1,158 @@
+mod common;
+
+use common::{baserow_config, fixture};
+use serde_json::json;
+use wcm::baserow::BaserowClient;
+use wcm::book_search::CombinedBookSearcher;
+use wcm::config::{BudgetFallback, Config, LlmBudgetConfig};
+use wcm::google_books::GoogleBooksClient;
+use wcm::llm::{LlmError, LlmProvider};
+use wcm::llm_budget::{self, month_of, MonthUsage};
+use wcm::open_library::OpenLibraryClient;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn usage_file(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("wcm-llm-usage-{}-{}.json", name, std::process::id()))
+}
+
+fn openai_config(usage_file: &std::path::Path) -> Config {
+    let mut config = Config::default();
+    config.llm.provider = "openai".to_string();
+    config.llm.openai.api_key = "test-key".to_string();
+    config.llm.budget.usage_file = usage_file.display().to_string();
+    config
+}
+
+#[test]
+fn usage_is_counted_per_month() {
+    let file = usage_file("months");
+    llm_budget::record(&file, "2026-09", 100, 20).unwrap();
+    llm_budget::record(&file, "2026-10", 300, 50).unwrap();
+    llm_budget::record(&file, "2026-10", 200, 50).unwrap();
+    let log = llm_budget::load(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    assert_eq!(month_of("2026-10-16"), "2026-10");
+    assert_eq!(log["2026-09"].tokens(), 120);
+    assert_eq!(log["2026-10"], MonthUsage { requests: 2, prompt_tokens: 500, completion_tokens: 100 });
+}
+
+#[test]
+fn the_budget_caps_tokens_or_spend() {
+    let usage = MonthUsage { requests: 3, prompt_tokens: 2_000_000, completion_tokens: 500_000 };
+    let mut budget = LlmBudgetConfig { input_price: 0.15, output_price: 0.60, ..LlmBudgetConfig::default() };
+    assert_eq!(usage.over_budget(&budget), None);
+    assert!((usage.spend(&budget) - 0.60).abs() < 1e-9);
+
+    budget.monthly_spend = Some(1.0);
+    assert_eq!(usage.over_budget(&budget), None);
+    budget.monthly_spend = Some(0.5);
+    assert!(usage.over_budget(&budget).unwrap().contains("0.60"));
+
+    budget.monthly_spend = None;
+    budget.monthly_tokens = Some(2_500_000);
+    assert!(usage.over_budget(&budget).unwrap().contains("2500000"));
+}
+
+#[tokio::test]
+async fn completions_are_recorded_until_the_budget_falls_back() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "model": "gpt-4o-mini",
+            "choices": [{ "message": { "role": "assistant", "content": "Dune" } }],
+            "usage": { "prompt_tokens": 40, "completion_tokens": 10 },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let file = usage_file("fallback");
+    let mut config = openai_config(&file);
+    config.llm.openai.base_url = server.uri();
+    config.llm.budget.monthly_tokens = Some(50);
+
+    let provider = LlmProvider::from_config(&config).unwrap();
+    assert_eq!(provider.name(), "openai");
+    let LlmProvider::OpenAi(client) = provider else { unreachable!() };
+    assert_eq!(client.generate_text("Name a book").await.unwrap(), "Dune");
+
+    let fallback = LlmProvider::from_config(&config).unwrap();
+    config.llm.budget.fallback = BudgetFallback::None;
+    let none = LlmProvider::from_config(&config);
+    let configured = LlmProvider::configured(&config).unwrap();
+    let usage = llm_budget::this_month(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    assert_eq!(usage.tokens(), 50);
+    assert_eq!(fallback.name(), "ollama");
+    assert!(matches!(none, Err(LlmError::BudgetExceeded(_))));
+    assert_eq!(configured.name(), "openai");
+}
+
+#[tokio::test]
+async fn a_spent_budget_without_fallback_leaves_the_categories_to_the_user() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/volumes"))
+        .respond_with(fixture("google_books_isbn.json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/3/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "count": 1, "next": null, "previous": null,
+            "results": [{ "id": 1, "Name": "Classics" }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/database/rows/table/2/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "count": 0, "next": null, "previous": null, "results": [],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/2/"))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let file = usage_file("spent");
+    llm_budget::record(&file, &month_of(&wcm::dates::today()), 400, 100).unwrap();
+    let mut config = openai_config(&file);
+    config.baserow = baserow_config(server.uri());
+    config.app.cache_dir = String::new();
+    config.llm.openai.base_url = server.uri();
+    config.llm.budget.monthly_tokens = Some(500);
+    config.llm.budget.fallback = BudgetFallback::None;
+    let searcher = |categories: Vec<String>| {
+        CombinedBookSearcher::new(
+            GoogleBooksClient::new(String::new(), server.uri()),
+            OpenLibraryClient::new(server.uri()),
+            BaserowClient::new(config.baserow.clone()),
+            config.clone(),
+        )
+        .with_categories(categories)
+        .with_covers(false)
+    };
+
+    let unpicked = searcher(Vec::new());
+    let book = unpicked.find_by_isbn("9780140449136").await.unwrap().books.remove(0);
+    let error = unpicked.add_book_unattended(&book, false).await.unwrap_err();
+    let added = searcher(vec!["Classics".to_string()]).add_book_unattended(&book, false).await;
+    std::fs::remove_file(&file).unwrap();
+
+    assert!(error.to_string().contains("LLM budget"), "{}", error);
+    assert_eq!(added.unwrap().id, 321);
+}
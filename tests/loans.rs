@@ -0,0 +1,85 @@
+mod common;
+
+use common::{baserow_config, fixture, media_row};
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::config::MediaFieldsConfig;
+use wcm::baserow::BaserowClient;
+use wcm::loans::{give_back, lend, on_loan, overdue, to_ical};
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, MockServer};
+
+fn library() -> Vec<MediaRow> {
+    let lent = json!({ "id": 3030, "value": "On Loan" });
+    vec![
+        media_row(1, json!({ "Title": "Dune", "Author": "Frank Herbert", "Status": lent, "Borrower": "Somchai", "Loaned": "2026-09-01", "Due": "2026-10-01" })),
+        media_row(2, json!({ "Title": "Emma", "Author": "Jane Austen", "Status": { "id": 3028, "value": "In Place" }, "Due": "2026-01-01" })),
+        media_row(3, json!({ "Title": "Ubik", "Author": "Philip K. Dick", "Status": lent, "Borrower": "Nok", "Due": "2026-10-20" })),
+        media_row(4, json!({ "Title": "Kim", "Author": "Rudyard Kipling", "Status": lent, "Borrower": "Nok" })),
+        media_row(5, json!({ "Title": "Beloved", "Author": "Toni Morrison", "Status": lent, "Due": "2026-09-15" })),
+    ]
+}
+
+#[test]
+fn overdue_loans_are_the_lent_books_past_their_due_date() {
+    let loans = on_loan(&library(), &MediaFieldsConfig::default());
+    assert_eq!(loans.iter().map(|loan| loan.id).collect::<Vec<_>>(), [1, 3, 4, 5], "only rows marked On Loan");
+
+    let late = overdue(&loans, "2026-10-16");
+    assert_eq!(late.iter().map(|loan| loan.id).collect::<Vec<_>>(), [5, 1], "longest overdue first");
+    assert_eq!(late[1].days_overdue("2026-10-16"), Some(15));
+    assert_eq!(overdue(&loans, "2026-10-01").iter().map(|loan| loan.id).collect::<Vec<_>>(), [5], "a book due today is not overdue yet");
+}
+
+#[test]
+fn the_ical_feed_has_an_all_day_event_per_due_date() {
+    let loans = on_loan(&library(), &MediaFieldsConfig::default());
+    let ical = to_ical(&loans, "2026-10-16 08:30");
+
+    assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ical.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(ical.matches("BEGIN:VEVENT").count(), 3, "the loan without a due date has no event");
+    assert!(ical.contains("UID:wcm-loan-1-20261001@wcm\r\nDTSTAMP:20261016T083000Z\r\nDTSTART;VALUE=DATE:20261001\r\nDTEND;VALUE=DATE:20261002\r\n"));
+    assert!(ical.lines().all(|line| line.trim_end_matches('\r').len() <= 75));
+}
+
+#[test]
+fn ical_text_is_escaped_and_long_lines_are_folded() {
+    let rows = vec![media_row(9, json!({
+        "Title": "Sapiens; a brief history, of humankind and of everything that came before us",
+        "Author": "Yuval Noah Harari",
+        "Status": { "id": 3030, "value": "On Loan" },
+        "Due": "2026-11-30",
+    }))];
+    let ical = to_ical(&on_loan(&rows, &MediaFieldsConfig::default()), "2026-10-16 08:30");
+
+    let unfolded = ical.replace("\r\n ", "");
+    assert!(unfolded.contains("Sapiens\\; a brief history\\, of humankind"));
+    assert!(ical.contains("\r\n "), "the summary is longer than 75 bytes");
+}
+
+#[tokio::test]
+async fn only_the_loan_fields_the_table_has_are_written() {
+    let server = MockServer::start().await;
+    Mock::given(method("PATCH"))
+        .and(path("/api/database/rows/table/2/6/"))
+        .and(body_json(json!({ "Status": 3030, "Borrower": "Nok" })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/api/database/rows/table/2/6/"))
+        .and(body_json(json!({ "Status": 3028, "Borrower": "" })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // A table with a Borrower field but no loan dates
+    let book = media_row(6, json!({ "Title": "Ubik", "Status": null, "Borrower": "" }));
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let fields = MediaFieldsConfig::default();
+    lend(&client, &book, &fields, "Nok", Some("2026-11-01"), "2026-10-16").await.unwrap();
+    give_back(&client, &book, &fields).await.unwrap();
+}
@@ -0,0 +1,38 @@
+use wcm::loc::LocClient;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn find_by_lccn_reads_the_title_and_first_contributor() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/item/54008556/"))
+        .and(query_param("fo", "json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "item": {
+                "title": "The fellowship of the ring /",
+                "contributor_names": ["Tolkien, J. R. R. (John Ronald Reuel), 1892-1973."],
+            },
+        })))
+        .mount(&server)
+        .await;
+
+    let client = LocClient::new(server.uri());
+    let book = client.find_by_lccn("54008556").await.unwrap().unwrap();
+
+    assert_eq!(book.title, "The fellowship of the ring");
+    assert_eq!(book.author, "J. R. R. Tolkien");
+    assert!(book.isbn.is_none());
+}
+
+#[tokio::test]
+async fn find_by_lccn_is_none_for_an_unknown_number() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let client = LocClient::new(server.uri());
+    assert!(client.find_by_lccn("99999999").await.unwrap().is_none());
+}
@@ -0,0 +1,85 @@
+use serde_json::Value;
+use wcm::book_search::{BookResult, ManualBook};
+use wcm::config::{CoverSource, FieldSource, MergeConfig};
+use wcm::google_books::BookItem;
+use wcm::merge::{cover_candidates, lookups_needed, merge, OtherRecords};
+use wcm::open_library::{OpenLibraryBook, OpenLibraryBookDetails};
+
+fn google() -> BookItem {
+    let response: Value = serde_json::from_str(include_str!("fixtures/google_books_isbn.json")).unwrap();
+    serde_json::from_value(response["items"][0].clone()).unwrap()
+}
+
+fn open_library() -> OpenLibraryBook {
+    let response: Value = serde_json::from_str(include_str!("fixtures/open_library_search.json")).unwrap();
+    serde_json::from_value(response["docs"][0].clone()).unwrap()
+}
+
+fn edition() -> OpenLibraryBookDetails {
+    let mut edition: Value = serde_json::from_str(include_str!("fixtures/open_library_edition.json")).unwrap();
+    edition["description"] = serde_json::json!({ "type": "/type/text", "value": "Fagles' verse translation of Homer's epic." });
+    edition["subjects"] = serde_json::json!(["Epic poetry, Greek"]);
+    serde_json::from_value(edition).unwrap()
+}
+
+#[test]
+fn only_the_catalogues_the_policy_names_are_asked() {
+    let google_book = BookResult::Google(google());
+    let ol_book = BookResult::OpenLibrary(open_library());
+
+    assert_eq!(lookups_needed(&google_book, &MergeConfig::default()), (false, false));
+    let policy = MergeConfig { subjects: FieldSource::OpenLibrary, ..Default::default() };
+    assert_eq!(lookups_needed(&google_book, &policy), (false, true));
+    // An Open Library result already has its subjects, but not a description
+    assert_eq!(lookups_needed(&ol_book, &policy), (false, false));
+    let policy = MergeConfig { description: FieldSource::OpenLibrary, ..Default::default() };
+    assert_eq!(lookups_needed(&ol_book, &policy), (false, true));
+    let policy = MergeConfig { cover: CoverSource::Largest, ..Default::default() };
+    assert_eq!(lookups_needed(&ol_book, &policy), (true, false));
+    assert_eq!(lookups_needed(&BookResult::Manual(ManualBook::default()), &policy), (false, false));
+}
+
+#[test]
+fn fields_come_from_the_preferred_catalogue() {
+    let policy = MergeConfig {
+        description: FieldSource::OpenLibrary,
+        subjects: FieldSource::OpenLibrary,
+        cover: CoverSource::OpenLibrary,
+    };
+    let others = OtherRecords { google: None, edition: Some(edition()) };
+    let merged = merge(&BookResult::Google(google()), &policy, &others, None);
+
+    assert_eq!(merged.get_description().as_deref(), Some("Fagles' verse translation of Homer's epic."));
+    assert_eq!(merged.get_subjects(), vec!["Epic poetry, Greek"]);
+    let BookResult::Google(item) = merged else { panic!("still a Google Books result") };
+    assert_eq!(item.get_best_cover_image().as_deref(), Some("https://covers.openlibrary.org/b/id/12660447-L.jpg"));
+    assert_eq!(item.get_full_title(), "The Odyssey: Translated by Robert Fagles");
+}
+
+#[test]
+fn a_field_the_preferred_catalogue_lacks_keeps_the_picked_value() {
+    let policy = MergeConfig { description: FieldSource::GoogleBooks, subjects: FieldSource::GoogleBooks, ..Default::default() };
+    let merged = merge(&BookResult::OpenLibrary(open_library()), &policy, &OtherRecords::default(), None);
+    assert_eq!(merged.get_description(), None);
+    assert_eq!(merged.get_subjects(), vec!["Epic poetry, Greek", "Odysseus (Greek mythology)"]);
+
+    let others = OtherRecords { google: Some(google()), edition: None };
+    let merged = merge(&BookResult::OpenLibrary(open_library()), &policy, &others, None);
+    assert_eq!(merged.get_description().as_deref(), Some("The epic tale of Odysseus and his ten-year journey home after the Trojan War."));
+    assert_eq!(merged.get_subjects(), vec!["Poetry"]);
+}
+
+#[test]
+fn largest_cover_chooses_between_both_catalogues() {
+    let others = OtherRecords { google: Some(google()), edition: None };
+    let book = BookResult::OpenLibrary(open_library());
+    assert_eq!(cover_candidates(&book, &others), vec![
+        "https://books.google.com/books/content?id=i4BOEAAAQBAJ&printsec=frontcover&img=1&zoom=1&source=gbs_api",
+        "https://covers.openlibrary.org/b/id/12660447-L.jpg",
+    ]);
+
+    let policy = MergeConfig { cover: CoverSource::Largest, ..Default::default() };
+    let merged = merge(&book, &policy, &others, Some("https://example.org/big.jpg".to_string()));
+    let BookResult::OpenLibrary(ol_book) = merged else { panic!("still an Open Library result") };
+    assert_eq!(ol_book.cover_url.as_deref(), Some("https://example.org/big.jpg"));
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+use wcm::metrics;
+use wcm::timing::Stage;
+
+#[test]
+fn metrics_are_rendered_in_the_prometheus_text_format() {
+    metrics::record_add();
+    metrics::record_api_error("openlibrary.org", Some(503));
+    metrics::record_api_error("localhost", None);
+    metrics::record_cache(true);
+    metrics::record_cache(false);
+    metrics::record_cache(false);
+    metrics::record_duration(Stage::Llm, Duration::from_millis(1500));
+    metrics::record_duration(Stage::Llm, Duration::from_secs(200));
+    metrics::record_task_run("enrich", false);
+
+    let text = metrics::render();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.contains(&"# TYPE wcm_books_added_total counter"));
+    assert!(lines.contains(&"wcm_books_added_total 1"));
+    assert!(lines.contains(&"wcm_api_errors_total{host=\"openlibrary.org\",status=\"503\"} 1"));
+    assert!(lines.contains(&"wcm_api_errors_total{host=\"localhost\",status=\"error\"} 1"));
+    assert!(lines.contains(&"wcm_cache_requests_total{result=\"hit\"} 1"));
+    assert!(lines.contains(&"wcm_cache_requests_total{result=\"miss\"} 2"));
+    assert!(lines.contains(&"wcm_daemon_task_runs_total{task=\"enrich\",result=\"failed\"} 1"));
+
+    // Buckets are cumulative, and the slow call only counts towards +Inf
+    assert!(lines.contains(&"wcm_request_duration_seconds_bucket{stage=\"llm\",le=\"1\"} 0"));
+    assert!(lines.contains(&"wcm_request_duration_seconds_bucket{stage=\"llm\",le=\"2.5\"} 1"));
+    assert!(lines.contains(&"wcm_request_duration_seconds_bucket{stage=\"llm\",le=\"120\"} 1"));
+    assert!(lines.contains(&"wcm_request_duration_seconds_bucket{stage=\"llm\",le=\"+Inf\"} 2"));
+    assert!(lines.contains(&"wcm_request_duration_seconds_sum{stage=\"llm\"} 201.5"));
+    assert!(lines.contains(&"wcm_request_duration_seconds_count{stage=\"llm\"} 2"));
+}
@@ -0,0 +1,69 @@
+use serde_json::{json, Value};
+use wcm::baserow::MediaRow;
+use wcm::config::MediaFieldsConfig;
+use wcm::next::{rank, reorder, ties};
+use wcm::sessions::Session;
+
+fn row(id: u64, fields: Value) -> MediaRow {
+    let mut row = json!({ "id": id, "Title": format!("Book {}", id), "Read": false });
+    row.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+    serde_json::from_value(row).unwrap()
+}
+
+fn ids(rows: &[MediaRow], sessions: &[Session], busy: bool) -> Vec<u64> {
+    rank(rows, &MediaFieldsConfig::default(), sessions, busy).iter().map(|candidate| candidate.id).collect()
+}
+
+#[test]
+fn later_volumes_wait_for_earlier_unread_ones() {
+    let rows = vec![
+        row(1, json!({ "Series": "Discworld", "Volume": 2 })),
+        row(2, json!({ "Series": "Discworld", "Volume": 1 })),
+        row(3, json!({})),
+    ];
+    assert_eq!(ids(&rows, &[], false), vec![2, 3, 1]);
+
+    let rows = vec![
+        row(3, json!({})),
+        row(1, json!({ "Series": "Discworld", "Volume": 2 })),
+        row(2, json!({ "Series": "Discworld", "Volume": 1, "Read": true })),
+    ];
+    assert_eq!(ids(&rows, &[], false), vec![1, 3]);
+}
+
+#[test]
+fn started_books_and_short_ones_when_busy_come_first() {
+    let rows = vec![
+        row(1, json!({ "Pages": 800 })),
+        row(2, json!({ "Pages": 180 })),
+        row(3, json!({ "Pages": 350 })),
+    ];
+    assert_eq!(ids(&rows, &[], true), vec![2, 3, 1]);
+    assert_eq!(ids(&rows, &[], false), vec![1, 2, 3]);
+
+    let session = Session { book_id: 1, title: "Book 1".to_string(), date: "2025-01-01".to_string(), pages: Some(20), minutes: None };
+    assert_eq!(ids(&rows, &[session], true), vec![1, 2, 3]);
+}
+
+#[test]
+fn variety_favours_categories_not_read_recently() {
+    let rows = vec![
+        row(1, json!({ "Category": [{ "id": 1, "value": "Fantasy" }] })),
+        row(2, json!({ "Category": [{ "id": 2, "value": "History" }] })),
+        row(3, json!({ "Category": [{ "id": 1, "value": "Fantasy" }], "Read": true, "Date Read": "2025-05-01" })),
+    ];
+    assert_eq!(ids(&rows, &[], false), vec![2, 1]);
+}
+
+#[test]
+fn ties_reaching_the_top_are_reordered_by_the_picks() {
+    let rows: Vec<MediaRow> = (1..=4).map(|id| row(id, json!({}))).collect();
+    let mut candidates = rank(&rows, &MediaFieldsConfig::default(), &[], false);
+    let tied = ties(&candidates, 2);
+    assert_eq!(tied, vec![0..4]);
+
+    reorder(&mut candidates[tied[0].clone()], &[(2, "Why".to_string()), (2, String::new()), (0, String::new())]);
+    let ids: Vec<u64> = candidates.iter().map(|candidate| candidate.id).collect();
+    assert_eq!(ids, vec![3, 1, 2, 4]);
+    assert_eq!(candidates[0].reasons, vec!["Why".to_string()]);
+}
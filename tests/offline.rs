@@ -0,0 +1,94 @@
+mod common;
+
+use common::{baserow_config, fixture};
+use wcm::baserow::{BaserowClient, MediaEntry};
+use wcm::book_search::{BookResult, ManualBook};
+use wcm::config::HooksConfig;
+use wcm::offline::{book_key, title_key, OfflineCache};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer};
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("wcm-offline-test-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn llm_results_are_kept_per_book() {
+    let dir = temp_dir("llm");
+    let cache = OfflineCache::new(&dir);
+    let dune = BookResult::Manual(ManualBook { title: "Dune".to_string(), author: "Frank Herbert".to_string(), isbn: Some("9780441013593".to_string()), cover_url: None });
+    let categories = vec!["Science Fiction".to_string(), "Classics".to_string()];
+    cache.store_llm("categories", &book_key(&dune), &categories).unwrap();
+    cache.store_llm("synopsis", &title_key("Dune", "Frank Herbert"), &"A desert planet.".to_string()).unwrap();
+
+    let loaded: Option<Vec<String>> = cache.load_llm("categories", "9780441013593");
+    let synopsis: Option<String> = cache.load_llm("synopsis", &title_key(" dune ", "Frank  HERBERT"));
+    let other: Option<Vec<String>> = cache.load_llm("categories", "9780441172719");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded, Some(categories));
+    assert_eq!(synopsis.as_deref(), Some("A desert planet."));
+    assert_eq!(other, None);
+}
+
+#[test]
+fn covers_are_kept_by_url() {
+    let dir = temp_dir("covers");
+    let cache = OfflineCache::new(&dir);
+    cache.store_cover("https://covers.openlibrary.org/b/isbn/9780441013593-L.jpg", b"jpeg").unwrap();
+
+    let kept = cache.load_cover("https://covers.openlibrary.org/b/isbn/9780441013593-L.jpg");
+    let missing = cache.load_cover("https://covers.openlibrary.org/b/isbn/9780441172719-L.jpg");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(kept.as_deref(), Some(&b"jpeg"[..]));
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn a_queued_entry_is_retried_with_its_cover() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/user-files/upload-file/"))
+        .respond_with(fixture("baserow_upload.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/database/rows/table/2/"))
+        .and(body_partial_json(serde_json::json!({
+            "Title": "Dune",
+            "Cover": [{ "name": "VXotniBOVm8tbstZkKsMKbj2Qg7KmPvn_cover.jpg" }],
+        })))
+        .respond_with(fixture("baserow_created_row.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = temp_dir("queue");
+    let entry = MediaEntry {
+        title: "Dune".to_string(),
+        author: "Frank Herbert".to_string(),
+        isbn: Some("9780441013593".to_string()),
+        synopsis: "A desert planet.".to_string(),
+        category: vec![3],
+        read: false,
+        rating: 0,
+        media_type: Some(1),
+        location: Vec::new(),
+        cover: Vec::new(),
+        status: 3028,
+        extra: Default::default(),
+    };
+    let path = wcm::retry::save_offline_entry(&dir, &entry, Some(b"jpeg")).unwrap();
+    assert!(wcm::retry::cover_path(&path).exists());
+    assert_eq!(wcm::retry::pending_entries(&dir).unwrap(), vec![path.clone()]);
+
+    let client = BaserowClient::new(baserow_config(server.uri()));
+    let id = wcm::retry::retry_entry(&client, &path, &HooksConfig::default()).await.unwrap();
+    let left = std::fs::read_dir(&dir).unwrap().count();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(id, 321);
+    assert_eq!(left, 0);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::fixture;
+use wcm::book_search::BookResult;
+use wcm::config::HttpConfig;
+use wcm::filters::SearchFilters;
+use wcm::open_library::OpenLibraryClient;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn search_by_isbn_parses_docs() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search.json"))
+        .and(query_param("isbn", "9780140449136"))
+        .respond_with(fixture("open_library_search.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OpenLibraryClient::new(server.uri());
+    let response = client.search_by_isbn("9780140449136").await.unwrap();
+
+    let book = &response.docs[0];
+    assert_eq!(book.get_full_title(), "The Odyssey");
+    assert_eq!(book.get_all_authors(), "Homer");
+    assert_eq!(book.get_primary_publisher().as_deref(), Some("Penguin Classics"));
+    assert_eq!(book.get_cover_url().as_deref(), Some("https://covers.openlibrary.org/b/id/12660447-L.jpg"));
+}
+
+#[tokio::test]
+async fn search_by_title_author_encodes_parameters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search.json"))
+        .and(query_param("title", "The Odyssey"))
+        .and(query_param("author", "Homer"))
+        .respond_with(fixture("open_library_search.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OpenLibraryClient::new(server.uri());
+    let response = client.search_by_title_author("The Odyssey", "Homer").await.unwrap();
+
+    assert_eq!(response.num_found, 1);
+}
+
+#[tokio::test]
+async fn filters_restrict_publisher_and_language() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search.json"))
+        .and(query_param("title", "The Odyssey"))
+        .and(query_param("publisher", "Penguin"))
+        .and(query_param("language", "eng"))
+        .respond_with(fixture("open_library_search.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let filters = SearchFilters { year: None, publisher: Some("Penguin".to_string()), lang: Some("en".to_string()) };
+    let client = OpenLibraryClient::new(server.uri()).with_filters(filters.clone());
+    let response = client.search_by_title("The Odyssey").await.unwrap();
+
+    let book = BookResult::OpenLibrary(response.docs[0].clone());
+    assert!(filters.matches(&book));
+    assert!(!SearchFilters { year: Some(1900), ..filters }.matches(&book));
+}
+
+#[tokio::test]
+async fn edition_by_isbn_has_classifications() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/isbn/9780140449136.json"))
+        .respond_with(fixture("open_library_edition.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OpenLibraryClient::new(server.uri());
+    let edition = client.get_edition_by_isbn("9780140449136").await.unwrap();
+
+    assert_eq!(edition.get_isbn_13().as_deref(), Some("9780140449136"));
+    assert_eq!(edition.dewey_decimal_class, Some(vec!["883/.01".to_string()]));
+    assert_eq!(edition.lc_classifications, Some(vec!["PA4025.A5 F33 2006".to_string()]));
+}
+
+#[tokio::test]
+async fn missing_edition_is_an_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+        .mount(&server)
+        .await;
+
+    let client = OpenLibraryClient::new(server.uri());
+
+    assert!(client.get_edition_by_isbn("9780000000000").await.is_err());
+}
+
+#[tokio::test]
+async fn shared_client_sends_configured_user_agent() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search.json"))
+        .and(header("User-Agent", "wcm-test/1.0"))
+        .respond_with(fixture("open_library_search.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let http = HttpConfig { user_agent: "wcm-test/1.0".to_string(), ..HttpConfig::default() };
+    let client = OpenLibraryClient::new(server.uri())
+        .with_http_client(wcm::http::build_client(&http).unwrap());
+
+    assert!(client.search_by_isbn("9780140449136").await.is_ok());
+}
@@ -0,0 +1,25 @@
+use wcm::periodicals::{issue_title, parse_issue_date, Issue};
+
+#[test]
+fn issue_dates_may_be_as_precise_as_the_cover() {
+    assert_eq!(parse_issue_date("2024").as_deref(), Some("2024"));
+    assert_eq!(parse_issue_date("2024-07").as_deref(), Some("2024-07"));
+    assert_eq!(parse_issue_date("2024-07-15").as_deref(), Some("2024-07-15"));
+    assert_eq!(parse_issue_date("2024-13"), None);
+    assert_eq!(parse_issue_date("July 2024"), None);
+}
+
+#[test]
+fn issues_are_titled_by_number_and_date() {
+    let dated = Issue { date: Some("2024-07".to_string()), number: None };
+    assert_eq!(issue_title("Wired ", &dated), "Wired, 2024-07");
+    assert_eq!(dated.date_value().as_deref(), Some("2024-07-01"));
+
+    let numbered = Issue { date: Some("2024".to_string()), number: Some("142".to_string()) };
+    assert_eq!(issue_title("Wired", &numbered), "Wired, No. 142 (2024)");
+    assert_eq!(numbered.date_value().as_deref(), Some("2024-01-01"));
+
+    let volume = Issue { date: None, number: Some("Vol. 3 No. 4".to_string()) };
+    assert_eq!(issue_title("Granta", &volume), "Granta, Vol. 3 No. 4");
+    assert_eq!(volume.date_value(), None);
+}
@@ -0,0 +1,25 @@
+use serde_json::json;
+use wcm::baserow::NamedRow;
+use wcm::linked::find_row;
+use wcm::publishers::{publisher_key, MIN_SIMILARITY};
+
+#[test]
+fn imprint_spellings_share_a_key() {
+    assert_eq!(publisher_key("Penguin Books Ltd"), "penguin");
+    assert_eq!(publisher_key("The Penguin Press"), "penguin");
+    assert_eq!(publisher_key("HarperCollins Publishers"), publisher_key("Harper Collins"));
+    assert_ne!(publisher_key("Penguin Classics"), publisher_key("Penguin"));
+    assert_eq!(publisher_key("The Press"), "thepress");
+}
+
+#[test]
+fn publishers_match_exactly_then_by_similarity() {
+    let rows: Vec<NamedRow> = vec![
+        serde_json::from_value(json!({ "id": 1, "Name": "Bloomsbury Publishing" })).unwrap(),
+        serde_json::from_value(json!({ "id": 2, "Name": "Penguin Books" })).unwrap(),
+    ];
+
+    assert_eq!(find_row(&rows, "Penguin", publisher_key, MIN_SIMILARITY).map(|row| row.id), Some(2));
+    assert_eq!(find_row(&rows, "Bloomsbery", publisher_key, MIN_SIMILARITY).map(|row| row.id), Some(1));
+    assert!(find_row(&rows, "Gollancz", publisher_key, MIN_SIMILARITY).is_none());
+}
@@ -0,0 +1,37 @@
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::llm::BookSuggestion;
+use wcm::recommend::{drop_owned, sample_library};
+
+fn row(id: u64, title: &str, rating: u32, date_read: Option<&str>) -> MediaRow {
+    serde_json::from_value(json!({ "id": id, "Title": title, "Rating": rating, "Date Read": date_read })).unwrap()
+}
+
+#[test]
+fn sample_takes_highly_rated_first_then_recent_reads() {
+    let rows = vec![
+        row(1, "Middling", 5, None),
+        row(2, "Good", 8, None),
+        row(3, "Best", 10, Some("2025-06-01")),
+        row(4, "Recent", 0, Some("2025-09-01")),
+        row(5, "Long Ago", 0, Some("2020-01-01")),
+        row(6, "Earlier", 0, Some("2025-03-01")),
+    ];
+
+    let sample = sample_library(&rows, "Date Read", 7, "2025-10-01", 10);
+    let ids: Vec<u64> = sample.iter().map(|row| row.id).collect();
+    assert_eq!(ids, vec![3, 2, 4, 6]);
+
+    let sample = sample_library(&rows, "Date Read", 7, "2025-10-01", 3);
+    assert_eq!(sample.len(), 3);
+}
+
+#[test]
+fn drop_owned_ignores_case_and_punctuation() {
+    let rows = vec![row(1, "The Left Hand of Darkness", 9, None)];
+    let suggestion = |title: &str| BookSuggestion { title: title.to_string(), author: "Ursula K. Le Guin".to_string(), reason: String::new() };
+
+    let kept = drop_owned(vec![suggestion("the left hand of darkness"), suggestion("The Dispossessed")], &rows);
+    let titles: Vec<&str> = kept.iter().map(|suggestion| suggestion.title.as_str()).collect();
+    assert_eq!(titles, vec!["The Dispossessed"]);
+}
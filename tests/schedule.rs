@@ -0,0 +1,67 @@
+use wcm::dates::{format_minutes, parse_days};
+use wcm::schedule::Schedule;
+
+/// Minutes since the epoch for `YYYY-MM-DD` at `hour:minute` UTC.
+fn at(date: &str, hour: u64, minute: u64) -> u64 {
+    parse_days(date).unwrap() as u64 * 1440 + hour * 60 + minute
+}
+
+fn next(expression: &str, after: u64) -> String {
+    format_minutes(Schedule::parse(expression).unwrap().next_after(after).unwrap())
+}
+
+#[test]
+fn schedules_need_five_valid_fields() {
+    assert!(Schedule::parse("0 3 * *").is_err());
+    assert!(Schedule::parse("60 * * * *").is_err());
+    assert!(Schedule::parse("0 3 0 * *").is_err());
+    assert!(Schedule::parse("*/0 * * * *").is_err());
+    assert!(Schedule::parse("0 3 * * mon").is_err());
+    assert!(Schedule::parse("0 3 * * *").is_ok());
+    assert!(Schedule::parse("@weekly").is_ok());
+}
+
+#[test]
+fn next_run_is_strictly_after_the_given_minute() {
+    assert_eq!(next("0 3 * * *", at("2024-07-01", 2, 59)), "2024-07-01 03:00");
+    assert_eq!(next("0 3 * * *", at("2024-07-01", 3, 0)), "2024-07-02 03:00");
+    assert_eq!(next("@hourly", at("2024-12-31", 23, 30)), "2025-01-01 00:00");
+    assert_eq!(next("@monthly", at("2024-02-15", 12, 0)), "2024-03-01 00:00");
+}
+
+#[test]
+fn steps_ranges_and_lists() {
+    assert_eq!(next("*/15 * * * *", at("2024-07-01", 10, 16)), "2024-07-01 10:30");
+    assert_eq!(next("0 9-17/4 * * *", at("2024-07-01", 13, 0)), "2024-07-01 17:00");
+    assert_eq!(next("30 6 1,15 * *", at("2024-07-02", 0, 0)), "2024-07-15 06:30");
+}
+
+#[test]
+fn sunday_is_zero_or_seven() {
+    // 2024-07-07 was a Sunday
+    assert_eq!(next("0 4 * * 0", at("2024-07-03", 0, 0)), "2024-07-07 04:00");
+    assert_eq!(next("0 4 * * 7", at("2024-07-03", 0, 0)), "2024-07-07 04:00");
+    assert_eq!(next("0 4 * * 1-5", at("2024-07-06", 0, 0)), "2024-07-08 04:00");
+}
+
+#[test]
+fn day_of_month_or_day_of_week_when_both_are_given() {
+    // Either the 13th or a Friday, as in cron
+    assert_eq!(next("0 0 13 * 5", at("2024-07-01", 0, 0)), "2024-07-05 00:00");
+    assert_eq!(next("0 0 13 * 5", at("2024-07-12", 0, 0)), "2024-07-13 00:00");
+}
+
+#[test]
+fn impossible_dates_never_fire() {
+    assert_eq!(Schedule::parse("0 0 30 2 *").unwrap().next_after(at("2024-01-01", 0, 0)), None);
+    assert_eq!(next("0 0 29 2 *", at("2024-03-01", 0, 0)), "2028-02-29 00:00");
+}
+
+#[test]
+fn impossible_dates_are_not_parsed() {
+    assert_eq!(parse_days("2024-02-29"), Some(19_782));
+    assert_eq!(parse_days("2023-02-29"), None);
+    assert_eq!(parse_days("2024-04-31"), None);
+    assert_eq!(parse_days("2024-13-01"), None);
+    assert_eq!(parse_days("2024-00-10"), None);
+}
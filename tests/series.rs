@@ -0,0 +1,49 @@
+use serde_json::json;
+use wcm::book_search::BookResult;
+use wcm::series::collect_volumes;
+
+fn google(title: &str, number: Option<&str>) -> BookResult {
+    let mut volume_info = json!({ "title": title, "authors": ["Terry Pratchett"] });
+    if let Some(number) = number {
+        volume_info["seriesInfo"] = json!({ "bookDisplayNumber": number });
+    }
+    BookResult::Google(serde_json::from_value(json!({
+        "kind": "books#volume",
+        "id": title,
+        "etag": "",
+        "selfLink": "",
+        "volumeInfo": volume_info,
+    })).unwrap())
+}
+
+fn open_library(title: &str, series: &[&str]) -> BookResult {
+    BookResult::OpenLibrary(serde_json::from_value(json!({
+        "key": format!("/works/{}", title),
+        "title": title,
+        "series": series,
+    })).unwrap())
+}
+
+#[test]
+fn volumes_are_ordered_and_deduplicated() {
+    let books = vec![
+        google("Mort", Some("4")),
+        google("The Colour of Magic", Some("1")),
+        open_library("The Colour of Magic", &["Discworld (1)"]),
+        open_library("Equal Rites", &["Discworld -- 3"]),
+        open_library("The Discworld Companion", &[]),
+        open_library("Good Omens", &["Standalone"]),
+    ];
+
+    let volumes = collect_volumes("Discworld", books);
+    let listed: Vec<(Option<u32>, String)> = volumes.iter()
+        .map(|volume| (volume.number, volume.book.get_full_title()))
+        .collect();
+
+    assert_eq!(listed, vec![
+        (Some(1), "The Colour of Magic".to_string()),
+        (Some(3), "Equal Rites".to_string()),
+        (Some(4), "Mort".to_string()),
+        (None, "The Discworld Companion".to_string()),
+    ]);
+}
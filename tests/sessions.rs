@@ -0,0 +1,25 @@
+use wcm::sessions::{progress, Session};
+
+fn session(book_id: u64, date: &str, pages: Option<u32>, minutes: Option<u32>) -> Session {
+    Session { book_id, title: "Mort".to_string(), date: date.to_string(), pages, minutes }
+}
+
+#[test]
+fn progress_estimates_finish_from_pace() {
+    let sessions = vec![
+        session(7, "2025-02-27", Some(30), Some(45)),
+        session(8, "2025-02-27", Some(100), None),
+        session(7, "2025-03-01", Some(30), Some(40)),
+        session(7, "2025-03-02", None, Some(10)),
+    ];
+
+    // 60 pages over four days is 15 a day, leaving 240 pages for 16 more days
+    let progress = progress(&sessions, 7, "Mort", Some(300), "2025-03-02");
+    assert_eq!(progress.sessions, 3);
+    assert_eq!(progress.pages, 60);
+    assert_eq!(progress.minutes, 95);
+    assert_eq!(progress.estimated_finish.as_deref(), Some("2025-03-18"));
+
+    let unknown_length = wcm::sessions::progress(&sessions, 7, "Mort", None, "2025-03-02");
+    assert_eq!(unknown_length.estimated_finish, None);
+}
@@ -0,0 +1,52 @@
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::book_search::BookResult;
+use wcm::llm::BookSuggestion;
+use wcm::similar::{collect_similar, SimilarSource};
+
+fn google(title: &str, categories: &[&str]) -> (BookResult, SimilarSource) {
+    let book = BookResult::Google(serde_json::from_value(json!({
+        "kind": "books#volume",
+        "id": title,
+        "etag": "",
+        "selfLink": "",
+        "volumeInfo": { "title": title, "authors": ["Author"], "categories": categories },
+    })).unwrap());
+    (book, SimilarSource::GoogleBooks)
+}
+
+fn open_library(title: &str, subjects: &[&str]) -> (BookResult, SimilarSource) {
+    let book = BookResult::OpenLibrary(serde_json::from_value(json!({
+        "key": format!("/works/{}", title),
+        "title": title,
+        "subject": subjects,
+    })).unwrap());
+    (book, SimilarSource::OpenLibrary)
+}
+
+#[test]
+fn books_found_by_more_sources_and_sharing_subjects_come_first() {
+    let subjects = vec!["Fantasy".to_string(), "Humorous fiction".to_string()];
+    let books = vec![
+        google("Guards! Guards!", &["Fiction"]),
+        google("Good Omens", &["Fantasy"]),
+        open_library("Good Omens", &["fantasy", "Humorous Fiction"]),
+        open_library("Small Gods", &["Fantasy"]),
+        google("Mort", &["Fantasy"]),
+    ];
+    let suggestions = vec![BookSuggestion {
+        title: "Small Gods".to_string(),
+        author: "Terry Pratchett".to_string(),
+        reason: "Also satire".to_string(),
+    }];
+    let owned: Vec<MediaRow> = vec![serde_json::from_value(json!({ "id": 1, "Title": "Mort" })).unwrap()];
+
+    let similar = collect_similar("Guards! Guards!", &subjects, books, suggestions, &owned);
+    let titles: Vec<&str> = similar.iter().map(|book| book.title.as_str()).collect();
+    assert_eq!(titles, vec!["Good Omens", "Small Gods"]);
+
+    assert_eq!(similar[0].sources, vec![SimilarSource::GoogleBooks, SimilarSource::OpenLibrary]);
+    assert_eq!(similar[0].shared_subjects, vec!["Fantasy".to_string(), "Humorous Fiction".to_string()]);
+    assert_eq!(similar[1].sources, vec![SimilarSource::OpenLibrary, SimilarSource::Llm]);
+    assert_eq!(similar[1].reason.as_deref(), Some("Also satire"));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::media_row;
+use serde_json::json;
+use wcm::baserow::MediaRow;
+use wcm::config::MediaFieldsConfig;
+use wcm::sessions::Session;
+use wcm::stats::{aggregate, format_summary, load_font, render_chart};
+
+fn category(name: &str) -> serde_json::Value {
+    json!({ "id": 1, "value": name })
+}
+
+fn library() -> Vec<MediaRow> {
+    vec![
+        media_row(1, json!({ "Created on": "2025-01-10T08:00:00Z", "Category": [category("History")], "Read": true, "Date Read": "2025-02-01", "Pages": 400 })),
+        media_row(2, json!({ "Created on": "2025-01-20T08:00:00Z", "Category": [category("History"), category("Rome")], "Read": true, "Date Read": "2025-03-05", "Pages": 300 })),
+        media_row(3, json!({ "Created on": "2025-03-02T08:00:00Z", "Category": [category("Fantasy")] })),
+        media_row(4, json!({ "Created on": "2024-12-31T08:00:00Z", "Category": [category("Fantasy")], "Read": true, "Date Read": "2024-12-31", "Pages": 200 })),
+        media_row(5, json!({ "Title": "No created date" })),
+    ]
+}
+
+fn sessions() -> Vec<Session> {
+    let session = |book_id, date: &str, pages| Session { book_id, title: String::new(), date: date.to_string(), pages, minutes: None };
+    vec![session(2, "2025-02-20", Some(120)), session(2, "2025-03-04", Some(180)), session(3, "2025-03-10", None)]
+}
+
+#[test]
+fn the_year_is_counted_by_month() {
+    let stats = aggregate(&library(), &sessions(), &MediaFieldsConfig::default(), 2025);
+
+    assert_eq!(stats.added, [2, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(stats.undated, 1);
+    assert_eq!(stats.read, 2);
+    // Book 1 was finished without logged sessions, so its page count counts in February
+    assert_eq!(stats.pages, [0, 520, 180, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let categories: Vec<(&str, u32)> = stats.categories.iter().map(|category| (category.name.as_str(), category.count)).collect();
+    assert_eq!(categories, [("History", 2), ("Fantasy", 1), ("Rome", 1)], "only the books added in the year, most first");
+}
+
+#[test]
+fn the_summary_ends_with_the_goal_bar() {
+    let mut stats = aggregate(&library(), &sessions(), &MediaFieldsConfig::default(), 2025);
+    assert!(!format_summary(&stats).contains('['));
+
+    let goal = wcm::goals::progress(&library(), "Date Read", 2025, 4, "2025-12-31");
+    let bar = wcm::goals::format_progress(&goal);
+    stats.goal = Some(goal);
+    assert!(format_summary(&stats).ends_with(&bar));
+    assert!(bar.contains("2/4"));
+    assert_eq!(serde_json::to_value(&stats).unwrap()["goal"]["target"], 4);
+}
+
+#[test]
+fn the_chart_is_written_as_a_png() {
+    // Needs a system font; without one there is nothing to draw the text with
+    let Ok(font) = load_font(None) else {
+        return;
+    };
+    let stats = aggregate(&library(), &sessions(), &MediaFieldsConfig::default(), 2025);
+    let path = std::env::temp_dir().join(format!("wcm-stats-test-{}.png", std::process::id()));
+
+    render_chart(&stats, &font, &path).unwrap();
+    let image = image::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!((image.width(), image.height()), (1200, 1500));
+}
@@ -0,0 +1,44 @@
+use wcm::book_search::{BookResult, ManualBook, PreparedBook};
+use wcm::config::EntryDefaults;
+use wcm::telegram::{format_summary, parse_book_query, strip_ebook_prefix, BookQuery};
+
+#[test]
+fn title_and_author_split_on_the_last_by() {
+    assert_eq!(
+        parse_book_query("Stand By Me BY Stephen King"),
+        Some(BookQuery::TitleAuthor("Stand By Me".to_string(), "Stephen King".to_string()))
+    );
+    // Lowercasing "İ" changes its length, which must not move the split
+    assert_eq!(
+        parse_book_query("İstanbul by Orhan Pamuk"),
+        Some(BookQuery::TitleAuthor("İstanbul".to_string(), "Orhan Pamuk".to_string()))
+    );
+    assert_eq!(parse_book_query("978-0-345-39180-3"), Some(BookQuery::Isbn("9780345391803".to_string())));
+}
+
+#[test]
+fn a_line_without_an_author_is_a_title() {
+    assert_eq!(parse_book_query("  The Left Hand of Darkness "), Some(BookQuery::Title("The Left Hand of Darkness".to_string())));
+    assert_eq!(parse_book_query("   "), None);
+}
+
+#[test]
+fn the_ebook_prefix_is_a_whole_word_in_any_case() {
+    assert_eq!(strip_ebook_prefix("Ebook  Dune by Frank Herbert"), (true, "Dune by Frank Herbert"));
+    assert_eq!(strip_ebook_prefix("ebook"), (true, ""));
+    assert_eq!(strip_ebook_prefix("ebooks of the year by Someone"), (false, "ebooks of the year by Someone"));
+}
+
+#[test]
+fn the_summary_follows_the_language() {
+    let book = BookResult::Manual(ManualBook { title: "บ้านเล็กในป่าใหญ่".to_string(), ..ManualBook::default() });
+    let prepared = PreparedBook::new(&book, vec!["Classics".to_string()], String::new(), true, Vec::new(), &EntryDefaults::default());
+
+    rust_i18n::set_locale("th");
+    let summary = format_summary(&prepared);
+    rust_i18n::set_locale("en");
+
+    assert!(summary.starts_with("สรุปก่อนยืนยัน"), "{}", summary);
+    assert!(summary.contains("ประเภท: "), "{}", summary);
+    assert!(summary.ends_with("เพิ่มหนังสือเล่มนี้เข้าห้องสมุดหรือไม่?"), "{}", summary);
+}
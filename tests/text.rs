@@ -0,0 +1,32 @@
+use wcm::book_search::BookResult;
+use wcm::text::{strip_html, truncate};
+
+#[test]
+fn html_descriptions_become_plain_text() {
+    let description = "<p><b>Dune</b> is set on Arrakis.</p><p>Paul&#39;s family &amp; the spice&nbsp;trade.<br>A classic &mdash; 1965.</p>";
+    assert_eq!(strip_html(description), "Dune is set on Arrakis.\n\nPaul's family & the spice trade.\nA classic \u{2014} 1965.");
+
+    assert_eq!(strip_html("Pages < 300 & more"), "Pages < 300 & more");
+    assert_eq!(strip_html("Plain text\nstays as it is"), "Plain text\nstays as it is");
+}
+
+#[test]
+fn truncation_counts_characters() {
+    let thai = "สามก๊กฉบับวณิพก";
+    assert_eq!(truncate(thai, 5), "สามก๊...");
+    assert_eq!(truncate(thai, 100), thai);
+    assert_eq!(truncate("Dune", 4), "Dune");
+}
+
+#[test]
+fn google_descriptions_are_stripped() {
+    let item = serde_json::json!({
+        "kind": "books#volume",
+        "id": "B1hSG45JCX4C",
+        "etag": "x",
+        "selfLink": "https://www.googleapis.com/books/v1/volumes/B1hSG45JCX4C",
+        "volumeInfo": { "title": "Dune", "description": "<p>A desert planet.</p>" }
+    });
+    let book = BookResult::Google(serde_json::from_value(item).unwrap());
+    assert_eq!(book.get_description().as_deref(), Some("A desert planet."));
+}
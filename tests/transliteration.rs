@@ -0,0 +1,27 @@
+use wcm::transliteration::{is_thai, parse_response, simple};
+
+#[test]
+fn only_titles_with_thai_letters_are_romanized() {
+    assert!(is_thai("ความสุขของกะทิ"));
+    assert!(is_thai("Harry Potter กับศิลาอาถรรพ์"));
+    assert!(!is_thai("The Odyssey"));
+    assert!(!is_thai("Cien años de soledad"));
+}
+
+#[test]
+fn the_simple_method_gives_plain_ascii() {
+    let romanized = simple("ความสุขของกะทิ  เล่ม 2");
+    assert!(romanized.is_ascii());
+    assert!(!romanized.contains('`'));
+    assert!(romanized.ends_with(" 2"));
+    assert!(!romanized.contains("  "));
+}
+
+#[test]
+fn the_romanized_title_is_taken_from_the_response() {
+    assert_eq!(parse_response("\n\"Khwam Suk Khong Kathi\"\n"), Some("Khwam Suk Khong Kathi".to_string()));
+    assert_eq!(parse_response("Romanized title: **Khwam Suk Khong Kathi**"), Some("Khwam Suk Khong Kathi".to_string()));
+    assert_eq!(parse_response("Harry Potter: Sila Athan"), Some("Harry Potter: Sila Athan".to_string()));
+    assert_eq!(parse_response("ความสุขของกะทิ"), None);
+    assert_eq!(parse_response("  \n"), None);
+}